@@ -80,14 +80,13 @@ use std::sync::Arc;
 
 use actix_http::header::{self, HeaderMap};
 use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
-use actix_web::http::StatusCode;
 use actix_web::Error;
 use bytes::{Bytes, BytesMut};
 use futures_util::future::{ok, Future, Ready};
 use futures_util::{FutureExt as _, TryStreamExt as _};
 
 use sentry_core::protocol::{self, ClientSdkPackage, Event, Request};
-use sentry_core::utils::{is_sensitive_header, scrub_pii_from_url};
+use sentry_core::utils::{is_sensitive_header, scrub_pii_from_url, span_status_from_http_status};
 use sentry_core::MaxRequestBodySize;
 use sentry_core::{Hub, SentryFutureExt};
 
@@ -313,11 +312,14 @@ where
                 value.to_str().ok().map(|value| (header.as_str(), value))
             });
 
-            let ctx = sentry_core::TransactionContext::continue_from_headers(
-                &name,
-                "http.server",
-                headers,
-            );
+            // The precedence of the trace headers is read from the client of the request's hub.
+            let ctx = Hub::run(hub.clone(), || {
+                sentry_core::TransactionContext::continue_from_headers(
+                    &name,
+                    "http.server",
+                    headers,
+                )
+            });
 
             let transaction = hub.start_transaction(ctx);
             transaction.set_request(sentry_req.clone());
@@ -384,7 +386,7 @@ where
 
             if let Some(transaction) = transaction {
                 if transaction.get_status().is_none() {
-                    let status = map_status(res.status());
+                    let status = span_status_from_http_status(res.status().as_u16());
                     transaction.set_status(status);
                 }
                 transaction.finish();
@@ -397,22 +399,6 @@ where
     }
 }
 
-fn map_status(status: StatusCode) -> protocol::SpanStatus {
-    match status {
-        StatusCode::UNAUTHORIZED => protocol::SpanStatus::Unauthenticated,
-        StatusCode::FORBIDDEN => protocol::SpanStatus::PermissionDenied,
-        StatusCode::NOT_FOUND => protocol::SpanStatus::NotFound,
-        StatusCode::TOO_MANY_REQUESTS => protocol::SpanStatus::ResourceExhausted,
-        status if status.is_client_error() => protocol::SpanStatus::InvalidArgument,
-        StatusCode::NOT_IMPLEMENTED => protocol::SpanStatus::Unimplemented,
-        StatusCode::SERVICE_UNAVAILABLE => protocol::SpanStatus::Unavailable,
-        status if status.is_server_error() => protocol::SpanStatus::InternalError,
-        StatusCode::CONFLICT => protocol::SpanStatus::AlreadyExists,
-        status if status.is_success() => protocol::SpanStatus::Ok,
-        _ => protocol::SpanStatus::UnknownError,
-    }
-}
-
 /// Extract a transaction name from the HTTP request
 fn transaction_name_from_http(req: &ServiceRequest) -> String {
     let path_part = req.match_pattern().unwrap_or_else(|| "<none>".to_string());
@@ -726,7 +712,10 @@ mod tests {
         }
     }
 
-    fn run_request_with_org_ids(incoming_org_id: &str, client_org_id: &str) -> Vec<Envelope> {
+    fn run_request_with_headers(
+        headers: &[(&'static str, String)],
+        options: sentry::ClientOptions,
+    ) -> Vec<Envelope> {
         sentry::test::with_captured_envelopes_options(
             || {
                 block_on(async {
@@ -742,18 +731,29 @@ mod tests {
                     )
                     .await;
 
-                    let req = TestRequest::get()
-                        .uri("/test")
-                        .insert_header((
-                            "sentry-trace",
-                            "09e04486820349518ac7b5d2adbf6ba5-9cf635fa5b870b3a-1",
-                        ))
-                        .insert_header(("baggage", format!("sentry-org_id={incoming_org_id}")))
+                    let req = headers
+                        .iter()
+                        .fold(TestRequest::get().uri("/test"), |req, header| {
+                            req.insert_header(header.clone())
+                        })
                         .to_request();
                     let res = call_service(&app, req).await;
                     assert!(res.status().is_success());
                 })
             },
+            options,
+        )
+    }
+
+    fn run_request_with_org_ids(incoming_org_id: &str, client_org_id: &str) -> Vec<Envelope> {
+        run_request_with_headers(
+            &[
+                (
+                    "sentry-trace",
+                    "09e04486820349518ac7b5d2adbf6ba5-9cf635fa5b870b3a-1".to_owned(),
+                ),
+                ("baggage", format!("sentry-org_id={incoming_org_id}")),
+            ],
             sentry::ClientOptions::new()
                 .org_id(client_org_id.parse().unwrap())
                 .strict_trace_continuation(true)
@@ -786,6 +786,66 @@ mod tests {
         assert_eq!(trace.parent_span_id, None);
     }
 
+    #[actix_web::test]
+    async fn test_transaction_continues_traceparent() {
+        let envelopes = run_request_with_headers(
+            &[(
+                "traceparent",
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".to_owned(),
+            )],
+            sentry::ClientOptions::new().traces_sample_rate(1.0),
+        );
+        let trace = trace_context_from_single_transaction(&envelopes);
+        assert_eq!(
+            trace.trace_id.to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+        assert_eq!(
+            trace.parent_span_id.map(|span_id| span_id.to_string()),
+            Some("00f067aa0ba902b7".to_owned())
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_transaction_trace_header_precedence() {
+        let headers = [
+            (
+                "sentry-trace",
+                "09e04486820349518ac7b5d2adbf6ba5-9cf635fa5b870b3a-1".to_owned(),
+            ),
+            (
+                "traceparent",
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".to_owned(),
+            ),
+        ];
+
+        let envelopes = run_request_with_headers(
+            &headers,
+            sentry::ClientOptions::new().traces_sample_rate(1.0),
+        );
+        let trace = trace_context_from_single_transaction(&envelopes);
+        assert_eq!(
+            trace.trace_id.to_string(),
+            "09e04486820349518ac7b5d2adbf6ba5"
+        );
+
+        let envelopes = run_request_with_headers(
+            &headers,
+            sentry::ClientOptions::new()
+                .traces_sample_rate(1.0)
+                .trace_header_precedence(sentry::TraceHeaderPrecedence::TraceParent),
+        );
+        let trace = trace_context_from_single_transaction(&envelopes);
+        assert_eq!(
+            trace.trace_id.to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+        assert_eq!(
+            trace.parent_span_id.map(|span_id| span_id.to_string()),
+            Some("00f067aa0ba902b7".to_owned())
+        );
+    }
+
     /// Ensures transaction name can be overridden in handler scope.
     #[actix_web::test]
     async fn test_override_transaction_name() {
@@ -10,6 +10,11 @@
 mod integration;
 mod parse;
 mod process;
+#[cfg(feature = "profiling")]
+pub mod profiling;
+mod sampler;
+mod source_context;
+mod threads;
 mod trim;
 mod utils;
 
@@ -18,6 +23,8 @@ pub use crate::integration::{
 };
 pub use crate::parse::parse_stacktrace;
 pub use crate::process::{backtrace_to_stacktrace, process_event_stacktrace};
+pub use crate::source_context::SourceContextIntegration;
+pub use crate::threads::{all_threads, ThreadHandle};
 pub use sentry_core::protocol::{Frame, Stacktrace};
 
 /// Returns the current backtrace as sentry stacktrace.
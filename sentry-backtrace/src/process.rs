@@ -1,6 +1,8 @@
 use std::borrow::Cow;
+use std::ffi::c_void;
+use std::path::Path;
 
-use backtrace::Backtrace;
+use backtrace::{Backtrace, SymbolName};
 use sentry_core::ClientOptions;
 
 use crate::trim::is_well_known_not_in_app;
@@ -85,38 +87,11 @@ pub fn backtrace_to_stacktrace(bt: &Backtrace) -> Option<Stacktrace> {
             let symbols = frame.symbols();
             symbols
                 .iter()
-                .map(move |sym| {
-                    let abs_path = sym.filename().map(|m| m.to_string_lossy().to_string());
-                    let filename = abs_path.as_ref().map(|p| filename(p).to_string());
-                    let real_symbol = sym
-                        .name()
-                        .map_or(Cow::Borrowed("<unknown>"), |n| Cow::Owned(n.to_string()));
-                    let symbol = strip_symbol(&real_symbol);
-                    let function = demangle_symbol(&symbol);
-                    Frame {
-                        symbol: if symbol != function {
-                            Some(symbol.into())
-                        } else {
-                            None
-                        },
-                        function: Some(function),
-                        instruction_addr: Some(frame.ip().into()),
-                        abs_path,
-                        filename,
-                        lineno: sym.lineno().map(u64::from),
-                        colno: None,
-                        ..Default::default()
-                    }
-
-                    // If there were no symbols at all, make sure to add at least one frame, as we
-                    // may be able to symbolicate it on the server.
-                })
+                .map(move |sym| symbol_frame(frame.ip(), sym.name(), sym.filename(), sym.lineno()))
+                // If there were no symbols at all, make sure to add at least one frame, as we
+                // may be able to symbolicate it on the server.
                 .chain(if symbols.is_empty() {
-                    Some(Frame {
-                        instruction_addr: Some(frame.ip().into()),
-                        function: Some("<unknown>".into()),
-                        ..Default::default()
-                    })
+                    Some(unknown_frame(frame.ip()))
                 } else {
                     None
                 })
@@ -124,3 +99,62 @@ pub fn backtrace_to_stacktrace(bt: &Backtrace) -> Option<Stacktrace> {
         .collect();
     Stacktrace::from_frames_reversed(frames)
 }
+
+/// Creates a frame for a symbol resolved at the instruction address `ip`.
+pub(crate) fn symbol_frame(
+    ip: *mut c_void,
+    name: Option<SymbolName<'_>>,
+    abs_path: Option<&Path>,
+    lineno: Option<u32>,
+) -> Frame {
+    let abs_path = abs_path.map(|m| m.to_string_lossy().to_string());
+    let filename = abs_path.as_ref().map(|p| filename(p).to_string());
+    let real_symbol = name.map_or(Cow::Borrowed("<unknown>"), |n| Cow::Owned(n.to_string()));
+    let symbol = strip_symbol(&real_symbol);
+    let function = demangle_symbol(&symbol);
+    Frame {
+        symbol: if symbol != function {
+            Some(symbol.into())
+        } else {
+            None
+        },
+        function: Some(function),
+        instruction_addr: Some(ip.into()),
+        abs_path,
+        filename,
+        lineno: lineno.map(u64::from),
+        colno: None,
+        ..Default::default()
+    }
+}
+
+/// Creates a frame for the instruction address `ip`, which could not be symbolicated.
+pub(crate) fn unknown_frame(ip: *mut c_void) -> Frame {
+    Frame {
+        instruction_addr: Some(ip.into()),
+        function: Some("<unknown>".into()),
+        ..Default::default()
+    }
+}
+
+/// Resolves the frames at the instruction address `ip`, innermost first.
+///
+/// There are multiple frames if functions were inlined at `ip`.
+pub(crate) fn resolve_frames(ip: usize) -> Vec<Frame> {
+    let ip = ip as *mut c_void;
+    let mut frames = Vec::new();
+    backtrace::resolve(ip, |symbol| {
+        frames.push(symbol_frame(
+            ip,
+            symbol.name(),
+            symbol.filename(),
+            symbol.lineno(),
+        ));
+    });
+    // If there were no symbols at all, make sure to add at least one frame, as we may be able to
+    // symbolicate it on the server.
+    if frames.is_empty() {
+        frames.push(unknown_frame(ip));
+    }
+    frames
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+use sentry_core::protocol::{ProfileChunk, ProfileSample, SampleProfile, ThreadMetadata};
+use sentry_core::types::Uuid;
+use sentry_core::ClientOptions;
+
+use crate::process::{process_event_stacktrace, resolve_frames};
+use crate::Stacktrace;
+
+/// Collects samples into a [`SampleProfile`], deduplicating their frames and stacks.
+#[derive(Default)]
+pub(crate) struct ChunkBuilder {
+    profile: SampleProfile,
+    /// The indexes of the frames resolved for each instruction address.
+    frame_ids: HashMap<usize, Vec<usize>>,
+    /// The index of each distinct stack.
+    stack_ids: HashMap<Vec<usize>, usize>,
+}
+
+impl ChunkBuilder {
+    /// Adds a sample of the stack with the given instruction addresses, innermost first.
+    pub(crate) fn add_sample(
+        &mut self,
+        thread_id: u64,
+        thread_name: Option<&str>,
+        timestamp: SystemTime,
+        ips: &[usize],
+    ) {
+        let mut stack = Vec::with_capacity(ips.len());
+        for &ip in ips {
+            let frames = &mut self.profile.frames;
+            let ids = self.frame_ids.entry(ip).or_insert_with(|| {
+                let start = frames.len();
+                frames.extend(resolve_frames(ip));
+                (start..frames.len()).collect()
+            });
+            stack.extend_from_slice(ids);
+        }
+
+        let stacks = &mut self.profile.stacks;
+        let stack_id = *self.stack_ids.entry(stack).or_insert_with_key(|stack| {
+            stacks.push(stack.clone());
+            stacks.len().saturating_sub(1)
+        });
+
+        let thread_id = thread_id.to_string();
+        self.profile
+            .thread_metadata
+            .entry(thread_id.clone())
+            .or_insert_with(|| ThreadMetadata {
+                name: thread_name.map(str::to_owned),
+            });
+        self.profile.samples.push(ProfileSample {
+            timestamp,
+            stack_id,
+            thread_id,
+        });
+    }
+
+    /// Creates a profile chunk from the collected samples, or `None` if there are none.
+    ///
+    /// Frames are marked as in-app based on the provided `ClientOptions`.
+    pub(crate) fn finish(self, options: &ClientOptions, profiler_id: Uuid) -> Option<ProfileChunk> {
+        let mut profile = self.profile;
+        if profile.samples.is_empty() {
+            return None;
+        }
+
+        let mut stacktrace = Stacktrace {
+            frames: profile.frames,
+            ..Default::default()
+        };
+        process_event_stacktrace(&mut stacktrace, options);
+        profile.frames = stacktrace.frames;
+
+        Some(ProfileChunk {
+            profiler_id,
+            profile,
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::c_void;
+
+    use super::*;
+
+    /// Returns an address within the given function, which resolves to the function itself.
+    fn ip(function: fn()) -> usize {
+        (function as usize).saturating_add(1)
+    }
+
+    fn sample_profile(builder: ChunkBuilder) -> SampleProfile {
+        builder
+            .finish(&ClientOptions::default(), Uuid::nil())
+            .unwrap()
+            .profile
+    }
+
+    #[test]
+    fn test_deduplicates_frames_and_stacks() {
+        let leaf = ip(test_deduplicates_frames_and_stacks);
+        let caller = ip(test_resolves_frames);
+        let timestamp = SystemTime::now();
+
+        let mut builder = ChunkBuilder::default();
+        builder.add_sample(1, Some("main"), timestamp, &[leaf, caller]);
+        builder.add_sample(1, Some("main"), timestamp, &[leaf, caller]);
+        builder.add_sample(2, None, timestamp, &[caller]);
+        let profile = sample_profile(builder);
+
+        assert_eq!(profile.stacks.len(), 2);
+        assert_eq!(profile.frames.len(), profile.stacks[0].len());
+        assert!(profile.stacks[0].ends_with(&profile.stacks[1]));
+        let samples: Vec<_> = profile
+            .samples
+            .iter()
+            .map(|sample| (sample.thread_id.as_str(), sample.stack_id))
+            .collect();
+        assert_eq!(samples, [("1", 0), ("1", 0), ("2", 1)]);
+        assert_eq!(profile.thread_metadata["1"].name.as_deref(), Some("main"));
+        assert_eq!(profile.thread_metadata["2"].name, None);
+    }
+
+    #[test]
+    fn test_resolves_frames() {
+        let ip = ip(test_resolves_frames);
+        let mut builder = ChunkBuilder::default();
+        builder.add_sample(1, None, SystemTime::now(), &[ip]);
+        let profile = sample_profile(builder);
+
+        let frame = profile.frames.last().unwrap();
+        assert_eq!(frame.instruction_addr, Some((ip as *mut c_void).into()));
+        assert!(frame
+            .function
+            .as_deref()
+            .unwrap()
+            .contains("test_resolves_frames"));
+    }
+
+    #[test]
+    fn test_empty_chunk() {
+        let builder = ChunkBuilder::default();
+        assert!(builder
+            .finish(&ClientOptions::default(), Uuid::nil())
+            .is_none());
+    }
+}
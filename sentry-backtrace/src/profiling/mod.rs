@@ -0,0 +1,250 @@
+//! Continuous CPU profiling.
+//!
+//! **Feature:** `profiling` (*disabled by default*)
+//!
+//! Threads opt into profiling by calling [`register_thread`]. While at least one thread is
+//! registered, a sampler thread captures the stacks of all registered threads every 10
+//! milliseconds, and sends them to Sentry as `profile_chunk` envelope items once a minute and when
+//! the last thread is unregistered.
+//!
+//! Whether a profiler session is recorded at all is decided when it starts, according to
+//! [`ClientOptions::profile_session_sample_rate`]. Transactions started while a session is running
+//! are linked to its profile chunks, so their flamegraphs can be shown alongside the trace.
+//!
+//! Stacks are captured by interrupting the registered threads with a `SIGPROF` signal, whose
+//! handler walks their frame pointers, so profiling is only supported on Linux and macOS on
+//! x86-64 and ARM64. Applications should be built with `-C force-frame-pointers=yes` for complete
+//! stacks. The handler is installed for the whole process and forwards other `SIGPROF` signals to
+//! a previously installed handler, but `SIGPROF` handlers installed afterwards disable profiling.
+//!
+//! # Examples
+//!
+//! ```
+//! let _profiling = sentry_backtrace::profiling::register_thread();
+//! // Work done on this thread is profiled until `_profiling` is dropped.
+//! ```
+//!
+//! [`ClientOptions::profile_session_sample_rate`]: sentry_core::ClientOptions::profile_session_sample_rate
+
+use std::marker::PhantomData;
+use std::mem;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime};
+
+use sentry_core::types::{random_uuid, Uuid};
+use sentry_core::{sentry_debug, Client, Hub};
+
+use self::chunk::ChunkBuilder;
+use crate::sampler::{self, ThreadHandle};
+
+mod chunk;
+
+/// The interval at which the stacks of the registered threads are sampled.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(10);
+/// How long to wait for a thread to handle the signal before skipping its sample.
+const SAMPLE_TIMEOUT: Duration = Duration::from_millis(5);
+/// The maximum duration covered by a single profile chunk.
+const CHUNK_DURATION: Duration = Duration::from_secs(60);
+
+static PROFILER: Mutex<Profiler> = Mutex::new(Profiler {
+    threads: Vec::new(),
+    session: None,
+});
+
+/// Registers the current thread for profiling.
+///
+/// The thread is profiled until the returned [`ThreadRegistration`] is dropped. Registering a
+/// thread multiple times is allowed, it is unregistered when the last registration is dropped.
+///
+/// The first registration starts a profiler session for the client bound to the current [`Hub`],
+/// if profiling is supported on this platform and the session is sampled. When the last
+/// registration of any thread is dropped, the session stops and its final profile chunk is sent.
+pub fn register_thread() -> ThreadRegistration {
+    let current = thread::current();
+    let id = thread_id(&current);
+
+    let mut profiler = lock_profiler();
+    let was_idle = profiler.threads.is_empty();
+    match profiler.threads.iter_mut().find(|thread| thread.id == id) {
+        Some(thread) => thread.registrations = thread.registrations.saturating_add(1),
+        None => profiler.threads.push(RegisteredThread {
+            id,
+            name: current.name().map(str::to_owned),
+            handle: ThreadHandle::current(),
+            registrations: 1,
+        }),
+    }
+    if was_idle && profiler.session.is_none() {
+        profiler.session = Hub::current().client().and_then(Session::start);
+    }
+
+    ThreadRegistration {
+        id,
+        _not_send: PhantomData,
+    }
+}
+
+/// A registration of a thread for profiling, created by [`register_thread`].
+///
+/// The thread is unregistered when the registration is dropped. Registrations cannot be sent to
+/// other threads, and must not be leaked.
+#[must_use = "the thread is unregistered when the registration is dropped"]
+#[derive(Debug)]
+pub struct ThreadRegistration {
+    id: u64,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ThreadRegistration {
+    fn drop(&mut self) {
+        let mut profiler = lock_profiler();
+        if let Some(index) = profiler.threads.iter().position(|t| t.id == self.id) {
+            let thread = &mut profiler.threads[index];
+            thread.registrations = thread.registrations.saturating_sub(1);
+            if thread.registrations == 0 {
+                profiler.threads.swap_remove(index);
+            }
+        }
+        let session = if profiler.threads.is_empty() {
+            profiler.session.take()
+        } else {
+            None
+        };
+        // The sampler thread needs the lock to finish its last sample.
+        drop(profiler);
+
+        if let Some(session) = session {
+            session.stop();
+        }
+    }
+}
+
+struct Profiler {
+    threads: Vec<RegisteredThread>,
+    session: Option<Session>,
+}
+
+struct RegisteredThread {
+    id: u64,
+    name: Option<String>,
+    handle: ThreadHandle,
+    registrations: usize,
+}
+
+/// A running profiler session, which owns the sampler thread.
+struct Session {
+    stop: Arc<StopSignal>,
+    sampler: JoinHandle<()>,
+}
+
+impl Session {
+    fn start(client: Arc<Client>) -> Option<Self> {
+        if !client.is_enabled() || !sampler::is_supported() {
+            return None;
+        }
+        if !client.sample_should_send(client.options().profile_session_sample_rate) {
+            sentry_debug!("[Profiling] profiler session was not sampled");
+            return None;
+        }
+
+        let profiler_id = random_uuid();
+        let stop = Arc::new(StopSignal::default());
+        let sampler = thread::Builder::new()
+            .name("sentry-profiler".into())
+            .spawn({
+                let stop = stop.clone();
+                move || run_sampler(&client, profiler_id, &stop)
+            })
+            .ok()?;
+
+        sentry_debug!("[Profiling] started profiler session {}", profiler_id);
+        sentry_core::profiling::set_profiler_id(Some(profiler_id));
+        Some(Self { stop, sampler })
+    }
+
+    /// Stops the session, and waits for the sampler thread to send the final chunk.
+    fn stop(self) {
+        sentry_core::profiling::set_profiler_id(None);
+        self.stop.notify();
+        if self.sampler.join().is_err() {
+            sentry_debug!("[Profiling] sampler thread panicked");
+        }
+    }
+}
+
+#[derive(Default)]
+struct StopSignal {
+    stopped: Mutex<bool>,
+    condvar: Condvar,
+}
+
+impl StopSignal {
+    fn notify(&self) {
+        *self.stopped.lock().unwrap_or_else(PoisonError::into_inner) = true;
+        self.condvar.notify_all();
+    }
+
+    /// Waits until the signal is notified or the timeout elapses, and returns whether it was
+    /// notified.
+    fn wait_timeout(&self, timeout: Duration) -> bool {
+        let stopped = self.stopped.lock().unwrap_or_else(PoisonError::into_inner);
+        let (stopped, _) = self
+            .condvar
+            .wait_timeout_while(stopped, timeout, |stopped| !*stopped)
+            .unwrap_or_else(PoisonError::into_inner);
+        *stopped
+    }
+}
+
+fn run_sampler(client: &Client, profiler_id: Uuid, stop: &StopSignal) {
+    let mut chunk = ChunkBuilder::default();
+    let mut chunk_start = Instant::now();
+    loop {
+        let stopped = stop.wait_timeout(SAMPLE_INTERVAL);
+        if !stopped {
+            sample_threads(&mut chunk);
+        }
+
+        if stopped || chunk_start.elapsed() >= CHUNK_DURATION {
+            if let Some(profile_chunk) = mem::take(&mut chunk).finish(client.options(), profiler_id)
+            {
+                client.capture_profile_chunk(profile_chunk);
+            }
+            chunk_start = Instant::now();
+        }
+        if stopped {
+            break;
+        }
+    }
+}
+
+fn sample_threads(chunk: &mut ChunkBuilder) {
+    let timestamp = SystemTime::now();
+    // Threads cannot unregister, and therefore cannot exit, while they are being sampled.
+    let samples: Vec<_> = {
+        let profiler = lock_profiler();
+        profiler
+            .threads
+            .iter()
+            .filter_map(|thread| {
+                let ips = sampler::sample_thread(&thread.handle, SAMPLE_TIMEOUT)?;
+                Some((thread.id, thread.name.clone(), ips))
+            })
+            .collect()
+    };
+    // Symbolication is slow, so it happens after releasing the lock.
+    for (thread_id, thread_name, ips) in samples {
+        chunk.add_sample(thread_id, thread_name.as_deref(), timestamp, &ips);
+    }
+}
+
+fn lock_profiler() -> MutexGuard<'static, Profiler> {
+    PROFILER.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn thread_id(thread: &thread::Thread) -> u64 {
+    // NOTE: `as_u64` is nightly only
+    // See https://github.com/rust-lang/rust/issues/67939
+    unsafe { mem::transmute(thread.id()) }
+}
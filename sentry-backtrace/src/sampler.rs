@@ -0,0 +1,424 @@
+//! Captures the stacks of other threads.
+//!
+//! The sampling thread requests a stack by storing the target thread in a global slot and sending
+//! it a `SIGPROF` signal. The signal handler then walks the frame pointers of the interrupted
+//! thread and stores the instruction addresses in the slot. The handler only performs
+//! async-signal-safe operations: it does not allocate, take locks, or call into the unwinder, and
+//! it reads stack memory in a way that cannot fault. Only one thread is sampled at a time.
+//!
+//! Walking frame pointers requires the code on the stack to maintain them. Applications should be
+//! built with `-C force-frame-pointers=yes` to get complete stacks, as stacks end at the first
+//! function which does not maintain a frame pointer.
+//!
+//! The `SIGPROF` handler is installed for the whole process the first time a thread is sampled.
+//! A previously installed handler is kept and invoked for all signals which were not sent by the
+//! sampler. Handlers installed afterwards replace the sampler's handler, and disable sampling.
+
+pub(crate) use self::imp::*;
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+mod imp {
+    use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+    use std::sync::{Mutex, OnceLock, PoisonError};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    /// The maximum number of frames captured per sample.
+    const MAX_FRAMES: usize = 128;
+
+    const IDLE: u8 = 0;
+    const REQUESTED: u8 = 1;
+    const WRITING: u8 = 2;
+    const DONE: u8 = 3;
+    /// The sampling thread gave up while the handler was writing. The handler resets the slot to
+    /// [`IDLE`] once it is done.
+    const ABANDONED: u8 = 4;
+
+    struct Slot {
+        state: AtomicU8,
+        target: AtomicUsize,
+        len: AtomicUsize,
+        ips: [AtomicUsize; MAX_FRAMES],
+    }
+
+    static SLOT: Slot = Slot {
+        state: AtomicU8::new(IDLE),
+        target: AtomicUsize::new(0),
+        len: AtomicUsize::new(0),
+        ips: [const { AtomicUsize::new(0) }; MAX_FRAMES],
+    };
+
+    /// Serializes the use of the slot by threads sampling other threads.
+    static SAMPLING: Mutex<()> = Mutex::new(());
+
+    /// The `SIGPROF` action that was installed before the sampler's handler, or `None` if the
+    /// handler could not be installed.
+    static PREVIOUS_ACTION: OnceLock<Option<libc::sigaction>> = OnceLock::new();
+
+    /// A thread which can be sampled.
+    ///
+    /// On Linux, threads are identified by their kernel thread ID, so that threads not started by
+    /// Rust can be sampled as well. On macOS, they are identified by their pthread handle.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub(crate) struct ThreadHandle(usize);
+
+    impl ThreadHandle {
+        /// Returns the handle of the current thread.
+        pub(crate) fn current() -> Self {
+            Self(current_thread())
+        }
+
+        /// Returns the handle of the thread with the given kernel thread ID.
+        #[cfg(target_os = "linux")]
+        pub(crate) fn from_tid(tid: libc::pid_t) -> Self {
+            Self(tid as usize)
+        }
+
+        /// Returns the kernel thread ID of the thread, which is only known on Linux.
+        pub(crate) fn tid(&self) -> Option<u64> {
+            cfg!(target_os = "linux").then_some(self.0 as u64)
+        }
+    }
+
+    /// Installs the signal handler if needed, and returns whether threads can be sampled.
+    ///
+    /// The handler is installed for the whole process, and chains to the previously installed
+    /// `SIGPROF` handler for signals which were not sent by the sampler.
+    pub(crate) fn is_supported() -> bool {
+        if !cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+            return false;
+        }
+        PREVIOUS_ACTION
+            .get_or_init(|| unsafe {
+                let mut action: libc::sigaction = std::mem::zeroed();
+                action.sa_sigaction = handle_sigprof
+                    as extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void)
+                    as usize;
+                action.sa_flags = libc::SA_RESTART | libc::SA_SIGINFO;
+                libc::sigemptyset(&mut action.sa_mask);
+                let mut previous: libc::sigaction = std::mem::zeroed();
+                (libc::sigaction(libc::SIGPROF, &action, &mut previous) == 0).then_some(previous)
+            })
+            .is_some()
+    }
+
+    /// Captures the instruction addresses of the stack of the given thread, innermost first.
+    ///
+    /// Returns `None` if the thread did not handle the signal within `timeout`, or if a previous
+    /// sample which was given up on is still being written.
+    pub(crate) fn sample_thread(thread: &ThreadHandle, timeout: Duration) -> Option<Vec<usize>> {
+        let _sampling = SAMPLING.lock().unwrap_or_else(PoisonError::into_inner);
+        let deadline = Instant::now().checked_add(timeout)?;
+        if SLOT.state.load(Ordering::Acquire) != IDLE {
+            // The handler of an abandoned sample has not finished yet.
+            return None;
+        }
+        SLOT.target.store(thread.0, Ordering::Relaxed);
+        SLOT.state.store(REQUESTED, Ordering::Release);
+        if !send_signal(thread.0) {
+            SLOT.state.store(IDLE, Ordering::Relaxed);
+            return None;
+        }
+
+        while SLOT.state.load(Ordering::Acquire) != DONE {
+            if Instant::now() >= deadline {
+                // The signal is ignored by the handler if it has not arrived yet. If the handler is
+                // already writing, it resets the slot when it is done.
+                let abandoned = SLOT
+                    .state
+                    .compare_exchange(REQUESTED, IDLE, Ordering::Relaxed, Ordering::Relaxed)
+                    .or_else(|_| {
+                        SLOT.state.compare_exchange(
+                            WRITING,
+                            ABANDONED,
+                            Ordering::Relaxed,
+                            Ordering::Relaxed,
+                        )
+                    });
+                if abandoned.is_ok() {
+                    return None;
+                }
+                // The handler finished in the meantime.
+                continue;
+            }
+            thread::yield_now();
+        }
+
+        let len = SLOT.len.load(Ordering::Relaxed).min(MAX_FRAMES);
+        let ips = SLOT.ips[..len]
+            .iter()
+            .map(|ip| ip.load(Ordering::Relaxed))
+            .collect();
+        SLOT.state.store(IDLE, Ordering::Release);
+        Some(ips)
+    }
+
+    extern "C" fn handle_sigprof(
+        signal: libc::c_int,
+        info: *mut libc::siginfo_t,
+        context: *mut libc::c_void,
+    ) {
+        if !is_sampler_signal(info) {
+            forward_signal(signal, info, context);
+            return;
+        }
+        if SLOT
+            .state
+            .compare_exchange(REQUESTED, WRITING, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            // A late signal for a request which was given up on.
+            return;
+        }
+        if SLOT.target.load(Ordering::Relaxed) != current_thread() {
+            // A late signal for a request which was given up on and reissued for another thread.
+            SLOT.state.store(REQUESTED, Ordering::Release);
+            return;
+        }
+
+        let errno = unsafe { *errno_location() };
+        let len = unsafe { walk_stack(context) };
+        unsafe { *errno_location() = errno };
+
+        SLOT.len.store(len, Ordering::Relaxed);
+        if SLOT
+            .state
+            .compare_exchange(WRITING, DONE, Ordering::Release, Ordering::Relaxed)
+            .is_err()
+        {
+            // The sampling thread gave up on this sample.
+            SLOT.state.store(IDLE, Ordering::Release);
+        }
+    }
+
+    /// Returns whether the signal was sent by [`sample_thread`].
+    #[cfg(target_os = "linux")]
+    fn is_sampler_signal(info: *mut libc::siginfo_t) -> bool {
+        // `tgkill` sets the sender's process ID, unlike timers or the kernel.
+        unsafe {
+            !info.is_null()
+                && (*info).si_code == libc::SI_TKILL
+                && (*info).si_pid() == libc::getpid()
+        }
+    }
+
+    /// Returns whether the signal was sent by [`sample_thread`].
+    #[cfg(target_os = "macos")]
+    fn is_sampler_signal(_info: *mut libc::siginfo_t) -> bool {
+        // `pthread_kill` does not identify the sender, so any signal arriving while a sample is
+        // pending is attributed to the sampler.
+        SLOT.state.load(Ordering::Relaxed) != IDLE
+    }
+
+    /// Invokes the `SIGPROF` handler which was installed before the sampler's handler.
+    fn forward_signal(signal: libc::c_int, info: *mut libc::siginfo_t, context: *mut libc::c_void) {
+        let Some(Some(previous)) = PREVIOUS_ACTION.get() else {
+            return;
+        };
+        let handler = previous.sa_sigaction;
+        if handler == libc::SIG_DFL || handler == libc::SIG_IGN {
+            // The default action would terminate the process, which a stray profiling signal
+            // should not do.
+            return;
+        }
+        unsafe {
+            if previous.sa_flags & libc::SA_SIGINFO != 0 {
+                let handler: extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void) =
+                    std::mem::transmute(handler);
+                handler(signal, info, context);
+            } else {
+                let handler: extern "C" fn(libc::c_int) = std::mem::transmute(handler);
+                handler(signal);
+            }
+        }
+    }
+
+    /// Walks the frame pointers of the interrupted thread, storing its instruction addresses in
+    /// the slot, and returns the number of stored addresses.
+    ///
+    /// The walk starts at the registers saved in the signal `context`, and ends at the first frame
+    /// record which is not above the previous one or cannot be read.
+    unsafe fn walk_stack(context: *mut libc::c_void) -> usize {
+        let Some(Registers { pc, fp, sp }) = registers(context) else {
+            return 0;
+        };
+        SLOT.ips[0].store(pc, Ordering::Relaxed);
+        let mut len = 1;
+        let mut fp = fp;
+        while len < MAX_FRAMES {
+            if fp < sp || !fp.is_multiple_of(std::mem::align_of::<usize>()) {
+                break;
+            }
+            // A frame record consists of the caller's frame pointer and the return address.
+            let Some([next_fp, return_address]) = read_frame_record(fp, sp) else {
+                break;
+            };
+            if return_address == 0 {
+                break;
+            }
+            SLOT.ips[len].store(return_address, Ordering::Relaxed);
+            len = len.saturating_add(1);
+            if next_fp <= fp {
+                break;
+            }
+            fp = next_fp;
+        }
+        len
+    }
+
+    /// The registers of the interrupted thread needed to walk its stack.
+    struct Registers {
+        pc: usize,
+        fp: usize,
+        sp: usize,
+    }
+
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    unsafe fn registers(context: *mut libc::c_void) -> Option<Registers> {
+        let context = context.cast::<libc::ucontext_t>().as_ref()?;
+        let gregs = &context.uc_mcontext.gregs;
+        Some(Registers {
+            pc: gregs[libc::REG_RIP as usize] as usize,
+            fp: gregs[libc::REG_RBP as usize] as usize,
+            sp: gregs[libc::REG_RSP as usize] as usize,
+        })
+    }
+
+    #[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+    unsafe fn registers(context: *mut libc::c_void) -> Option<Registers> {
+        let context = context.cast::<libc::ucontext_t>().as_ref()?;
+        let mcontext = &context.uc_mcontext;
+        Some(Registers {
+            pc: mcontext.pc as usize,
+            fp: mcontext.regs[29] as usize,
+            sp: mcontext.sp as usize,
+        })
+    }
+
+    #[cfg(all(target_os = "macos", target_arch = "x86_64"))]
+    unsafe fn registers(context: *mut libc::c_void) -> Option<Registers> {
+        let context = context.cast::<libc::ucontext_t>().as_ref()?;
+        let state = &context.uc_mcontext.as_ref()?.__ss;
+        Some(Registers {
+            pc: state.__rip as usize,
+            fp: state.__rbp as usize,
+            sp: state.__rsp as usize,
+        })
+    }
+
+    #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
+    unsafe fn registers(context: *mut libc::c_void) -> Option<Registers> {
+        let context = context.cast::<libc::ucontext_t>().as_ref()?;
+        let state = &context.uc_mcontext.as_ref()?.__ss;
+        Some(Registers {
+            pc: state.__pc as usize,
+            fp: state.__fp as usize,
+            sp: state.__sp as usize,
+        })
+    }
+
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    unsafe fn registers(_context: *mut libc::c_void) -> Option<Registers> {
+        None
+    }
+
+    /// Reads the frame record at `fp` without faulting on invalid addresses.
+    ///
+    /// On Linux, the memory is read with `process_vm_readv`, which fails instead of faulting.
+    #[cfg(target_os = "linux")]
+    unsafe fn read_frame_record(fp: usize, _sp: usize) -> Option<[usize; 2]> {
+        let mut record = [0usize; 2];
+        let size = std::mem::size_of_val(&record);
+        let local = libc::iovec {
+            iov_base: record.as_mut_ptr().cast(),
+            iov_len: size,
+        };
+        let remote = libc::iovec {
+            iov_base: fp as *mut libc::c_void,
+            iov_len: size,
+        };
+        let read = libc::process_vm_readv(libc::getpid(), &local, 1, &remote, 1, 0);
+        (read == size as isize).then_some(record)
+    }
+
+    /// Reads the frame record at `fp` without faulting on invalid addresses.
+    ///
+    /// On macOS, the record is only read if it lies within the stack of the current thread.
+    #[cfg(target_os = "macos")]
+    unsafe fn read_frame_record(fp: usize, sp: usize) -> Option<[usize; 2]> {
+        let stack_top = libc::pthread_get_stackaddr_np(libc::pthread_self()) as usize;
+        let end = fp.checked_add(2 * std::mem::size_of::<usize>())?;
+        if fp < sp || end > stack_top {
+            return None;
+        }
+        Some(std::ptr::read_volatile(fp as *const [usize; 2]))
+    }
+
+    #[cfg(target_os = "linux")]
+    fn current_thread() -> usize {
+        unsafe { libc::syscall(libc::SYS_gettid) as usize }
+    }
+
+    #[cfg(target_os = "macos")]
+    fn current_thread() -> usize {
+        unsafe { libc::pthread_self() as usize }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn send_signal(thread: usize) -> bool {
+        unsafe {
+            libc::syscall(
+                libc::SYS_tgkill,
+                libc::getpid(),
+                thread as libc::pid_t,
+                libc::SIGPROF,
+            ) == 0
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    fn send_signal(thread: usize) -> bool {
+        unsafe { libc::pthread_kill(thread as libc::pthread_t, libc::SIGPROF) == 0 }
+    }
+
+    #[cfg(target_os = "linux")]
+    unsafe fn errno_location() -> *mut libc::c_int {
+        libc::__errno_location()
+    }
+
+    #[cfg(target_os = "macos")]
+    unsafe fn errno_location() -> *mut libc::c_int {
+        libc::__error()
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+mod imp {
+    use std::time::Duration;
+
+    /// A thread which can be sampled.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub(crate) struct ThreadHandle;
+
+    impl ThreadHandle {
+        /// Returns the handle of the current thread.
+        pub(crate) fn current() -> Self {
+            Self
+        }
+
+        /// Returns the kernel thread ID of the thread, which is not known on this platform.
+        pub(crate) fn tid(&self) -> Option<u64> {
+            None
+        }
+    }
+
+    /// Returns whether threads can be sampled, which is not the case on this platform.
+    pub(crate) fn is_supported() -> bool {
+        false
+    }
+
+    /// Sampling threads is not supported on this platform.
+    pub(crate) fn sample_thread(_thread: &ThreadHandle, _timeout: Duration) -> Option<Vec<usize>> {
+        None
+    }
+}
@@ -0,0 +1,388 @@
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use sentry_core::protocol::{Event, Frame, Stacktrace};
+use sentry_core::{ClientOptions, Integration};
+
+/// Integration to add source code context to in-app stack frames.
+///
+/// For every frame marked as in-app, this integration reads the source file at the frame's
+/// `abs_path` and fills its `pre_context`, `context_line` and `post_context` with the lines
+/// around its `lineno`. This requires the sources to be available at runtime, so the integration
+/// is not enabled by default:
+///
+/// ```
+/// let integration = sentry_backtrace::SourceContextIntegration::new()
+///     .remap_path("/build/app", "/srv/app");
+/// let options = sentry_core::ClientOptions::new().add_integration(integration);
+/// ```
+///
+/// Frames are only classified as in-app by the `ProcessStacktraceIntegration`, which runs before
+/// any manually added integration. Recently read files are kept in a small cache, and files
+/// larger than [`max_file_size`](Self::max_file_size) are skipped.
+#[derive(Debug)]
+pub struct SourceContextIntegration {
+    context_lines: usize,
+    max_file_size: u64,
+    max_frames: usize,
+    path_mappings: Vec<(PathBuf, PathBuf)>,
+    cache: Mutex<SourceCache>,
+}
+
+impl Default for SourceContextIntegration {
+    fn default() -> Self {
+        Self {
+            context_lines: 5,
+            max_file_size: 1024 * 1024,
+            max_frames: 50,
+            path_mappings: Vec::new(),
+            cache: Mutex::new(SourceCache::new(32)),
+        }
+    }
+}
+
+impl SourceContextIntegration {
+    /// Creates a new Integration to add source context to in-app frames.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of lines added before and after the line of each frame.
+    ///
+    /// Defaults to `5`.
+    #[must_use]
+    pub fn context_lines(mut self, context_lines: usize) -> Self {
+        self.context_lines = context_lines;
+        self
+    }
+
+    /// Sets the maximum size in bytes of source files that are read.
+    ///
+    /// Frames in larger files get no source context. Defaults to 1 MiB.
+    #[must_use]
+    pub fn max_file_size(mut self, max_file_size: u64) -> Self {
+        self.max_file_size = max_file_size;
+        self
+    }
+
+    /// Sets the maximum number of frames per event that source context is looked up for.
+    ///
+    /// Frames closest to where the event was captured are processed first. Defaults to `50`.
+    #[must_use]
+    pub fn max_frames(mut self, max_frames: usize) -> Self {
+        self.max_frames = max_frames;
+        self
+    }
+
+    /// Sets the number of source files kept in memory.
+    ///
+    /// The least recently used file is evicted when the cache is full. Files that could not be
+    /// read are cached as well. Defaults to `32`.
+    #[must_use]
+    pub fn cache_size(self, cache_size: usize) -> Self {
+        Self {
+            cache: Mutex::new(SourceCache::new(cache_size)),
+            ..self
+        }
+    }
+
+    /// Reads the sources of frames below the `from` path from below the `to` path instead.
+    ///
+    /// This is useful if the application was built in a different directory than where its
+    /// sources are deployed. Paths are matched by whole components, and the first matching
+    /// mapping is used.
+    #[must_use]
+    pub fn remap_path(mut self, from: impl Into<PathBuf>, to: impl Into<PathBuf>) -> Self {
+        self.path_mappings.push((from.into(), to.into()));
+        self
+    }
+
+    fn add_source_context(&self, stacktrace: &mut Stacktrace, budget: &mut usize) {
+        for frame in stacktrace.frames.iter_mut().rev() {
+            if *budget == 0 {
+                return;
+            }
+            if frame.in_app != Some(true) || frame.context_line.is_some() {
+                continue;
+            }
+            let (Some(abs_path), Some(lineno)) = (&frame.abs_path, frame.lineno) else {
+                continue;
+            };
+            *budget = budget.saturating_sub(1);
+
+            let path = self.remap(Path::new(abs_path));
+            let lines = self.cache.lock().unwrap().get(path, self.max_file_size);
+            if let Some(lines) = lines {
+                fill_frame(frame, &lines, lineno, self.context_lines);
+            }
+        }
+    }
+
+    fn remap<'a>(&self, path: &'a Path) -> Cow<'a, Path> {
+        for (from, to) in &self.path_mappings {
+            if let Ok(rest) = path.strip_prefix(from) {
+                return to.join(rest).into();
+            }
+        }
+        path.into()
+    }
+}
+
+impl Integration for SourceContextIntegration {
+    fn name(&self) -> &'static str {
+        "source-context"
+    }
+
+    fn process_event(
+        &self,
+        mut event: Event<'static>,
+        _options: &ClientOptions,
+    ) -> Option<Event<'static>> {
+        let mut budget = self.max_frames;
+        for exc in event.exception.iter_mut().rev() {
+            if let Some(ref mut stacktrace) = exc.stacktrace {
+                self.add_source_context(stacktrace, &mut budget);
+            }
+        }
+        if let Some(ref mut stacktrace) = event.stacktrace {
+            self.add_source_context(stacktrace, &mut budget);
+        }
+        for th in &mut event.threads {
+            if let Some(ref mut stacktrace) = th.stacktrace {
+                self.add_source_context(stacktrace, &mut budget);
+            }
+        }
+        Some(event)
+    }
+}
+
+/// Fills the context of `frame` with the lines around the 1-based `lineno`.
+fn fill_frame(frame: &mut Frame, lines: &[String], lineno: u64, context_lines: usize) {
+    let Some(index) = usize::try_from(lineno).ok().and_then(|l| l.checked_sub(1)) else {
+        return;
+    };
+    let Some(context_line) = lines.get(index) else {
+        return;
+    };
+    let start = index.saturating_sub(context_lines);
+    let end = lines
+        .len()
+        .min(index.saturating_add(1).saturating_add(context_lines));
+    frame.pre_context = lines[start..index].to_vec();
+    frame.context_line = Some(context_line.clone());
+    frame.post_context = lines[index..end][1..].to_vec();
+}
+
+/// A least recently used cache of source file lines.
+#[derive(Debug)]
+struct SourceCache {
+    capacity: usize,
+    /// Cached files, the most recently used last. `None` marks files that could not be read.
+    entries: VecDeque<(PathBuf, Option<Arc<[String]>>)>,
+}
+
+impl SourceCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::new(),
+        }
+    }
+
+    fn get(&mut self, path: Cow<'_, Path>, max_file_size: u64) -> Option<Arc<[String]>> {
+        if let Some(index) = self.entries.iter().position(|(p, _)| *p == *path) {
+            let entry = self.entries.remove(index)?;
+            let lines = entry.1.clone();
+            self.entries.push_back(entry);
+            return lines;
+        }
+
+        let lines = read_lines(&path, max_file_size);
+        if self.capacity > 0 {
+            if self.entries.len() >= self.capacity {
+                self.entries.pop_front();
+            }
+            self.entries.push_back((path.into_owned(), lines.clone()));
+        }
+        lines
+    }
+}
+
+fn read_lines(path: &Path, max_file_size: u64) -> Option<Arc<[String]>> {
+    let metadata = fs::metadata(path).ok()?;
+    if !metadata.is_file() || metadata.len() > max_file_size {
+        return None;
+    }
+    let contents = fs::read(path).ok()?;
+    Some(
+        String::from_utf8_lossy(&contents)
+            .lines()
+            .map(str::to_owned)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use sentry_core::protocol::Exception;
+
+    use super::*;
+
+    /// Creates a fresh directory below the system temp directory.
+    fn source_dir() -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "sentry-source-context-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_source(dir: &Path, name: &str, lines: usize) -> PathBuf {
+        let path = dir.join(name);
+        let source: String = (1..=lines).map(|i| format!("line {i}\n")).collect();
+        fs::write(&path, source).unwrap();
+        path
+    }
+
+    fn frame(path: &Path, lineno: u64, in_app: bool) -> Frame {
+        Frame {
+            abs_path: Some(path.to_string_lossy().into_owned()),
+            lineno: Some(lineno),
+            in_app: Some(in_app),
+            ..Default::default()
+        }
+    }
+
+    fn event_with_frames(frames: Vec<Frame>) -> Event<'static> {
+        Event {
+            exception: vec![Exception {
+                ty: "Error".into(),
+                stacktrace: Some(Stacktrace {
+                    frames,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }]
+            .into(),
+            ..Default::default()
+        }
+    }
+
+    fn process(integration: &SourceContextIntegration, frames: Vec<Frame>) -> Vec<Frame> {
+        let event = integration
+            .process_event(event_with_frames(frames), &ClientOptions::default())
+            .unwrap();
+        event.exception.values[0]
+            .stacktrace
+            .as_ref()
+            .unwrap()
+            .frames
+            .clone()
+    }
+
+    #[test]
+    fn adds_context_to_in_app_frames() {
+        let dir = source_dir();
+        let path = write_source(&dir, "main.rs", 10);
+
+        let integration = SourceContextIntegration::new().context_lines(2);
+        let frames = process(
+            &integration,
+            vec![
+                frame(&path, 2, true),
+                frame(&path, 9, false),
+                frame(&path, 10, true),
+            ],
+        );
+
+        assert_eq!(frames[0].pre_context, ["line 1"]);
+        assert_eq!(frames[0].context_line.as_deref(), Some("line 2"));
+        assert_eq!(frames[0].post_context, ["line 3", "line 4"]);
+
+        assert_eq!(frames[1].context_line, None);
+
+        assert_eq!(frames[2].pre_context, ["line 8", "line 9"]);
+        assert_eq!(frames[2].context_line.as_deref(), Some("line 10"));
+        assert!(frames[2].post_context.is_empty());
+
+        let frames = process(&integration, vec![frame(&path, 11, true)]);
+        assert_eq!(frames[0].context_line, None);
+
+        let integration = SourceContextIntegration::new().context_lines(usize::MAX);
+        let frames = process(&integration, vec![frame(&path, 5, true)]);
+        assert_eq!(frames[0].pre_context.len(), 4);
+        assert_eq!(frames[0].post_context.len(), 5);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn respects_budgets() {
+        let dir = source_dir();
+        let small = write_source(&dir, "small.rs", 3);
+        let large = write_source(&dir, "large.rs", 1000);
+
+        let integration = SourceContextIntegration::new()
+            .max_file_size(100)
+            .max_frames(2);
+        let frames = process(
+            &integration,
+            vec![
+                frame(&small, 1, true),
+                frame(&small, 2, true),
+                frame(&large, 1, true),
+            ],
+        );
+
+        // The newest frame uses up the budget even though its file is too large.
+        assert_eq!(frames[0].context_line, None);
+        assert_eq!(frames[1].context_line.as_deref(), Some("line 2"));
+        assert_eq!(frames[2].context_line, None);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn remaps_paths() {
+        let dir = source_dir();
+        write_source(&dir, "lib.rs", 3);
+
+        let integration = SourceContextIntegration::new().remap_path("/build/app/src", &dir);
+        let frames = process(
+            &integration,
+            vec![frame(Path::new("/build/app/src/lib.rs"), 2, true)],
+        );
+
+        assert_eq!(frames[0].context_line.as_deref(), Some("line 2"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn evicts_least_recently_used_files() {
+        let dir = source_dir();
+        let a = write_source(&dir, "a.rs", 1);
+        let b = write_source(&dir, "b.rs", 1);
+        let c = write_source(&dir, "c.rs", 1);
+
+        let mut cache = SourceCache::new(2);
+        cache.get(a.as_path().into(), u64::MAX);
+        cache.get(b.as_path().into(), u64::MAX);
+        cache.get(a.as_path().into(), u64::MAX);
+        cache.get(c.as_path().into(), u64::MAX);
+
+        let cached: Vec<_> = cache.entries.iter().map(|(p, _)| p.clone()).collect();
+        assert_eq!(cached, [a, c]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
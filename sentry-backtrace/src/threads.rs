@@ -0,0 +1,271 @@
+//! Captures the stacks of other threads of the process.
+
+use std::thread;
+use std::time::Duration;
+
+use sentry_core::protocol::{Thread, ThreadId};
+
+use crate::process::resolve_frames;
+use crate::sampler;
+use crate::{current_stacktrace, Stacktrace};
+
+/// A handle to a thread, which allows capturing its stack from other threads.
+///
+/// The stack is captured by briefly interrupting the thread with a `SIGPROF` signal, which is
+/// handled by walking the frame pointers of the thread. Stacks end at the first function which
+/// does not maintain a frame pointer, so applications should be built with
+/// `-C force-frame-pointers=yes` for complete stacks.
+///
+/// The `SIGPROF` handler is installed for the whole process when the first stack is captured.
+/// `SIGPROF` signals which were not sent to capture a stack are forwarded to the previously
+/// installed handler, but handlers installed afterwards prevent capturing stacks.
+///
+/// Capturing the stacks of other threads is only supported on Linux and macOS on x86-64 and
+/// ARM64.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// let handle = sentry_backtrace::ThreadHandle::current();
+/// let thread = std::thread::spawn(move || handle.capture(Duration::from_millis(50)))
+///     .join()
+///     .unwrap();
+/// assert!(!thread.current);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ThreadHandle {
+    handle: sampler::ThreadHandle,
+    thread: thread::Thread,
+}
+
+impl ThreadHandle {
+    /// Returns a handle to the current thread.
+    pub fn current() -> Self {
+        Self {
+            handle: sampler::ThreadHandle::current(),
+            thread: thread::current(),
+        }
+    }
+
+    /// Captures information about the thread, including its stacktrace.
+    ///
+    /// The thread is identified by its kernel thread ID on Linux, and named after the name of the
+    /// Rust thread. If the thread does not handle the signal within `timeout`, for example because
+    /// it exited, or capturing stacks is not supported on this platform, no stacktrace is attached.
+    pub fn capture(&self, timeout: Duration) -> Thread {
+        let current = self.handle == sampler::ThreadHandle::current();
+        let stacktrace = if current {
+            current_stacktrace()
+        } else if sampler::is_supported() {
+            sampler::sample_thread(&self.handle, timeout).and_then(|ips| ips_to_stacktrace(&ips))
+        } else {
+            None
+        };
+        Thread {
+            id: self.handle.tid().map(ThreadId::Int),
+            name: self.thread.name().map(str::to_owned),
+            stacktrace,
+            current,
+            ..Default::default()
+        }
+    }
+}
+
+/// Captures information about all threads of the current process.
+///
+/// The current thread is marked as `current`, and its stacktrace is attached if
+/// `with_current_stack` is `true`. The stacks of the other threads are captured by briefly
+/// interrupting them with a `SIGPROF` signal, and threads which do not handle the signal in time
+/// are returned without a stacktrace. Each thread is waited for at most 50 milliseconds, and all
+/// threads for at most half a second. The stacks are symbolicated once all threads were sampled,
+/// which takes additional time. See [`ThreadHandle`] for the requirements of capturing stacks this
+/// way.
+///
+/// Threads are identified by their kernel thread IDs. Other threads are named after the names the
+/// kernel keeps for them, which are truncated to 15 bytes.
+///
+/// Capturing other threads is only supported on Linux. On other platforms, only the current
+/// thread is returned, like by [`current_thread`](crate::current_thread).
+pub fn all_threads(with_current_stack: bool) -> Vec<Thread> {
+    imp::all_threads(with_current_stack)
+}
+
+/// Creates a stacktrace from instruction addresses, innermost first.
+fn ips_to_stacktrace(ips: &[usize]) -> Option<Stacktrace> {
+    let frames = ips.iter().flat_map(|&ip| resolve_frames(ip)).collect();
+    Stacktrace::from_frames_reversed(frames)
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::fs;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use sentry_core::protocol::Thread;
+
+    use super::ips_to_stacktrace;
+    use crate::sampler::{self, ThreadHandle};
+    use crate::{current_stacktrace, current_thread};
+
+    /// How long to wait for a thread to handle the signal before giving up on its stack.
+    const THREAD_TIMEOUT: Duration = Duration::from_millis(50);
+    /// How long to spend on sampling the stacks of all threads. Threads which are left when this
+    /// has passed are returned without a stacktrace. The stacks are symbolicated afterwards.
+    const TOTAL_TIMEOUT: Duration = Duration::from_millis(500);
+
+    pub(super) fn all_threads(with_current_stack: bool) -> Vec<Thread> {
+        let Ok(tasks) = fs::read_dir("/proc/self/task") else {
+            return vec![current_thread(with_current_stack)];
+        };
+        let mut tids: Vec<libc::pid_t> = tasks
+            .filter_map(|task| task.ok()?.file_name().to_str()?.parse().ok())
+            .collect();
+        tids.sort_unstable();
+
+        let current = ThreadHandle::current();
+        let supported = sampler::is_supported();
+        let start = Instant::now();
+        // Sample all threads before symbolicating any stack, which is slow.
+        let samples: Vec<_> = tids
+            .into_iter()
+            .map(|tid| {
+                let handle = ThreadHandle::from_tid(tid);
+                let is_current = handle == current;
+                let timeout = TOTAL_TIMEOUT
+                    .saturating_sub(start.elapsed())
+                    .min(THREAD_TIMEOUT);
+                let ips = (!is_current && supported && !timeout.is_zero())
+                    .then(|| sampler::sample_thread(&handle, timeout))
+                    .flatten();
+                (tid, is_current, ips)
+            })
+            .collect();
+
+        samples
+            .into_iter()
+            .map(|(tid, is_current, ips)| {
+                let (name, stacktrace) = if is_current {
+                    let name = thread::current().name().map(str::to_owned);
+                    let stacktrace = with_current_stack.then(current_stacktrace).flatten();
+                    (name.or_else(|| thread_name(tid)), stacktrace)
+                } else {
+                    let stacktrace = ips.and_then(|ips| ips_to_stacktrace(&ips));
+                    (thread_name(tid), stacktrace)
+                };
+                Thread {
+                    id: Some(tid.into()),
+                    name,
+                    stacktrace,
+                    current: is_current,
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
+    fn thread_name(tid: libc::pid_t) -> Option<String> {
+        let name = fs::read_to_string(format!("/proc/self/task/{tid}/comm")).ok()?;
+        Some(name.trim_end().to_owned())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    use sentry_core::protocol::Thread;
+
+    use crate::current_thread;
+
+    pub(super) fn all_threads(with_current_stack: bool) -> Vec<Thread> {
+        vec![current_thread(with_current_stack)]
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{mpsc, Arc, Barrier};
+    use std::thread;
+    use std::time::Duration;
+
+    use sentry_core::protocol::Thread;
+
+    use super::{all_threads, ThreadHandle};
+
+    /// Waits for the dump by sleeping, as blocking in the optimized standard library would end
+    /// the frame pointer chain before this function.
+    #[inline(never)]
+    fn wait_for_dump(parked: &Barrier, done: &AtomicBool) {
+        parked.wait();
+        while !done.load(Ordering::Acquire) {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// Runs `capture` while a thread named `name` waits in `wait_for_dump`.
+    ///
+    /// Tests run in parallel, so every test names its worker differently.
+    fn with_worker<R>(name: &str, capture: impl FnOnce(&ThreadHandle) -> R) -> R {
+        let parked = Arc::new(Barrier::new(2));
+        let done = Arc::new(AtomicBool::new(false));
+        let (handle_sender, handle) = mpsc::channel();
+        let worker = thread::Builder::new()
+            .name(name.into())
+            .spawn({
+                let parked = parked.clone();
+                let done = done.clone();
+                move || {
+                    handle_sender.send(ThreadHandle::current()).unwrap();
+                    wait_for_dump(&parked, &done)
+                }
+            })
+            .unwrap();
+        let handle = handle.recv().unwrap();
+        parked.wait();
+
+        let result = capture(&handle);
+        done.store(true, Ordering::Release);
+        worker.join().unwrap();
+        result
+    }
+
+    fn assert_waiting_worker(worker: &Thread) {
+        assert!(!worker.current);
+        let frames = &worker.stacktrace.as_ref().unwrap().frames;
+        assert!(frames.iter().any(|frame| frame
+            .function
+            .as_deref()
+            .is_some_and(|function| function.contains("wait_for_dump"))));
+    }
+
+    #[test]
+    fn test_captures_other_threads() {
+        let threads = with_worker("dump-all-worker", |_| all_threads(false));
+
+        let current: Vec<_> = threads.iter().filter(|thread| thread.current).collect();
+        assert_eq!(current.len(), 1);
+        assert!(current[0].stacktrace.is_none());
+
+        let worker = threads
+            .iter()
+            .find(|thread| thread.name.as_deref() == Some("dump-all-worker"))
+            .expect("the worker thread should be captured");
+        assert_waiting_worker(worker);
+    }
+
+    #[test]
+    fn test_captures_thread_handle() {
+        let worker = with_worker("dump-one-worker", |handle| {
+            handle.capture(Duration::from_millis(50))
+        });
+        assert_eq!(worker.name.as_deref(), Some("dump-one-worker"));
+        assert!(worker.id.is_some());
+        assert_waiting_worker(&worker);
+
+        let current = ThreadHandle::current().capture(Duration::ZERO);
+        assert!(current.current);
+        assert!(current.stacktrace.is_some());
+    }
+}
@@ -1,16 +1,24 @@
 use std::borrow::Cow;
+use std::sync::OnceLock;
 
 use sentry_core::protocol::map::Entry;
-use sentry_core::protocol::Event;
+use sentry_core::protocol::{Context, DeviceContext, Event};
 use sentry_core::{ClientOptions, Integration};
 
-use crate::utils::{device_context, os_context, rust_context, server_name};
+use crate::utils::{
+    container_context, kubernetes_context, os_context, rust_context, server_name,
+    static_device_context, DeviceRefresher,
+};
 
 /// Adds Contexts to Sentry Events.
 ///
 /// This integration is enabled by default in `sentry` and adds `device`, `os`
 /// and `rust` contexts to Events, and also sets a `server_name` if it is not
-/// already defined.
+/// already defined. On Linux, it also adds `container` and `kubernetes`
+/// contexts when running in a container or a Kubernetes pod.
+///
+/// Information which does not change while the process runs is collected once,
+/// whereas volatile values such as the free memory are refreshed for every event.
 ///
 /// See the [Contexts Interface] documentation for more info.
 ///
@@ -27,6 +35,17 @@ pub struct ContextIntegration {
     add_os: bool,
     add_rust: bool,
     add_device: bool,
+    add_container: bool,
+    cache: OnceLock<CachedContexts>,
+}
+
+/// The contexts which are collected only once.
+#[derive(Debug)]
+struct CachedContexts {
+    device: DeviceContext,
+    device_refresher: DeviceRefresher,
+    container: Option<Context>,
+    kubernetes: Option<Context>,
 }
 
 impl Default for ContextIntegration {
@@ -35,6 +54,8 @@ impl Default for ContextIntegration {
             add_os: true,
             add_rust: true,
             add_device: true,
+            add_container: true,
+            cache: OnceLock::new(),
         }
     }
 }
@@ -64,6 +85,25 @@ impl ContextIntegration {
         self.add_device = add_device;
         self
     }
+
+    /// Add `container` and `kubernetes` contexts, enabled by default.
+    ///
+    /// These contexts are only added on Linux, when running in a container or
+    /// Kubernetes pod respectively.
+    #[must_use]
+    pub fn add_container(mut self, add_container: bool) -> Self {
+        self.add_container = add_container;
+        self
+    }
+
+    fn cache(&self) -> &CachedContexts {
+        self.cache.get_or_init(|| CachedContexts {
+            device: static_device_context(),
+            device_refresher: DeviceRefresher::new(),
+            container: container_context(),
+            kubernetes: kubernetes_context(),
+        })
+    }
 }
 
 impl Integration for ContextIntegration {
@@ -99,7 +139,25 @@ impl Integration for ContextIntegration {
             event
                 .contexts
                 .entry("device".to_string())
-                .or_insert_with(device_context);
+                .or_insert_with(|| {
+                    let cache = self.cache();
+                    let mut device = cache.device.clone();
+                    cache.device_refresher.refresh(&mut device);
+                    device.into()
+                });
+        }
+        if self.add_container {
+            let cache = self.cache();
+            for (key, context) in [
+                ("container", &cache.container),
+                ("kubernetes", &cache.kubernetes),
+            ] {
+                if let (Entry::Vacant(entry), Some(context)) =
+                    (event.contexts.entry(key.to_string()), context)
+                {
+                    entry.insert(context.clone());
+                }
+            }
         }
 
         Some(event)
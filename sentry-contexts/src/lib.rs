@@ -2,7 +2,8 @@
 //!
 //! This integration is enabled by default in `sentry` and adds `device`, `os`
 //! and `rust` contexts to Events, and also sets a `server_name` if it is not
-//! already defined.
+//! already defined. On Linux, it also adds `container` and `kubernetes`
+//! contexts when running in a container or a Kubernetes pod.
 //!
 //! See the [Contexts Interface] documentation for more info.
 //!
@@ -20,6 +21,8 @@
 #![warn(missing_docs)]
 
 mod integration;
+#[cfg(target_os = "linux")]
+mod linux;
 /// Contains functions to retrieve various contexts that can be useful
 /// to attach to events
 pub mod utils;
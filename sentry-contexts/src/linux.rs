@@ -0,0 +1,575 @@
+//! Reads device, container and Kubernetes information from the `/proc` and `/sys` filesystems.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use sentry_core::protocol::{DeviceContext, Map, Value};
+
+/// DMI values which firmware vendors use as placeholders for unset fields.
+const DMI_PLACEHOLDERS: &[&str] = &[
+    "To be filled by O.E.M.",
+    "System Product Name",
+    "Default string",
+    "Not Specified",
+    "None",
+];
+
+/// The keys of `/proc/cpuinfo` describing the processor, in order of preference.
+const CPU_DESCRIPTION_KEYS: &[&str] = &["model name", "Processor", "Hardware", "cpu model", "cpu"];
+
+/// Runtimes which name a container's cgroup `<prefix><id>`, optionally with a `.scope` suffix.
+const SCOPE_RUNTIMES: &[(&str, &str)] = &[
+    ("docker-", "docker"),
+    ("libpod-", "podman"),
+    ("crio-", "cri-o"),
+    ("cri-containerd-", "containerd"),
+];
+
+/// The memory cgroup of the process, which is resolved once.
+#[derive(Debug, Default)]
+pub(crate) struct MemoryCgroup {
+    /// The memory limit of the cgroup, if it is below the total memory.
+    limit: Option<u64>,
+    /// The file with the current memory usage of the cgroup, if it has a limit.
+    usage: Option<PathBuf>,
+}
+
+impl MemoryCgroup {
+    /// Returns the memory which can still be used before reaching the limit.
+    fn available(&self) -> Option<u64> {
+        let usage: u64 = fs::read_to_string(self.usage.as_ref()?)
+            .ok()?
+            .trim()
+            .parse()
+            .ok()?;
+        Some(self.limit?.saturating_sub(usage))
+    }
+}
+
+/// A view of the `/proc` and `/sys` filesystems below a root directory.
+#[derive(Debug)]
+pub(crate) struct System {
+    root: PathBuf,
+}
+
+impl System {
+    /// Creates a view of the filesystems of the running system.
+    pub(crate) fn new() -> Self {
+        Self { root: "/".into() }
+    }
+
+    /// Creates a view of filesystems mounted below `root`, such as a fixture directory.
+    #[cfg(test)]
+    fn with_root(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, path: &str) -> PathBuf {
+        self.root.join(path.trim_start_matches('/'))
+    }
+
+    fn read(&self, path: &str) -> Option<String> {
+        fs::read_to_string(self.path(path)).ok()
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        self.path(path).exists()
+    }
+
+    /// Fills the information of `device` which does not change while the process runs.
+    pub(crate) fn fill_device(&self, device: &mut DeviceContext) {
+        if let Some(meminfo) = self.read("/proc/meminfo") {
+            device.memory_size = meminfo_value(&meminfo, "MemTotal");
+        }
+        if let Some(limit) = self.memory_cgroup().limit {
+            device.usable_memory = Some(device.memory_size.map_or(limit, |total| total.min(limit)));
+        } else {
+            device.usable_memory = device.memory_size;
+        }
+
+        if let Some(cpuinfo) = self.read("/proc/cpuinfo") {
+            let processor_count = cpuinfo
+                .lines()
+                .filter(|line| field(line).is_some_and(|(key, _)| key == "processor"))
+                .count();
+            device.processor_count = u32::try_from(processor_count).ok().filter(|&n| n > 0);
+            device.cpu_description = CPU_DESCRIPTION_KEYS
+                .iter()
+                .find_map(|key| cpuinfo_value(&cpuinfo, key))
+                .map(str::to_owned);
+            device.processor_frequency =
+                cpuinfo_value(&cpuinfo, "cpu MHz").and_then(|mhz| mhz.parse().ok());
+        }
+        if device.processor_frequency.is_none() {
+            device.processor_frequency = self
+                .read("/sys/devices/system/cpu/cpu0/cpufreq/cpuinfo_max_freq")
+                .and_then(|khz| khz.trim().parse::<f64>().ok())
+                .map(|khz| khz / 1000.0);
+        }
+
+        device.boot_time = self.read("/proc/stat").and_then(|stat| {
+            let seconds = stat.lines().find_map(|line| line.strip_prefix("btime "))?;
+            SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(seconds.trim().parse().ok()?))
+        });
+
+        if device.model.is_none() {
+            device.model = self.dmi("product_name");
+        }
+        if device.family.is_none() {
+            device.family = self.dmi("product_family");
+        }
+    }
+
+    /// Resolves the memory cgroup of the process, and its limit.
+    pub(crate) fn memory_cgroup(&self) -> MemoryCgroup {
+        let Some(dir) = self.cgroup_dir("memory") else {
+            return MemoryCgroup::default();
+        };
+        let limit = self.memory_limit(&dir);
+        let usage = limit.and_then(|_| {
+            ["memory.current", "memory.usage_in_bytes"]
+                .into_iter()
+                .map(|file| dir.join(file))
+                .find(|path| path.is_file())
+        });
+        MemoryCgroup { limit, usage }
+    }
+
+    /// Updates the information of `device` which changes while the process runs.
+    ///
+    /// This only reads the available memory of the system and the memory usage of `memory`.
+    pub(crate) fn refresh_device(&self, memory: &MemoryCgroup, device: &mut DeviceContext) {
+        let available = self.read("/proc/meminfo").and_then(|meminfo| {
+            meminfo_value(&meminfo, "MemAvailable").or_else(|| meminfo_value(&meminfo, "MemFree"))
+        });
+        let cgroup_available = memory.available();
+        device.free_memory = match (available, cgroup_available) {
+            (Some(available), Some(cgroup_available)) => Some(available.min(cgroup_available)),
+            (available, cgroup_available) => available.or(cgroup_available),
+        };
+    }
+
+    /// Returns the `container` context if the process runs in a container.
+    pub(crate) fn container_context(&self) -> Option<Map<String, Value>> {
+        let cgroup = self.read("/proc/self/cgroup").unwrap_or_default();
+        let runtime = self.container_runtime(&cgroup);
+        let id = container_id(&cgroup).or_else(|| {
+            let mountinfo = self.read("/proc/self/mountinfo")?;
+            mountinfo_container_id(&mountinfo)
+        });
+        if runtime.is_none() && id.is_none() {
+            return None;
+        }
+
+        let mut context = Map::new();
+        if let Some(id) = id {
+            context.insert("id".into(), id.into());
+        }
+        if let Some(runtime) = runtime {
+            context.insert("runtime".into(), runtime.into());
+        }
+        if let Some(limit) = self.memory_cgroup().limit {
+            context.insert("memory_limit".into(), limit.into());
+        }
+        if let Some(limit) = self.cpu_limit() {
+            context.insert("cpu_limit".into(), limit.into());
+        }
+        Some(context)
+    }
+
+    /// Returns the `kubernetes` context if the process runs in a Kubernetes pod.
+    ///
+    /// Values which are only available through the environment are looked up with `env`, using
+    /// the variable names of the Kubernetes documentation for exposing pod information.
+    pub(crate) fn kubernetes_context(
+        &self,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Option<Map<String, Value>> {
+        let namespace = self
+            .read("/var/run/secrets/kubernetes.io/serviceaccount/namespace")
+            .map(|namespace| namespace.trim().to_owned());
+        if namespace.is_none() && env("KUBERNETES_SERVICE_HOST").is_none() {
+            return None;
+        }
+
+        let mut context = Map::new();
+        if let Some(pod_name) = env("POD_NAME").or_else(|| env("HOSTNAME")) {
+            context.insert("pod_name".into(), pod_name.into());
+        }
+        if let Some(namespace) = env("POD_NAMESPACE").or(namespace) {
+            context.insert("namespace".into(), namespace.into());
+        }
+        if let Some(pod_uid) = self.read("/proc/self/cgroup").and_then(|c| pod_uid(&c)) {
+            context.insert("pod_uid".into(), pod_uid.into());
+        }
+        if let Some(node_name) = env("NODE_NAME") {
+            context.insert("node_name".into(), node_name.into());
+        }
+        Some(context)
+    }
+
+    fn dmi(&self, name: &str) -> Option<String> {
+        let value = self.read(&format!("/sys/class/dmi/id/{name}"))?;
+        let value = value.trim();
+        (!value.is_empty() && !DMI_PLACEHOLDERS.contains(&value)).then(|| value.to_owned())
+    }
+
+    fn container_runtime(&self, cgroup: &str) -> Option<&'static str> {
+        if self.exists("/.dockerenv") {
+            return Some("docker");
+        }
+        if self.exists("/run/.containerenv") {
+            return Some("podman");
+        }
+        cgroup_runtime(cgroup)
+    }
+
+    /// Returns the directory of the cgroup the process belongs to for the given v1 controller,
+    /// or of its unified v2 cgroup.
+    fn cgroup_dir(&self, controller: &str) -> Option<PathBuf> {
+        let cgroup = self.read("/proc/self/cgroup")?;
+        let (hierarchy, path) = cgroup
+            .lines()
+            .find_map(|line| {
+                let mut parts = line.splitn(3, ':');
+                let (_, controllers, path) = (parts.next()?, parts.next()?, parts.next()?);
+                if controllers.split(',').any(|c| c == controller) {
+                    Some((controllers, path))
+                } else {
+                    None
+                }
+            })
+            .or_else(|| {
+                let path = cgroup.lines().find_map(|line| line.strip_prefix("0::"))?;
+                Some(("", path))
+            })?;
+
+        let hierarchy = self.path("/sys/fs/cgroup").join(hierarchy);
+        // Without a cgroup namespace, the path is relative to the host's hierarchy, whereas the
+        // container only sees its own cgroup mounted at the root of the hierarchy.
+        let dir = hierarchy.join(path.trim_start_matches('/'));
+        Some(if dir.is_dir() { dir } else { hierarchy })
+    }
+
+    fn read_cgroup(&self, controller: &str, file: &str) -> Option<String> {
+        let dir = self.cgroup_dir(controller)?;
+        let value = fs::read_to_string(dir.join(file)).ok()?;
+        Some(value.trim().to_owned())
+    }
+
+    /// Returns the memory limit of the cgroup in `dir`, if it is below the total memory.
+    fn memory_limit(&self, dir: &Path) -> Option<u64> {
+        let limit = ["memory.max", "memory.limit_in_bytes"]
+            .into_iter()
+            .find_map(|file| fs::read_to_string(dir.join(file)).ok())?
+            .trim()
+            .parse::<u64>()
+            .ok()?;
+        // Unlimited cgroup v1 limits are reported as a huge number rather than `max`.
+        let total = self
+            .read("/proc/meminfo")
+            .and_then(|meminfo| meminfo_value(&meminfo, "MemTotal"));
+        total.is_none_or(|total| limit < total).then_some(limit)
+    }
+
+    /// Returns the CPU limit of the process' cgroup, in CPUs.
+    fn cpu_limit(&self) -> Option<f64> {
+        let (quota, period) = match self.read_cgroup("cpu", "cpu.max") {
+            Some(max) => {
+                let (quota, period) = max.split_once(' ')?;
+                (quota.parse::<f64>().ok()?, period.parse::<f64>().ok()?)
+            }
+            None => (
+                self.read_cgroup("cpu", "cpu.cfs_quota_us")?.parse().ok()?,
+                self.read_cgroup("cpu", "cpu.cfs_period_us")?.parse().ok()?,
+            ),
+        };
+        (quota > 0.0 && period > 0.0).then(|| quota / period)
+    }
+}
+
+/// Splits a `key: value` line of `/proc/meminfo` or `/proc/cpuinfo`.
+fn field(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once(':')?;
+    Some((key.trim(), value.trim()))
+}
+
+/// Returns a value of `/proc/meminfo` in bytes.
+fn meminfo_value(meminfo: &str, key: &str) -> Option<u64> {
+    let value = meminfo
+        .lines()
+        .filter_map(field)
+        .find_map(|(k, value)| (k == key).then_some(value))?;
+    let kib: u64 = value.trim_end_matches("kB").trim().parse().ok()?;
+    kib.checked_mul(1024)
+}
+
+/// Returns the first non-empty value of `/proc/cpuinfo` with the given key.
+fn cpuinfo_value<'a>(cpuinfo: &'a str, key: &str) -> Option<&'a str> {
+    cpuinfo
+        .lines()
+        .filter_map(field)
+        .find_map(|(k, value)| (k == key && !value.is_empty()).then_some(value))
+}
+
+/// Returns whether `id` looks like the 64 character hex ID of a container.
+fn is_container_id(id: &str) -> bool {
+    id.len() == 64 && id.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Finds a container ID in the cgroup paths of `/proc/self/cgroup`.
+///
+/// Depending on the runtime and cgroup driver, the ID is a path segment on its own, as in
+/// `/docker/<id>`, or wrapped in a systemd scope, as in `/docker-<id>.scope`.
+fn container_id(cgroup: &str) -> Option<String> {
+    cgroup
+        .lines()
+        .filter_map(|line| line.splitn(3, ':').nth(2))
+        .flat_map(|path| path.rsplit('/'))
+        .map(|segment| segment.trim_end_matches(".scope"))
+        .map(|segment| segment.rsplit_once('-').map_or(segment, |(_, id)| id))
+        .find(|segment| is_container_id(segment))
+        .map(str::to_owned)
+}
+
+/// Finds the container runtime in the cgroup paths of `/proc/self/cgroup`.
+///
+/// Runtimes are recognized by the path segments naming the container's cgroup, as in
+/// `/docker/<id>`, `/docker-<id>.scope` or `/lxc/<name>`, so that host services such as
+/// `docker.service` are not mistaken for containers.
+fn cgroup_runtime(cgroup: &str) -> Option<&'static str> {
+    cgroup
+        .lines()
+        .filter_map(|line| line.splitn(3, ':').nth(2))
+        .find_map(|path| {
+            let segments: Vec<&str> = path.split('/').collect();
+            segments.iter().enumerate().find_map(|(index, segment)| {
+                let next = segments.get(index.saturating_add(1)).copied();
+                match *segment {
+                    "docker" if next.is_some_and(is_container_id) => return Some("docker"),
+                    "lxc" if next.is_some_and(|name| !name.is_empty()) => return Some("lxc"),
+                    _ if segment.starts_with("lxc.payload.") => return Some("lxc"),
+                    _ => {}
+                }
+                let segment = segment.trim_end_matches(".scope");
+                SCOPE_RUNTIMES.iter().find_map(|(prefix, runtime)| {
+                    let id = segment.strip_prefix(prefix)?;
+                    is_container_id(id).then_some(*runtime)
+                })
+            })
+        })
+}
+
+/// Finds a container ID in the files mounted from the runtime's container directory, such as
+/// `/var/lib/docker/containers/<id>/hostname`.
+///
+/// This is needed with cgroup namespaces, which hide the container's cgroup path.
+fn mountinfo_container_id(mountinfo: &str) -> Option<String> {
+    mountinfo
+        .lines()
+        .flat_map(|line| line.split_whitespace())
+        .filter_map(|path| path.split_once("/containers/").map(|(_, rest)| rest))
+        .filter_map(|rest| rest.split('/').next())
+        .find(|id| is_container_id(id))
+        .map(str::to_owned)
+}
+
+/// Finds the UID of the Kubernetes pod in the cgroup paths of `/proc/self/cgroup`.
+///
+/// The cgroupfs driver uses segments like `pod<uid>`, and the systemd driver uses segments like
+/// `kubepods-burstable-pod<uid>.slice`, with underscores in place of the dashes of the UID.
+fn pod_uid(cgroup: &str) -> Option<String> {
+    cgroup
+        .lines()
+        .filter_map(|line| line.splitn(3, ':').nth(2))
+        .flat_map(|path| path.split('/'))
+        .filter_map(|segment| {
+            let segment = segment.trim_end_matches(".slice");
+            let (_, uid) = segment.rsplit_once("pod")?;
+            let uid = uid.replace('_', "-");
+            (uid.len() == 36 && uid.bytes().all(|b| b.is_ascii_hexdigit() || b == b'-'))
+                .then_some(uid)
+        })
+        .next()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    fn fixture(name: &str) -> System {
+        System::with_root(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/fixtures/linux")
+                .join(name),
+        )
+    }
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (*value).to_owned())
+        }
+    }
+
+    #[test]
+    fn test_host_device() {
+        let system = fixture("host");
+        let mut device = DeviceContext::default();
+        system.fill_device(&mut device);
+        system.refresh_device(&system.memory_cgroup(), &mut device);
+
+        assert_eq!(device.memory_size, Some(16_318_504 * 1024));
+        assert_eq!(device.usable_memory, Some(16_318_504 * 1024));
+        assert_eq!(device.free_memory, Some(9_834_188 * 1024));
+        assert_eq!(device.processor_count, Some(4));
+        assert_eq!(
+            device.cpu_description.as_deref(),
+            Some("Intel(R) Core(TM) i7-8650U CPU @ 1.90GHz")
+        );
+        assert_eq!(device.processor_frequency, Some(2112.0));
+        assert_eq!(
+            device.boot_time,
+            SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(1_700_000_000))
+        );
+        assert_eq!(device.model.as_deref(), Some("ThinkPad X1 Carbon 6th"));
+        assert_eq!(device.family, None);
+
+        assert_eq!(system.container_context(), None);
+        assert_eq!(system.kubernetes_context(env(&[])), None);
+    }
+
+    #[test]
+    fn test_docker_container() {
+        let system = fixture("docker");
+        let mut device = DeviceContext::default();
+        system.fill_device(&mut device);
+        system.refresh_device(&system.memory_cgroup(), &mut device);
+
+        assert_eq!(device.memory_size, Some(8_000_000 * 1024));
+        assert_eq!(device.usable_memory, Some(536_870_912));
+        // The container's free memory is lower than the host's.
+        assert_eq!(device.free_memory, Some(536_870_912 - 104_857_600));
+        assert_eq!(device.processor_count, Some(2));
+        assert_eq!(device.cpu_description.as_deref(), Some("ARMv8 Processor"));
+        assert_eq!(device.processor_frequency, Some(1800.0));
+        assert_eq!(device.model, None);
+
+        let container = system.container_context().unwrap();
+        assert_eq!(
+            container["id"],
+            "8d4a7e3e0d5c6f1b2a3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d5e6f708192"
+        );
+        assert_eq!(container["runtime"], "docker");
+        assert_eq!(container["memory_limit"], 536_870_912);
+        assert_eq!(container["cpu_limit"], 1.5);
+        assert_eq!(system.kubernetes_context(env(&[])), None);
+    }
+
+    #[test]
+    fn test_kubernetes_pod() {
+        let system = fixture("kubernetes");
+        let mut device = DeviceContext::default();
+        system.fill_device(&mut device);
+
+        assert_eq!(device.usable_memory, Some(268_435_456));
+
+        let container = system.container_context().unwrap();
+        assert_eq!(
+            container["id"],
+            "f1e2d3c4b5a6978869504132a1b2c3d4e5f60718293a4b5c6d7e8f9012345678"
+        );
+        assert_eq!(container["runtime"], "containerd");
+        assert_eq!(container["memory_limit"], 268_435_456);
+        assert_eq!(container["cpu_limit"], 0.5);
+
+        let kubernetes = system
+            .kubernetes_context(env(&[
+                ("KUBERNETES_SERVICE_HOST", "10.0.0.1"),
+                ("HOSTNAME", "api-7d9f8b6c5-x2v4q"),
+                ("NODE_NAME", "node-1"),
+            ]))
+            .unwrap();
+        assert_eq!(kubernetes["pod_name"], "api-7d9f8b6c5-x2v4q");
+        assert_eq!(kubernetes["namespace"], "production");
+        assert_eq!(
+            kubernetes["pod_uid"],
+            "0b6f2c2e-5c2a-4b8e-9a1d-2f3e4d5c6b7a"
+        );
+        assert_eq!(kubernetes["node_name"], "node-1");
+    }
+
+    #[test]
+    fn test_refresh_device_reads_usage() {
+        let root =
+            std::env::temp_dir().join(format!("sentry-contexts-refresh-{}", std::process::id()));
+        let cgroup = root.join("sys/fs/cgroup");
+        fs::create_dir_all(&cgroup).unwrap();
+        fs::create_dir_all(root.join("proc/self")).unwrap();
+        fs::write(root.join("proc/self/cgroup"), "0::/\n").unwrap();
+        fs::write(root.join("proc/meminfo"), "MemTotal: 8000000 kB\n").unwrap();
+        fs::write(cgroup.join("memory.max"), "1000\n").unwrap();
+        fs::write(cgroup.join("memory.current"), "400\n").unwrap();
+
+        let system = System::with_root(&root);
+        let memory = system.memory_cgroup();
+        let mut device = DeviceContext::default();
+        system.refresh_device(&memory, &mut device);
+        assert_eq!(device.free_memory, Some(600));
+
+        // The limit is resolved once, and only the usage is read again.
+        fs::write(cgroup.join("memory.max"), "2000\n").unwrap();
+        fs::write(cgroup.join("memory.current"), "700\n").unwrap();
+        system.refresh_device(&memory, &mut device);
+        assert_eq!(device.free_memory, Some(300));
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_cgroup_runtime() {
+        let id = "8d4a7e3e0d5c6f1b2a3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d5e6f708192";
+        for (cgroup, runtime) in [
+            (format!("12:memory:/docker/{id}"), Some("docker")),
+            (
+                format!("0::/system.slice/docker-{id}.scope"),
+                Some("docker"),
+            ),
+            (
+                format!("0::/machine.slice/libpod-{id}.scope/container"),
+                Some("podman"),
+            ),
+            (format!("0::/kubepods/crio-{id}.scope"), Some("cri-o")),
+            (
+                format!("0::/kubepods.slice/cri-containerd-{id}.scope"),
+                Some("containerd"),
+            ),
+            ("0::/lxc/web".to_owned(), Some("lxc")),
+            ("0::/lxc.payload.web".to_owned(), Some("lxc")),
+            // Host services of container runtimes are not containers.
+            ("0::/system.slice/docker.service".to_owned(), None),
+            ("0::/system.slice/containerd.service".to_owned(), None),
+            (
+                "0::/user.slice/user-1000.slice/session-2.scope".to_owned(),
+                None,
+            ),
+        ] {
+            assert_eq!(cgroup_runtime(&cgroup), runtime, "{cgroup}");
+        }
+    }
+
+    #[test]
+    fn test_missing_files() {
+        let system = fixture("missing");
+        let mut device = DeviceContext::default();
+        system.fill_device(&mut device);
+        system.refresh_device(&system.memory_cgroup(), &mut device);
+
+        assert_eq!(device, DeviceContext::default());
+        assert_eq!(system.container_context(), None);
+    }
+}
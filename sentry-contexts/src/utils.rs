@@ -185,14 +185,89 @@ pub fn rust_context() -> Context {
 }
 
 /// Returns the device context.
+///
+/// On Linux, this includes the memory, processors and boot time of the device, as well as the
+/// memory limit of the process' cgroup.
 pub fn device_context() -> Context {
-    DeviceContext {
+    let mut device = static_device_context();
+    DeviceRefresher::new().refresh(&mut device);
+    device.into()
+}
+
+/// Returns the parts of the device context which do not change while the process runs.
+pub(crate) fn static_device_context() -> DeviceContext {
+    #[cfg_attr(not(target_os = "linux"), expect(unused_mut))]
+    let mut device = DeviceContext {
         model: model_support::get_model(),
         family: model_support::get_family(),
         arch: Some(ARCH.into()),
         ..Default::default()
+    };
+    #[cfg(target_os = "linux")]
+    crate::linux::System::new().fill_device(&mut device);
+    device
+}
+
+/// Updates the parts of the device context which change while the process runs, such as the
+/// free memory.
+#[derive(Debug)]
+pub(crate) struct DeviceRefresher {
+    #[cfg(target_os = "linux")]
+    memory: crate::linux::MemoryCgroup,
+}
+
+impl DeviceRefresher {
+    /// Resolves where the volatile information is read from, such as the process' cgroup.
+    pub(crate) fn new() -> Self {
+        Self {
+            #[cfg(target_os = "linux")]
+            memory: crate::linux::System::new().memory_cgroup(),
+        }
+    }
+
+    /// Updates the volatile information of `device`.
+    pub(crate) fn refresh(&self, device: &mut DeviceContext) {
+        #[cfg(target_os = "linux")]
+        crate::linux::System::new().refresh_device(&self.memory, device);
+        #[cfg(not(target_os = "linux"))]
+        let _ = device;
+    }
+}
+
+/// Returns the container context, if the process runs in a container.
+///
+/// The context contains the ID of the container, its runtime, and the memory and CPU limits of
+/// its cgroup. This is only supported on Linux.
+pub fn container_context() -> Option<Context> {
+    #[cfg(target_os = "linux")]
+    {
+        crate::linux::System::new()
+            .container_context()
+            .map(Context::Other)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+/// Returns the Kubernetes context, if the process runs in a Kubernetes pod.
+///
+/// The context contains the name, namespace and UID of the pod, and the name of its node. The pod
+/// and node names are read from the `POD_NAME`, `POD_NAMESPACE` and `NODE_NAME` environment
+/// variables if they are exposed to the container, and otherwise default to the hostname and the
+/// namespace of the service account. This is only supported on Linux.
+pub fn kubernetes_context() -> Option<Context> {
+    #[cfg(target_os = "linux")]
+    {
+        crate::linux::System::new()
+            .kubernetes_context(|name| std::env::var(name).ok())
+            .map(Context::Other)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
     }
-    .into()
 }
 
 #[cfg(test)]
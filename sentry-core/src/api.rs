@@ -1,7 +1,7 @@
 #[cfg(feature = "release-health")]
 use sentry_types::protocol::v7::SessionStatus;
 
-use crate::protocol::{Event, Level};
+use crate::protocol::{Event, Feedback, Level};
 use crate::types::Uuid;
 use crate::{Hub, Integration, IntoBreadcrumbs, Scope};
 
@@ -63,6 +63,41 @@ pub fn capture_message(msg: &str, level: Level) -> Uuid {
     Hub::with_active(|hub| hub.capture_message(msg, level))
 }
 
+/// Captures user feedback on the currently active client if any.
+///
+/// Feedback can be linked to an event by setting its
+/// [`associated_event_id`](crate::protocol::Feedback::associated_event_id) to the ID returned
+/// when capturing that event. The user and tags of the current scope, as well as the release and
+/// environment of the client, are added to the feedback unless it already has them.
+///
+/// The return value is the ID of the feedback. If the feedback was discarded for any reason,
+/// return value will be the nil UUID (`Uuid::nil`).
+///
+/// # Examples
+///
+/// ```
+/// use sentry::protocol::{EnvelopeItem, Feedback};
+///
+/// let envelopes = sentry::test::with_captured_envelopes(|| {
+///     let event_id = sentry::capture_message("something went wrong", sentry::Level::Error);
+///     sentry::capture_feedback(Feedback {
+///         message: "The app crashed when I clicked the button.".into(),
+///         contact_email: Some("jane@example.com".into()),
+///         associated_event_id: Some(event_id),
+///         ..Default::default()
+///     });
+/// });
+///
+/// let feedback = envelopes[1].items().find_map(|item| match item {
+///     EnvelopeItem::Feedback(feedback) => Some(feedback),
+///     _ => None,
+/// });
+/// assert!(feedback.is_some());
+/// ```
+pub fn capture_feedback(feedback: Feedback) -> Uuid {
+    Hub::with_active(|hub| hub.capture_feedback(feedback))
+}
+
 /// Records a breadcrumb by calling a function.
 ///
 /// The total number of breadcrumbs that can be recorded are limited by the
@@ -1,26 +1,62 @@
-#![cfg(any(feature = "logs", feature = "metrics"))]
-
 //! Generic batching for Sentry envelope items.
 
+use std::collections::HashMap;
 use std::sync::{Arc, Condvar, Mutex, MutexGuard};
 use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 
 use super::EnvelopeSender;
+use crate::constants::MIN_BATCH_FLUSH_INTERVAL;
+use crate::protocol::client_report::{self, LossSource, Reason as ClientReportReason};
 use crate::protocol::EnvelopeItem;
-use crate::Envelope;
+use crate::{ClientOptions, Envelope};
+#[cfg(feature = "logs")]
 use sentry_types::protocol::v7::Log;
+use sentry_types::protocol::v7::StreamedSpan;
 #[cfg(feature = "metrics")]
-use sentry_types::protocol::v7::Metric;
+use sentry_types::protocol::v7::{LogAttribute, Metric, MetricType};
+
+/// The limits and behavior of a [`Batcher`].
+#[derive(Debug, Clone, Copy)]
+pub(super) struct BatcherConfig {
+    /// Flush when there's this many items in the buffer
+    max_items: usize,
+    /// Or when this much time has passed from the last flush
+    flush_interval: Duration,
+    /// Drop items which would grow the buffer beyond this many bytes
+    max_queue_bytes: u64,
+    /// Whether items with the same aggregation key are merged
+    aggregate: bool,
+}
 
-// Flush when there's 100 items in the buffer
-const MAX_ITEMS: usize = 100;
-// Or when 5 seconds have passed from the last flush
-const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+impl BatcherConfig {
+    /// Creates the configuration of a non-aggregating batcher from the client options.
+    pub(super) fn new(options: &ClientOptions) -> Self {
+        Self {
+            max_items: options.max_batch_items.max(1),
+            flush_interval: options.batch_flush_interval.max(MIN_BATCH_FLUSH_INTERVAL),
+            max_queue_bytes: options.max_batch_queue_bytes,
+            aggregate: false,
+        }
+    }
+
+    /// Sets whether items with the same [aggregation key](Batch::aggregation_key) are merged
+    /// until the queue is flushed.
+    #[cfg(feature = "metrics")]
+    pub(super) fn aggregate(self, aggregate: bool) -> Self {
+        Self { aggregate, ..self }
+    }
+}
 
 #[derive(Debug)]
 struct BatchQueue<T> {
     items: Vec<T>,
+    /// The estimated size of `items`, in bytes.
+    bytes: u64,
+    /// The indexes of the aggregated items in `items`, by their aggregation key.
+    ///
+    /// This is only used if the batcher aggregates items.
+    aggregates: HashMap<String, usize>,
 }
 
 pub(super) trait IntoBatchEnvelopeItem: Sized {
@@ -36,17 +72,124 @@ where
     }
 }
 
-pub(super) trait Batch: IntoBatchEnvelopeItem {
+pub(super) trait Batch: IntoBatchEnvelopeItem + LossSource {
     const TYPE_NAME: &str;
+
+    /// Returns the estimated size of the item, in bytes.
+    fn byte_size(&self) -> u64;
+
+    /// Returns the key identifying the items this item can be aggregated with, or `None` if the
+    /// item must be sent on its own.
+    fn aggregation_key(&self) -> Option<String> {
+        None
+    }
+
+    /// Merges `other` into this item, both having the same aggregation key.
+    fn merge(&mut self, _other: Self) {}
 }
 
+#[cfg(feature = "logs")]
 impl Batch for Log {
     const TYPE_NAME: &str = "logs";
+
+    fn byte_size(&self) -> u64 {
+        client_report::log_byte_size(self)
+    }
 }
 
 #[cfg(feature = "metrics")]
 impl Batch for Metric {
     const TYPE_NAME: &str = "metrics";
+
+    fn byte_size(&self) -> u64 {
+        client_report::metric_byte_size(self)
+    }
+
+    /// Counters and gauges are aggregated by their type, name, unit and attributes. Distributions
+    /// are sent as they are, as their individual values are needed for percentiles.
+    fn aggregation_key(&self) -> Option<String> {
+        if self.r#type == MetricType::Distribution {
+            return None;
+        }
+        serde_json::to_string(&(&self.r#type, &self.name, &self.unit, &self.attributes)).ok()
+    }
+
+    /// Counters are summed up. Gauges keep the last value, and record the minimum, maximum, sum
+    /// and count of their values in the `sentry.gauge.*` attributes.
+    fn merge(&mut self, other: Self) {
+        if self.r#type == MetricType::Gauge {
+            let stats = GaugeStats::of(self).add(other.value);
+            stats.write_to(self);
+            self.value = other.value;
+        } else {
+            self.value += other.value;
+        }
+        self.timestamp = other.timestamp;
+        self.trace_id = other.trace_id;
+        self.span_id = other.span_id;
+    }
+}
+
+/// The aggregated values of a gauge.
+#[cfg(feature = "metrics")]
+struct GaugeStats {
+    min: f64,
+    max: f64,
+    sum: f64,
+    count: u64,
+}
+
+#[cfg(feature = "metrics")]
+impl GaugeStats {
+    const MIN: &str = "sentry.gauge.min";
+    const MAX: &str = "sentry.gauge.max";
+    const SUM: &str = "sentry.gauge.sum";
+    const COUNT: &str = "sentry.gauge.count";
+
+    /// Reads the stats of a gauge, which is treated as a single value if it was not aggregated.
+    fn of(gauge: &Metric) -> Self {
+        let get = |key| {
+            gauge
+                .attributes
+                .get(key)
+                .and_then(|LogAttribute(value)| value.as_f64())
+        };
+        Self {
+            min: get(Self::MIN).unwrap_or(gauge.value),
+            max: get(Self::MAX).unwrap_or(gauge.value),
+            sum: get(Self::SUM).unwrap_or(gauge.value),
+            count: gauge
+                .attributes
+                .get(Self::COUNT)
+                .and_then(|LogAttribute(value)| value.as_u64())
+                .unwrap_or(1),
+        }
+    }
+
+    fn add(self, value: f64) -> Self {
+        Self {
+            min: self.min.min(value),
+            max: self.max.max(value),
+            sum: self.sum + value,
+            count: self.count.saturating_add(1),
+        }
+    }
+
+    fn write_to(self, gauge: &mut Metric) {
+        let attributes = &mut gauge.attributes;
+        attributes.insert(Self::MIN.into(), self.min.into());
+        attributes.insert(Self::MAX.into(), self.max.into());
+        attributes.insert(Self::SUM.into(), self.sum.into());
+        attributes.insert(Self::COUNT.into(), self.count.into());
+    }
+}
+
+impl Batch for StreamedSpan {
+    const TYPE_NAME: &str = "spans";
+
+    fn byte_size(&self) -> u64 {
+        client_report::span_byte_size(self)
+    }
 }
 
 /// Accumulates items in the queue and submits them through the transport when one of the flushing
@@ -54,6 +197,7 @@ impl Batch for Metric {
 pub(super) struct Batcher<T: Batch> {
     envelope_sender: EnvelopeSender,
     queue: Arc<Mutex<BatchQueue<T>>>,
+    config: BatcherConfig,
     shutdown: Arc<(Mutex<bool>, Condvar)>,
     worker: Option<JoinHandle<()>>,
 }
@@ -63,13 +207,18 @@ where
     T: Batch + Send + 'static,
 {
     /// Creates a new Batcher that will submit envelopes to the transport.
-    pub(super) fn new(envelope_sender: EnvelopeSender) -> Self {
-        let queue = Arc::new(Mutex::new(BatchQueue { items: Vec::new() }));
+    pub(super) fn new(envelope_sender: EnvelopeSender, config: BatcherConfig) -> Self {
+        let queue = Arc::new(Mutex::new(BatchQueue {
+            items: Vec::new(),
+            bytes: 0,
+            aggregates: HashMap::new(),
+        }));
         let shutdown = Arc::new((Mutex::new(false), Condvar::new()));
 
         let worker_envelope_sender = envelope_sender.clone();
         let worker_queue = queue.clone();
         let worker_shutdown = shutdown.clone();
+        let flush_interval = config.flush_interval;
         let worker = std::thread::Builder::new()
             .name(format!("sentry-{}-batcher", T::TYPE_NAME))
             .spawn(move || {
@@ -81,14 +230,14 @@ where
                 }
                 let mut last_flush = Instant::now();
                 loop {
-                    let timeout = FLUSH_INTERVAL
+                    let timeout = flush_interval
                         .checked_sub(last_flush.elapsed())
                         .unwrap_or_else(|| Duration::from_secs(0));
                     shutdown = cvar.wait_timeout(shutdown, timeout).unwrap().0;
                     if *shutdown {
                         return;
                     }
-                    if last_flush.elapsed() >= FLUSH_INTERVAL {
+                    if last_flush.elapsed() >= flush_interval {
                         Batcher::flush_queue_internal(
                             worker_queue.lock().unwrap(),
                             &worker_envelope_sender,
@@ -102,6 +251,7 @@ where
         Self {
             envelope_sender,
             queue,
+            config,
             shutdown,
             worker: Some(worker),
         }
@@ -111,11 +261,44 @@ where
 impl<T: Batch> Batcher<T> {
     /// Enqueues an item for delayed sending.
     ///
-    /// This will automatically flush the queue if it reaches a size of `MAX_ITEMS`.
+    /// This will automatically flush the queue if it reaches the configured maximum number of
+    /// items. Aggregated items only count once towards this limit. Items which would make the
+    /// queue exceed its maximum size are dropped and recorded as lost, while the size of merged
+    /// items is updated as they grow.
     pub(super) fn enqueue(&self, item: T) {
         let mut queue = self.queue.lock().unwrap();
+        let key = self
+            .config
+            .aggregate
+            .then(|| item.aggregation_key())
+            .flatten();
+        if let Some(&index) = key.as_ref().and_then(|key| queue.aggregates.get(key)) {
+            let aggregate = &mut queue.items[index];
+            let old_size = aggregate.byte_size();
+            aggregate.merge(item);
+            let new_size = aggregate.byte_size();
+            queue.bytes = queue
+                .bytes
+                .saturating_sub(old_size)
+                .saturating_add(new_size);
+            return;
+        }
+
+        let bytes = queue.bytes.saturating_add(item.byte_size());
+        if bytes > self.config.max_queue_bytes {
+            drop(queue);
+            sentry_debug!("[Batcher({})] Queue is full, dropping item", T::TYPE_NAME);
+            self.envelope_sender
+                .record_lost_data(&item, ClientReportReason::QueueOverflow);
+            return;
+        }
+        queue.bytes = bytes;
+        if let Some(key) = key {
+            let index = queue.items.len();
+            queue.aggregates.insert(key, index);
+        }
         queue.items.push(item);
-        if queue.items.len() >= MAX_ITEMS {
+        if queue.items.len() >= self.config.max_items {
             Batcher::flush_queue_internal(queue, &self.envelope_sender);
         }
     }
@@ -135,6 +318,8 @@ impl<T: Batch> Batcher<T> {
         envelope_sender: &EnvelopeSender,
     ) {
         let items = std::mem::take(&mut queue_lock.items);
+        queue_lock.bytes = 0;
+        queue_lock.aggregates.clear();
         drop(queue_lock);
 
         if items.is_empty() {
@@ -165,8 +350,24 @@ impl<T: Batch> Drop for Batcher<T> {
 
 #[cfg(all(test, feature = "test", feature = "logs"))]
 mod tests {
-    use crate::logger_info;
-    use crate::test;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use super::BatcherConfig;
+    use crate::protocol::{EnvelopeItem, ItemContainer};
+    use crate::test::{self, TestTransport};
+    use crate::{logger_info, Client, ClientOptions, Envelope, Hub};
+
+    fn log_counts(envelopes: &[Envelope]) -> Vec<usize> {
+        envelopes
+            .iter()
+            .flat_map(|envelope| envelope.items())
+            .filter_map(|item| match item {
+                EnvelopeItem::ItemContainer(ItemContainer::Logs(logs)) => Some(logs.len()),
+                _ => None,
+            })
+            .collect()
+    }
 
     // Test that logs are sent in batches
     #[test]
@@ -223,4 +424,97 @@ mod tests {
             }
         }
     }
+
+    // Test that the batch size can be configured
+    #[test]
+    fn test_logs_batching_max_items() {
+        let envelopes = test::with_captured_envelopes_options(
+            || {
+                for i in 0..25 {
+                    logger_info!("test log {}", i);
+                }
+            },
+            ClientOptions::new().enable_logs(true).max_batch_items(10),
+        );
+
+        assert_eq!(log_counts(&envelopes), [10, 10, 5]);
+    }
+
+    // Test that items overflowing the queue are dropped and recorded in a client report
+    #[test]
+    fn test_logs_batching_queue_overflow() {
+        let envelopes = test::with_captured_envelopes_options(
+            || {
+                for _ in 0..5 {
+                    // 10 bytes of body, plus the default attributes
+                    logger_info!("0123456789");
+                }
+            },
+            ClientOptions::new()
+                .enable_logs(true)
+                .max_batch_queue_bytes(250),
+        );
+
+        let kept = log_counts(&envelopes);
+        assert_eq!(kept.len(), 1);
+        assert!(kept[0] > 0 && kept[0] < 5, "unexpected logs: {kept:?}");
+        let dropped = u64::try_from(5 - kept[0]).unwrap();
+
+        let report = envelopes
+            .iter()
+            .flat_map(|envelope| envelope.items())
+            .find_map(|item| match item {
+                EnvelopeItem::ClientReport(report) => Some(report),
+                _ => None,
+            })
+            .expect("expected a client report");
+        let discarded = serde_json::to_value(report).unwrap()["discarded_events"].clone();
+        let items = discarded
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|item| item["category"] == "log_item")
+            .unwrap();
+        assert_eq!(items["reason"], "queue_overflow");
+        assert_eq!(items["quantity"], dropped);
+    }
+
+    // Test that the queue is flushed at the configured interval
+    #[test]
+    fn test_logs_batching_flush_interval() {
+        let transport = TestTransport::new();
+        let options = ClientOptions::new()
+            .dsn("https://public@sentry.invalid/1")
+            .transport(transport.clone())
+            .enable_logs(true)
+            .batch_flush_interval(Duration::from_millis(10));
+        let client = Arc::new(Client::with_options(options));
+
+        Hub::run(
+            Arc::new(Hub::new(Some(client.clone()), Arc::new(Default::default()))),
+            || logger_info!("test log"),
+        );
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut envelopes = Vec::new();
+        while envelopes.is_empty() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+            envelopes = transport.fetch_and_clear_envelopes();
+        }
+        assert_eq!(log_counts(&envelopes), [1]);
+    }
+
+    // Test that too short flush intervals are raised to the minimum
+    #[test]
+    fn test_batch_flush_interval_minimum() {
+        let options = ClientOptions::new().batch_flush_interval(Duration::ZERO);
+        assert_eq!(options.batch_flush_interval, Duration::from_millis(10));
+
+        let options = ClientOptions {
+            batch_flush_interval: Duration::ZERO,
+            ..Default::default()
+        };
+        let config = BatcherConfig::new(&options);
+        assert_eq!(config.flush_interval, Duration::from_millis(10));
+    }
 }
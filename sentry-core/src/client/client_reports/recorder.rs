@@ -79,11 +79,15 @@ pub enum TransportLossReason {
     ///
     /// Converts to [`Reason::QueueOverflow`]
     QueueOverflow,
+    /// Used when the offline cache drops data because it exceeds the cache's size or age budget.
+    ///
+    /// Converts to [`Reason::CacheOverflow`]
+    CacheOverflow,
 }
 
 impl Recorder {
     /// Record an envelope item lost for a given reason.
-    pub fn record_lost_data<L: LossSource>(&self, data: &L, reason: TransportLossReason) {
+    pub fn record_lost_data<L: LossSource + ?Sized>(&self, data: &L, reason: TransportLossReason) {
         #[cfg(all(target_has_atomic = "8", target_has_atomic = "64"))]
         if let Some(aggregator) = self.aggregator() {
             aggregator.record_lost_data(data, reason.into_reason());
@@ -146,6 +150,7 @@ impl TransportLossReason {
             Self::NetworkError => Reason::NetworkError,
             Self::RatelimitBackoff => Reason::RatelimitBackoff,
             Self::QueueOverflow => Reason::QueueOverflow,
+            Self::CacheOverflow => Reason::CacheOverflow,
         }
     }
 }
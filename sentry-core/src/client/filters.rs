@@ -0,0 +1,144 @@
+//! The inbound filters configured in the [`ClientOptions`].
+
+use crate::protocol::{Event, Transaction};
+use crate::ClientOptions;
+
+/// Returns whether `event` is dropped by the `ignore_loggers` or `ignore_errors` options.
+pub(super) fn is_ignored_event(options: &ClientOptions, event: &Event<'_>) -> bool {
+    if let Some(logger) = event.logger.as_deref() {
+        let is_ignored_logger = |ignored: &&str| {
+            logger
+                .strip_prefix(*ignored)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+        };
+        if options.ignore_loggers.iter().any(is_ignored_logger) {
+            return true;
+        }
+    }
+
+    let patterns = &options.ignore_errors;
+    if patterns.is_empty() {
+        return false;
+    }
+
+    let mut messages = event
+        .message
+        .as_deref()
+        .into_iter()
+        .chain(event.logentry.as_ref().map(|entry| entry.message.as_str()));
+    if messages.any(|message| patterns.matches(message)) {
+        return true;
+    }
+
+    event.exception.values.iter().any(|exception| {
+        patterns.matches(&exception.ty)
+            || exception.value.as_deref().is_some_and(|value| {
+                patterns.matches(value) || patterns.matches(&format!("{}: {value}", exception.ty))
+            })
+    })
+}
+
+/// Returns whether `transaction` is dropped by the `ignore_transactions` option.
+pub(super) fn is_ignored_transaction(
+    options: &ClientOptions,
+    transaction: &Transaction<'_>,
+) -> bool {
+    is_ignored_transaction_name(options, transaction.name.as_deref())
+}
+
+/// Returns whether transactions named `name` are dropped by the `ignore_transactions` option.
+pub(super) fn is_ignored_transaction_name(options: &ClientOptions, name: Option<&str>) -> bool {
+    name.is_some_and(|name| options.ignore_transactions.matches(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::{Exception, LogEntry};
+
+    use super::*;
+
+    #[test]
+    fn ignores_errors_by_exception_and_message() {
+        let options = ClientOptions::new().ignore_errors(["^Os$", "Broken pipe", "^Error: fatal$"]);
+        let event_with_exception = |ty: &str, value: &str| Event {
+            exception: vec![Exception {
+                ty: ty.into(),
+                value: Some(value.into()),
+                ..Default::default()
+            }]
+            .into(),
+            ..Default::default()
+        };
+
+        assert!(is_ignored_event(
+            &options,
+            &event_with_exception("Os", "other")
+        ));
+        assert!(is_ignored_event(
+            &options,
+            &event_with_exception("Custom", "Broken pipe (os error 32)")
+        ));
+        assert!(is_ignored_event(
+            &options,
+            &event_with_exception("Error", "fatal")
+        ));
+        assert!(!is_ignored_event(
+            &options,
+            &event_with_exception("Error", "fatal error")
+        ));
+
+        let message = Event {
+            message: Some("Broken pipe".into()),
+            ..Default::default()
+        };
+        assert!(is_ignored_event(&options, &message));
+        let logentry = Event {
+            logentry: Some(LogEntry {
+                message: "write failed: Broken pipe".into(),
+                params: vec![],
+            }),
+            ..Default::default()
+        };
+        assert!(is_ignored_event(&options, &logentry));
+        assert!(!is_ignored_event(&options, &Event::default()));
+    }
+
+    #[test]
+    fn ignores_loggers_and_their_modules() {
+        let options = ClientOptions::new().ignore_loggers(["hyper"]);
+        let event_with_logger = |logger: &str| Event {
+            logger: Some(logger.into()),
+            ..Default::default()
+        };
+
+        assert!(is_ignored_event(&options, &event_with_logger("hyper")));
+        assert!(is_ignored_event(
+            &options,
+            &event_with_logger("hyper::proto::h1")
+        ));
+        assert!(!is_ignored_event(
+            &options,
+            &event_with_logger("hyper_util")
+        ));
+        assert!(!is_ignored_event(&options, &Event::default()));
+    }
+
+    #[test]
+    fn ignores_transactions_by_name() {
+        let options = ClientOptions::new().ignore_transactions(["^GET /healthz$"]);
+        let transaction = |name: &str| Transaction {
+            name: Some(name.into()),
+            ..Default::default()
+        };
+
+        assert!(is_ignored_transaction(
+            &options,
+            &transaction("GET /healthz")
+        ));
+        assert!(!is_ignored_transaction(
+            &options,
+            &transaction("GET /healthz/deep")
+        ));
+        assert!(!is_ignored_transaction(&options, &Transaction::default()));
+    }
+}
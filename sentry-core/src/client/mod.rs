@@ -5,31 +5,35 @@ use std::collections::BTreeMap;
 use std::fmt;
 use std::panic::RefUnwindSafe;
 use std::sync::Arc;
-#[cfg(any(feature = "logs", feature = "metrics", feature = "release-health"))]
 use std::sync::RwLock;
 use std::time::Duration;
 
 #[cfg(feature = "metrics")]
 use crate::metrics::IntoProtocolMetric;
+#[cfg(feature = "profiling")]
+use crate::protocol::ProfileChunk;
 #[cfg(feature = "release-health")]
 use crate::protocol::SessionUpdate;
 use crate::transport::TransportOptions;
 use rand::random;
 use sentry_types::protocol::v7::client_report::{
-    Category as ClientReportCategory, LossSource, Reason as ClientReportReason,
+    Category as ClientReportCategory, ItemLoss, LossSource, Reason as ClientReportReason,
 };
 use sentry_types::random_uuid;
 
-#[cfg(any(feature = "logs", feature = "metrics"))]
-use self::batcher::Batcher;
+use self::batcher::{Batcher, BatcherConfig};
 use crate::constants::SDK_INFO;
-use crate::protocol::{ClientSdkInfo, Event};
+use crate::protocol::{
+    ClientSdkInfo, Event, Feedback, MonitorCheckIn, Span, StreamedSpan, Transaction,
+};
 #[cfg(feature = "release-health")]
 use crate::session::SessionFlusher;
 use crate::types::{Dsn, Uuid};
 #[cfg(feature = "release-health")]
 use crate::SessionMode;
-use crate::{ClientOptions, Envelope, EventSamplingStrategy, Hub, Integration, Scope};
+use crate::{
+    ClientOptions, Envelope, EventSamplingStrategy, Hub, Integration, Scope, TraceLifecycle,
+};
 
 #[cfg(feature = "logs")]
 use sentry_types::protocol::v7::Context;
@@ -42,6 +46,7 @@ use sentry_types::protocol::v7::Metric;
 
 mod batcher;
 mod envelope_sender;
+mod filters;
 
 pub(crate) mod client_reports;
 
@@ -86,6 +91,7 @@ pub struct Client {
     logs_batcher: RwLock<Option<Batcher<Log>>>,
     #[cfg(feature = "metrics")]
     metrics_batcher: RwLock<Option<Batcher<Metric>>>,
+    spans_batcher: RwLock<Option<Batcher<StreamedSpan>>>,
     #[cfg(feature = "logs")]
     default_log_attributes: Option<BTreeMap<String, LogAttribute>>,
     #[cfg(feature = "metrics")]
@@ -113,18 +119,24 @@ impl Clone for Client {
             self.options.session_mode,
         )));
 
+        let batcher_config = BatcherConfig::new(&self.options);
+
         #[cfg(feature = "logs")]
         let logs_batcher = RwLock::new(if self.options.enable_logs {
-            Some(Batcher::new(envelope_sender.clone()))
+            Some(Batcher::new(envelope_sender.clone(), batcher_config))
         } else {
             None
         });
 
         #[cfg(feature = "metrics")]
-        let metrics_batcher = RwLock::new(
-            self.options
-                .enable_metrics
-                .then(|| Batcher::new(envelope_sender.clone())),
+        let metrics_batcher = RwLock::new(self.options.enable_metrics.then(|| {
+            let config = batcher_config.aggregate(self.options.aggregate_metrics);
+            Batcher::new(envelope_sender.clone(), config)
+        }));
+
+        let spans_batcher = RwLock::new(
+            (self.options.trace_lifecycle == TraceLifecycle::Stream)
+                .then(|| Batcher::new(envelope_sender.clone(), batcher_config)),
         );
 
         Client {
@@ -136,6 +148,7 @@ impl Clone for Client {
             logs_batcher,
             #[cfg(feature = "metrics")]
             metrics_batcher,
+            spans_batcher,
             #[cfg(feature = "logs")]
             default_log_attributes: self.default_log_attributes.clone(),
             #[cfg(feature = "metrics")]
@@ -202,18 +215,24 @@ impl Client {
             options.session_mode,
         )));
 
+        let batcher_config = BatcherConfig::new(&options);
+
         #[cfg(feature = "logs")]
         let logs_batcher = RwLock::new(if options.enable_logs {
-            Some(Batcher::new(envelope_sender.clone()))
+            Some(Batcher::new(envelope_sender.clone(), batcher_config))
         } else {
             None
         });
 
         #[cfg(feature = "metrics")]
-        let metrics_batcher = RwLock::new(
-            options
-                .enable_metrics
-                .then(|| Batcher::new(envelope_sender.clone())),
+        let metrics_batcher = RwLock::new(options.enable_metrics.then(|| {
+            let config = batcher_config.aggregate(options.aggregate_metrics);
+            Batcher::new(envelope_sender.clone(), config)
+        }));
+
+        let spans_batcher = RwLock::new(
+            (options.trace_lifecycle == TraceLifecycle::Stream)
+                .then(|| Batcher::new(envelope_sender.clone(), batcher_config)),
         );
 
         let client = Client {
@@ -225,6 +244,7 @@ impl Client {
             logs_batcher,
             #[cfg(feature = "metrics")]
             metrics_batcher,
+            spans_batcher,
             #[cfg(feature = "logs")]
             default_log_attributes: None,
             #[cfg(feature = "metrics")]
@@ -367,6 +387,12 @@ impl Client {
             }
         }
 
+        if filters::is_ignored_event(&self.options, &event) {
+            sentry_debug!("inbound filters dropped event {:?}", event.event_id);
+            self.record_lost_event(ClientReportReason::Filtered);
+            return None;
+        }
+
         if event.release.is_none() {
             event.release.clone_from(&self.options.release);
         }
@@ -406,6 +432,105 @@ impl Client {
         }
     }
 
+    /// Prepares a transaction for transmission to sentry, processing it through the transaction
+    /// processors of the scope, the integrations, the inbound filters and the
+    /// `before_send_transaction` callback.
+    ///
+    /// Returns `None` and records `losses` as lost if it was dropped. These are the transaction
+    /// and its spans, or only the spans when spans are streamed.
+    pub(crate) fn prepare_transaction(
+        &self,
+        mut transaction: Transaction<'static>,
+        scope: Option<&Scope>,
+        losses: &[ItemLoss],
+    ) -> Option<Transaction<'static>> {
+        if let Some(scope) = scope {
+            transaction = match scope.process_transaction(transaction) {
+                Some(transaction) => transaction,
+                None => {
+                    self.record_lost_data(losses, ClientReportReason::EventProcessor);
+                    return None;
+                }
+            };
+        }
+
+        for (_, integration) in self.integrations.iter() {
+            let id = transaction.event_id;
+            transaction = match integration.process_transaction(transaction, &self.options) {
+                Some(transaction) => transaction,
+                None => {
+                    sentry_debug!("integration dropped transaction {:?}", id);
+                    self.record_lost_data(losses, ClientReportReason::EventProcessor);
+                    return None;
+                }
+            };
+        }
+
+        if filters::is_ignored_transaction(&self.options, &transaction) {
+            sentry_debug!(
+                "inbound filters dropped transaction {:?}",
+                transaction.event_id
+            );
+            self.record_lost_data(losses, ClientReportReason::Filtered);
+            return None;
+        }
+
+        if let Some(ref func) = self.options.before_send_transaction {
+            sentry_debug!("invoking before_send_transaction callback");
+            let id = transaction.event_id;
+            transaction = match func(transaction) {
+                Some(transaction) => transaction,
+                None => {
+                    sentry_debug!("before_send_transaction dropped transaction {:?}", id);
+                    self.record_lost_data(losses, ClientReportReason::BeforeSend);
+                    return None;
+                }
+            };
+        }
+
+        Some(transaction)
+    }
+
+    /// Prepares a finished child span of a transaction for streaming.
+    ///
+    /// The span is dropped if `segment_name`, the name of its transaction, is ignored, and is
+    /// otherwise passed to the span processors of the integrations and the `before_send_span`
+    /// callback.
+    pub(crate) fn prepare_span(&self, mut span: Span, segment_name: Option<&str>) -> Option<Span> {
+        if filters::is_ignored_transaction_name(&self.options, segment_name) {
+            sentry_debug!("inbound filters dropped span {}", span.span_id);
+            self.record_lost_data(&span, ClientReportReason::Filtered);
+            return None;
+        }
+
+        let losses: Vec<_> = span.losses().collect();
+        for (_, integration) in self.integrations.iter() {
+            let id = span.span_id;
+            span = match integration.process_span(span, &self.options) {
+                Some(span) => span,
+                None => {
+                    sentry_debug!("integration dropped span {}", id);
+                    self.record_lost_data(&losses[..], ClientReportReason::EventProcessor);
+                    return None;
+                }
+            };
+        }
+
+        if let Some(ref func) = self.options.before_send_span {
+            let id = span.span_id;
+            span = match func(span) {
+                Some(span) => span,
+                None => {
+                    sentry_debug!("before_send_span dropped span {}", id);
+                    self.record_lost_data(&losses[..], ClientReportReason::BeforeSend);
+                    return None;
+                }
+            };
+        }
+
+        Some(span)
+    }
+
     /// Returns the options of this client.
     pub fn options(&self) -> &ClientOptions {
         &self.options
@@ -474,6 +599,109 @@ impl Client {
         event_id
     }
 
+    /// Captures user feedback and sends it to sentry.
+    ///
+    /// The feedback is filled with the user and tags of the `scope`, and the release and
+    /// environment of the client, before being passed to `before_send_feedback`.
+    pub fn capture_feedback(&self, mut feedback: Feedback, scope: Option<&Scope>) -> Uuid {
+        let mut event_id = Default::default();
+        self.envelope_sender.send_envelope_with(|| {
+            if feedback.event_id.is_nil() {
+                feedback.event_id = random_uuid();
+            }
+            if let Some(scope) = scope {
+                scope.apply_to_feedback(&mut feedback);
+            }
+            if feedback.release.is_none() {
+                feedback.release = self.options.release.as_deref().map(str::to_owned);
+            }
+            if feedback.environment.is_none() {
+                feedback.environment = self.options.environment.as_deref().map(str::to_owned);
+            }
+
+            if let Some(ref func) = self.options.before_send_feedback {
+                sentry_debug!("invoking before_send_feedback callback");
+                feedback = match func(feedback) {
+                    Some(feedback) => feedback,
+                    None => {
+                        sentry_debug!("before_send_feedback dropped feedback");
+                        self.record_loss(
+                            ClientReportCategory::Feedback,
+                            ClientReportReason::BeforeSend,
+                            1,
+                        );
+                        return None;
+                    }
+                };
+            }
+
+            event_id = feedback.event_id;
+            Some(feedback.into())
+        });
+        event_id
+    }
+
+    /// Captures a monitor check-in and sends it to sentry.
+    ///
+    /// The check-in is linked to the trace of the `scope`, and is assigned the environment of the
+    /// client unless it already has one.
+    pub fn capture_check_in(&self, mut check_in: MonitorCheckIn, scope: Option<&Scope>) -> Uuid {
+        let mut check_in_id = Default::default();
+        self.envelope_sender.send_envelope_with(|| {
+            if let Some(scope) = scope {
+                scope.apply_to_check_in(&mut check_in);
+            }
+            if check_in.environment.is_none() {
+                check_in.environment = self.options.environment.as_deref().map(str::to_owned);
+            }
+
+            check_in_id = check_in.check_in_id;
+            Some(check_in.into())
+        });
+        check_in_id
+    }
+
+    /// Sends a chunk of a continuous profile to sentry.
+    ///
+    /// The chunk is assigned the release and environment of the client unless it already has
+    /// them, as well as the SDK information of the client.
+    #[cfg(feature = "profiling")]
+    pub fn capture_profile_chunk(&self, mut chunk: ProfileChunk) -> Uuid {
+        let mut chunk_id = Default::default();
+        self.envelope_sender.send_envelope_with(|| {
+            if chunk.release.is_none() {
+                chunk.release = self.options.release.as_deref().map(str::to_owned);
+            }
+            if chunk.environment.is_none() {
+                chunk.environment = self.options.environment.as_deref().map(str::to_owned);
+            }
+            if chunk.client_sdk.is_none() {
+                chunk.client_sdk = Some(self.sdk_info.clone());
+            }
+
+            chunk_id = chunk.chunk_id;
+            Some(chunk.into())
+        });
+        chunk_id
+    }
+
+    /// Enqueues a finished span to be sent in the next batch of streamed spans.
+    ///
+    /// This does nothing unless the client streams spans.
+    pub(crate) fn enqueue_span(&self, mut span: StreamedSpan) {
+        if let Some(ref batcher) = *self.spans_batcher.read().unwrap() {
+            for (key, value) in [
+                ("sentry.sdk.name", &self.sdk_info.name),
+                ("sentry.sdk.version", &self.sdk_info.version),
+            ] {
+                span.attributes
+                    .entry(key.to_owned())
+                    .or_insert_with(|| value.as_str().into());
+            }
+            batcher.enqueue(span);
+        }
+    }
+
     pub(crate) fn record_lost_data<L>(&self, data: &L, reason: ClientReportReason)
     where
         L: LossSource + ?Sized,
@@ -522,6 +750,9 @@ impl Client {
         if let Some(ref batcher) = *self.metrics_batcher.read().unwrap() {
             batcher.flush();
         }
+        if let Some(ref batcher) = *self.spans_batcher.read().unwrap() {
+            batcher.flush();
+        }
         self.envelope_sender
             .flush(timeout.unwrap_or(self.options.shutdown_timeout))
     }
@@ -540,6 +771,7 @@ impl Client {
         drop(self.logs_batcher.write().unwrap().take());
         #[cfg(feature = "metrics")]
         drop(self.metrics_batcher.write().unwrap().take());
+        drop(self.spans_batcher.write().unwrap().take());
         self.envelope_sender
             .shutdown(timeout.unwrap_or(self.options.shutdown_timeout))
     }
@@ -582,6 +814,18 @@ impl Client {
             }
         }
 
+        let losses: Vec<_> = log.losses().collect();
+        for (_, integration) in self.integrations.iter() {
+            log = match integration.process_log(log, &self.options) {
+                Some(log) => log,
+                None => {
+                    sentry_debug!("integration dropped log");
+                    self.record_lost_data(losses.as_slice(), ClientReportReason::EventProcessor);
+                    return None;
+                }
+            };
+        }
+
         if let Some(ref func) = self.options.before_send_log {
             let losses: Vec<_> = log.losses().collect();
             log = match func(log) {
@@ -626,6 +870,18 @@ impl Client {
             metric.attributes.entry(key.clone()).or_insert(val.clone());
         }
 
+        let losses: Vec<_> = metric.losses().collect();
+        for (_, integration) in self.integrations.iter() {
+            metric = match integration.process_metric(metric, &self.options) {
+                Some(metric) => metric,
+                None => {
+                    sentry_debug!("integration dropped metric");
+                    self.record_lost_data(losses.as_slice(), ClientReportReason::EventProcessor);
+                    return None;
+                }
+            };
+        }
+
         if let Some(ref func) = self.options.before_send_metric {
             let losses: Vec<_> = metric.losses().collect();
             metric = match func(metric) {
@@ -656,6 +912,9 @@ fn build_envelope_sender(client_options: &ClientOptions) -> EnvelopeSender {
         http_proxy,
         https_proxy,
         accept_invalid_certs,
+        offline_cache,
+        compression,
+        retry,
         ..
     } = client_options;
 
@@ -667,6 +926,9 @@ fn build_envelope_sender(client_options: &ClientOptions) -> EnvelopeSender {
                 http_proxy: http_proxy.clone(),
                 https_proxy: https_proxy.clone(),
                 accept_invalid_certs: *accept_invalid_certs,
+                offline_cache: offline_cache.clone(),
+                compression: *compression,
+                retry: *retry,
                 client_report_recorder,
             };
 
@@ -3,11 +3,17 @@ use std::fmt;
 use std::sync::Arc;
 use std::time::Duration;
 
-use crate::constants::USER_AGENT;
+use regex::Regex;
+
+use crate::constants::{MIN_BATCH_FLUSH_INTERVAL, USER_AGENT};
 use crate::performance::{TracesSampler, TransactionContext};
-use crate::protocol::{Breadcrumb, Event, Log, Metric, OrganizationId};
+use crate::protocol::{
+    Breadcrumb, Event, Feedback, Log, Metric, OrganizationId, Span, Transaction,
+};
 use crate::types::Dsn;
-use crate::{Integration, IntoDsn, TransportFactory};
+use crate::{
+    CompressionOptions, Integration, IntoDsn, OfflineCacheOptions, RetryPolicy, TransportFactory,
+};
 
 /// Type alias for before event/breadcrumb handlers.
 pub type BeforeCallback<T> = Arc<dyn Fn(T) -> Option<T> + Send + Sync>;
@@ -126,6 +132,141 @@ impl Default for EventSamplingStrategy {
     }
 }
 
+/// Defines how the spans of a transaction are sent.
+///
+/// See [`ClientOptions::trace_lifecycle`] for details.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TraceLifecycle {
+    /// Finished spans are buffered in their transaction, and sent together with it when it is
+    /// finished.
+    #[default]
+    Static,
+    /// Finished spans are sent in batches as they finish, independently of their transaction.
+    Stream,
+}
+
+/// Defines which header an incoming trace is continued from, if both are present.
+///
+/// See [`ClientOptions::trace_header_precedence`] for details.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TraceHeaderPrecedence {
+    /// Continue the trace from the `sentry-trace` header.
+    #[default]
+    SentryTrace,
+    /// Continue the trace from the W3C `traceparent` header.
+    TraceParent,
+}
+
+/// The URLs of outgoing requests that distributed tracing headers are attached to.
+///
+/// See [`ClientOptions::trace_propagation_targets`] for details.
+#[derive(Clone, Default)]
+pub struct TracePropagationTargets {
+    /// `None` matches all URLs.
+    targets: Option<Vec<Regex>>,
+}
+
+impl TracePropagationTargets {
+    /// Matches the URLs of all outgoing requests.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Matches the URLs which contain one of the given targets.
+    ///
+    /// Targets are regular expressions, which can match anywhere in the URL. Targets which are
+    /// not valid regular expressions are matched literally.
+    pub fn new<I, T>(targets: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        let targets = targets
+            .into_iter()
+            .map(|target| {
+                let target = target.as_ref();
+                Regex::new(target).unwrap_or_else(|_| {
+                    Regex::new(&regex::escape(target)).expect("escaped pattern is valid")
+                })
+            })
+            .collect();
+        Self {
+            targets: Some(targets),
+        }
+    }
+
+    /// Returns whether distributed tracing headers should be attached to a request to `url`.
+    pub fn matches(&self, url: &str) -> bool {
+        match &self.targets {
+            Some(targets) => targets.iter().any(|target| target.is_match(url)),
+            None => true,
+        }
+    }
+}
+
+impl fmt::Debug for TracePropagationTargets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.targets {
+            Some(targets) => f
+                .debug_list()
+                .entries(targets.iter().map(Regex::as_str))
+                .finish(),
+            None => f.write_str("All"),
+        }
+    }
+}
+
+/// A list of patterns that events or transactions are filtered by.
+///
+/// See [`ClientOptions::ignore_errors`] and [`ClientOptions::ignore_transactions`] for details.
+#[derive(Clone, Default)]
+pub struct IgnorePatterns {
+    patterns: Vec<Regex>,
+}
+
+impl IgnorePatterns {
+    /// Creates a list from the given patterns.
+    ///
+    /// Patterns are regular expressions, which can match anywhere in the value. Patterns which
+    /// are not valid regular expressions are matched literally.
+    pub fn new<I, T>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        let patterns = patterns
+            .into_iter()
+            .map(|pattern| {
+                let pattern = pattern.as_ref();
+                Regex::new(pattern).unwrap_or_else(|_| {
+                    Regex::new(&regex::escape(pattern)).expect("escaped pattern is valid")
+                })
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    /// Returns whether the list contains no patterns.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Returns whether one of the patterns matches `value`.
+    pub fn matches(&self, value: &str) -> bool {
+        self.patterns.iter().any(|pattern| pattern.is_match(value))
+    }
+}
+
+impl fmt::Debug for IgnorePatterns {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.patterns.iter().map(Regex::as_str))
+            .finish()
+    }
+}
+
 /// Configuration settings for the client.
 ///
 /// These options are explained in more detail in the general
@@ -168,6 +309,15 @@ pub struct ClientOptions {
     /// [`traces_sampler`](method@ClientOptions::traces_sampler), or can be left at the default
     /// disabled value.
     pub traces_sampling_strategy: TracesSamplingStrategy,
+    /// How the spans of a transaction are sent.
+    ///
+    /// See [`trace_lifecycle`](method@ClientOptions::trace_lifecycle) for details.
+    pub trace_lifecycle: TraceLifecycle,
+    /// The sample rate for continuous profiling sessions.
+    ///
+    /// See [`profile_session_sample_rate`](method@ClientOptions::profile_session_sample_rate) for
+    /// details.
+    pub profile_session_sample_rate: f32,
     /// The organization ID used for trace continuation.
     ///
     /// See [`org_id`](method@ClientOptions::org_id) for details.
@@ -177,6 +327,20 @@ pub struct ClientOptions {
     /// See [`strict_trace_continuation`](method@ClientOptions::strict_trace_continuation) for
     /// details.
     pub strict_trace_continuation: bool,
+    /// The URLs of outgoing requests that distributed tracing headers are attached to.
+    ///
+    /// See [`trace_propagation_targets`](method@ClientOptions::trace_propagation_targets) for
+    /// details.
+    pub trace_propagation_targets: TracePropagationTargets,
+    /// The header an incoming trace is continued from, if both are present.
+    ///
+    /// See [`trace_header_precedence`](method@ClientOptions::trace_header_precedence) for
+    /// details.
+    pub trace_header_precedence: TraceHeaderPrecedence,
+    /// Attaches the W3C `traceparent` and `tracestate` headers to outgoing requests.
+    ///
+    /// See [`propagate_traceparent`](method@ClientOptions::propagate_traceparent) for details.
+    pub propagate_traceparent: bool,
     /// Maximum number of breadcrumbs.
     ///
     /// See [`max_breadcrumbs`](method@ClientOptions::max_breadcrumbs) for details.
@@ -201,6 +365,19 @@ pub struct ClientOptions {
     ///
     /// See [`in_app_exclude`](method@ClientOptions::in_app_exclude) for details.
     pub in_app_exclude: Vec<&'static str>,
+    // Filters
+    /// Patterns of error events that are not sent.
+    ///
+    /// See [`ignore_errors`](method@ClientOptions::ignore_errors) for details.
+    pub ignore_errors: IgnorePatterns,
+    /// Patterns of transaction names that are not sent.
+    ///
+    /// See [`ignore_transactions`](method@ClientOptions::ignore_transactions) for details.
+    pub ignore_transactions: IgnorePatterns,
+    /// Loggers of events that are not sent.
+    ///
+    /// See [`ignore_loggers`](method@ClientOptions::ignore_loggers) for details.
+    pub ignore_loggers: Vec<&'static str>,
     // Integration options
     /// A list of integrations to enable.
     ///
@@ -216,6 +393,14 @@ pub struct ClientOptions {
     ///
     /// See [`before_send`](method@ClientOptions::before_send) for details.
     pub before_send: Option<BeforeCallback<Event<'static>>>,
+    /// Callback that is executed before transaction sending.
+    ///
+    /// See [`before_send_transaction`](method@ClientOptions::before_send_transaction) for details.
+    pub before_send_transaction: Option<BeforeCallback<Transaction<'static>>>,
+    /// Callback that is executed before sending each streamed span.
+    ///
+    /// See [`before_send_span`](method@ClientOptions::before_send_span) for details.
+    pub before_send_span: Option<BeforeCallback<Span>>,
     /// Callback that is executed for each Breadcrumb being added.
     ///
     /// See [`before_breadcrumb`](method@ClientOptions::before_breadcrumb) for details.
@@ -224,6 +409,10 @@ pub struct ClientOptions {
     ///
     /// See [`before_send_log`](method@ClientOptions::before_send_log) for details.
     pub before_send_log: Option<BeforeCallback<Log>>,
+    /// Callback that is executed before sending user feedback.
+    ///
+    /// See [`before_send_feedback`](method@ClientOptions::before_send_feedback) for details.
+    pub before_send_feedback: Option<BeforeCallback<Feedback>>,
     // Transport options
     /// The transport to use.
     ///
@@ -241,6 +430,18 @@ pub struct ClientOptions {
     ///
     /// See [`shutdown_timeout`](method@ClientOptions::shutdown_timeout) for details.
     pub shutdown_timeout: Duration,
+    /// Options for caching envelopes on disk when they cannot be sent.
+    ///
+    /// See [`offline_cache`](method@ClientOptions::offline_cache) for details.
+    pub offline_cache: Option<OfflineCacheOptions>,
+    /// Options for compressing envelope bodies.
+    ///
+    /// See [`compression`](method@ClientOptions::compression) for details.
+    pub compression: Option<CompressionOptions>,
+    /// The policy for retrying envelopes after transient send failures.
+    ///
+    /// See [`retry`](method@ClientOptions::retry) for details.
+    pub retry: Option<RetryPolicy>,
     /// The maximum size of an HTTP request body to capture.
     ///
     /// See [`max_request_body_size`](method@ClientOptions::max_request_body_size) for details.
@@ -257,6 +458,22 @@ pub struct ClientOptions {
     ///
     /// See [`before_send_metric`](method@ClientOptions::before_send_metric) for details.
     pub before_send_metric: Option<BeforeCallback<Metric>>,
+    /// Whether counters and gauges are aggregated before sending.
+    ///
+    /// See [`aggregate_metrics`](method@ClientOptions::aggregate_metrics) for details.
+    pub aggregate_metrics: bool,
+    /// The maximum number of logs, metrics or streamed spans sent in a single batch.
+    ///
+    /// See [`max_batch_items`](method@ClientOptions::max_batch_items) for details.
+    pub max_batch_items: usize,
+    /// The interval at which batches of logs, metrics and streamed spans are sent.
+    ///
+    /// See [`batch_flush_interval`](method@ClientOptions::batch_flush_interval) for details.
+    pub batch_flush_interval: Duration,
+    /// The maximum size of the logs, metrics or streamed spans queued for a batch, in bytes.
+    ///
+    /// See [`max_batch_queue_bytes`](method@ClientOptions::max_batch_queue_bytes) for details.
+    pub max_batch_queue_bytes: u64,
     // Other options not documented in Unified API
     /// Whether to disable SSL verification.
     ///
@@ -414,6 +631,56 @@ impl ClientOptions {
         }
     }
 
+    /// Sets [how the spans of a transaction are sent](field@ClientOptions::trace_lifecycle).
+    ///
+    /// By default, finished spans are buffered in their transaction, and sent together with it.
+    /// Transactions keep at most 1000 spans, so long-running transactions lose most of their spans
+    /// and hold the others in memory until they finish.
+    ///
+    /// With [`TraceLifecycle::Stream`], finished spans are instead sent in batches as they finish,
+    /// and a finished transaction is sent as the root span of its spans, called a segment, rather
+    /// than as a transaction event. Only sampled spans are sent in either case.
+    ///
+    /// As the spans are sent before their transaction finishes, they are processed on their own:
+    /// each finished span is dropped if the current name of its transaction matches the
+    /// [`ignore_transactions`](method@ClientOptions::ignore_transactions) patterns, and otherwise
+    /// passed to the [span processors](crate::Integration::process_span) of the integrations and
+    /// the [`before_send_span`](method@ClientOptions::before_send_span) callback. The finished
+    /// transaction, which has no child spans, goes through the processing of transactions once
+    /// before it is sent as the segment.
+    #[inline]
+    pub fn trace_lifecycle(self, trace_lifecycle: TraceLifecycle) -> Self {
+        Self {
+            trace_lifecycle,
+            ..self
+        }
+    }
+
+    /// Sets the [sample rate](field@ClientOptions::profile_session_sample_rate) for continuous
+    /// profiling sessions.
+    ///
+    /// Whether a profiler session is sampled is decided once when the profiler starts, and all
+    /// profile chunks of a sampled session are sent. The `profiling` feature is required to
+    /// profile. Must be between `0.0` and `1.0`, defaults to `0.0`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `profile_session_sample_rate` is outside the allowed range.
+    #[inline]
+    pub fn profile_session_sample_rate(self, profile_session_sample_rate: f32) -> Self {
+        if !(0.0..=1.0).contains(&profile_session_sample_rate) {
+            panic!(
+                "Profile session sample rate {profile_session_sample_rate} is outside the allowed \
+                 range [0.0, 1.0]."
+            )
+        }
+
+        Self {
+            profile_session_sample_rate,
+            ..self
+        }
+    }
+
     /// Sets the [organization ID](field@ClientOptions::org_id) used for trace continuation.
     ///
     /// By default, we infer the organization ID from the DSN when available. Setting this option
@@ -446,6 +713,65 @@ impl ClientOptions {
         }
     }
 
+    /// Sets the [URLs](field@ClientOptions::trace_propagation_targets) of outgoing requests that
+    /// distributed tracing headers are attached to.
+    ///
+    /// HTTP client integrations only attach the `sentry-trace` header to requests whose URL
+    /// matches one of the targets. Targets are regular expressions, which can match anywhere in
+    /// the URL, for example `"^https://api\\.example\\.com/"`. Targets which are not valid
+    /// regular expressions are matched literally.
+    ///
+    /// By default, the headers are attached to all outgoing requests. Passing an empty list
+    /// disables attaching them.
+    #[inline]
+    pub fn trace_propagation_targets<I, T>(self, targets: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        let trace_propagation_targets = TracePropagationTargets::new(targets);
+        Self {
+            trace_propagation_targets,
+            ..self
+        }
+    }
+
+    /// Sets the [header](field@ClientOptions::trace_header_precedence) an incoming trace is
+    /// continued from, if both are present.
+    ///
+    /// Incoming traces are continued from either the `sentry-trace` header or the W3C
+    /// `traceparent` header. If a request has both, which can happen behind gateways that emit
+    /// `traceparent`, this decides which one is used. A header which cannot be parsed is ignored
+    /// in favor of the other one.
+    ///
+    /// Defaults to [`TraceHeaderPrecedence::SentryTrace`].
+    #[inline]
+    pub fn trace_header_precedence(self, trace_header_precedence: TraceHeaderPrecedence) -> Self {
+        Self {
+            trace_header_precedence,
+            ..self
+        }
+    }
+
+    /// Enables or disables attaching the W3C
+    /// [`traceparent` and `tracestate` headers](field@ClientOptions::propagate_traceparent) to
+    /// outgoing requests.
+    ///
+    /// When enabled, the headers returned by
+    /// [`TransactionOrSpan::iter_headers`](crate::TransactionOrSpan::iter_headers) and
+    /// [`Scope::iter_trace_propagation_headers`](crate::Scope::iter_trace_propagation_headers)
+    /// include a `traceparent` header next to the `sentry-trace` header, and the `tracestate`
+    /// header of the incoming trace, if any.
+    ///
+    /// Defaults to `false`.
+    #[inline]
+    pub fn propagate_traceparent(self, propagate_traceparent: bool) -> Self {
+        Self {
+            propagate_traceparent,
+            ..self
+        }
+    }
+
     /// Sets the [maximum number of breadcrumbs](field@ClientOptions::max_breadcrumbs).
     ///
     /// Defaults to `100`.
@@ -522,6 +848,72 @@ impl ClientOptions {
         }
     }
 
+    /// Sets the [patterns](field@ClientOptions::ignore_errors) of error events that are not sent.
+    ///
+    /// An event is dropped if one of the patterns matches its message, or the type, the value,
+    /// or `"{type}: {value}"` of one of its exceptions, for example `"Broken pipe"`. Patterns are
+    /// regular expressions, which can match anywhere in the value. Patterns which are not valid
+    /// regular expressions are matched literally.
+    ///
+    /// Dropped events are recorded in client reports with the `filtered` reason, and do not
+    /// reach the [`before_send`](method@ClientOptions::before_send) callback.
+    #[inline]
+    pub fn ignore_errors<I, T>(self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        let ignore_errors = IgnorePatterns::new(patterns);
+        Self {
+            ignore_errors,
+            ..self
+        }
+    }
+
+    /// Sets the [patterns](field@ClientOptions::ignore_transactions) of transaction names that
+    /// are not sent.
+    ///
+    /// A transaction is dropped if one of the patterns matches its name, for example
+    /// `"^GET /healthz$"`. Patterns are regular expressions, which can match anywhere in the
+    /// name. Patterns which are not valid regular expressions are matched literally.
+    ///
+    /// Dropped transactions are recorded in client reports with the `filtered` reason, and do not
+    /// reach the [`before_send_transaction`](method@ClientOptions::before_send_transaction)
+    /// callback.
+    #[inline]
+    pub fn ignore_transactions<I, T>(self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        let ignore_transactions = IgnorePatterns::new(patterns);
+        Self {
+            ignore_transactions,
+            ..self
+        }
+    }
+
+    /// Sets the [loggers](field@ClientOptions::ignore_loggers) of events that are not sent.
+    ///
+    /// An event is dropped if its logger is one of the given loggers, or one of their modules.
+    /// The `log` and `tracing` integrations use the target of a record as the logger, which is
+    /// its module path by default, so that for example `"hyper"` drops the events of the `hyper`
+    /// crate and its modules, but not those of `hyper_util`.
+    ///
+    /// Dropped events are recorded in client reports with the `filtered` reason, and do not
+    /// reach the [`before_send`](method@ClientOptions::before_send) callback.
+    #[inline]
+    pub fn ignore_loggers<I>(self, ignore_loggers: I) -> Self
+    where
+        I: IntoIterator<Item = &'static str>,
+    {
+        let ignore_loggers = ignore_loggers.into_iter().collect();
+        Self {
+            ignore_loggers,
+            ..self
+        }
+    }
+
     /// Sets the [integrations](field@ClientOptions::integrations) to enable, replacing the
     /// existing list.
     ///
@@ -566,6 +958,49 @@ impl ClientOptions {
         }
     }
 
+    /// Sets the [callback](field@ClientOptions::before_send_transaction) that is executed before
+    /// sending each transaction.
+    ///
+    /// The callback runs after the transaction processors of the scope and the integrations. It
+    /// can modify the transaction, for example to rename it or scrub span data, or drop it by
+    /// returning `None`, which is recorded in client reports.
+    ///
+    /// When [streaming spans](method@ClientOptions::trace_lifecycle), the callback only runs for
+    /// the finished transaction, which no longer contains the spans that were already sent. Use
+    /// [`before_send_span`](method@ClientOptions::before_send_span) to process the spans.
+    #[inline]
+    pub fn before_send_transaction<F>(self, before_send_transaction: F) -> Self
+    where
+        F: Fn(Transaction<'static>) -> Option<Transaction<'static>> + Send + Sync + 'static,
+    {
+        let before_send_transaction =
+            Some(Arc::new(before_send_transaction) as BeforeCallback<Transaction<'static>>);
+        Self {
+            before_send_transaction,
+            ..self
+        }
+    }
+
+    /// Sets the [callback](field@ClientOptions::before_send_span) that is executed before sending
+    /// each streamed span.
+    ///
+    /// The callback is only used when [streaming spans](method@ClientOptions::trace_lifecycle),
+    /// and runs for every finished child span after the span processors of the integrations. It
+    /// can modify the span, for example to scrub its data, or drop it by returning `None`, which
+    /// is recorded in client reports. The segment span of a transaction is processed by
+    /// [`before_send_transaction`](method@ClientOptions::before_send_transaction) instead.
+    #[inline]
+    pub fn before_send_span<F>(self, before_send_span: F) -> Self
+    where
+        F: Fn(Span) -> Option<Span> + Send + Sync + 'static,
+    {
+        let before_send_span = Some(Arc::new(before_send_span) as BeforeCallback<Span>);
+        Self {
+            before_send_span,
+            ..self
+        }
+    }
+
     /// Sets the [callback](field@ClientOptions::before_breadcrumb) that is executed before adding
     /// each breadcrumb.
     #[inline]
@@ -595,6 +1030,22 @@ impl ClientOptions {
         }
     }
 
+    /// Sets the [callback](field@ClientOptions::before_send_feedback) that is executed before
+    /// sending user feedback.
+    ///
+    /// This callback can modify feedback or return `None` to drop it.
+    #[inline]
+    pub fn before_send_feedback<F>(self, before_send_feedback: F) -> Self
+    where
+        F: Fn(Feedback) -> Option<Feedback> + Send + Sync + 'static,
+    {
+        let before_send_feedback = Some(Arc::new(before_send_feedback) as BeforeCallback<Feedback>);
+        Self {
+            before_send_feedback,
+            ..self
+        }
+    }
+
     /// Sets the [callback](field@ClientOptions::before_send_metric) that is executed before
     /// sending each metric.
     ///
@@ -612,6 +1063,69 @@ impl ClientOptions {
         }
     }
 
+    /// Enables or disables [aggregation](field@ClientOptions::aggregate_metrics) of metrics.
+    ///
+    /// Metrics are sent in batches, see [`max_batch_items`](method@ClientOptions::max_batch_items)
+    /// and [`batch_flush_interval`](method@ClientOptions::batch_flush_interval). When enabled,
+    /// counters and gauges with the same name, unit and attributes are merged into a single metric
+    /// per batch, which reduces the size of envelopes when metrics are captured in hot loops:
+    ///
+    /// - Counters are summed up.
+    /// - Gauges keep their last value, and carry the minimum, maximum, sum and count of all their
+    ///   values in the `sentry.gauge.min`, `sentry.gauge.max`, `sentry.gauge.sum` and
+    ///   `sentry.gauge.count` attributes.
+    /// - Distributions are not aggregated, and all of their values are sent.
+    ///
+    /// Aggregated metrics carry the timestamp, trace and span of their last value. Metrics are
+    /// aggregated after [`before_send_metric`](method@ClientOptions::before_send_metric), which
+    /// sees every individual metric. Defaults to `false`.
+    #[inline]
+    pub fn aggregate_metrics(self, aggregate_metrics: bool) -> Self {
+        Self {
+            aggregate_metrics,
+            ..self
+        }
+    }
+
+    /// Sets the [maximum number of items](field@ClientOptions::max_batch_items) in a batch.
+    ///
+    /// Logs, metrics and streamed spans are queued, and sent in batches once this many items are
+    /// queued, or every [`batch_flush_interval`](method@ClientOptions::batch_flush_interval).
+    /// Values below `1` are treated as `1`. Defaults to `100`.
+    #[inline]
+    pub fn max_batch_items(self, max_batch_items: usize) -> Self {
+        Self {
+            max_batch_items,
+            ..self
+        }
+    }
+
+    /// Sets the [interval](field@ClientOptions::batch_flush_interval) at which batches are sent.
+    ///
+    /// Queued logs, metrics and streamed spans are sent at this interval, unless the batch filled
+    /// up earlier. Intervals below 10 ms are treated as 10 ms. Defaults to 5 seconds.
+    #[inline]
+    pub fn batch_flush_interval(self, batch_flush_interval: Duration) -> Self {
+        Self {
+            batch_flush_interval: batch_flush_interval.max(MIN_BATCH_FLUSH_INTERVAL),
+            ..self
+        }
+    }
+
+    /// Sets the [maximum size](field@ClientOptions::max_batch_queue_bytes) of a batch queue.
+    ///
+    /// The size of queued items is estimated like Relay computes the size of logs and metrics,
+    /// from their body, name and attributes. Items which would make the queue exceed this size
+    /// are dropped and recorded as `queue_overflow` in client reports. Each of the logs, metrics
+    /// and streamed spans queues is limited separately. Defaults to 1 MiB.
+    #[inline]
+    pub fn max_batch_queue_bytes(self, max_batch_queue_bytes: u64) -> Self {
+        Self {
+            max_batch_queue_bytes,
+            ..self
+        }
+    }
+
     /// Sets the [transport](field@ClientOptions::transport) to use.
     ///
     /// This is typically either a function taking the client options by reference and returning a
@@ -662,6 +1176,46 @@ impl ClientOptions {
         }
     }
 
+    /// Enables the [offline cache](field@ClientOptions::offline_cache) for envelopes which cannot
+    /// be sent.
+    ///
+    /// When enabled, the HTTP transports provided by the `sentry` crate write envelopes that could
+    /// not be delivered to disk, and send them once delivery succeeds again or on the next start.
+    /// Custom transports may ignore this option. Disabled by default.
+    #[inline]
+    pub fn offline_cache(self, offline_cache: OfflineCacheOptions) -> Self {
+        let offline_cache = Some(offline_cache);
+        Self {
+            offline_cache,
+            ..self
+        }
+    }
+
+    /// Enables [compression](field@ClientOptions::compression) of envelope bodies.
+    ///
+    /// When enabled, the HTTP transports provided by the `sentry` crate compress envelope bodies
+    /// above the configured size threshold and set the `Content-Encoding` header accordingly.
+    /// Custom transports may ignore this option. Disabled by default.
+    #[inline]
+    pub fn compression(self, compression: CompressionOptions) -> Self {
+        let compression = Some(compression);
+        Self {
+            compression,
+            ..self
+        }
+    }
+
+    /// Enables [retries](field@ClientOptions::retry) of envelopes after transient send failures.
+    ///
+    /// When enabled, the HTTP transports provided by the `sentry` crate send envelopes again after
+    /// network errors and server errors, backing off as configured by the [`RetryPolicy`].
+    /// Custom transports may ignore this option. Disabled by default.
+    #[inline]
+    pub fn retry(self, retry: RetryPolicy) -> Self {
+        let retry = Some(retry);
+        Self { retry, ..self }
+    }
+
     /// Sets the [maximum request body size](field@ClientOptions::max_request_body_size) to
     /// capture.
     ///
@@ -773,6 +1327,15 @@ impl fmt::Debug for ClientOptions {
         struct BeforeSend;
         let before_send = self.before_send.as_ref().map(|_| BeforeSend);
         #[derive(Debug)]
+        struct BeforeSendTransaction;
+        let before_send_transaction = self
+            .before_send_transaction
+            .as_ref()
+            .map(|_| BeforeSendTransaction);
+        #[derive(Debug)]
+        struct BeforeSendSpan;
+        let before_send_span = self.before_send_span.as_ref().map(|_| BeforeSendSpan);
+        #[derive(Debug)]
         struct BeforeBreadcrumb;
         let before_breadcrumb = self.before_breadcrumb.as_ref().map(|_| BeforeBreadcrumb);
         let before_send_log = {
@@ -785,6 +1348,13 @@ impl fmt::Debug for ClientOptions {
             struct BeforeSendMetric;
             self.before_send_metric.as_ref().map(|_| BeforeSendMetric)
         };
+        let before_send_feedback = {
+            #[derive(Debug)]
+            struct BeforeSendFeedback;
+            self.before_send_feedback
+                .as_ref()
+                .map(|_| BeforeSendFeedback)
+        };
         #[derive(Debug)]
         struct TransportFactory;
 
@@ -798,20 +1368,33 @@ impl fmt::Debug for ClientOptions {
             .field("environment", &self.environment)
             .field("event_sampling_strategy", &self.event_sampling_strategy)
             .field("traces_sampling_strategy", &self.traces_sampling_strategy)
+            .field("trace_lifecycle", &self.trace_lifecycle)
+            .field(
+                "profile_session_sample_rate",
+                &self.profile_session_sample_rate,
+            )
             .field("max_breadcrumbs", &self.max_breadcrumbs)
             .field("attach_stacktrace", &self.attach_stacktrace)
             .field("send_default_pii", &self.send_default_pii)
             .field("server_name", &self.server_name)
             .field("in_app_include", &self.in_app_include)
             .field("in_app_exclude", &self.in_app_exclude)
+            .field("ignore_errors", &self.ignore_errors)
+            .field("ignore_transactions", &self.ignore_transactions)
+            .field("ignore_loggers", &self.ignore_loggers)
             .field("integrations", &integrations)
             .field("default_integrations", &self.default_integrations)
             .field("before_send", &before_send)
+            .field("before_send_transaction", &before_send_transaction)
+            .field("before_send_span", &before_send_span)
             .field("before_breadcrumb", &before_breadcrumb)
             .field("transport", &TransportFactory)
             .field("http_proxy", &self.http_proxy)
             .field("https_proxy", &self.https_proxy)
             .field("shutdown_timeout", &self.shutdown_timeout)
+            .field("offline_cache", &self.offline_cache)
+            .field("compression", &self.compression)
+            .field("retry", &self.retry)
             .field("accept_invalid_certs", &self.accept_invalid_certs)
             .field("auto_session_tracking", &self.auto_session_tracking)
             .field("session_mode", &self.session_mode)
@@ -819,8 +1402,16 @@ impl fmt::Debug for ClientOptions {
             .field("before_send_log", &before_send_log)
             .field("enable_metrics", &self.enable_metrics)
             .field("before_send_metric", &before_send_metric)
+            .field("aggregate_metrics", &self.aggregate_metrics)
+            .field("max_batch_items", &self.max_batch_items)
+            .field("batch_flush_interval", &self.batch_flush_interval)
+            .field("max_batch_queue_bytes", &self.max_batch_queue_bytes)
+            .field("before_send_feedback", &before_send_feedback)
             .field("org_id", &self.org_id)
             .field("strict_trace_continuation", &self.strict_trace_continuation)
+            .field("trace_propagation_targets", &self.trace_propagation_targets)
+            .field("trace_header_precedence", &self.trace_header_precedence)
+            .field("propagate_traceparent", &self.propagate_traceparent)
             .field("user_agent", &self.user_agent)
             .finish()
     }
@@ -832,25 +1423,38 @@ impl Default for ClientOptions {
             dsn: None,
             org_id: None,
             strict_trace_continuation: false,
+            trace_propagation_targets: TracePropagationTargets::all(),
+            trace_header_precedence: TraceHeaderPrecedence::default(),
+            propagate_traceparent: false,
             debug: false,
             release: None,
             environment: None,
             event_sampling_strategy: Default::default(),
             traces_sampling_strategy: Default::default(),
+            trace_lifecycle: Default::default(),
+            profile_session_sample_rate: 0.0,
             max_breadcrumbs: 100,
             attach_stacktrace: false,
             send_default_pii: false,
             server_name: None,
             in_app_include: vec![],
             in_app_exclude: vec![],
+            ignore_errors: IgnorePatterns::default(),
+            ignore_transactions: IgnorePatterns::default(),
+            ignore_loggers: vec![],
             integrations: vec![],
             default_integrations: true,
             before_send: None,
+            before_send_transaction: None,
+            before_send_span: None,
             before_breadcrumb: None,
             transport: None,
             http_proxy: None,
             https_proxy: None,
             shutdown_timeout: Duration::from_secs(2),
+            offline_cache: None,
+            compression: None,
+            retry: None,
             accept_invalid_certs: false,
             auto_session_tracking: false,
             session_mode: SessionMode::Application,
@@ -860,6 +1464,11 @@ impl Default for ClientOptions {
             before_send_log: None,
             enable_metrics: true,
             before_send_metric: None,
+            aggregate_metrics: false,
+            max_batch_items: 100,
+            batch_flush_interval: Duration::from_secs(5),
+            max_batch_queue_bytes: 1024 * 1024,
+            before_send_feedback: None,
         }
     }
 }
@@ -2,6 +2,7 @@
 #![allow(dead_code)]
 
 use std::sync::LazyLock;
+use std::time::Duration;
 
 use crate::protocol::{ClientSdkInfo, ClientSdkPackage};
 
@@ -9,6 +10,9 @@ use crate::protocol::{ClientSdkInfo, ClientSdkPackage};
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 pub(crate) const USER_AGENT: &str = concat!("sentry.rust/", env!("CARGO_PKG_VERSION"));
 
+/// The shortest interval at which batches of logs, metrics and streamed spans are sent.
+pub(crate) const MIN_BATCH_FLUSH_INTERVAL: Duration = Duration::from_millis(10);
+
 pub(crate) static SDK_INFO: LazyLock<ClientSdkInfo> = LazyLock::new(|| ClientSdkInfo {
     name: "sentry.rust".into(),
     version: VERSION.into(),
@@ -66,6 +66,89 @@ pub trait SentryFutureExt: Sized {
 
 impl<F> SentryFutureExt for F where F: Future {}
 
+/// Spawns a future on the current tokio runtime, bound to a new hub.
+///
+/// The hub is forked from the current one, so the task starts with a copy of the current scope,
+/// including its active span, but changes it makes to its scope do not leak into other tasks.
+/// This is a shorthand for:
+///
+/// ```rust
+/// # use sentry::{Hub, SentryFutureExt};
+/// # async fn run(future: impl std::future::Future<Output = ()> + Send + 'static) {
+/// tokio::spawn(future.bind_hub(Hub::new_from_top(Hub::current())));
+/// # }
+/// ```
+///
+/// **Feature:** `tokio` (*disabled by default*)
+///
+/// # Panics
+///
+/// Panics if called outside of a tokio runtime, like [`tokio::spawn`].
+#[cfg(feature = "tokio")]
+pub fn spawn<F>(future: F) -> tokio::task::JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(future.bind_hub(Hub::new_from_top(Hub::current())))
+}
+
+/// Tokio runtime builder extensions for Sentry.
+///
+/// **Feature:** `tokio` (*disabled by default*)
+#[cfg(feature = "tokio")]
+pub trait SentryRuntimeBuilderExt {
+    /// Runs every thread of the runtime on a new hub forked from the given one.
+    ///
+    /// Without this, the threads of the runtime use hubs forked from the main hub, and do not see
+    /// changes made to the scope of the hub that built the runtime. Note that all tasks which run
+    /// on a thread share its hub, so tasks should still be spawned with [`spawn`] if they modify
+    /// their scope.
+    ///
+    /// This sets the `on_thread_start` and `on_thread_stop` callbacks of the builder, replacing
+    /// any callbacks set before.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sentry::{Hub, SentryRuntimeBuilderExt};
+    ///
+    /// let runtime = tokio::runtime::Builder::new_multi_thread()
+    ///     .bind_hub(Hub::current())
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    fn bind_hub<H>(&mut self, hub: H) -> &mut Self
+    where
+        H: Into<Arc<Hub>>;
+}
+
+#[cfg(feature = "tokio")]
+impl SentryRuntimeBuilderExt for tokio::runtime::Builder {
+    fn bind_hub<H>(&mut self, hub: H) -> &mut Self
+    where
+        H: Into<Arc<Hub>>,
+    {
+        use std::cell::RefCell;
+
+        use crate::hub_impl::SwitchGuard;
+
+        thread_local! {
+            /// The guard switching a runtime thread to its hub, dropped when the thread stops.
+            static RUNTIME_HUB: RefCell<Option<SwitchGuard>> = const { RefCell::new(None) };
+        }
+
+        let hub = hub.into();
+        self.on_thread_start(move || {
+            let guard = SwitchGuard::new(Arc::new(Hub::new_from_top(&hub)));
+            RUNTIME_HUB.with_borrow_mut(|runtime_hub| *runtime_hub = Some(guard));
+        })
+        .on_thread_stop(|| {
+            drop(RUNTIME_HUB.with_borrow_mut(Option::take));
+        })
+    }
+}
+
 #[cfg(all(test, feature = "test"))]
 mod tests {
     use crate::test::with_captured_events;
@@ -105,4 +188,62 @@ mod tests {
         assert_eq!(events[1].transaction, Some("transaction1".into()));
         assert_eq!(events[2].transaction, Some("transaction2".into()));
     }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_spawn() {
+        let mut events = with_captured_events(|| {
+            let runtime = Runtime::new().unwrap();
+            configure_scope(|scope| scope.set_tag("outer", "true"));
+
+            runtime.block_on(async {
+                let task1 = crate::spawn(async {
+                    configure_scope(|scope| scope.set_transaction(Some("transaction1")));
+                    capture_message("oh hai from 1", Level::Info);
+                });
+                let task2 = crate::spawn(async {
+                    configure_scope(|scope| scope.set_transaction(Some("transaction2")));
+                    capture_message("oh hai from 2", Level::Info);
+                });
+
+                task1.await.unwrap();
+                task2.await.unwrap();
+            });
+
+            capture_message("oh hai from outside", Level::Info);
+        });
+
+        events.sort_by(|a, b| a.transaction.cmp(&b.transaction));
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].transaction, None);
+        assert_eq!(events[1].transaction, Some("transaction1".into()));
+        assert_eq!(events[2].transaction, Some("transaction2".into()));
+        assert!(events.iter().all(|event| event.tags["outer"] == "true"));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_runtime_bind_hub() {
+        use crate::SentryRuntimeBuilderExt;
+
+        let events = with_captured_events(|| {
+            configure_scope(|scope| scope.set_tag("outer", "true"));
+            let runtime = tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(2)
+                .bind_hub(Hub::current())
+                .build()
+                .unwrap();
+
+            runtime.block_on(async {
+                tokio::spawn(async {
+                    capture_message("oh hai from a worker", Level::Info);
+                })
+                .await
+                .unwrap();
+            });
+        });
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].tags["outer"], "true");
+    }
 }
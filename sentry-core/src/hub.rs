@@ -6,7 +6,7 @@ use crate::metrics::IntoProtocolMetric;
 use crate::protocol::Log;
 #[cfg(feature = "release-health")]
 use crate::protocol::SessionStatus;
-use crate::protocol::{Event, Level};
+use crate::protocol::{Event, Feedback, Level, MonitorCheckIn};
 use crate::types::Uuid;
 use crate::{Integration, IntoBreadcrumbs, Scope, ScopeGuard};
 
@@ -122,6 +122,32 @@ impl Hub {
         }}
     }
 
+    /// Captures user feedback.
+    ///
+    /// See the global [`capture_feedback`](fn.capture_feedback.html)
+    /// for more documentation.
+    pub fn capture_feedback(&self, feedback: Feedback) -> Uuid {
+        use_without_client!(feedback);
+        with_client_impl! {{
+            let top = self.inner.with(|stack| stack.top().clone());
+            let Some(ref client) = top.client else { return Default::default() };
+            client.capture_feedback(feedback, Some(&top.scope))
+        }}
+    }
+
+    /// Captures a monitor check-in.
+    ///
+    /// See [`monitor::capture_check_in`](crate::monitor::capture_check_in)
+    /// for more documentation.
+    pub fn capture_check_in(&self, check_in: MonitorCheckIn) -> Uuid {
+        use_without_client!(check_in);
+        with_client_impl! {{
+            let top = self.inner.with(|stack| stack.top().clone());
+            let Some(ref client) = top.client else { return Default::default() };
+            client.capture_check_in(check_in, Some(&top.scope))
+        }}
+    }
+
     /// Captures an arbitrary message.
     ///
     /// See the global [`capture_message`](fn.capture_message.html)
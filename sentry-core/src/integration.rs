@@ -1,6 +1,6 @@
 use std::any::{type_name, Any};
 
-use crate::protocol::Event;
+use crate::protocol::{Event, Log, Metric, Span, Transaction};
 use crate::ClientOptions;
 
 /// Integration abstraction.
@@ -78,6 +78,49 @@ pub trait Integration: Sync + Send + Any + AsAny {
         let _ = options;
         Some(event)
     }
+
+    /// The Integrations Transaction Processor Hook.
+    ///
+    /// An integration can process, or even completely drop a `Transaction`, including its spans.
+    /// This runs after the scope was applied to the transaction, and before the
+    /// `before_send_transaction` callback.
+    fn process_transaction(
+        &self,
+        transaction: Transaction<'static>,
+        options: &ClientOptions,
+    ) -> Option<Transaction<'static>> {
+        let _ = options;
+        Some(transaction)
+    }
+
+    /// The Integrations Span Processor Hook.
+    ///
+    /// An integration can process, or even completely drop a `Span`. This is only called when
+    /// [streaming spans](crate::ClientOptions::trace_lifecycle), for every finished child span,
+    /// before the `before_send_span` callback. Otherwise, spans are processed as part of their
+    /// transaction by [`process_transaction`](Integration::process_transaction).
+    fn process_span(&self, span: Span, options: &ClientOptions) -> Option<Span> {
+        let _ = options;
+        Some(span)
+    }
+
+    /// The Integrations Log Processor Hook.
+    ///
+    /// An integration can process, or even completely drop a `Log`. This runs before the
+    /// `before_send_log` callback.
+    fn process_log(&self, log: Log, options: &ClientOptions) -> Option<Log> {
+        let _ = options;
+        Some(log)
+    }
+
+    /// The Integrations Metric Processor Hook.
+    ///
+    /// An integration can process, or even completely drop a `Metric`. This runs before the
+    /// `before_send_metric` callback.
+    fn process_metric(&self, metric: Metric, options: &ClientOptions) -> Option<Metric> {
+        let _ = options;
+        Some(metric)
+    }
 }
 
 // This is needed as a workaround to be able to safely downcast integrations
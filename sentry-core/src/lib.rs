@@ -54,6 +54,23 @@
 //! # });
 //! ```
 //!
+//! The [`thread::spawn`] helper, and the `spawn` helper for tokio tasks, do this when spawning a
+//! thread or task:
+//!
+//! ```rust
+//! # let rt = tokio::runtime::Runtime::new().unwrap();
+//! # rt.block_on(async {
+//! sentry::configure_scope(|scope| scope.set_tag("worker", "true"));
+//!
+//! // The thread and the task see the tag, but do not leak their own scope changes.
+//! let result = sentry::thread::spawn(|| 1_u32).join();
+//! assert_eq!(result.unwrap(), 1);
+//!
+//! let result = sentry::spawn(async { 1_u32 }).await;
+//! assert_eq!(result.unwrap(), 1);
+//! # });
+//! ```
+//!
 //! For tasks that are not concurrent and do not outlive the current execution
 //! context, no *new* [`Hub`] needs to be created, but the current [`Hub`] has
 //! to be bound.
@@ -90,6 +107,8 @@
 //!
 //! - `feature = "client"`: Activates the [`Client`] type and certain
 //!   [`Hub`] functionality.
+//! - `feature = "tokio"`: Activates the `spawn` helper for tokio tasks and the
+//!   `SentryRuntimeBuilderExt` trait, which bind new hubs to tasks and runtime threads.
 //! - `feature = "test"`: Activates the [`test`] module, which can be used to
 //!   write integration tests. It comes with a test transport which can capture
 //!   all sent events for inspection.
@@ -118,28 +137,40 @@ mod integration;
 mod intodsn;
 mod performance;
 mod scope;
+pub mod thread;
 mod transport;
 
 // public api or exports from this crate
 pub use crate::api::*;
 pub use crate::breadcrumbs::IntoBreadcrumbs;
 pub use crate::clientoptions::{
-    BeforeCallback, ClientOptions, EventSamplingStrategy, SessionMode, TracesSamplingStrategy,
+    BeforeCallback, ClientOptions, EventSamplingStrategy, IgnorePatterns, SessionMode,
+    TraceHeaderPrecedence, TraceLifecycle, TracePropagationTargets, TracesSamplingStrategy,
 };
 pub use crate::error::{capture_error, event_from_error, parse_type_from_debug};
+#[cfg(feature = "tokio")]
+pub use crate::futures::{spawn, SentryRuntimeBuilderExt};
 pub use crate::futures::{SentryFuture, SentryFutureExt};
 pub use crate::hub::Hub;
 pub use crate::integration::Integration;
 pub use crate::intodsn::IntoDsn;
 pub use crate::performance::*;
 pub use crate::scope::{Scope, ScopeGuard};
-pub use crate::transport::{Transport, TransportFactory, TransportOptions};
+pub use crate::transport::{
+    CompressionAlgorithm, CompressionOptions, OfflineCacheOptions, RetryPolicy, Transport,
+    TransportFactory, TransportOptions,
+};
 #[cfg(feature = "logs")]
 mod logger; // structured logging macros exported with `#[macro_export]`
 
 #[cfg(feature = "metrics")]
 pub mod metrics;
 
+pub mod monitor;
+
+#[cfg(feature = "profiling")]
+pub mod profiling;
+
 // client feature
 #[cfg(feature = "client")]
 mod client;
@@ -0,0 +1,212 @@
+//! APIs for sending [Cron Monitor] check-ins.
+//!
+//! A job is monitored by sending an [`InProgress`] check-in when it starts, and an [`Ok`] or
+//! [`Error`] check-in including its duration when it finishes. [`with_check_in`] and
+//! [`with_check_in_async`] do this for a closure or future, [`start_check_in`] returns a
+//! [`CheckInGuard`] for jobs that do not fit into a single closure, and [`capture_check_in`] sends
+//! a single [`MonitorCheckIn`].
+//!
+//! Check-ins are sent through the current [`Hub`], and are linked to its current trace. When a
+//! [`MonitorConfig`] is passed, the monitor is created or updated on Sentry with that
+//! configuration, so that schedules can be registered from code.
+//!
+//! [Cron Monitor]: https://docs.sentry.io/product/crons/
+//! [`InProgress`]: MonitorCheckInStatus::InProgress
+//! [`Ok`]: MonitorCheckInStatus::Ok
+//! [`Error`]: MonitorCheckInStatus::Error
+//!
+//! # Examples
+//!
+//! ```
+//! use sentry::monitor;
+//! use sentry::protocol::{MonitorConfig, MonitorSchedule};
+//!
+//! fn cleanup() -> Result<(), std::io::Error> {
+//!     // ...
+//!     # Ok(())
+//! }
+//!
+//! let config = MonitorConfig {
+//!     max_runtime: Some(30),
+//!     ..MonitorConfig::new(MonitorSchedule::from_crontab("0 3 * * *").unwrap())
+//! };
+//! // Reports the job as failed if `cleanup` returns an error or panics.
+//! let result = monitor::with_check_in("nightly-cleanup", Some(config), cleanup);
+//! ```
+
+use std::future::Future;
+#[cfg(feature = "client")]
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+use crate::protocol::{MonitorCheckIn, MonitorCheckInStatus, MonitorConfig};
+use crate::types::{random_uuid, Uuid};
+use crate::Hub;
+
+/// Sends a monitor check-in through the current [`Hub`].
+///
+/// The check-in is linked to the current trace, and is assigned the environment of the client
+/// unless it already has one.
+///
+/// The return value is the check-in ID. If the check-in was discarded for any reason, the return
+/// value will be the nil UUID (`Uuid::nil`).
+pub fn capture_check_in(check_in: MonitorCheckIn) -> Uuid {
+    Hub::with_active(|hub| hub.capture_check_in(check_in))
+}
+
+/// Starts a check-in for the monitor with the given slug.
+///
+/// This sends an [`InProgress`](MonitorCheckInStatus::InProgress) check-in and returns a guard,
+/// which sends the final check-in with the duration of the job when it is
+/// [finished](CheckInGuard::finish) or dropped.
+///
+/// If `monitor_config` is provided, the monitor is created or updated on Sentry.
+pub fn start_check_in(monitor_slug: &str, monitor_config: Option<MonitorConfig>) -> CheckInGuard {
+    let guard = CheckInGuard {
+        #[cfg(feature = "client")]
+        hub: Hub::with_active(|hub| Some(hub.clone())),
+        check_in_id: random_uuid(),
+        monitor_slug: monitor_slug.to_owned(),
+        monitor_config,
+        start: Instant::now(),
+        finished: false,
+        drop_status: MonitorCheckInStatus::Ok,
+    };
+    guard.send(MonitorCheckInStatus::InProgress);
+    guard
+}
+
+/// Runs the given job, surrounded by check-ins for the monitor with the given slug.
+///
+/// The final check-in has the status reported by the [`CheckInOutcome`] of the job's return value.
+/// If the job panics, an [`Error`](MonitorCheckInStatus::Error) check-in is sent before the panic
+/// continues to unwind.
+///
+/// See [`start_check_in`] for details.
+pub fn with_check_in<F, R>(monitor_slug: &str, monitor_config: Option<MonitorConfig>, job: F) -> R
+where
+    F: FnOnce() -> R,
+    R: CheckInOutcome,
+{
+    let guard = start_check_in(monitor_slug, monitor_config);
+    let result = job();
+    guard.finish(result.check_in_status());
+    result
+}
+
+/// Runs the given future, surrounded by check-ins for the monitor with the given slug.
+///
+/// The check-in starts when the returned future is first polled. Like [`with_check_in`], the final
+/// check-in has the status reported by the future's output, or
+/// [`Error`](MonitorCheckInStatus::Error) if polling it panics. If the returned future is dropped
+/// before completion, for example because of a timeout or because it lost a `select!`, the job
+/// was cancelled and an [`Error`](MonitorCheckInStatus::Error) check-in is sent as well.
+pub fn with_check_in_async<F>(
+    monitor_slug: &str,
+    monitor_config: Option<MonitorConfig>,
+    job: F,
+) -> impl Future<Output = F::Output>
+where
+    F: Future,
+    F::Output: CheckInOutcome,
+{
+    let monitor_slug = monitor_slug.to_owned();
+    async move {
+        let mut guard = start_check_in(&monitor_slug, monitor_config);
+        guard.drop_status = MonitorCheckInStatus::Error;
+        let result = job.await;
+        guard.finish(result.check_in_status());
+        result
+    }
+}
+
+/// Determines the status of the final check-in for the result of a monitored job.
+pub trait CheckInOutcome {
+    /// Returns the status to report for this result.
+    fn check_in_status(&self) -> MonitorCheckInStatus;
+}
+
+impl CheckInOutcome for () {
+    fn check_in_status(&self) -> MonitorCheckInStatus {
+        MonitorCheckInStatus::Ok
+    }
+}
+
+impl<T, E> CheckInOutcome for Result<T, E> {
+    fn check_in_status(&self) -> MonitorCheckInStatus {
+        match self {
+            Ok(_) => MonitorCheckInStatus::Ok,
+            Err(_) => MonitorCheckInStatus::Error,
+        }
+    }
+}
+
+/// A running monitor check-in, created by [`start_check_in`].
+///
+/// The final check-in is sent by [`finish`](Self::finish). A guard which is dropped without being
+/// finished sends an [`Error`](MonitorCheckInStatus::Error) check-in if the thread is panicking,
+/// and an [`Ok`](MonitorCheckInStatus::Ok) check-in otherwise.
+#[must_use = "the final check-in is sent when the guard is dropped"]
+#[derive(Debug)]
+pub struct CheckInGuard {
+    #[cfg(feature = "client")]
+    hub: Option<Arc<Hub>>,
+    check_in_id: Uuid,
+    monitor_slug: String,
+    monitor_config: Option<MonitorConfig>,
+    start: Instant,
+    finished: bool,
+    /// The status sent if the guard is dropped without being finished while not panicking.
+    drop_status: MonitorCheckInStatus,
+}
+
+impl CheckInGuard {
+    /// Returns the ID shared by the check-ins of this job.
+    pub fn check_in_id(&self) -> Uuid {
+        self.check_in_id
+    }
+
+    /// Sends the final check-in with the given status.
+    pub fn finish(mut self, status: MonitorCheckInStatus) {
+        self.finished = true;
+        self.send(status);
+    }
+
+    fn send(&self, status: MonitorCheckInStatus) {
+        let duration = match status {
+            MonitorCheckInStatus::InProgress => None,
+            _ => Some(self.start.elapsed().as_secs_f64()),
+        };
+        let check_in = MonitorCheckIn {
+            check_in_id: self.check_in_id,
+            monitor_slug: self.monitor_slug.clone(),
+            status,
+            environment: None,
+            duration,
+            monitor_config: self.monitor_config.clone(),
+            contexts: None,
+        };
+
+        #[cfg(feature = "client")]
+        if let Some(hub) = &self.hub {
+            hub.capture_check_in(check_in);
+        }
+        #[cfg(not(feature = "client"))]
+        let _ = check_in;
+    }
+}
+
+impl Drop for CheckInGuard {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        let status = if thread::panicking() {
+            MonitorCheckInStatus::Error
+        } else {
+            self.drop_status
+        };
+        self.send(status);
+    }
+}
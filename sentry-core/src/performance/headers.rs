@@ -7,6 +7,7 @@ use std::fmt::{Display, Formatter, Result as FmtResult};
 use sentry_types::protocol::v7::OrganizationId;
 
 use crate::protocol::{SpanId, TraceId};
+use crate::TraceHeaderPrecedence;
 
 /// A key-value header pair.
 type Header<'h> = (&'h str, &'h str);
@@ -21,19 +22,26 @@ const SENTRY_TRACE: &str = "sentry-trace";
 /// The Baggage header
 const BAGGAGE: &str = "baggage";
 
+/// The W3C Trace Context `traceparent` header
+const TRACEPARENT: &str = "traceparent";
+
+/// The W3C Trace Context `tracestate` header
+const TRACESTATE: &str = "tracestate";
+
 /// The [trace propagation] context.
 ///
 /// Contains the information necessary for propagating Sentry traces and continuing traces from
 /// incoming requests.
 ///
 /// The data stored in this struct can be parsed from and transmitted as `sentry-trace` and Sentry
-/// baggage headers.
+/// baggage headers, or as W3C [`traceparent` and `tracestate`] headers.
 ///
 /// Note that the Rust SDK only partially supports trace propagation, certain features such as
 /// [dynamic sampling] may be missing or incomplete.
 ///
 /// [trace propagation]: https://develop.sentry.dev/sdk/foundations/trace-propagation/
 /// [dynamic sampling]: https://develop.sentry.dev/sdk/foundations/trace-propagation/dynamic-sampling-context/
+/// [`traceparent` and `tracestate`]: https://www.w3.org/TR/trace-context/
 #[derive(Debug, PartialEq, Clone, Default)]
 pub struct TracePropagationContext {
     pub(crate) trace_id: TraceId,
@@ -41,15 +49,17 @@ pub struct TracePropagationContext {
     pub(super) sampled: Option<bool>,
     #[cfg(feature = "client")]
     pub(super) org_id: Option<OrganizationId>,
+    #[cfg(feature = "client")]
+    pub(super) tracestate: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 #[non_exhaustive]
 /// Error type returned by [`TracePropagationContext::try_from_headers`].
 pub enum HeaderParseError {
-    /// The `sentry-trace` header was missing.
+    /// The `sentry-trace` and `traceparent` headers were missing.
     Missing,
-    /// There was a `sentry-trace` header, but it was invalid.
+    /// There was a `sentry-trace` or `traceparent` header, but it was invalid.
     Invalid,
 }
 
@@ -71,6 +81,8 @@ impl TracePropagationContext {
             sampled: None,
             #[cfg(feature = "client")]
             org_id: None,
+            #[cfg(feature = "client")]
+            tracestate: None,
         }
     }
 
@@ -88,6 +100,8 @@ impl TracePropagationContext {
             sampled,
             #[cfg(feature = "client")]
                 org_id: _,
+            #[cfg(feature = "client")]
+                tracestate: _,
         } = self;
 
         let sampled_suffix = sampled
@@ -97,37 +111,133 @@ impl TracePropagationContext {
         format!("{trace_id}-{span_id}{sampled_suffix}")
     }
 
+    /// Computes the `sentry-trace` header for an outgoing request to `url`.
+    ///
+    /// Returns [`None`] if tracing headers should not be attached to requests to `url`, see
+    /// [`should_propagate_trace`].
+    pub fn sentry_trace_header_for(&self, url: &str) -> Option<String> {
+        should_propagate_trace(url).then(|| self.sentry_trace_header())
+    }
+
+    /// Computes the W3C `traceparent` header for this [`TracePropagationContext`].
+    ///
+    /// The `sampled` flag is only set if the trace is sampled. An undecided sampling decision is
+    /// emitted as not sampled, as the header cannot express it.
+    pub fn traceparent_header(&self) -> String {
+        let flags = if self.sampled == Some(true) {
+            "01"
+        } else {
+            "00"
+        };
+        format!("00-{}-{}-{flags}", self.trace_id, self.span_id)
+    }
+
+    /// Returns the W3C `tracestate` header of the incoming trace this context was continued
+    /// from, if any.
+    #[cfg(feature = "client")]
+    pub fn tracestate_header(&self) -> Option<&str> {
+        self.tracestate.as_deref()
+    }
+
+    /// Sets the W3C `tracestate` header to forward with this context.
+    #[cfg(feature = "client")]
+    pub(crate) fn with_tracestate(self, tracestate: Option<String>) -> Self {
+        Self { tracestate, ..self }
+    }
+
     /// Attempt to parse a list of Sentry headers into [`TracePropagationContext`].
     ///
-    /// The parsing will fail if there is no valid `sentry-trace` header.
+    /// The trace is continued from either the `sentry-trace` header or the W3C `traceparent`
+    /// header. If both are present, the
+    /// [`trace_header_precedence`](crate::ClientOptions::trace_header_precedence) option of the
+    /// client bound to the current hub decides which one is used.
+    ///
+    /// The parsing will fail if there is neither a valid `sentry-trace` nor a valid `traceparent`
+    /// header.
     pub fn try_from_headers<'a, I>(headers: I) -> Result<Self, HeaderParseError>
     where
         I: IntoIterator<Item = Header<'a>>,
     {
-        let mut context_result = Err(HeaderParseError::Missing);
+        #[cfg(feature = "client")]
+        let precedence = crate::Hub::current()
+            .client()
+            .map(|client| client.options().trace_header_precedence)
+            .unwrap_or_default();
+        #[cfg(not(feature = "client"))]
+        let precedence = TraceHeaderPrecedence::default();
+
+        Self::try_from_headers_with_precedence(headers, precedence)
+    }
+
+    /// Attempt to parse a list of Sentry headers into [`TracePropagationContext`], using the given
+    /// precedence if both a `sentry-trace` and a `traceparent` header are present.
+    ///
+    /// See [`TracePropagationContext::try_from_headers`] for details.
+    pub fn try_from_headers_with_precedence<'a, I>(
+        headers: I,
+        precedence: TraceHeaderPrecedence,
+    ) -> Result<Self, HeaderParseError>
+    where
+        I: IntoIterator<Item = Header<'a>>,
+    {
+        let mut sentry_trace = Err(HeaderParseError::Missing);
+        let mut traceparent = Err(HeaderParseError::Missing);
         #[cfg(feature = "client")]
         let mut baggage = SentryBaggage::default();
+        #[cfg(feature = "client")]
+        let mut tracestate: Option<String> = None;
 
         for (header, value) in headers {
             if header.eq_ignore_ascii_case(SENTRY_TRACE) {
                 // Parse the header, falling back to the previous header value if Ok (headers not
                 // guaranteed unique), only falling back to invalid error if there's no prev value.
-                context_result = TracePropagationContext::from_sentry_trace(value)
-                    .map_or(context_result, Ok)
+                sentry_trace = TracePropagationContext::from_sentry_trace(value)
+                    .map_or(sentry_trace, Ok)
+                    .map_err(|_| HeaderParseError::Invalid);
+            } else if header.eq_ignore_ascii_case(TRACEPARENT) {
+                traceparent = TracePropagationContext::from_traceparent(value)
+                    .map_or(traceparent, Ok)
                     .map_err(|_| HeaderParseError::Invalid);
             } else if header.eq_ignore_ascii_case(BAGGAGE) {
                 #[cfg(feature = "client")]
                 baggage.update_from_header(value);
+            } else if header.eq_ignore_ascii_case(TRACESTATE) {
+                // Multiple `tracestate` headers are combined as a single comma-separated list.
+                #[cfg(feature = "client")]
+                match &mut tracestate {
+                    Some(tracestate) => {
+                        tracestate.push(',');
+                        tracestate.push_str(value);
+                    }
+                    None => tracestate = Some(value.to_owned()),
+                }
             }
         }
 
-        let context = context_result?;
+        #[cfg(feature = "client")]
+        let traceparent_trace_id = traceparent.as_ref().ok().map(|context| context.trace_id);
+        let (preferred, fallback) = match precedence {
+            TraceHeaderPrecedence::SentryTrace => (sentry_trace, traceparent),
+            TraceHeaderPrecedence::TraceParent => (traceparent, sentry_trace),
+        };
+        let context = match (preferred, fallback) {
+            (Ok(context), _) | (Err(_), Ok(context)) => context,
+            (Err(HeaderParseError::Invalid), _) | (_, Err(HeaderParseError::Invalid)) => {
+                return Err(HeaderParseError::Invalid)
+            }
+            (Err(HeaderParseError::Missing), Err(HeaderParseError::Missing)) => {
+                return Err(HeaderParseError::Missing)
+            }
+        };
 
         #[cfg(feature = "client")]
         let SentryBaggage { org_id } = baggage;
         Ok(TracePropagationContext {
             #[cfg(feature = "client")]
             org_id,
+            // The `tracestate` belongs to the trace of the `traceparent` header.
+            #[cfg(feature = "client")]
+            tracestate: tracestate.filter(|_| traceparent_trace_id == Some(context.trace_id)),
             ..context
         })
     }
@@ -153,10 +263,87 @@ impl TracePropagationContext {
             sampled,
             #[cfg(feature = "client")]
             org_id: None,
+            #[cfg(feature = "client")]
+            tracestate: None,
+        })
+    }
+
+    /// Attempts to construct a [`TracePropagationContext`] from the given W3C `traceparent`
+    /// header.
+    ///
+    /// The trace is sampled if the `sampled` flag is set, and not sampled otherwise.
+    ///
+    /// Returns [`None`] if the header cannot be parsed.
+    fn from_traceparent(header: &str) -> Option<Self> {
+        let header = header.trim();
+        let mut parts = header.split('-');
+
+        let version = parts.next()?;
+        let trace_id = parts.next()?;
+        let span_id = parts.next()?;
+        let flags = parts.next()?;
+
+        let is_hex = |part: &str, len| {
+            part.len() == len
+                && part
+                    .bytes()
+                    .all(|byte| matches!(byte, b'0'..=b'9' | b'a'..=b'f'))
+        };
+        // Version `ff` is invalid, and version `00` has exactly four fields. Future versions may
+        // append fields, which are ignored.
+        if !is_hex(version, 2)
+            || version == "ff"
+            || (version == "00" && parts.next().is_some())
+            || !is_hex(trace_id, 32)
+            || !is_hex(span_id, 16)
+            || !is_hex(flags, 2)
+        {
+            return None;
+        }
+
+        let trace_id: TraceId = trace_id.parse().ok()?;
+        let span_id: SpanId = span_id.parse().ok()?;
+        if trace_id == TraceId::from([0; 16]) || span_id == SpanId::from([0; 8]) {
+            return None;
+        }
+        let flags = u8::from_str_radix(flags, 16).ok()?;
+
+        Some(Self {
+            trace_id,
+            span_id,
+            sampled: Some(flags & 0x01 != 0),
+            #[cfg(feature = "client")]
+            org_id: None,
+            #[cfg(feature = "client")]
+            tracestate: None,
         })
     }
 }
 
+/// Returns whether distributed tracing headers should be attached to an outgoing request to `url`.
+///
+/// This is the case if `url` matches the
+/// [`trace_propagation_targets`](crate::ClientOptions::trace_propagation_targets) of the client
+/// bound to the current hub. If no client is bound, headers are attached to all requests.
+pub fn should_propagate_trace(url: &str) -> bool {
+    #[cfg(feature = "client")]
+    {
+        should_client_propagate_trace(crate::Hub::current().client().as_deref(), url)
+    }
+    #[cfg(not(feature = "client"))]
+    {
+        let _ = url;
+        true
+    }
+}
+
+/// Returns whether distributed tracing headers should be attached to an outgoing request to `url`,
+/// according to the options of `client`.
+#[cfg(feature = "client")]
+pub(crate) fn should_client_propagate_trace(client: Option<&crate::Client>, url: &str) -> bool {
+    client.is_none_or(|client| client.options().trace_propagation_targets.matches(url))
+}
+
 /// Extracts distributed tracing metadata from headers (or, generally, key-value pairs),
 /// considering the values for `sentry-trace`.
 #[deprecated = "use TracePropagationContext::try_from_headers instead"]
@@ -171,6 +358,8 @@ where
         sampled,
         #[cfg(feature = "client")]
             org_id: _,
+        #[cfg(feature = "client")]
+            tracestate: _,
     } = TracePropagationContext::try_from_headers(headers).ok()?;
 
     Some(SentryTrace {
@@ -187,7 +376,7 @@ impl Display for HeaderParseError {
             HeaderParseError::Invalid => "invalid",
         };
 
-        write!(f, "{msg} {SENTRY_TRACE} header")
+        write!(f, "{msg} {SENTRY_TRACE} or {TRACEPARENT} header")
     }
 }
 
@@ -214,6 +403,8 @@ impl From<SentryTrace> for TracePropagationContext {
             sampled: trace.sampled,
             #[cfg(feature = "client")]
             org_id: None,
+            #[cfg(feature = "client")]
+            tracestate: None,
         }
     }
 }
@@ -289,6 +480,8 @@ mod tests {
                 sampled: Some(false),
                 #[cfg(feature = "client")]
                 org_id: None,
+                #[cfg(feature = "client")]
+                tracestate: None,
             }
         );
 
@@ -363,4 +556,148 @@ mod tests {
 
         assert_eq!(trace.org_id, None);
     }
+
+    #[test]
+    fn parses_traceparent() {
+        let trace = TracePropagationContext::try_from_headers([(
+            "traceparent",
+            "00-09e04486820349518ac7b5d2adbf6ba5-9cf635fa5b870b3a-01",
+        )])
+        .expect("should parse successfully");
+        assert_eq!(
+            trace.trace_id,
+            "09e04486820349518ac7b5d2adbf6ba5".parse().unwrap()
+        );
+        assert_eq!(trace.span_id, "9cf635fa5b870b3a".parse().unwrap());
+        assert_eq!(trace.sampled, Some(true));
+
+        let trace = TracePropagationContext::try_from_headers([(
+            "TraceParent",
+            "00-09e04486820349518ac7b5d2adbf6ba5-9cf635fa5b870b3a-00",
+        )])
+        .expect("should parse successfully");
+        assert_eq!(trace.sampled, Some(false));
+
+        let trace =
+            TracePropagationContext::new(Default::default(), Default::default()).with_sampled(true);
+        let parsed = TracePropagationContext::try_from_headers([(
+            "traceparent",
+            trace.traceparent_header().as_str(),
+        )])
+        .expect("should parse successfully");
+        assert_eq!(parsed, trace);
+    }
+
+    #[test]
+    fn rejects_invalid_traceparent() {
+        for header in [
+            "00-09e04486820349518ac7b5d2adbf6ba5-9cf635fa5b870b3a",
+            "00-09e04486820349518ac7b5d2adbf6ba5-9cf635fa5b870b3a-01-extra",
+            "ff-09e04486820349518ac7b5d2adbf6ba5-9cf635fa5b870b3a-01",
+            "00-09E04486820349518AC7B5D2ADBF6BA5-9cf635fa5b870b3a-01",
+            "00-00000000000000000000000000000000-9cf635fa5b870b3a-01",
+            "00-09e04486820349518ac7b5d2adbf6ba5-0000000000000000-01",
+            "00-09e04486820349518ac7b5d2adbf6ba5-9cf635fa5b870b3a-1",
+        ] {
+            assert!(
+                matches!(
+                    TracePropagationContext::try_from_headers([("traceparent", header)]),
+                    Err(HeaderParseError::Invalid)
+                ),
+                "{header} should be invalid"
+            );
+        }
+
+        // Future versions may append fields.
+        assert!(TracePropagationContext::try_from_headers([(
+            "traceparent",
+            "01-09e04486820349518ac7b5d2adbf6ba5-9cf635fa5b870b3a-01-extra",
+        )])
+        .is_ok());
+    }
+
+    #[test]
+    fn applies_trace_header_precedence() {
+        let headers = [
+            (
+                "sentry-trace",
+                "09e04486820349518ac7b5d2adbf6ba5-9cf635fa5b870b3a-1",
+            ),
+            (
+                "traceparent",
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00",
+            ),
+        ];
+
+        let trace = TracePropagationContext::try_from_headers_with_precedence(
+            headers,
+            TraceHeaderPrecedence::SentryTrace,
+        )
+        .expect("should parse successfully");
+        assert_eq!(
+            trace.trace_id,
+            "09e04486820349518ac7b5d2adbf6ba5".parse().unwrap()
+        );
+
+        let trace = TracePropagationContext::try_from_headers_with_precedence(
+            headers,
+            TraceHeaderPrecedence::TraceParent,
+        )
+        .expect("should parse successfully");
+        assert_eq!(
+            trace.trace_id,
+            "4bf92f3577b34da6a3ce929d0e0e4736".parse().unwrap()
+        );
+
+        // An invalid preferred header falls back to the other one.
+        let trace = TracePropagationContext::try_from_headers_with_precedence(
+            [
+                ("sentry-trace", "invalid"),
+                (
+                    "traceparent",
+                    "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00",
+                ),
+            ],
+            TraceHeaderPrecedence::SentryTrace,
+        )
+        .expect("should parse successfully");
+        assert_eq!(
+            trace.trace_id,
+            "4bf92f3577b34da6a3ce929d0e0e4736".parse().unwrap()
+        );
+    }
+
+    #[cfg(feature = "client")]
+    #[test]
+    fn keeps_tracestate_of_continued_traceparent() {
+        let traceparent = (
+            "traceparent",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        );
+        let trace = TracePropagationContext::try_from_headers([
+            traceparent,
+            ("tracestate", "congo=t61rcWkgMzE"),
+            ("tracestate", "rojo=00f067aa0ba902b7"),
+        ])
+        .expect("should parse successfully");
+        assert_eq!(
+            trace.tracestate_header(),
+            Some("congo=t61rcWkgMzE,rojo=00f067aa0ba902b7")
+        );
+
+        // The `tracestate` is dropped if the trace is continued from a different `sentry-trace`.
+        let trace = TracePropagationContext::try_from_headers_with_precedence(
+            [
+                (
+                    "sentry-trace",
+                    "09e04486820349518ac7b5d2adbf6ba5-9cf635fa5b870b3a-1",
+                ),
+                traceparent,
+                ("tracestate", "congo=t61rcWkgMzE"),
+            ],
+            TraceHeaderPrecedence::SentryTrace,
+        )
+        .expect("should parse successfully");
+        assert_eq!(trace.tracestate_header(), None);
+    }
 }
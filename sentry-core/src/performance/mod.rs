@@ -5,13 +5,13 @@ use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::SystemTime;
 
 #[cfg(feature = "client")]
-use sentry_types::protocol::v7::client_report::Reason as ClientReportReason;
+use sentry_types::protocol::v7::client_report::{LossSource, Reason as ClientReportReason};
 #[cfg(feature = "client")]
 use sentry_types::protocol::v7::OrganizationId;
 use sentry_types::protocol::v7::SpanId;
 
 #[cfg(feature = "client")]
-use crate::clientoptions::TracesSamplingStrategy;
+use crate::clientoptions::{TraceLifecycle, TracesSamplingStrategy};
 use crate::{protocol, Hub};
 
 #[cfg(feature = "client")]
@@ -19,7 +19,7 @@ use crate::Client;
 
 #[expect(deprecated, reason = "backwards-compatibility re-export")]
 pub use self::headers::{parse_sentry_trace_header as parse_headers, SentryTrace};
-pub use self::headers::{HeaderParseError, TracePropagationContext};
+pub use self::headers::{should_propagate_trace, HeaderParseError, TracePropagationContext};
 
 mod headers;
 
@@ -107,11 +107,12 @@ pub type CustomTransactionContext = serde_json::Map<String, serde_json::Value>;
 
 /// Information from an incoming trace.
 ///
-/// Currently this just contains the org ID supplied by the incoming trace.
+/// Currently this contains the org ID supplied by the incoming trace, and its W3C `tracestate`.
 #[cfg(feature = "client")]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 struct IncomingTrace {
     org_id: Option<OrganizationId>,
+    tracestate: Option<String>,
 }
 
 /// The Transaction Context used to start a new Performance Monitoring Transaction.
@@ -195,8 +196,8 @@ impl TransactionContext {
 
     /// Creates a new Transaction Context based on the distributed tracing `headers`.
     ///
-    /// The `headers` in particular need to include the `sentry-trace` header,
-    /// which is used to associate the transaction with a distributed trace.
+    /// The `headers` in particular need to include the `sentry-trace` or the W3C `traceparent`
+    /// header, which is used to associate the transaction with a distributed trace.
     #[must_use = "this must be used with `start_transaction`"]
     pub fn continue_from_headers<'a, I: IntoIterator<Item = (&'a str, &'a str)>>(
         name: &str,
@@ -246,6 +247,8 @@ impl TransactionContext {
             sampled,
             #[cfg(feature = "client")]
             org_id,
+            #[cfg(feature = "client")]
+            ref tracestate,
         } = context;
 
         Self {
@@ -255,7 +258,10 @@ impl TransactionContext {
             parent_span_id: Some(context_span_id),
             sampled,
             #[cfg(feature = "client")]
-            incoming_trace: Some(IncomingTrace { org_id }),
+            incoming_trace: Some(IncomingTrace {
+                org_id,
+                tracestate: tracestate.clone(),
+            }),
             span_id: span_id.unwrap_or_default(),
             custom: None,
         }
@@ -503,6 +509,18 @@ impl TransactionOrSpan {
         }
     }
 
+    /// Sets a named measurement on the root Transaction.
+    ///
+    /// See [`Transaction::set_measurement`] for details.
+    pub fn set_measurement(&self, name: &str, value: f64, unit: Option<protocol::Unit>) {
+        match self {
+            TransactionOrSpan::Transaction(transaction) => {
+                transaction.set_measurement(name, value, unit)
+            }
+            TransactionOrSpan::Span(span) => span.set_measurement(name, value, unit),
+        }
+    }
+
     /// Get the TransactionContext of the Transaction/Span.
     ///
     /// Note that this clones the underlying value.
@@ -563,6 +581,17 @@ impl TransactionOrSpan {
         }
     }
 
+    /// Returns the headers needed for distributed tracing of an outgoing request to `url`.
+    ///
+    /// No headers are returned if `url` does not match the
+    /// [`trace_propagation_targets`](crate::ClientOptions::trace_propagation_targets) option.
+    pub fn iter_headers_for(&self, url: &str) -> TraceHeadersIter {
+        match self {
+            TransactionOrSpan::Transaction(transaction) => transaction.iter_headers_for(url),
+            TransactionOrSpan::Span(span) => span.iter_headers_for(url),
+        }
+    }
+
     /// Get the sampling decision for this Transaction/Span.
     pub fn is_sampled(&self) -> bool {
         match self {
@@ -658,9 +687,41 @@ pub(crate) struct TransactionInner {
     client: Option<Arc<Client>>,
     sampled: bool,
     pub(crate) context: protocol::TraceContext,
+    /// The W3C `tracestate` of the incoming trace, forwarded in outgoing requests.
+    #[cfg(feature = "client")]
+    tracestate: Option<String>,
     pub(crate) transaction: Option<protocol::Transaction<'static>>,
 }
 
+impl TransactionInner {
+    /// Returns whether the client of the transaction propagates W3C `traceparent` headers.
+    fn propagate_traceparent(&self) -> bool {
+        #[cfg(feature = "client")]
+        {
+            self.client
+                .as_ref()
+                .is_some_and(|client| client.options().propagate_traceparent)
+        }
+        #[cfg(not(feature = "client"))]
+        {
+            false
+        }
+    }
+
+    /// Returns whether the client of the transaction propagates traces to `url`.
+    fn should_propagate_trace(&self, url: &str) -> bool {
+        #[cfg(feature = "client")]
+        {
+            headers::should_client_propagate_trace(self.client.as_deref(), url)
+        }
+        #[cfg(not(feature = "client"))]
+        {
+            let _ = url;
+            true
+        }
+    }
+}
+
 type TransactionArc = Arc<Mutex<TransactionInner>>;
 
 /// Functional implementation of how a new transaction's sample rate is chosen.
@@ -763,9 +824,10 @@ impl Transaction {
                 let options = client.options();
                 let sdk_org_id = options.org_id.or_else(|| options.dsn.as_ref()?.org_id());
 
-                if ctx.incoming_trace.is_some_and(
-                    |IncomingTrace {
+                if ctx.incoming_trace.as_ref().is_some_and(
+                    |&IncomingTrace {
                          org_id: incoming_org_id,
+                         ..
                      }| {
                         !should_continue_trace(
                             incoming_org_id,
@@ -777,13 +839,21 @@ impl Transaction {
                     ctx.reject_incoming_trace();
                 }
 
-                (
-                    client.determine_sampling_decision(&ctx),
-                    Some(protocol::Transaction {
-                        name: Some(ctx.name),
-                        ..Default::default()
-                    }),
-                )
+                let sampled = client.determine_sampling_decision(&ctx);
+                #[cfg_attr(not(feature = "profiling"), expect(unused_mut))]
+                let mut transaction = protocol::Transaction {
+                    name: Some(ctx.name),
+                    ..Default::default()
+                };
+                #[cfg(feature = "profiling")]
+                if let Some(profiler_id) = crate::profiling::profiler_id() {
+                    transaction.contexts.insert(
+                        "profile".into(),
+                        protocol::ProfileContext { profiler_id }.into(),
+                    );
+                }
+
+                (sampled, Some(transaction))
             }
             None => (
                 (
@@ -801,12 +871,16 @@ impl Transaction {
             op: Some(ctx.op),
             ..Default::default()
         };
+        let tracestate = ctx
+            .incoming_trace
+            .and_then(|incoming_trace| incoming_trace.tracestate);
 
         Self {
             inner: Arc::new(Mutex::new(TransactionInner {
                 client,
                 sampled,
                 context,
+                tracestate,
                 transaction,
             })),
             metadata: TransactionMetadata { sample_rate },
@@ -856,6 +930,28 @@ impl Transaction {
         }
     }
 
+    /// Sets a named numeric measurement, such as the number of processed rows or a cache hit
+    /// ratio, replacing any previous measurement with the same name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sentry::protocol::Unit;
+    ///
+    /// let transaction =
+    ///     sentry::start_transaction(sentry::TransactionContext::new("import", "task"));
+    /// transaction.set_measurement("rows_processed", 1234.0, None);
+    /// transaction.set_measurement("cache_hit_ratio", 0.75, Some(Unit::Ratio));
+    /// transaction.finish();
+    /// ```
+    pub fn set_measurement(&self, name: &str, value: f64, unit: Option<protocol::Unit>) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(transaction) = inner.transaction.as_mut() {
+            let measurement = protocol::Measurement { value, unit };
+            transaction.measurements.insert(name.into(), measurement);
+        }
+    }
+
     /// Returns an iterating accessor to the transaction's
     /// [data attributes](protocol::TraceContext::data).
     ///
@@ -924,8 +1020,21 @@ impl Transaction {
         let inner = self.inner.lock().unwrap();
         let trace = TracePropagationContext::new(inner.context.trace_id, inner.context.span_id)
             .with_sampled(inner.sampled);
-        TraceHeadersIter {
-            sentry_trace: Some(trace.sentry_trace_header()),
+        #[cfg(feature = "client")]
+        let trace = trace.with_tracestate(inner.tracestate.clone());
+        TraceHeadersIter::from_context(&trace, inner.propagate_traceparent())
+    }
+
+    /// Returns the headers needed for distributed tracing of an outgoing request to `url`.
+    ///
+    /// No headers are returned if `url` does not match the
+    /// [`trace_propagation_targets`](crate::ClientOptions::trace_propagation_targets) option.
+    pub fn iter_headers_for(&self, url: &str) -> TraceHeadersIter {
+        let should_propagate = self.inner.lock().unwrap().should_propagate_trace(url);
+        if should_propagate {
+            self.iter_headers()
+        } else {
+            TraceHeadersIter::empty()
         }
     }
 
@@ -946,7 +1055,13 @@ impl Transaction {
             if !inner.sampled {
                 if let Some(transaction) = inner.transaction.take() {
                     if let Some(client) = inner.client.as_ref() {
-                        client.record_lost_data(&transaction, ClientReportReason::SampleRate);
+                        if client.options().trace_lifecycle == TraceLifecycle::Stream {
+                            // The child spans were already discarded when they finished.
+                            let root_span = transaction_root_span(&transaction, &inner.context);
+                            client.record_lost_data(&root_span, ClientReportReason::SampleRate);
+                        } else {
+                            client.record_lost_data(&transaction, ClientReportReason::SampleRate);
+                        }
                     }
                 }
                 return;
@@ -959,23 +1074,45 @@ impl Transaction {
                         .contexts
                         .insert("trace".into(), inner.context.clone().into());
 
-                    Hub::current().with_current_scope(|scope| scope.apply_to_transaction(&mut transaction));
                     let opts = client.options();
                     transaction.release.clone_from(&opts.release);
                     transaction.environment.clone_from(&opts.environment);
                     transaction.sdk = Some(std::borrow::Cow::Owned(client.sdk_info.clone()));
                     transaction.server_name.clone_from(&opts.server_name);
 
+                    let context = inner.context.clone();
+                    let sampled = inner.sampled;
+                    drop(inner);
+
+                    let stream = opts.trace_lifecycle == TraceLifecycle::Stream;
+                    let losses: Vec<_> = if stream {
+                        transaction_root_span(&transaction, &context).losses().collect()
+                    } else {
+                        transaction.losses().collect()
+                    };
+                    // Processors and callbacks may use the hub, so they must not run while its
+                    // scope is locked.
+                    let scope = Hub::current().with_current_scope(|scope| scope.clone());
+                    scope.apply_to_transaction(&mut transaction);
+                    let Some(transaction) =
+                        client.prepare_transaction(transaction, Some(&scope), &losses)
+                    else {
+                        return;
+                    };
+
+                    if stream {
+                        client.enqueue_span(segment_span(&transaction, &context));
+                        return;
+                    }
+
                     let mut dsc = protocol::DynamicSamplingContext::new()
-                        .with_trace_id(inner.context.trace_id)
+                        .with_trace_id(context.trace_id)
                         .with_sample_rate(self.metadata.sample_rate)
-                        .with_sampled(inner.sampled);
+                        .with_sampled(sampled);
                     if let Some(public_key) = client.dsn().map(|dsn| dsn.public_key()) {
                         dsc = dsc.with_public_key(public_key.to_owned());
                     }
 
-                    drop(inner);
-
                     let mut envelope = protocol::Envelope::new().with_headers(
                         protocol::EnvelopeHeaders::new().with_trace(dsc)
                     );
@@ -1052,6 +1189,125 @@ impl Transaction {
     }
 }
 
+/// Creates the root span of a transaction, which represents the transaction itself.
+#[cfg(feature = "client")]
+fn transaction_root_span(
+    transaction: &protocol::Transaction,
+    context: &protocol::TraceContext,
+) -> protocol::Span {
+    protocol::Span {
+        span_id: context.span_id,
+        trace_id: context.trace_id,
+        parent_span_id: context.parent_span_id,
+        op: context.op.clone(),
+        description: transaction.name.clone(),
+        timestamp: transaction.timestamp,
+        start_timestamp: transaction.start_timestamp,
+        status: context.status,
+        tags: transaction.tags.clone(),
+        data: context.data.clone(),
+        ..Default::default()
+    }
+}
+
+/// Converts a finished span of the segment with the given ID and name for streaming.
+#[cfg(feature = "client")]
+fn streamed_span(
+    span: &protocol::Span,
+    segment_id: SpanId,
+    segment_name: Option<&str>,
+) -> protocol::StreamedSpan {
+    let mut streamed = protocol::StreamedSpan {
+        trace_id: span.trace_id,
+        span_id: span.span_id,
+        parent_span_id: span.parent_span_id,
+        name: span
+            .description
+            .as_ref()
+            .or(span.op.as_ref())
+            .cloned()
+            .unwrap_or_default(),
+        status: match span.status {
+            None | Some(protocol::SpanStatus::Ok) => protocol::StreamedSpanStatus::Ok,
+            Some(_) => protocol::StreamedSpanStatus::Error,
+        },
+        is_segment: false,
+        start_timestamp: span.start_timestamp,
+        end_timestamp: span.timestamp.unwrap_or_else(SystemTime::now),
+        attributes: span
+            .data
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone().into()))
+            .chain(
+                span.tags
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.as_str().into())),
+            )
+            .collect(),
+    };
+
+    let attributes = &mut streamed.attributes;
+    if let Some(op) = &span.op {
+        attributes.insert("sentry.op".into(), op.as_str().into());
+    }
+    attributes.insert("sentry.segment.id".into(), segment_id.to_string().into());
+    if let Some(name) = segment_name {
+        attributes.insert("sentry.segment.name".into(), name.into());
+    }
+    streamed
+}
+
+/// Converts a finished transaction into the segment span for streaming.
+///
+/// The attributes of the segment include the data which would otherwise be sent on the
+/// transaction event, such as its tags, user, release, environment, measurements and the ID of
+/// the profiler session it is linked to. Measurements are named after their key, without units.
+#[cfg(feature = "client")]
+fn segment_span(
+    transaction: &protocol::Transaction,
+    context: &protocol::TraceContext,
+) -> protocol::StreamedSpan {
+    let root_span = transaction_root_span(transaction, context);
+    let mut segment = streamed_span(&root_span, context.span_id, transaction.name.as_deref());
+    segment.is_segment = true;
+
+    let attributes = &mut segment.attributes;
+    if let Some(origin) = &context.origin {
+        attributes.insert("sentry.origin".into(), origin.as_str().into());
+    }
+    if let Some(user) = &transaction.user {
+        let user_attributes = [
+            ("user.id", &user.id),
+            ("user.email", &user.email),
+            ("user.name", &user.username),
+        ];
+        for (key, value) in user_attributes {
+            if let Some(value) = value {
+                attributes.insert(key.into(), value.as_str().into());
+            }
+        }
+    }
+    for (key, value) in [
+        ("sentry.release", &transaction.release),
+        ("sentry.environment", &transaction.environment),
+        ("server.address", &transaction.server_name),
+    ] {
+        if let Some(value) = value {
+            attributes.insert(key.into(), value.as_ref().into());
+        }
+    }
+    for (name, measurement) in &transaction.measurements {
+        attributes.insert(name.clone(), measurement.value.into());
+    }
+    if let Some(protocol::Context::Profile(profile)) = transaction.contexts.get("profile") {
+        attributes.insert(
+            "sentry.profiler_id".into(),
+            profile.profiler_id.to_string().into(),
+        );
+    }
+    segment
+}
+
 impl PartialEq for Transaction {
     fn eq(&self, other: &Self) -> bool {
         Arc::ptr_eq(&self.inner, &other.inner)
@@ -1113,6 +1369,18 @@ impl Span {
         span.tags.insert(key.into(), value.to_string());
     }
 
+    /// Sets a named measurement on the root Transaction of this Span.
+    ///
+    /// Spans do not have measurements of their own, so this bubbles the measurement up to the
+    /// Transaction. See [`Transaction::set_measurement`] for details.
+    pub fn set_measurement(&self, name: &str, value: f64, unit: Option<protocol::Unit>) {
+        let mut transaction = self.transaction.lock().unwrap();
+        if let Some(transaction) = transaction.transaction.as_mut() {
+            let measurement = protocol::Measurement { value, unit };
+            transaction.measurements.insert(name.into(), measurement);
+        }
+    }
+
     /// Returns a smart pointer to the span's [`data` field](protocol::Span::data).
     ///
     /// Since [`Data`] implements `Deref` and `DerefMut`, this can be used to read and mutate
@@ -1205,11 +1473,26 @@ impl Span {
     /// Use [`crate::Scope::iter_trace_propagation_headers`] to obtain the active
     /// trace's distributed tracing headers.
     pub fn iter_headers(&self) -> TraceHeadersIter {
-        let span = self.span.lock().unwrap();
-        let trace =
-            TracePropagationContext::new(span.trace_id, span.span_id).with_sampled(self.sampled);
-        TraceHeadersIter {
-            sentry_trace: Some(trace.sentry_trace_header()),
+        let trace = {
+            let span = self.span.lock().unwrap();
+            TracePropagationContext::new(span.trace_id, span.span_id).with_sampled(self.sampled)
+        };
+        let transaction = self.transaction.lock().unwrap();
+        #[cfg(feature = "client")]
+        let trace = trace.with_tracestate(transaction.tracestate.clone());
+        TraceHeadersIter::from_context(&trace, transaction.propagate_traceparent())
+    }
+
+    /// Returns the headers needed for distributed tracing of an outgoing request to `url`.
+    ///
+    /// No headers are returned if `url` does not match the
+    /// [`trace_propagation_targets`](crate::ClientOptions::trace_propagation_targets) option.
+    pub fn iter_headers_for(&self, url: &str) -> TraceHeadersIter {
+        let should_propagate = self.transaction.lock().unwrap().should_propagate_trace(url);
+        if should_propagate {
+            self.iter_headers()
+        } else {
+            TraceHeadersIter::empty()
         }
     }
 
@@ -1231,13 +1514,36 @@ impl Span {
             }
             span.finish_with_timestamp(_timestamp);
             let mut inner = self.transaction.lock().unwrap();
-            if let Some(transaction) = inner.transaction.as_mut() {
+            let Some(client) = inner.client.clone() else {
+                return;
+            };
+            let Some(transaction) = inner.transaction.as_mut() else {
+                return;
+            };
+
+            if client.options().trace_lifecycle != TraceLifecycle::Stream {
                 if transaction.spans.len() <= MAX_SPANS {
                     transaction.spans.push(span.clone());
-                } else if let Some(client) = inner.client.as_ref() {
+                } else {
                     client.record_lost_data(&*span, ClientReportReason::BufferOverflow);
                 }
+                return;
             }
+            if !self.sampled {
+                client.record_lost_data(&*span, ClientReportReason::SampleRate);
+                return;
+            }
+
+            let segment_name = transaction.name.clone();
+            let segment_id = inner.context.span_id;
+            drop(inner);
+            let finished = span.clone();
+            // Processors and callbacks may use the span, so they must not run while it is locked.
+            drop(span);
+            let Some(span) = client.prepare_span(finished, segment_name.as_deref()) else {
+                return;
+            };
+            client.enqueue_span(streamed_span(&span, segment_id, segment_name.as_deref()));
         }}
     }
 
@@ -1317,17 +1623,53 @@ pub type TraceHeader = (&'static str, String);
 
 /// An Iterator over HTTP header names and values needed for distributed tracing.
 ///
-/// This currently only yields the `sentry-trace` header, but other headers
-/// may be added in the future.
+/// This yields the `sentry-trace` header, followed by the W3C `traceparent` and `tracestate`
+/// headers if the [`propagate_traceparent`](crate::ClientOptions::propagate_traceparent) option
+/// is enabled. Other headers may be added in the future.
 pub struct TraceHeadersIter {
     sentry_trace: Option<String>,
+    traceparent: Option<String>,
+    tracestate: Option<String>,
 }
 
 impl TraceHeadersIter {
+    /// Returns the headers for the given trace, including the W3C headers if
+    /// `propagate_traceparent` is set.
+    pub(crate) fn from_context(
+        trace: &TracePropagationContext,
+        propagate_traceparent: bool,
+    ) -> Self {
+        #[cfg(feature = "client")]
+        let tracestate = trace
+            .tracestate_header()
+            .filter(|_| propagate_traceparent)
+            .map(str::to_owned);
+        #[cfg(not(feature = "client"))]
+        let tracestate = None;
+
+        Self {
+            sentry_trace: Some(trace.sentry_trace_header()),
+            traceparent: propagate_traceparent.then(|| trace.traceparent_header()),
+            tracestate,
+        }
+    }
+
+    /// Returns the headers for the given trace, according to the options of the client bound to
+    /// the current hub.
     #[cfg(feature = "client")]
-    pub(crate) fn new(sentry_trace: String) -> Self {
+    pub(crate) fn from_current_client(trace: &TracePropagationContext) -> Self {
+        let propagate_traceparent = Hub::current()
+            .client()
+            .is_some_and(|client| client.options().propagate_traceparent);
+        Self::from_context(trace, propagate_traceparent)
+    }
+
+    /// Returns no headers.
+    pub(crate) fn empty() -> Self {
         Self {
-            sentry_trace: Some(sentry_trace),
+            sentry_trace: None,
+            traceparent: None,
+            tracestate: None,
         }
     }
 }
@@ -1336,7 +1678,11 @@ impl Iterator for TraceHeadersIter {
     type Item = (&'static str, String);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.sentry_trace.take().map(|st| ("sentry-trace", st))
+        self.sentry_trace
+            .take()
+            .map(|st| ("sentry-trace", st))
+            .or_else(|| self.traceparent.take().map(|tp| ("traceparent", tp)))
+            .or_else(|| self.tracestate.take().map(|ts| ("tracestate", ts)))
     }
 }
 
@@ -1368,6 +1714,144 @@ mod tests {
         assert_eq!(parsed.sampled, Some(true));
     }
 
+    #[cfg(feature = "client")]
+    #[test]
+    fn iter_headers_for_respects_trace_propagation_targets() {
+        let options = crate::ClientOptions::new()
+            .traces_sample_rate(1.0)
+            .trace_propagation_targets(["^https://api\\.internal/", "localhost:8080"]);
+        crate::test::with_captured_envelopes_options(
+            || {
+                let trx = start_transaction(TransactionContext::new("noop", "noop"));
+                let span = trx.start_child("noop", "noop");
+
+                assert!(span
+                    .iter_headers_for("https://api.internal/users")
+                    .next()
+                    .is_some());
+                assert!(trx
+                    .iter_headers_for("http://localhost:8080/")
+                    .next()
+                    .is_some());
+                assert!(span
+                    .iter_headers_for("https://third-party.com/api.internal/")
+                    .next()
+                    .is_none());
+                crate::configure_scope(|scope| {
+                    assert!(scope
+                        .iter_trace_propagation_headers_for("https://third-party.com/")
+                        .next()
+                        .is_none());
+                });
+            },
+            options,
+        );
+    }
+
+    #[cfg(feature = "client")]
+    #[test]
+    fn iter_headers_use_the_client_of_the_transaction() {
+        let options = crate::ClientOptions::new()
+            .traces_sample_rate(1.0)
+            .propagate_traceparent(true)
+            .trace_propagation_targets(["^https://api\\.internal/"]);
+        crate::test::with_captured_envelopes_options(
+            || {
+                let trx = start_transaction(TransactionContext::new("noop", "noop"));
+                let span = trx.start_child("noop", "noop");
+
+                // The headers do not depend on the client of the hub they are used on.
+                let hub = Arc::new(Hub::new(None, Default::default()));
+                Hub::run(hub, || {
+                    let names: Vec<_> = span
+                        .iter_headers_for("https://api.internal/users")
+                        .map(|(name, _)| name)
+                        .collect();
+                    assert_eq!(names, ["sentry-trace", "traceparent"]);
+                    assert!(trx
+                        .iter_headers_for("https://third-party.com/")
+                        .next()
+                        .is_none());
+                });
+            },
+            options,
+        );
+    }
+
+    #[test]
+    fn iter_headers_propagates_traceparent_and_tracestate() {
+        let headers = [
+            (
+                "traceparent",
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            ),
+            ("tracestate", "congo=t61rcWkgMzE"),
+        ];
+        let options = crate::ClientOptions::new()
+            .traces_sample_rate(1.0)
+            .propagate_traceparent(true);
+        crate::test::with_captured_envelopes_options(
+            || {
+                let ctx = TransactionContext::continue_from_headers("noop", "noop", headers);
+                let trx = start_transaction(ctx);
+                let span = trx.start_child("noop", "noop");
+
+                let headers: Vec<_> = span.iter_headers().collect();
+                let span_id = span.get_span_id();
+                assert_eq!(
+                    headers,
+                    [
+                        (
+                            "sentry-trace",
+                            format!("4bf92f3577b34da6a3ce929d0e0e4736-{span_id}-1")
+                        ),
+                        (
+                            "traceparent",
+                            format!("00-4bf92f3577b34da6a3ce929d0e0e4736-{span_id}-01")
+                        ),
+                        ("tracestate", "congo=t61rcWkgMzE".to_owned()),
+                    ]
+                );
+            },
+            options,
+        );
+
+        // Only the `sentry-trace` header is propagated by default.
+        crate::test::with_captured_envelopes(|| {
+            let ctx = TransactionContext::continue_from_headers("noop", "noop", headers);
+            let trx = start_transaction(ctx);
+            let names: Vec<_> = trx.iter_headers().map(|(name, _)| name).collect();
+            assert_eq!(names, ["sentry-trace"]);
+        });
+    }
+
+    #[test]
+    fn spans_set_measurements_on_their_transaction() {
+        let envelopes = crate::test::with_captured_envelopes_options(
+            || {
+                let trx = start_transaction(TransactionContext::new("noop", "noop"));
+                trx.set_measurement("rows_processed", 10.0, None);
+                let span = trx.start_child("noop", "noop");
+                span.set_measurement("rows_processed", 42.0, None);
+                span.set_measurement("cache_hit_ratio", 0.5, Some(protocol::Unit::Ratio));
+                span.finish();
+                trx.finish();
+            },
+            crate::ClientOptions::new().traces_sample_rate(1.0),
+        );
+
+        let Some(protocol::EnvelopeItem::Transaction(transaction)) = envelopes[0].items().next()
+        else {
+            panic!("expected a transaction");
+        };
+        assert_eq!(transaction.measurements["rows_processed"].value, 42.0);
+        assert_eq!(transaction.measurements["rows_processed"].unit, None);
+        assert_eq!(
+            transaction.measurements["cache_hit_ratio"].unit,
+            Some(protocol::Unit::Ratio)
+        );
+    }
+
     #[test]
     fn transaction_context_public_getters() {
         let mut ctx = TransactionContext::new("test-name", "test-operation");
@@ -0,0 +1,23 @@
+//! Shared state of continuous profiling.
+//!
+//! The profiler itself is implemented outside of this crate. While a profiler session is running,
+//! it publishes its ID here, so that transactions started in the meantime are linked to the
+//! profile chunks recorded by the session through their `profile` context.
+
+use std::sync::{PoisonError, RwLock};
+
+use crate::types::Uuid;
+
+static PROFILER_ID: RwLock<Option<Uuid>> = RwLock::new(None);
+
+/// Returns the ID of the running profiler session, if any.
+pub fn profiler_id() -> Option<Uuid> {
+    *PROFILER_ID.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Publishes the ID of the running profiler session.
+///
+/// Profilers call this with `Some` when a session starts, and with `None` when it stops.
+pub fn set_profiler_id(profiler_id: Option<Uuid>) {
+    *PROFILER_ID.write().unwrap_or_else(PoisonError::into_inner) = profiler_id;
+}
@@ -3,7 +3,7 @@ use std::panic::RefUnwindSafe;
 
 #[cfg(feature = "logs")]
 use crate::protocol::Log;
-use crate::protocol::{Context, Event, Level, User, Value};
+use crate::protocol::{Context, Event, Feedback, Level, MonitorCheckIn, Transaction, User, Value};
 use crate::TransactionOrSpan;
 
 /// A minimal API scope guard.
@@ -100,6 +100,9 @@ impl Scope {
     }
 
     /// Add an event processor to the scope.
+    ///
+    /// Event processors are not run on transactions, see
+    /// [`add_transaction_processor`](Self::add_transaction_processor).
     pub fn add_event_processor<F>(&mut self, f: F)
     where
         F: Fn(Event<'static>) -> Option<Event<'static>> + Send + Sync + RefUnwindSafe + 'static,
@@ -108,12 +111,40 @@ impl Scope {
         minimal_unreachable!();
     }
 
+    /// Add a transaction processor to the scope.
+    ///
+    /// Event processors are not run on transactions, so a processor which should apply to both
+    /// is registered with both methods.
+    pub fn add_transaction_processor<F>(&mut self, f: F)
+    where
+        F: Fn(Transaction<'static>) -> Option<Transaction<'static>>
+            + Send
+            + Sync
+            + RefUnwindSafe
+            + 'static,
+    {
+        let _f = f;
+        minimal_unreachable!();
+    }
+
     /// Applies the contained scoped data to fill an event.
     pub fn apply_to_event(&self, event: Event<'static>) -> Option<Event<'static>> {
         let _event = event;
         minimal_unreachable!();
     }
 
+    /// Applies the contained scoped data to fill user feedback.
+    pub fn apply_to_feedback(&self, feedback: &mut Feedback) {
+        let _feedback = feedback;
+        minimal_unreachable!();
+    }
+
+    /// Applies the contained scoped data to a monitor check-in, linking it to the current trace.
+    pub fn apply_to_check_in(&self, check_in: &mut MonitorCheckIn) {
+        let _check_in = check_in;
+        minimal_unreachable!();
+    }
+
     /// Applies the contained scoped data to fill a log.
     #[cfg(feature = "logs")]
     pub fn apply_to_log(&self, log: &mut Log) {
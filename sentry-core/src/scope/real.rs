@@ -16,11 +16,14 @@ use crate::protocol::LogAttribute;
 #[cfg(feature = "metrics")]
 use crate::protocol::Metric;
 use crate::protocol::{
-    Attachment, Breadcrumb, Context, Event, Level, TraceContext, Transaction, User, Value,
+    Attachment, Breadcrumb, Context, Event, Feedback, Level, MonitorCheckIn, MonitorCheckInTrace,
+    TraceContext, Transaction, User, Value,
 };
 #[cfg(feature = "release-health")]
 use crate::session::Session;
-use crate::{Client, TraceHeader, TraceHeadersIter, TracePropagationContext};
+use crate::{
+    should_propagate_trace, Client, TraceHeader, TraceHeadersIter, TracePropagationContext,
+};
 
 #[derive(Debug)]
 pub struct Stack {
@@ -30,6 +33,8 @@ pub struct Stack {
 
 type EventProcessor =
     Arc<dyn Fn(Event<'static>) -> Option<Event<'static>> + Send + Sync + RefUnwindSafe>;
+type TransactionProcessor =
+    Arc<dyn Fn(Transaction<'static>) -> Option<Transaction<'static>> + Send + Sync + RefUnwindSafe>;
 
 /// Holds contextual data for the current scope.
 ///
@@ -60,6 +65,7 @@ pub struct Scope {
     pub(crate) tags: Arc<HashMap<String, String>>,
     pub(crate) contexts: Arc<HashMap<String, Context>>,
     pub(crate) event_processors: Arc<Vec<EventProcessor>>,
+    pub(crate) transaction_processors: Arc<Vec<TransactionProcessor>>,
     #[cfg(feature = "release-health")]
     pub(crate) session: Arc<Mutex<Option<Session>>>,
     pub(crate) span: Arc<Option<TransactionOrSpan>>,
@@ -79,7 +85,8 @@ impl fmt::Debug for Scope {
             .field("extra", &self.extra)
             .field("tags", &self.tags)
             .field("contexts", &self.contexts)
-            .field("event_processors", &self.event_processors.len());
+            .field("event_processors", &self.event_processors.len())
+            .field("transaction_processors", &self.transaction_processors.len());
 
         #[cfg(feature = "release-health")]
         debug_struct.field("session", &self.session);
@@ -258,6 +265,9 @@ impl Scope {
     }
 
     /// Add an event processor to the scope.
+    ///
+    /// Event processors are not run on transactions, see
+    /// [`add_transaction_processor`](Self::add_transaction_processor).
     pub fn add_event_processor<F>(&mut self, f: F)
     where
         F: Fn(Event<'static>) -> Option<Event<'static>> + Send + Sync + RefUnwindSafe + 'static,
@@ -265,6 +275,46 @@ impl Scope {
         Arc::make_mut(&mut self.event_processors).push(Arc::new(f));
     }
 
+    /// Add a transaction processor to the scope.
+    ///
+    /// Transaction processors are run on finished transactions before they are sent, and can
+    /// modify them or drop them by returning `None`. Unlike event processors, they are not run
+    /// on error events.
+    ///
+    /// Transactions are kept apart from event processors because they are a different type,
+    /// carrying spans and measurements which an `Event` cannot represent, so passing them through
+    /// an event processor would lose data. A processor which should apply to both errors and
+    /// transactions is registered with both methods:
+    ///
+    /// ```
+    /// fn scrub(user: &mut Option<sentry::User>) {
+    ///     if let Some(user) = user {
+    ///         user.ip_address = None;
+    ///     }
+    /// }
+    ///
+    /// sentry::configure_scope(|scope| {
+    ///     scope.add_event_processor(|mut event| {
+    ///         scrub(&mut event.user);
+    ///         Some(event)
+    ///     });
+    ///     scope.add_transaction_processor(|mut transaction| {
+    ///         scrub(&mut transaction.user);
+    ///         Some(transaction)
+    ///     });
+    /// });
+    /// ```
+    pub fn add_transaction_processor<F>(&mut self, f: F)
+    where
+        F: Fn(Transaction<'static>) -> Option<Transaction<'static>>
+            + Send
+            + Sync
+            + RefUnwindSafe
+            + 'static,
+    {
+        Arc::make_mut(&mut self.transaction_processors).push(Arc::new(f));
+    }
+
     /// Adds an attachment to the scope
     pub fn add_attachment(&mut self, attachment: Attachment) {
         Arc::make_mut(&mut self.attachments).push(attachment);
@@ -356,6 +406,52 @@ impl Scope {
         );
     }
 
+    /// Runs the transaction processors of the scope on a finished transaction.
+    ///
+    /// Returns `None` if a processor dropped the transaction.
+    pub(crate) fn process_transaction(
+        &self,
+        mut transaction: Transaction<'static>,
+    ) -> Option<Transaction<'static>> {
+        for processor in self.transaction_processors.as_ref() {
+            let id = transaction.event_id;
+            transaction = match processor(transaction) {
+                Some(transaction) => transaction,
+                None => {
+                    sentry_debug!("transaction processor dropped transaction {}", id);
+                    return None;
+                }
+            }
+        }
+
+        Some(transaction)
+    }
+
+    /// Applies the contained scoped data to fill user feedback.
+    pub fn apply_to_feedback(&self, feedback: &mut Feedback) {
+        if feedback.user.is_none() {
+            if let Some(user) = self.user.as_deref() {
+                feedback.user = Some(user.clone());
+            }
+        }
+
+        feedback
+            .tags
+            .extend(self.tags.iter().map(|(k, v)| (k.to_owned(), v.to_owned())));
+    }
+
+    /// Applies the contained scoped data to a monitor check-in, linking it to the current trace.
+    pub fn apply_to_check_in(&self, check_in: &mut MonitorCheckIn) {
+        let contexts = check_in.contexts.get_or_insert_with(Default::default);
+        if contexts.trace.is_none() {
+            let trace_id = match self.span.as_ref() {
+                Some(span) => span.get_trace_context().trace_id,
+                None => self.propagation_context.trace_id,
+            };
+            contexts.trace = Some(MonitorCheckInTrace { trace_id });
+        }
+    }
+
     /// Applies the contained scoped data to a log, setting the `trace_id` and certain default
     /// attributes.
     #[cfg(feature = "logs")]
@@ -477,6 +573,11 @@ impl Scope {
     }
 
     /// Returns the headers needed for distributed tracing.
+    ///
+    /// Use [`Scope::iter_trace_propagation_headers_for`] to obtain the headers for an outgoing
+    /// request, taking the
+    /// [`trace_propagation_targets`](crate::ClientOptions::trace_propagation_targets) option into
+    /// account.
     pub fn iter_trace_propagation_headers(&self) -> impl Iterator<Item = TraceHeader> {
         if let Some(span) = self.get_span() {
             span.iter_headers()
@@ -485,7 +586,28 @@ impl Scope {
                 self.propagation_context.trace_id,
                 self.propagation_context.span_id,
             );
-            TraceHeadersIter::new(data.sentry_trace_header())
+            TraceHeadersIter::from_current_client(&data)
+        }
+    }
+
+    /// Returns the headers needed for distributed tracing of an outgoing request to `url`.
+    ///
+    /// No headers are returned if `url` does not match the
+    /// [`trace_propagation_targets`](crate::ClientOptions::trace_propagation_targets) option.
+    pub fn iter_trace_propagation_headers_for(
+        &self,
+        url: &str,
+    ) -> impl Iterator<Item = TraceHeader> {
+        if let Some(span) = self.get_span() {
+            span.iter_headers_for(url)
+        } else if !should_propagate_trace(url) {
+            TraceHeadersIter::empty()
+        } else {
+            let data = TracePropagationContext::new(
+                self.propagation_context.trace_id,
+                self.propagation_context.span_id,
+            );
+            TraceHeadersIter::from_current_client(&data)
         }
     }
 }
@@ -0,0 +1,105 @@
+//! Spawning threads bound to a new hub.
+//!
+//! Threads spawned with [`std::thread::spawn`] run on a hub forked from the main hub, so they do
+//! not see the scope of the thread spawning them, including its active span. The helpers in this
+//! module fork the current hub instead.
+
+use std::io;
+use std::thread::{self, JoinHandle};
+
+#[cfg(feature = "client")]
+use std::sync::Arc;
+
+#[cfg(feature = "client")]
+use crate::Hub;
+
+/// Spawns a new thread, bound to a new hub forked from the current one.
+///
+/// The thread starts with a copy of the current scope, including its active span, but changes it
+/// makes to its scope do not leak into the current thread. Otherwise, this behaves like
+/// [`std::thread::spawn`].
+///
+/// # Example
+///
+/// ```rust
+/// sentry::configure_scope(|scope| scope.set_tag("job", "import"));
+///
+/// let handle = sentry::thread::spawn(|| {
+///     // The event has the `job` tag.
+///     sentry::capture_message("importing", sentry::Level::Info);
+/// });
+/// handle.join().unwrap();
+/// ```
+pub fn spawn<F, T>(f: F) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    thread::spawn(bind_current_hub(f))
+}
+
+/// Spawns a new thread with the given builder, bound to a new hub forked from the current one.
+///
+/// See [`spawn`] for details. Like [`std::thread::Builder::spawn`], this returns an error if the
+/// thread could not be created.
+pub fn spawn_with<F, T>(builder: thread::Builder, f: F) -> io::Result<JoinHandle<T>>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    builder.spawn(bind_current_hub(f))
+}
+
+/// Wraps `f` to run on a new hub forked from the current one.
+fn bind_current_hub<F, T>(f: F) -> impl FnOnce() -> T
+where
+    F: FnOnce() -> T,
+{
+    #[cfg(feature = "client")]
+    {
+        let hub = Arc::new(Hub::new_from_top(Hub::current()));
+        move || Hub::run(hub, f)
+    }
+    #[cfg(not(feature = "client"))]
+    {
+        f
+    }
+}
+
+#[cfg(all(test, feature = "test"))]
+mod tests {
+    use crate::test::with_captured_events;
+    use crate::{capture_message, configure_scope, Level};
+
+    #[test]
+    fn test_spawn() {
+        let events = with_captured_events(|| {
+            configure_scope(|scope| scope.set_tag("outer", "true"));
+
+            super::spawn(|| {
+                configure_scope(|scope| scope.set_tag("inner", "true"));
+                capture_message("oh hai from the thread", Level::Info);
+            })
+            .join()
+            .unwrap();
+
+            let builder = std::thread::Builder::new().name("worker".into());
+            super::spawn_with(builder, || {
+                capture_message("oh hai from the worker", Level::Info);
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+            capture_message("oh hai from outside", Level::Info);
+        });
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].tags["outer"], "true");
+        assert_eq!(events[0].tags["inner"], "true");
+        for event in &events[1..] {
+            assert_eq!(event.tags["outer"], "true");
+            assert!(!event.tags.contains_key("inner"));
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! Includes the [`CompressionOptions`] struct.
+
+/// The default size, in bytes, below which envelope bodies are sent uncompressed.
+const DEFAULT_THRESHOLD: usize = 1024;
+
+/// An algorithm used to compress envelope bodies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CompressionAlgorithm {
+    /// Compress with gzip, sent as `Content-Encoding: gzip`.
+    ///
+    /// Requires the `gzip` feature of the `sentry` crate.
+    Gzip,
+    /// Compress with Zstandard, sent as `Content-Encoding: zstd`.
+    ///
+    /// Requires the `zstd` feature of the `sentry` crate.
+    Zstd,
+}
+
+impl CompressionAlgorithm {
+    /// The value of the `Content-Encoding` header for bodies compressed with this algorithm.
+    pub fn content_encoding(&self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+        }
+    }
+}
+
+/// Options for compressing envelope bodies in the HTTP transports.
+///
+/// Serialized envelopes at least [`threshold`](field@CompressionOptions::threshold) bytes large
+/// are compressed with the configured [`CompressionAlgorithm`] and sent with the corresponding
+/// `Content-Encoding` header. Smaller envelopes are sent as-is, since compressing them saves
+/// little.
+///
+/// If the `sentry` crate was compiled without support for the configured algorithm, envelopes are
+/// sent uncompressed.
+///
+/// # Examples
+///
+/// ```
+/// use sentry::{ClientOptions, CompressionAlgorithm, CompressionOptions};
+///
+/// let options = ClientOptions::new()
+///     .compression(CompressionOptions::new(CompressionAlgorithm::Gzip).threshold(4096));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CompressionOptions {
+    /// The compression algorithm.
+    pub algorithm: CompressionAlgorithm,
+    /// The size, in bytes, below which envelope bodies are sent uncompressed.
+    ///
+    /// See [`threshold`](method@CompressionOptions::threshold) for details.
+    pub threshold: usize,
+}
+
+impl CompressionOptions {
+    /// Creates options which compress envelope bodies with the given algorithm.
+    pub fn new(algorithm: CompressionAlgorithm) -> Self {
+        Self {
+            algorithm,
+            threshold: DEFAULT_THRESHOLD,
+        }
+    }
+
+    /// Sets the [size](field@CompressionOptions::threshold), in bytes, below which envelope bodies
+    /// are sent uncompressed.
+    ///
+    /// Defaults to 1024 bytes.
+    #[must_use]
+    pub fn threshold(self, threshold: usize) -> Self {
+        Self { threshold, ..self }
+    }
+}
@@ -3,9 +3,15 @@ use std::time::Duration;
 
 use crate::{ClientOptions, Envelope};
 
+mod compression;
+mod offline_cache;
 mod options;
+mod retry;
 
+pub use self::compression::{CompressionAlgorithm, CompressionOptions};
+pub use self::offline_cache::OfflineCacheOptions;
 pub use self::options::TransportOptions;
+pub use self::retry::RetryPolicy;
 
 /// The trait for transports.
 ///
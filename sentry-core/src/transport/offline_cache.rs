@@ -0,0 +1,81 @@
+//! Includes the [`OfflineCacheOptions`] struct.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// The default maximum total size of the offline cache, in bytes (10 MiB).
+const DEFAULT_MAX_SIZE: u64 = 10 * 1024 * 1024;
+
+/// The default maximum age of cached envelopes (one day).
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Options for the offline envelope cache of the HTTP transports.
+///
+/// When configured, transports which support offline caching persist envelopes that could not be
+/// sent, for example because Sentry is unreachable, to the given directory. Cached envelopes are sent in order on the next start of the transport, or once
+/// sending succeeds again.
+///
+/// The cache is bounded by a total size budget and a maximum age. Envelopes that exceed either
+/// budget are removed and recorded as lost in client reports.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use sentry::{ClientOptions, OfflineCacheOptions};
+///
+/// let options = ClientOptions::new().offline_cache(
+///     OfflineCacheOptions::new("/var/cache/my-app/sentry")
+///         .max_size(50 * 1024 * 1024)
+///         .max_age(Duration::from_secs(6 * 60 * 60)),
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OfflineCacheOptions {
+    /// The directory in which envelopes are stored.
+    ///
+    /// The directory is created if it does not exist. It should not be shared with other
+    /// applications or with other clients of the same application.
+    pub path: PathBuf,
+    /// The maximum total size of all cached envelopes, in bytes.
+    ///
+    /// See [`max_size`](method@OfflineCacheOptions::max_size) for details.
+    pub max_size: u64,
+    /// The maximum age of a cached envelope.
+    ///
+    /// See [`max_age`](method@OfflineCacheOptions::max_age) for details.
+    pub max_age: Duration,
+}
+
+impl OfflineCacheOptions {
+    /// Creates options for an offline cache in the given directory.
+    pub fn new<P>(path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            path: path.into(),
+            max_size: DEFAULT_MAX_SIZE,
+            max_age: DEFAULT_MAX_AGE,
+        }
+    }
+
+    /// Sets the [maximum total size](field@OfflineCacheOptions::max_size) of the cache, in bytes.
+    ///
+    /// When storing an envelope that failed to send would exceed this budget, the oldest envelopes
+    /// are removed first. Defaults to 10 MiB.
+    #[must_use]
+    pub fn max_size(self, max_size: u64) -> Self {
+        Self { max_size, ..self }
+    }
+
+    /// Sets the [maximum age](field@OfflineCacheOptions::max_age) of cached envelopes.
+    ///
+    /// Envelopes older than this are removed instead of being sent. Defaults to one day.
+    #[must_use]
+    pub fn max_age(self, max_age: Duration) -> Self {
+        Self { max_age, ..self }
+    }
+}
@@ -6,7 +6,7 @@ use sentry_types::Dsn;
 
 #[cfg(feature = "client")]
 use crate::client_report::Recorder as ClientReportRecorder;
-use crate::ClientOptions;
+use crate::{ClientOptions, CompressionOptions, OfflineCacheOptions, RetryPolicy};
 
 /// Options for a transport.
 #[derive(Debug)]
@@ -23,6 +23,12 @@ pub struct TransportOptions {
     pub https_proxy: Option<Cow<'static, str>>,
     /// Whether TLS certificate validation should be disabled.
     pub accept_invalid_certs: bool,
+    /// Options for caching envelopes on disk when they cannot be sent.
+    pub offline_cache: Option<OfflineCacheOptions>,
+    /// Options for compressing envelope bodies.
+    pub compression: Option<CompressionOptions>,
+    /// The policy for retrying envelopes after transient send failures.
+    pub retry: Option<RetryPolicy>,
     /// A handle for recording lost Sentry data.
     #[cfg(feature = "client")]
     pub client_report_recorder: ClientReportRecorder,
@@ -43,6 +49,9 @@ impl TransportOptions {
             https_proxy,
             accept_invalid_certs,
             user_agent,
+            offline_cache,
+            compression,
+            retry,
             ..
         } = options;
 
@@ -52,6 +61,9 @@ impl TransportOptions {
             http_proxy: http_proxy.clone(),
             https_proxy: https_proxy.clone(),
             accept_invalid_certs: *accept_invalid_certs,
+            offline_cache: offline_cache.clone(),
+            compression: *compression,
+            retry: *retry,
             #[cfg(feature = "client")]
             client_report_recorder: ClientReportRecorder::new_no_op(),
         })
@@ -71,6 +83,9 @@ impl TransportOptions {
             http_proxy,
             https_proxy,
             accept_invalid_certs,
+            offline_cache,
+            compression,
+            retry,
             #[cfg(feature = "client")]
                 client_report_recorder: _,
         } = self;
@@ -83,6 +98,9 @@ impl TransportOptions {
             http_proxy,
             https_proxy,
             accept_invalid_certs,
+            offline_cache,
+            compression,
+            retry,
             ..Default::default()
         }
     }
@@ -0,0 +1,128 @@
+//! Includes the [`RetryPolicy`] struct.
+
+use std::time::Duration;
+
+/// The default maximum number of attempts to send an envelope.
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// The default delay before the first retry.
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// The default upper bound for the delay between two attempts.
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+/// The default time after the first attempt after which an envelope is no longer retried.
+const DEFAULT_DEADLINE: Duration = Duration::from_secs(30);
+
+/// A policy for retrying envelopes after transient send failures in the HTTP transports.
+///
+/// When configured, envelopes which could not be sent because of a network error or a server
+/// error (HTTP `5xx`) are sent again after an exponentially growing, jittered delay. The delay is
+/// extended as needed to respect the rate limits reported by Sentry.
+///
+/// An envelope is retried until it was attempted [`max_attempts`](field@RetryPolicy::max_attempts)
+/// times, or until the next attempt would start later than the
+/// [`deadline`](field@RetryPolicy::deadline). It is then stored in the offline cache if one is
+/// configured, and recorded as lost in client reports otherwise. Pending retries are abandoned the
+/// same way when the transport shuts down.
+///
+/// Envelopes are sent one at a time, so new envelopes are queued while a retry is pending.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use sentry::{ClientOptions, RetryPolicy};
+///
+/// let options = ClientOptions::new().retry(
+///     RetryPolicy::new()
+///         .max_attempts(5)
+///         .deadline(Duration::from_secs(60)),
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RetryPolicy {
+    /// The maximum number of attempts to send an envelope, including the first one.
+    ///
+    /// See [`max_attempts`](method@RetryPolicy::max_attempts) for details.
+    pub max_attempts: u32,
+    /// The delay before the first retry.
+    ///
+    /// See [`initial_backoff`](method@RetryPolicy::initial_backoff) for details.
+    pub initial_backoff: Duration,
+    /// The upper bound for the delay between two attempts.
+    ///
+    /// See [`max_backoff`](method@RetryPolicy::max_backoff) for details.
+    pub max_backoff: Duration,
+    /// The time after the first attempt after which an envelope is no longer retried.
+    ///
+    /// See [`deadline`](method@RetryPolicy::deadline) for details.
+    pub deadline: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+            deadline: DEFAULT_DEADLINE,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a retry policy with the default settings.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the [maximum number of attempts](field@RetryPolicy::max_attempts) to send an
+    /// envelope, including the first one.
+    ///
+    /// A value of `1` disables retries. Defaults to 3.
+    #[must_use]
+    pub fn max_attempts(self, max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..self
+        }
+    }
+
+    /// Sets the [delay before the first retry](field@RetryPolicy::initial_backoff).
+    ///
+    /// The delay doubles with every further retry, up to the
+    /// [`max_backoff`](field@RetryPolicy::max_backoff). Each delay is randomly shortened by up to
+    /// half, so that many clients do not retry in lockstep. Defaults to 500 milliseconds.
+    #[must_use]
+    pub fn initial_backoff(self, initial_backoff: Duration) -> Self {
+        Self {
+            initial_backoff,
+            ..self
+        }
+    }
+
+    /// Sets the [upper bound for the delay](field@RetryPolicy::max_backoff) between two attempts.
+    ///
+    /// Rate limits reported by Sentry may extend the delay beyond this bound. Defaults to 10
+    /// seconds.
+    #[must_use]
+    pub fn max_backoff(self, max_backoff: Duration) -> Self {
+        Self {
+            max_backoff,
+            ..self
+        }
+    }
+
+    /// Sets the [time after the first attempt](field@RetryPolicy::deadline) after which an
+    /// envelope is no longer retried.
+    ///
+    /// No retry is started after the deadline, but a retry started before it is not cut short.
+    /// Defaults to 30 seconds.
+    #[must_use]
+    pub fn deadline(self, deadline: Duration) -> Self {
+        Self { deadline, ..self }
+    }
+}
@@ -1,5 +1,11 @@
 //! Utilities reused across dependant crates and integrations.
 
+use crate::protocol::SpanStatus;
+#[cfg(feature = "client")]
+use crate::protocol::{Breadcrumb, Level, Map};
+#[cfg(feature = "client")]
+use crate::{Hub, Span, TraceHeader};
+
 const SENSITIVE_HEADERS_UPPERCASE: &[&str] = &[
     "AUTHORIZATION",
     "PROXY_AUTHORIZATION",
@@ -30,3 +36,175 @@ pub fn scrub_pii_from_url(mut url: url::Url) -> url::Url {
     }
     url
 }
+
+/// Returns the status of the span of an HTTP request which received the given status code.
+pub fn span_status_from_http_status(status: u16) -> SpanStatus {
+    match status {
+        401 => SpanStatus::Unauthenticated,
+        403 => SpanStatus::PermissionDenied,
+        404 => SpanStatus::NotFound,
+        409 => SpanStatus::AlreadyExists,
+        429 => SpanStatus::ResourceExhausted,
+        400..=499 => SpanStatus::InvalidArgument,
+        501 => SpanStatus::Unimplemented,
+        503 => SpanStatus::Unavailable,
+        500..=599 => SpanStatus::InternalError,
+        200..=299 => SpanStatus::Ok,
+        _ => SpanStatus::UnknownError,
+    }
+}
+
+/// An outgoing HTTP request, as recorded by HTTP client integrations.
+///
+/// Starting the request starts an `http.client` span as a child of the span active on the scope
+/// of the hub, recording the method, URL and content length of the request. The URL is recorded
+/// without its query string and fragment, which are recorded separately in the `http.query` and
+/// `http.fragment` span data.
+///
+/// Finishing the request records the response on the span and adds an `http` breadcrumb. If the
+/// request is dropped before it is finished, the span is finished with the `aborted` status.
+#[cfg(feature = "client")]
+#[derive(Debug)]
+pub struct OutgoingHttpRequest {
+    method: String,
+    url: String,
+    span: Option<Span>,
+}
+
+#[cfg(feature = "client")]
+impl OutgoingHttpRequest {
+    /// Starts recording a request with the given method to `uri`.
+    pub fn start(hub: &Hub, method: &str, uri: &str, content_length: Option<u64>) -> Self {
+        let (url, query, fragment) = match uri.parse() {
+            Ok(url) => split_url(scrub_pii_from_url(url)),
+            Err(_) => (uri.to_owned(), None, None),
+        };
+
+        let parent_span = hub.configure_scope(|scope| scope.get_span());
+        let span = parent_span.map(|parent| {
+            let span = parent.start_child("http.client", &format!("{method} {url}"));
+            span.set_data("http.request.method", method.into());
+            span.set_data("url", url.as_str().into());
+            if let Some(query) = query {
+                span.set_data("http.query", query.into());
+            }
+            if let Some(fragment) = fragment {
+                span.set_data("http.fragment", fragment.into());
+            }
+            if let Some(length) = content_length {
+                span.set_data("http.request_content_length", length.into());
+            }
+            span
+        });
+
+        Self {
+            method: method.to_owned(),
+            url,
+            span,
+        }
+    }
+
+    /// Returns the distributed tracing headers to attach to the request.
+    ///
+    /// No headers are returned if the URL does not match the
+    /// [`trace_propagation_targets`](crate::ClientOptions::trace_propagation_targets) option.
+    pub fn trace_headers(&self, hub: &Hub) -> Vec<TraceHeader> {
+        match &self.span {
+            Some(span) => span.iter_headers_for(&self.url).collect(),
+            None => hub.configure_scope(|scope| {
+                scope
+                    .iter_trace_propagation_headers_for(&self.url)
+                    .collect()
+            }),
+        }
+    }
+
+    /// Finishes the span and adds a breadcrumb for the response.
+    ///
+    /// `status` is the status code of the response, or `None` if the request failed.
+    pub fn finish(mut self, hub: &Hub, status: Option<u16>, content_length: Option<u64>) {
+        let mut data = Map::new();
+        data.insert("method".into(), self.method.as_str().into());
+        data.insert("url".into(), self.url.as_str().into());
+        if let Some(status) = status {
+            data.insert("status_code".into(), status.into());
+        }
+
+        if let Some(span) = self.span.take() {
+            match status {
+                Some(status) => {
+                    span.set_data("http.response.status_code", status.into());
+                    if let Some(length) = content_length {
+                        span.set_data("http.response_content_length", length.into());
+                    }
+                    span.set_status(span_status_from_http_status(status));
+                }
+                None => span.set_status(SpanStatus::UnknownError),
+            }
+            span.finish();
+        }
+
+        let level = match status {
+            Some(500..) | None => Level::Error,
+            Some(400..=499) => Level::Warning,
+            Some(_) => Level::Info,
+        };
+        hub.add_breadcrumb(Breadcrumb {
+            ty: "http".into(),
+            category: Some("http".into()),
+            level,
+            data,
+            ..Default::default()
+        });
+    }
+}
+
+#[cfg(feature = "client")]
+impl Drop for OutgoingHttpRequest {
+    fn drop(&mut self) {
+        // If the request gets dropped without receiving a response,
+        // still finish the span to make sure this is not lost.
+        if let Some(span) = self.span.take() {
+            span.set_status(SpanStatus::Aborted);
+            span.finish();
+        }
+    }
+}
+
+/// Splits the query and fragment off `url`, so that they can be recorded separately.
+#[cfg(feature = "client")]
+fn split_url(mut url: url::Url) -> (String, Option<String>, Option<String>) {
+    let query = url.query().map(str::to_owned);
+    let fragment = url.fragment().map(str::to_owned);
+    url.set_query(None);
+    url.set_fragment(None);
+    (url.into(), query, fragment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_http_status_to_span_status() {
+        for (status, span_status) in [
+            (200, SpanStatus::Ok),
+            (204, SpanStatus::Ok),
+            (400, SpanStatus::InvalidArgument),
+            (401, SpanStatus::Unauthenticated),
+            (404, SpanStatus::NotFound),
+            (409, SpanStatus::AlreadyExists),
+            (429, SpanStatus::ResourceExhausted),
+            (500, SpanStatus::InternalError),
+            (501, SpanStatus::Unimplemented),
+            (503, SpanStatus::Unavailable),
+            (302, SpanStatus::UnknownError),
+        ] {
+            assert_eq!(
+                span_status_from_http_status(status),
+                span_status,
+                "{status}"
+            );
+        }
+    }
+}
@@ -5,7 +5,9 @@ use std::sync::Arc;
 use sentry_core::protocol::client_report::Reason;
 use sentry_core::protocol::{EnvelopeItem, Event};
 use sentry_core::test::TestTransport;
-use sentry_core::{Client, ClientOptions, Envelope, Hub, Integration, Scope, TransactionContext};
+use sentry_core::{
+    Client, ClientOptions, Envelope, Hub, Integration, Scope, TraceLifecycle, TransactionContext,
+};
 
 struct DroppingIntegration;
 
@@ -98,6 +100,23 @@ fn client_report_records_before_send_drop() {
     );
 }
 
+#[test]
+fn client_report_records_ignore_errors_drop() {
+    let options = ClientOptions::new().ignore_errors(["Broken pipe"]);
+
+    assert_drop_records_client_report(
+        options,
+        |client| {
+            let event = Event {
+                message: Some("Broken pipe".into()),
+                ..Default::default()
+            };
+            client.capture_event(event, None);
+        },
+        Reason::Filtered,
+    );
+}
+
 #[test]
 fn client_report_records_sample_rate_drop() {
     let options = ClientOptions::new().sample_rate(0.0);
@@ -141,6 +160,128 @@ fn client_report_records_unsampled_transaction_and_spans() {
     );
 }
 
+fn assert_transaction_drop_records_client_report<F>(
+    options: ClientOptions,
+    configure: F,
+    reason: &str,
+) where
+    F: FnOnce(&mut Scope),
+{
+    let transport = TestTransport::new();
+    let client = Arc::new(client_with_options(
+        transport.clone(),
+        options.traces_sample_rate(1.0),
+    ));
+
+    Hub::run(
+        Arc::new(Hub::new(Some(client.clone()), Arc::new(Default::default()))),
+        || {
+            sentry_core::configure_scope(configure);
+            let transaction = sentry_core::start_transaction(TransactionContext::new("tx", "op"));
+            transaction.start_child("child", "one").finish();
+            transaction.finish();
+        },
+    );
+    client.send_envelope(Envelope::new());
+
+    let envelopes = transport.fetch_and_clear_envelopes();
+    assert_eq!(envelopes.len(), 1);
+    assert_client_report(
+        &envelopes[0],
+        serde_json::json!([
+            { "category": "transaction", "reason": reason, "quantity": 1 },
+            { "category": "span", "reason": reason, "quantity": 2 },
+        ]),
+    );
+}
+
+#[test]
+fn client_report_records_scope_transaction_processor_drop() {
+    assert_transaction_drop_records_client_report(
+        ClientOptions::default(),
+        |scope| scope.add_transaction_processor(|_| None),
+        "event_processor",
+    );
+}
+
+#[test]
+fn client_report_records_before_send_transaction_drop() {
+    assert_transaction_drop_records_client_report(
+        ClientOptions::new().before_send_transaction(|_| None),
+        |_| {},
+        "before_send",
+    );
+}
+
+#[test]
+fn client_report_records_ignore_transactions_drop() {
+    assert_transaction_drop_records_client_report(
+        ClientOptions::new().ignore_transactions(["^tx$"]),
+        |_| {},
+        "filtered",
+    );
+}
+
+#[test]
+fn client_report_records_unsampled_streamed_spans() {
+    let transport = TestTransport::new();
+    let client = Arc::new(client_with_options(
+        transport.clone(),
+        ClientOptions::new()
+            .traces_sample_rate(0.0)
+            .trace_lifecycle(TraceLifecycle::Stream),
+    ));
+
+    Hub::run(
+        Arc::new(Hub::new(Some(client.clone()), Arc::new(Default::default()))),
+        || {
+            let transaction = sentry_core::start_transaction(TransactionContext::new("tx", "op"));
+            transaction.start_child("child", "one").finish();
+            transaction.start_child("child", "two").finish();
+            transaction.finish();
+        },
+    );
+    client.send_envelope(Envelope::new());
+
+    let envelopes = transport.fetch_and_clear_envelopes();
+    assert_eq!(envelopes.len(), 1);
+    assert_client_report(
+        &envelopes[0],
+        serde_json::json!([{ "category": "span", "reason": "sample_rate", "quantity": 3 }]),
+    );
+}
+
+#[test]
+fn client_report_records_dropped_streamed_spans() {
+    let transport = TestTransport::new();
+    let client = Arc::new(client_with_options(
+        transport.clone(),
+        ClientOptions::new()
+            .traces_sample_rate(1.0)
+            .trace_lifecycle(TraceLifecycle::Stream)
+            .before_send_span(|_| None)
+            .before_send_transaction(|_| None),
+    ));
+
+    Hub::run(
+        Arc::new(Hub::new(Some(client.clone()), Arc::new(Default::default()))),
+        || {
+            let transaction = sentry_core::start_transaction(TransactionContext::new("tx", "op"));
+            transaction.start_child("child", "one").finish();
+            transaction.start_child("child", "two").finish();
+            transaction.finish();
+        },
+    );
+    client.send_envelope(Envelope::new());
+
+    let envelopes = transport.fetch_and_clear_envelopes();
+    assert_eq!(envelopes.len(), 1);
+    assert_client_report(
+        &envelopes[0],
+        serde_json::json!([{ "category": "span", "reason": "before_send", "quantity": 3 }]),
+    );
+}
+
 #[test]
 fn client_report_records_transaction_span_cap_drop() {
     // Keep in sync with `MAX_SPANS` in `sentry-core/src/performance.rs`.
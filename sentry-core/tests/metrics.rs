@@ -5,6 +5,7 @@ use std::collections::HashSet;
 use anyhow::{Context, Result};
 
 use sentry::protocol::{MetricType, Unit, Value};
+use sentry_core::protocol::client_report::metric_byte_size;
 use sentry_core::protocol::{EnvelopeItem, ItemContainer};
 use sentry_core::{metrics, test};
 use sentry_core::{ClientOptions, TransactionContext};
@@ -590,8 +591,175 @@ fn before_send_metric_can_modify() {
     );
 }
 
+/// Test that metrics are not aggregated by default.
+#[test]
+fn metrics_not_aggregated_by_default() {
+    let envelopes = test::with_captured_envelopes_options(
+        || {
+            metrics::counter("test", 1).capture();
+            metrics::counter("test", 1).capture();
+        },
+        ClientOptions::default(),
+    );
+    let metrics = extract_metrics(envelopes).expect("expected a single metrics item");
+
+    assert_eq!(metrics.len(), 2);
+}
+
+/// Test that counters with the same name, unit and attributes are summed up.
+#[test]
+fn aggregated_counters_are_summed() {
+    let options = ClientOptions::new().aggregate_metrics(true);
+
+    let envelopes = test::with_captured_envelopes_options(
+        || {
+            for _ in 0..1_000 {
+                metrics::counter("http.requests", 1)
+                    .attribute("http.route", "/users")
+                    .capture();
+            }
+            metrics::counter("http.requests", 2)
+                .attribute("http.route", "/health")
+                .capture();
+            metrics::counter("http.requests", 3)
+                .attribute("http.route", "/health")
+                .capture();
+        },
+        options,
+    );
+    let metrics = extract_metrics(envelopes).expect("expected a single metrics item");
+
+    let values: Vec<_> = metrics
+        .iter()
+        .map(|metric| {
+            assert_eq!(metric.r#type, MetricType::Counter);
+            assert_eq!(metric.name, "http.requests");
+            let route = metric.attributes.get("http.route").cloned();
+            (route, metric.value)
+        })
+        .collect();
+    assert_eq!(
+        values,
+        [
+            (Some("/users".into()), 1_000.0),
+            (Some("/health".into()), 5.0)
+        ]
+    );
+}
+
+/// Test that gauges keep their last value along with the minimum, maximum, sum and count.
+#[test]
+fn aggregated_gauges_keep_stats() {
+    let options = ClientOptions::new().aggregate_metrics(true);
+
+    let envelopes = test::with_captured_envelopes_options(
+        || {
+            for depth in [4, 10, 1, 3] {
+                metrics::gauge("queue.depth", depth).capture();
+            }
+            metrics::gauge("queue.depth", 7).unit(Unit::Byte).capture();
+        },
+        options,
+    );
+    let metrics = extract_metrics(envelopes).expect("expected a single metrics item");
+    assert_eq!(
+        metrics.len(),
+        2,
+        "gauges with other units must not be merged"
+    );
+
+    let gauge = &metrics[0];
+    assert_eq!(gauge.r#type, MetricType::Gauge);
+    assert_eq!(gauge.value, 3.0);
+    let stat = |key: &str| gauge.attributes.get(key).cloned();
+    assert_eq!(stat("sentry.gauge.min"), Some(1.0.into()));
+    assert_eq!(stat("sentry.gauge.max"), Some(10.0.into()));
+    assert_eq!(stat("sentry.gauge.sum"), Some(18.0.into()));
+    assert_eq!(stat("sentry.gauge.count"), Some(4.into()));
+
+    let single = &metrics[1];
+    assert_eq!(single.value, 7.0);
+    assert_eq!(single.unit, Some(Unit::Byte));
+    assert!(!single.attributes.contains_key("sentry.gauge.count"));
+}
+
+/// Test that distributions keep all their values when aggregating metrics.
+#[test]
+fn aggregated_distributions_keep_values() {
+    let options = ClientOptions::new().aggregate_metrics(true);
+
+    let envelopes = test::with_captured_envelopes_options(
+        || {
+            for value in [1.5, 2.5, 1.5] {
+                metrics::distribution("http.response_time", value).capture();
+            }
+        },
+        options,
+    );
+    let metrics = extract_metrics(envelopes).expect("expected a single metrics item");
+
+    let values: Vec<_> = metrics.iter().map(|metric| metric.value).collect();
+    assert_eq!(values, [1.5, 2.5, 1.5]);
+}
+
+/// Test that the queue accounts for the attributes added to aggregated gauges.
+#[test]
+fn aggregated_gauges_count_towards_queue_size() {
+    let capture = || {
+        metrics::gauge("queue.depth", 1).capture();
+        metrics::gauge("queue.depth", 2).capture();
+        metrics::counter("http.requests", 1).capture();
+    };
+    let options = || ClientOptions::new().aggregate_metrics(true);
+
+    let metrics = extract_metrics(test::with_captured_envelopes_options(capture, options()))
+        .expect("expected a single metrics item");
+    let [gauge, counter] = metrics.as_slice() else {
+        panic!("expected a gauge and a counter, got {metrics:?}");
+    };
+    let mut single_gauge = gauge.clone();
+    single_gauge
+        .attributes
+        .retain(|key, _| !key.starts_with("sentry.gauge."));
+    let single_gauge_size = metric_byte_size(&single_gauge);
+    assert!(metric_byte_size(gauge) > single_gauge_size);
+
+    // The counter would fit next to a gauge with a single value, but not the aggregated gauge.
+    let max_batch_queue_bytes = single_gauge_size + metric_byte_size(counter);
+    let envelopes = test::with_captured_envelopes_options(
+        capture,
+        options().max_batch_queue_bytes(max_batch_queue_bytes),
+    );
+    let metrics: Vec<_> = envelopes
+        .into_iter()
+        .flat_map(|envelope| envelope.into_items())
+        .filter_map(|item| item.into_metrics())
+        .flatten()
+        .collect();
+    assert_eq!(metrics.len(), 1);
+    assert_eq!(metrics[0].name, "queue.depth");
+}
+
 /// Returns a [`Metric`] with [type `Counter`](MetricType),
 /// the provided name, and a value of `1.0`.
+/// Helper to extract the metrics from a list of captured envelopes.
+///
+/// Asserts that there is only a single envelope, which contains only a single
+/// metrics item, and returns its metrics, or an error if failed.
+fn extract_metrics<I>(envelopes: I) -> Result<Vec<Metric>>
+where
+    I: IntoIterator<Item = Envelope>,
+{
+    envelopes
+        .try_into_only_item()
+        .context("expected exactly one envelope")?
+        .into_items()
+        .try_into_only_item()
+        .context("expected exactly one item")?
+        .into_metrics()
+        .context("expected a metrics item")
+}
+
 /// Helper to extract the single metric from a list of captured envelopes.
 ///
 /// Asserts that the envelope contains only a single item, which contains only
@@ -0,0 +1,222 @@
+#![cfg(feature = "test")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use sentry_core::protocol::{
+    EnvelopeItem, ItemContainer, SpanStatus, StreamedSpan, StreamedSpanStatus, Value,
+};
+use sentry_core::{test, ClientOptions, TraceLifecycle, TransactionContext};
+use sentry_types::protocol::v7::{Envelope, LogAttribute};
+
+fn streaming_options() -> ClientOptions {
+    ClientOptions::new()
+        .traces_sample_rate(1.0)
+        .trace_lifecycle(TraceLifecycle::Stream)
+        .release("app@1.0.0")
+}
+
+fn streamed_spans(envelopes: &[Envelope]) -> Vec<StreamedSpan> {
+    envelopes
+        .iter()
+        .flat_map(|envelope| envelope.items())
+        .filter_map(|item| match item {
+            EnvelopeItem::ItemContainer(ItemContainer::Spans(spans)) => Some(spans.clone()),
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+fn attribute<'a>(span: &'a StreamedSpan, key: &str) -> Option<&'a Value> {
+    span.attributes.get(key).map(|LogAttribute(value)| value)
+}
+
+/// Test that spans and their transaction are sent as streamed spans.
+#[test]
+fn sends_spans_and_segment() {
+    let envelopes = test::with_captured_envelopes_options(
+        || {
+            let transaction = sentry_core::start_transaction(TransactionContext::new(
+                "GET /users",
+                "http.server",
+            ));
+            transaction.set_tag("region", "eu");
+            let span = transaction.start_child("db.query", "SELECT * FROM users");
+            span.set_data("db.system", "postgresql".into());
+            span.set_status(SpanStatus::InternalError);
+            span.finish();
+            transaction.finish();
+        },
+        streaming_options(),
+    );
+
+    assert!(envelopes
+        .iter()
+        .flat_map(|envelope| envelope.items())
+        .all(|item| !matches!(item, EnvelopeItem::Transaction(_))));
+
+    let spans = streamed_spans(&envelopes);
+    assert_eq!(spans.len(), 2);
+    let (span, segment) = (&spans[0], &spans[1]);
+
+    assert!(segment.is_segment);
+    assert_eq!(segment.name, "GET /users");
+    assert_eq!(segment.status, StreamedSpanStatus::Ok);
+    assert_eq!(attribute(segment, "sentry.op"), Some(&"http.server".into()));
+    assert_eq!(
+        attribute(segment, "sentry.release"),
+        Some(&"app@1.0.0".into())
+    );
+    assert_eq!(attribute(segment, "region"), Some(&"eu".into()));
+    assert!(attribute(segment, "sentry.sdk.name").is_some());
+
+    assert!(!span.is_segment);
+    assert_eq!(span.name, "SELECT * FROM users");
+    assert_eq!(span.status, StreamedSpanStatus::Error);
+    assert_eq!(span.trace_id, segment.trace_id);
+    assert_eq!(span.parent_span_id, Some(segment.span_id));
+    assert_eq!(attribute(span, "sentry.op"), Some(&"db.query".into()));
+    assert_eq!(attribute(span, "db.system"), Some(&"postgresql".into()));
+    assert_eq!(
+        attribute(span, "sentry.segment.id"),
+        Some(&segment.span_id.to_string().into())
+    );
+    assert_eq!(
+        attribute(span, "sentry.segment.name"),
+        Some(&"GET /users".into())
+    );
+}
+
+/// Test that spans are sent in batches before their transaction finishes, without a span limit.
+#[test]
+fn sends_spans_in_batches() {
+    let envelopes = test::with_captured_envelopes_options(
+        || {
+            let transaction =
+                sentry_core::start_transaction(TransactionContext::new("job", "queue.process"));
+            for _ in 0..1_050 {
+                transaction.start_child("task", "step").finish();
+            }
+            transaction.finish();
+        },
+        streaming_options(),
+    );
+
+    assert!(envelopes.len() > 1);
+    let spans = streamed_spans(&envelopes);
+    assert_eq!(spans.len(), 1_051);
+    assert_eq!(spans.iter().filter(|span| span.is_segment).count(), 1);
+}
+
+/// Test that spans of unsampled transactions are not sent.
+#[test]
+fn does_not_send_unsampled_spans() {
+    let envelopes = test::with_captured_envelopes_options(
+        || {
+            let transaction =
+                sentry_core::start_transaction(TransactionContext::new("job", "queue.process"));
+            transaction.start_child("task", "step").finish();
+            transaction.finish();
+        },
+        streaming_options().traces_sample_rate(0.0),
+    );
+
+    assert!(streamed_spans(&envelopes).is_empty());
+}
+
+/// Test that streamed spans are filtered by the name of their transaction and processed on their
+/// own, and that each transaction is processed once as the segment.
+#[test]
+fn processes_spans_and_segments() {
+    let processed_transactions = Arc::new(AtomicUsize::new(0));
+    let envelopes = test::with_captured_envelopes_options(
+        || {
+            for name in ["GET /health", "GET /metrics", "GET /users"] {
+                let transaction =
+                    sentry_core::start_transaction(TransactionContext::new(name, "http.server"));
+                let span = transaction.start_child("db.query", "SELECT * FROM users");
+                span.set_data("db.password", "hunter2".into());
+                span.finish();
+                transaction.finish();
+            }
+        },
+        streaming_options()
+            .ignore_transactions(["GET /metrics"])
+            .before_send_span(|mut span| {
+                span.data.remove("db.password");
+                Some(span)
+            })
+            .before_send_transaction({
+                let processed_transactions = processed_transactions.clone();
+                move |transaction| {
+                    processed_transactions.fetch_add(1, Ordering::SeqCst);
+                    assert!(transaction.spans.is_empty());
+                    (transaction.name.as_deref() != Some("GET /health")).then_some(transaction)
+                }
+            }),
+    );
+
+    assert_eq!(processed_transactions.load(Ordering::SeqCst), 2);
+    let spans = streamed_spans(&envelopes);
+    let names: Vec<_> = spans
+        .iter()
+        .map(|span| {
+            let segment_name = attribute(span, "sentry.segment.name").unwrap();
+            (span.is_segment, segment_name.as_str().unwrap())
+        })
+        .collect();
+    // The child span of `GET /health` was sent before its segment was dropped.
+    assert_eq!(
+        names,
+        [
+            (false, "GET /health"),
+            (false, "GET /users"),
+            (true, "GET /users")
+        ]
+    );
+    assert!(spans
+        .iter()
+        .all(|span| attribute(span, "db.password").is_none()));
+}
+
+/// Test that the segment carries the measurements of its transaction.
+#[test]
+fn sends_measurements_on_segment() {
+    let envelopes = test::with_captured_envelopes_options(
+        || {
+            let transaction =
+                sentry_core::start_transaction(TransactionContext::new("job", "queue.process"));
+            transaction.set_measurement("rows_processed", 42.0, None);
+            transaction.finish();
+        },
+        streaming_options(),
+    );
+
+    let spans = streamed_spans(&envelopes);
+    assert_eq!(spans.len(), 1);
+    assert_eq!(attribute(&spans[0], "rows_processed"), Some(&42.0.into()));
+}
+
+/// Test that the segment is linked to the running profiler session.
+#[cfg(feature = "profiling")]
+#[test]
+fn sends_profiler_id_on_segment() {
+    let profiler_id = sentry_types::random_uuid();
+    let envelopes = test::with_captured_envelopes_options(
+        || {
+            sentry_core::profiling::set_profiler_id(Some(profiler_id));
+            let transaction =
+                sentry_core::start_transaction(TransactionContext::new("job", "queue.process"));
+            sentry_core::profiling::set_profiler_id(None);
+            transaction.finish();
+        },
+        streaming_options(),
+    );
+
+    let spans = streamed_spans(&envelopes);
+    assert_eq!(
+        attribute(&spans[0], "sentry.profiler_id"),
+        Some(&profiler_id.to_string().into())
+    );
+}
@@ -0,0 +1,120 @@
+use regex::Regex;
+use sentry_core::protocol::{Event, Log, Metric, Span, Transaction};
+use sentry_core::{ClientOptions, Integration};
+
+use crate::scrubber::{Rule, Scrubber, DEFAULT_DENYLIST};
+
+/// The Sentry Data Scrubber Integration.
+///
+/// See the [crate level documentation](crate) for the data that is scrubbed.
+#[derive(Debug, Clone)]
+pub struct DataScrubberIntegration {
+    scrubber: Scrubber,
+}
+
+impl DataScrubberIntegration {
+    /// Creates a new Data Scrubber Integration with the default denylist and rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new Data Scrubber Integration without any denied keys or rules.
+    pub fn empty() -> Self {
+        Self {
+            scrubber: Scrubber::default(),
+        }
+    }
+
+    /// Adds a key to the denylist.
+    ///
+    /// Values of keys containing `key` are replaced with `[Filtered]`. Keys are compared ignoring
+    /// case, and treating `-` like `_`.
+    #[must_use]
+    pub fn deny_key(mut self, key: &str) -> Self {
+        self.scrubber.deny_key(key);
+        self
+    }
+
+    /// Adds a rule, which replaces all matches of `pattern` in string values with `[Filtered]`.
+    #[must_use]
+    pub fn add_rule(self, pattern: Regex) -> Self {
+        self.with_rule(Rule::new(pattern))
+    }
+
+    fn with_rule(mut self, rule: Rule) -> Self {
+        self.scrubber.rules.push(rule);
+        self
+    }
+}
+
+impl Default for DataScrubberIntegration {
+    fn default() -> Self {
+        DEFAULT_DENYLIST
+            .iter()
+            .fold(Self::empty(), |integration, key| integration.deny_key(key))
+            .with_rule(Rule::credit_card())
+            .with_rule(Rule::iban())
+    }
+}
+
+impl Integration for DataScrubberIntegration {
+    fn name(&self) -> &'static str {
+        "data-scrubber"
+    }
+
+    fn process_event(
+        &self,
+        mut event: Event<'static>,
+        _options: &ClientOptions,
+    ) -> Option<Event<'static>> {
+        let scrubber = &self.scrubber;
+        scrubber.scrub_optional_string(&mut event.message);
+        for exception in &mut event.exception.values {
+            scrubber.scrub_optional_string(&mut exception.value);
+        }
+        if let Some(request) = &mut event.request {
+            scrubber.scrub_request(request);
+        }
+        for breadcrumb in &mut event.breadcrumbs.values {
+            scrubber.scrub_breadcrumb(breadcrumb);
+        }
+        scrubber.scrub_contexts(&mut event.contexts);
+        scrubber.scrub_strings(&mut event.tags);
+        scrubber.scrub_values(&mut event.extra);
+        Some(event)
+    }
+
+    fn process_transaction(
+        &self,
+        mut transaction: Transaction<'static>,
+        _options: &ClientOptions,
+    ) -> Option<Transaction<'static>> {
+        let scrubber = &self.scrubber;
+        if let Some(request) = &mut transaction.request {
+            scrubber.scrub_request(request);
+        }
+        for span in &mut transaction.spans {
+            scrubber.scrub_span(span);
+        }
+        scrubber.scrub_contexts(&mut transaction.contexts);
+        scrubber.scrub_strings(&mut transaction.tags);
+        scrubber.scrub_values(&mut transaction.extra);
+        Some(transaction)
+    }
+
+    fn process_span(&self, mut span: Span, _options: &ClientOptions) -> Option<Span> {
+        self.scrubber.scrub_span(&mut span);
+        Some(span)
+    }
+
+    fn process_log(&self, mut log: Log, _options: &ClientOptions) -> Option<Log> {
+        self.scrubber.scrub_string(&mut log.body);
+        self.scrubber.scrub_attributes(&mut log.attributes);
+        Some(log)
+    }
+
+    fn process_metric(&self, mut metric: Metric, _options: &ClientOptions) -> Option<Metric> {
+        self.scrubber.scrub_attributes(&mut metric.attributes);
+        Some(metric)
+    }
+}
@@ -0,0 +1,50 @@
+//! The Sentry Data Scrubber integration.
+//!
+//! The [`DataScrubberIntegration`] removes secrets and personal data from events, transactions,
+//! logs and metrics before they are passed to the `before_send` callbacks and sent to Sentry.
+//!
+//! Values are replaced with `[Filtered]` if either:
+//!
+//! - their key is on the denylist. By default, this includes all keys containing `password`,
+//!   `passwd`, `secret`, `token`, `auth`, `cookie`, `api_key`, `apikey`, `credentials`,
+//!   `private_key` or `privatekey`, ignoring case.
+//! - they contain text matching one of the rules. By default, this includes credit card numbers
+//!   and IBANs passing their checksums. Only the matching part of a value is replaced.
+//!
+//! The parameters of query strings and the individual cookies of requests are scrubbed by their
+//! names, unless the `cookie` key is denied, which scrubs all cookies.
+//!
+//! The integration scrubs:
+//!
+//! - the message, exception values, tags, extra data, request, breadcrumbs, and context data of
+//!   events.
+//! - the tags, extra data, request, context data, and span data and tags of transactions.
+//! - the data and tags of streamed spans.
+//! - the body and attributes of logs.
+//! - the attributes of metrics.
+//!
+//! # Configuration
+//!
+//! Further keys and rules can be added to the defaults, or [`DataScrubberIntegration::empty`] can
+//! be used to only scrub the configured keys and rules.
+//!
+//! ```rust
+//! use sentry_data_scrubber::{DataScrubberIntegration, Regex};
+//!
+//! let integration = DataScrubberIntegration::new()
+//!     .deny_key("ssn")
+//!     .add_rule(Regex::new(r"sk_live_\w+").unwrap());
+//! let options = sentry_core::ClientOptions::new().add_integration(integration);
+//! ```
+
+#![doc(html_favicon_url = "https://sentry-brand.storage.googleapis.com/favicon.ico")]
+#![doc(html_logo_url = "https://sentry-brand.storage.googleapis.com/sentry-glyph-black.png")]
+#![warn(missing_docs)]
+#![deny(unsafe_code)]
+
+mod integration;
+mod scrubber;
+
+pub use integration::DataScrubberIntegration;
+#[doc(no_inline)]
+pub use regex::Regex;
@@ -0,0 +1,475 @@
+use std::borrow::Cow;
+use std::sync::LazyLock;
+
+use regex::{Captures, Regex};
+use sentry_core::protocol::{Breadcrumb, Context, LogAttribute, Map, Request, Span, Value};
+
+/// The replacement for scrubbed values.
+pub(crate) const FILTERED: &str = "[Filtered]";
+
+/// The keys whose values are scrubbed by default.
+///
+/// Keys match if they contain any of these, ignoring case and treating `-` like `_`.
+pub(crate) const DEFAULT_DENYLIST: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "auth",
+    "cookie",
+    "api_key",
+    "apikey",
+    "credentials",
+    "private_key",
+    "privatekey",
+];
+
+/// Credit card numbers with 13 to 19 digits, which may be grouped by spaces or dashes.
+static CREDIT_CARD: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(?:\d[ -]?){12,18}\d\b").unwrap());
+
+/// International Bank Account Numbers, which may be grouped by spaces.
+static IBAN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b[A-Z]{2}\d{2}(?: ?[A-Z0-9]){11,30}\b").unwrap());
+
+/// A pattern of values to scrub, with an optional check that a match really is sensitive.
+#[derive(Debug, Clone)]
+pub(crate) struct Rule {
+    regex: Regex,
+    validate: Option<fn(&str) -> bool>,
+}
+
+impl Rule {
+    pub(crate) fn new(regex: Regex) -> Self {
+        Self {
+            regex,
+            validate: None,
+        }
+    }
+
+    pub(crate) fn credit_card() -> Self {
+        Self {
+            regex: CREDIT_CARD.clone(),
+            validate: Some(is_valid_credit_card),
+        }
+    }
+
+    pub(crate) fn iban() -> Self {
+        Self {
+            regex: IBAN.clone(),
+            validate: Some(is_valid_iban),
+        }
+    }
+
+    /// Returns the replacement for a match of the rule.
+    ///
+    /// Patterns like the credit card one can match more digits or characters than the sensitive
+    /// value when it is followed or preceded by other groups, as in `4111 1111 1111 1111 12/25`.
+    /// If a match fails validation, the longest run of its space or dash separated groups which
+    /// matches the pattern and passes validation is replaced instead, and the remaining groups
+    /// are checked the same way.
+    fn replace(&self, matched: &str) -> String {
+        let Some(validate) = self.validate else {
+            return FILTERED.to_owned();
+        };
+        let groups: Vec<(usize, usize)> = matched
+            .split([' ', '-'])
+            .scan(0usize, |start, group| {
+                let span = (*start, start.saturating_add(group.len()));
+                *start = span.1.saturating_add(1);
+                Some(span)
+            })
+            .filter(|(start, end)| start < end)
+            .collect();
+
+        for len in (1..=groups.len()).rev() {
+            for window in groups.windows(len) {
+                let (start, end) = (window[0].0, window[len.saturating_sub(1)].1);
+                let candidate = &matched[start..end];
+                let is_match = self
+                    .regex
+                    .find(candidate)
+                    .is_some_and(|found| found.len() == candidate.len());
+                if is_match && validate(candidate) {
+                    return format!(
+                        "{}{FILTERED}{}",
+                        self.replace_all(&matched[..start]),
+                        self.replace_all(&matched[end..]),
+                    );
+                }
+            }
+        }
+        matched.to_owned()
+    }
+
+    /// Replaces the matches of the rule in `value`.
+    fn replace_all<'a>(&self, value: &'a str) -> Cow<'a, str> {
+        self.regex
+            .replace_all(value, |captures: &Captures| self.replace(&captures[0]))
+    }
+}
+
+/// Scrubs values by their keys and contents.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Scrubber {
+    /// Lowercase key fragments, with `-` replaced by `_`.
+    pub(crate) denylist: Vec<String>,
+    pub(crate) rules: Vec<Rule>,
+}
+
+impl Scrubber {
+    pub(crate) fn deny_key(&mut self, key: &str) {
+        self.denylist.push(normalize_key(key).into_owned());
+    }
+
+    fn is_denied(&self, key: &str) -> bool {
+        let key = normalize_key(key);
+        self.denylist
+            .iter()
+            .any(|denied| key.contains(denied.as_str()))
+    }
+
+    /// Replaces the parts of `value` matching any rule, and returns whether it changed.
+    pub(crate) fn scrub_string(&self, value: &mut String) -> bool {
+        let mut changed = false;
+        for rule in &self.rules {
+            let scrubbed = rule.replace_all(value);
+            if let Cow::Owned(scrubbed) = scrubbed {
+                if scrubbed != *value {
+                    *value = scrubbed;
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+
+    pub(crate) fn scrub_optional_string(&self, value: &mut Option<String>) {
+        if let Some(value) = value {
+            self.scrub_string(value);
+        }
+    }
+
+    pub(crate) fn scrub_value(&self, value: &mut Value) {
+        match value {
+            Value::String(string) => {
+                self.scrub_string(string);
+            }
+            Value::Array(values) => values.iter_mut().for_each(|value| self.scrub_value(value)),
+            Value::Object(values) => {
+                for (key, value) in values.iter_mut() {
+                    self.scrub_entry(key, value);
+                }
+            }
+            Value::Null | Value::Bool(_) | Value::Number(_) => {}
+        }
+    }
+
+    fn scrub_entry(&self, key: &str, value: &mut Value) {
+        if self.is_denied(key) {
+            if !value.is_null() {
+                *value = FILTERED.into();
+            }
+        } else {
+            self.scrub_value(value);
+        }
+    }
+
+    pub(crate) fn scrub_values(&self, values: &mut Map<String, Value>) {
+        for (key, value) in values.iter_mut() {
+            self.scrub_entry(key, value);
+        }
+    }
+
+    pub(crate) fn scrub_strings(&self, values: &mut Map<String, String>) {
+        for (key, value) in values.iter_mut() {
+            if self.is_denied(key) {
+                FILTERED.clone_into(value);
+            } else {
+                self.scrub_string(value);
+            }
+        }
+    }
+
+    pub(crate) fn scrub_attributes<K: AsRef<str>>(&self, attributes: &mut Map<K, LogAttribute>) {
+        for (key, LogAttribute(value)) in attributes.iter_mut() {
+            self.scrub_entry(key.as_ref(), value);
+        }
+    }
+
+    pub(crate) fn scrub_request(&self, request: &mut Request) {
+        if let Some(url) = &mut request.url {
+            if let Some(query) = url.query() {
+                if let Some(query) = self.scrub_parameters(query, "&") {
+                    url.set_query(Some(&query));
+                }
+            }
+        }
+        if let Some(query) = &mut request.query_string {
+            if let Some(scrubbed) = self.scrub_parameters(query, "&") {
+                *query = scrubbed;
+            }
+        }
+        // The cookies are the value of the `Cookie` header, so they are scrubbed like it, and
+        // otherwise by the names of the individual cookies.
+        if let Some(cookies) = &mut request.cookies {
+            if self.is_denied("cookie") {
+                FILTERED.clone_into(cookies);
+            } else if let Some(scrubbed) = self.scrub_parameters(cookies, "; ") {
+                *cookies = scrubbed;
+            }
+        }
+        self.scrub_optional_string(&mut request.data);
+        self.scrub_strings(&mut request.headers);
+        self.scrub_strings(&mut request.env);
+    }
+
+    /// Scrubs the `key=value` parameters of a query string or cookies, and returns them if they
+    /// changed.
+    fn scrub_parameters(&self, parameters: &str, separator: &str) -> Option<String> {
+        let mut changed = false;
+        let parameters: Vec<_> = parameters
+            .split(separator)
+            .map(|parameter| match parameter.split_once('=') {
+                Some((key, _)) if self.is_denied(key) => {
+                    changed = true;
+                    format!("{key}={FILTERED}")
+                }
+                _ => {
+                    let mut parameter = parameter.to_owned();
+                    changed |= self.scrub_string(&mut parameter);
+                    parameter
+                }
+            })
+            .collect();
+        changed.then(|| parameters.join(separator))
+    }
+
+    pub(crate) fn scrub_breadcrumb(&self, breadcrumb: &mut Breadcrumb) {
+        self.scrub_optional_string(&mut breadcrumb.message);
+        self.scrub_values(&mut breadcrumb.data);
+    }
+
+    pub(crate) fn scrub_contexts(&self, contexts: &mut Map<String, Context>) {
+        for context in contexts.values_mut() {
+            match context {
+                Context::Trace(trace) => self.scrub_values(&mut trace.data),
+                Context::Other(values) => self.scrub_values(values),
+                _ => {}
+            }
+        }
+    }
+
+    pub(crate) fn scrub_span(&self, span: &mut Span) {
+        self.scrub_values(&mut span.data);
+        self.scrub_strings(&mut span.tags);
+    }
+}
+
+fn normalize_key(key: &str) -> Cow<'_, str> {
+    if key
+        .bytes()
+        .any(|byte| byte.is_ascii_uppercase() || byte == b'-')
+    {
+        Cow::Owned(key.to_ascii_lowercase().replace('-', "_"))
+    } else {
+        Cow::Borrowed(key)
+    }
+}
+
+/// Checks the length and Luhn checksum of a credit card number.
+fn is_valid_credit_card(number: &str) -> bool {
+    let digits: Vec<u32> = number.chars().filter_map(|c| c.to_digit(10)).collect();
+    if !(13..=19).contains(&digits.len()) {
+        return false;
+    }
+    let sum = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .fold(0u32, |sum, (i, &digit)| {
+            let digit = if i % 2 == 1 {
+                let doubled = digit.saturating_mul(2);
+                if doubled > 9 {
+                    doubled.saturating_sub(9)
+                } else {
+                    doubled
+                }
+            } else {
+                digit
+            };
+            sum.saturating_add(digit)
+        });
+    sum % 10 == 0
+}
+
+/// Checks the length and mod-97 checksum of an IBAN.
+fn is_valid_iban(iban: &str) -> bool {
+    let chars: Vec<char> = iban.chars().filter(|c| *c != ' ').collect();
+    if !(15..=34).contains(&chars.len()) {
+        return false;
+    }
+    let (country, account) = chars.split_at(4);
+    let remainder = account
+        .iter()
+        .chain(country)
+        .filter_map(|c| c.to_digit(36))
+        .fold(0u32, |remainder, value| {
+            let shift = if value > 9 { 100 } else { 10 };
+            remainder.saturating_mul(shift).saturating_add(value) % 97
+        });
+    remainder == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scrubber() -> Scrubber {
+        let mut scrubber = Scrubber {
+            denylist: Vec::new(),
+            rules: vec![Rule::credit_card(), Rule::iban()],
+        };
+        DEFAULT_DENYLIST
+            .iter()
+            .for_each(|key| scrubber.deny_key(key));
+        scrubber
+    }
+
+    fn scrubbed(value: &str) -> String {
+        let mut value = value.to_owned();
+        scrubber().scrub_string(&mut value);
+        value
+    }
+
+    #[test]
+    fn test_scrubs_denied_keys() {
+        let scrubber = scrubber();
+        let mut values: Map<String, Value> = [
+            ("Password", "hunter2".into()),
+            ("X-Api-Key", "abc".into()),
+            ("access_token", Value::Null),
+            ("user", serde_json::json!({ "name": "jane", "secret": 42 })),
+            ("count", 3.into()),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_owned(), value))
+        .collect();
+        scrubber.scrub_values(&mut values);
+
+        assert_eq!(
+            Value::Object(values.into_iter().collect()),
+            serde_json::json!({
+                "Password": FILTERED,
+                "X-Api-Key": FILTERED,
+                "access_token": null,
+                "user": { "name": "jane", "secret": FILTERED },
+                "count": 3,
+            })
+        );
+    }
+
+    #[test]
+    fn test_scrubs_credit_cards() {
+        assert_eq!(
+            scrubbed("paid with 4111 1111 1111 1111 today"),
+            format!("paid with {FILTERED} today")
+        );
+        assert_eq!(
+            scrubbed("card 5500-0000-0000-0004"),
+            format!("card {FILTERED}")
+        );
+        // Groups following or preceding the number are not part of it.
+        assert_eq!(
+            scrubbed("card 4111 1111 1111 1111 12/25"),
+            format!("card {FILTERED} 12/25")
+        );
+        assert_eq!(
+            scrubbed("ref 12 4111 1111 1111 1111"),
+            format!("ref 12 {FILTERED}")
+        );
+        // Fails the Luhn check.
+        assert_eq!(scrubbed("order 4111111111111112"), "order 4111111111111112");
+        assert_eq!(scrubbed("timestamp 1700000000"), "timestamp 1700000000");
+    }
+
+    #[test]
+    fn test_scrubs_ibans() {
+        assert_eq!(
+            scrubbed("to DE89 3704 0044 0532 0130 00."),
+            format!("to {FILTERED}.")
+        );
+        assert_eq!(scrubbed("GB82WEST12345698765432"), FILTERED);
+        // Words following the IBAN are not part of it.
+        assert_eq!(
+            scrubbed("DE89 3704 0044 0532 0130 00 BIC COBADEFFXXX"),
+            format!("{FILTERED} BIC COBADEFFXXX")
+        );
+        assert_eq!(
+            scrubbed("GB82WEST12345698765432 NOW"),
+            format!("{FILTERED} NOW")
+        );
+        // Fails the checksum.
+        assert_eq!(scrubbed("GB82WEST12345698765431"), "GB82WEST12345698765431");
+    }
+
+    #[test]
+    fn test_scrubs_custom_rules() {
+        let mut scrubber = scrubber();
+        scrubber
+            .rules
+            .push(Rule::new(Regex::new(r"sk_live_\w+").unwrap()));
+        let mut value = "key sk_live_abc123 used".to_owned();
+        assert!(scrubber.scrub_string(&mut value));
+        assert_eq!(value, format!("key {FILTERED} used"));
+    }
+
+    #[test]
+    fn test_scrubs_requests() {
+        let scrubber = scrubber();
+        let mut request = Request {
+            url: "https://example.com/login?user=jane&token=abc".parse().ok(),
+            query_string: Some("user=jane&password=hunter2".into()),
+            cookies: Some("session=abc".into()),
+            headers: [
+                ("Authorization".to_owned(), "Bearer abc".to_owned()),
+                ("Accept".to_owned(), "text/html".to_owned()),
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+        scrubber.scrub_request(&mut request);
+
+        assert_eq!(
+            request.url.unwrap().as_str(),
+            "https://example.com/login?user=jane&token=[Filtered]"
+        );
+        assert_eq!(
+            request.query_string.as_deref(),
+            Some("user=jane&password=[Filtered]")
+        );
+        assert_eq!(request.cookies.as_deref(), Some(FILTERED));
+        assert_eq!(request.headers["Authorization"], FILTERED);
+        assert_eq!(request.headers["Accept"], "text/html");
+    }
+
+    #[test]
+    fn test_scrubs_cookies_by_name() {
+        let mut scrubber = Scrubber::default();
+        scrubber.deny_key("session");
+        let mut request = Request {
+            cookies: Some("theme=dark; session_id=abc".into()),
+            ..Default::default()
+        };
+        scrubber.scrub_request(&mut request);
+        assert_eq!(
+            request.cookies.as_deref(),
+            Some("theme=dark; session_id=[Filtered]")
+        );
+
+        scrubber.deny_key("cookie");
+        scrubber.scrub_request(&mut request);
+        assert_eq!(request.cookies.as_deref(), Some(FILTERED));
+    }
+}
@@ -0,0 +1,317 @@
+//! The Sentry event deduplication integration.
+//!
+//! The `DedupeIntegration`, which is enabled by default in `sentry`, drops events which are
+//! duplicates of the previously captured event. This happens when the same error propagates
+//! through several layers which all capture it, for example when an error is captured in a
+//! request handler and then again by the middleware reporting the failed request.
+//!
+//! Two events are duplicates if they have the same exceptions, compared by their type, value and
+//! stack frames, and the same fingerprint. Only events captured within a short window, one second
+//! by default, of the previous event are dropped, so an error which keeps recurring is still
+//! reported. Dropped events are recorded in client reports.
+//!
+//! # Configuration
+//!
+//! Events without exceptions, such as messages, can be compared by their message and stack trace
+//! as well, and the window can be changed. A configured integration replaces the default one:
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! let integration = sentry_dedupe::DedupeIntegration::new()
+//!     .compare_messages(true)
+//!     .window(Duration::from_millis(100));
+//! let options = sentry::ClientOptions::new().add_integration(integration);
+//! ```
+//!
+//! Use `ClientOptions::default_integrations(false)` to disable deduplication altogether.
+
+#![doc(html_favicon_url = "https://sentry-brand.storage.googleapis.com/favicon.ico")]
+#![doc(html_logo_url = "https://sentry-brand.storage.googleapis.com/sentry-glyph-black.png")]
+#![warn(missing_docs)]
+#![deny(unsafe_code)]
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use sentry_core::protocol::{Event, Frame, Stacktrace};
+use sentry_core::{ClientOptions, Integration};
+
+/// The default window in which duplicates of an event are dropped.
+const DEFAULT_WINDOW: Duration = Duration::from_secs(1);
+
+/// Integration that drops consecutive duplicate events.
+///
+/// See the [crate level documentation](crate) for details.
+#[derive(Debug)]
+pub struct DedupeIntegration {
+    compare_messages: bool,
+    window: Duration,
+    last_event: Mutex<Option<(EventSignature, Instant)>>,
+}
+
+impl Default for DedupeIntegration {
+    fn default() -> Self {
+        Self {
+            compare_messages: false,
+            window: DEFAULT_WINDOW,
+            last_event: Mutex::new(None),
+        }
+    }
+}
+
+impl DedupeIntegration {
+    /// Creates a new integration which deduplicates errors.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether events without exceptions are compared by their message.
+    ///
+    /// When disabled, only events with exceptions are deduplicated. Defaults to `false`.
+    #[must_use]
+    pub fn compare_messages(mut self, compare_messages: bool) -> Self {
+        self.compare_messages = compare_messages;
+        self
+    }
+
+    /// Sets the window after the previous event in which a duplicate of it is dropped.
+    ///
+    /// Defaults to one second.
+    #[must_use]
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+}
+
+impl Integration for DedupeIntegration {
+    fn name(&self) -> &'static str {
+        "dedupe"
+    }
+
+    fn process_event(
+        &self,
+        event: Event<'static>,
+        _options: &ClientOptions,
+    ) -> Option<Event<'static>> {
+        let Some(signature) = EventSignature::new(&event, self.compare_messages) else {
+            return Some(event);
+        };
+
+        let now = Instant::now();
+        let mut last_event = self.last_event.lock().unwrap();
+        if let Some((last_signature, captured_at)) = last_event.as_ref() {
+            if *last_signature == signature && now.duration_since(*captured_at) < self.window {
+                sentry_core::sentry_debug!("dropping duplicate event {}", event.event_id);
+                return None;
+            }
+        }
+        *last_event = Some((signature, now));
+        Some(event)
+    }
+}
+
+/// The parts of an event that are compared to find duplicates.
+#[derive(Debug, PartialEq)]
+struct EventSignature {
+    exceptions: Vec<ExceptionSignature>,
+    message: Option<String>,
+    frames: Vec<FrameSignature>,
+    fingerprint: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+struct ExceptionSignature {
+    ty: String,
+    value: Option<String>,
+    frames: Vec<FrameSignature>,
+}
+
+#[derive(Debug, PartialEq)]
+struct FrameSignature {
+    function: Option<String>,
+    module: Option<String>,
+    filename: Option<String>,
+    lineno: Option<u64>,
+    colno: Option<u64>,
+}
+
+impl EventSignature {
+    /// Returns the signature of `event`, or `None` if the event is never considered a duplicate.
+    fn new(event: &Event<'_>, compare_messages: bool) -> Option<Self> {
+        let exceptions: Vec<_> = event
+            .exception
+            .values
+            .iter()
+            .map(|exception| ExceptionSignature {
+                ty: exception.ty.clone(),
+                value: exception.value.clone(),
+                frames: frames(exception.stacktrace.as_ref()),
+            })
+            .collect();
+
+        let message = event
+            .message
+            .clone()
+            .or_else(|| event.logentry.as_ref().map(|entry| entry.message.clone()));
+        if exceptions.is_empty() && (!compare_messages || message.is_none()) {
+            return None;
+        }
+
+        Some(Self {
+            exceptions,
+            message,
+            frames: frames(event.stacktrace.as_ref()),
+            fingerprint: event
+                .fingerprint
+                .iter()
+                .map(|part| part.to_string())
+                .collect(),
+        })
+    }
+}
+
+fn frames(stacktrace: Option<&Stacktrace>) -> Vec<FrameSignature> {
+    stacktrace
+        .map(|stacktrace| stacktrace.frames.iter().map(FrameSignature::from).collect())
+        .unwrap_or_default()
+}
+
+impl From<&Frame> for FrameSignature {
+    fn from(frame: &Frame) -> Self {
+        Self {
+            function: frame.function.clone(),
+            module: frame.module.clone(),
+            filename: frame.filename.clone(),
+            lineno: frame.lineno,
+            colno: frame.colno,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use sentry::protocol::{Exception, Level};
+
+    use super::*;
+
+    fn error_event(ty: &str, value: &str) -> Event<'static> {
+        Event {
+            exception: vec![Exception {
+                ty: ty.into(),
+                value: Some(value.into()),
+                ..Default::default()
+            }]
+            .into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn drops_consecutive_duplicate_errors() {
+        let events = sentry::test::with_captured_events_options(
+            || {
+                sentry::capture_event(error_event("Error", "database is down"));
+                sentry::capture_event(error_event("Error", "database is down"));
+                sentry::capture_event(error_event("Error", "cache is down"));
+                sentry::capture_event(error_event("Error", "database is down"));
+            },
+            sentry::ClientOptions::new().add_integration(DedupeIntegration::new()),
+        );
+
+        let values: Vec<_> = events
+            .iter()
+            .map(|event| event.exception[0].value.as_deref().unwrap())
+            .collect();
+        assert_eq!(
+            values,
+            ["database is down", "cache is down", "database is down"]
+        );
+    }
+
+    #[test]
+    fn compares_stack_frames_and_fingerprints() {
+        let with_frame = |lineno| {
+            let mut event = error_event("Error", "failed");
+            event.exception[0].stacktrace = Some(Stacktrace {
+                frames: vec![Frame {
+                    function: Some("main".into()),
+                    lineno: Some(lineno),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            });
+            event
+        };
+        let with_fingerprint = |fingerprint: &'static str| Event {
+            fingerprint: vec![fingerprint.into()].into(),
+            ..with_frame(1)
+        };
+
+        let events = sentry::test::with_captured_events_options(
+            || {
+                sentry::capture_event(with_frame(1));
+                sentry::capture_event(with_frame(2));
+                sentry::capture_event(with_fingerprint("custom"));
+                sentry::capture_event(with_fingerprint("custom"));
+            },
+            sentry::ClientOptions::new().add_integration(DedupeIntegration::new()),
+        );
+
+        assert_eq!(events.len(), 3);
+    }
+
+    #[test]
+    fn compares_messages_unless_disabled() {
+        let capture_messages = || {
+            sentry::capture_message("retrying", Level::Warning);
+            sentry::capture_message("retrying", Level::Warning);
+        };
+
+        let events = sentry::test::with_captured_events_options(
+            capture_messages,
+            sentry::ClientOptions::new().add_integration(DedupeIntegration::new()),
+        );
+        assert_eq!(events.len(), 2);
+
+        let events = sentry::test::with_captured_events_options(
+            capture_messages,
+            sentry::ClientOptions::new()
+                .add_integration(DedupeIntegration::new().compare_messages(true)),
+        );
+        assert_eq!(events.len(), 1);
+    }
+
+    #[test]
+    fn keeps_duplicates_outside_the_window() {
+        let events = sentry::test::with_captured_events_options(
+            || {
+                sentry::capture_event(error_event("Error", "database is down"));
+                sentry::capture_event(error_event("Error", "database is down"));
+                thread::sleep(Duration::from_millis(100));
+                sentry::capture_event(error_event("Error", "database is down"));
+                sentry::capture_event(error_event("Error", "cache is down"));
+                sentry::capture_event(error_event("Error", "database is down"));
+            },
+            sentry::ClientOptions::new()
+                .add_integration(DedupeIntegration::new().window(Duration::from_millis(50))),
+        );
+
+        let values: Vec<_> = events
+            .iter()
+            .map(|event| event.exception[0].value.as_deref().unwrap())
+            .collect();
+        assert_eq!(
+            values,
+            [
+                "database is down",
+                "database is down",
+                "cache is down",
+                "database is down"
+            ]
+        );
+    }
+}
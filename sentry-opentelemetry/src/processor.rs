@@ -32,6 +32,24 @@ type SpanMap = Arc<Mutex<HashMap<sentry_core::protocol::SpanId, TransactionOrSpa
 
 static SPAN_MAP: LazyLock<SpanMap> = LazyLock::new(|| Arc::new(Mutex::new(HashMap::new())));
 
+/// A mapping from Sentry span IDs to the URLs of the requests described by the corresponding OTEL
+/// spans, taken from their `url.full` or `http.url` attribute.
+/// This is used by [`crate::SentryPropagator`] to apply the `trace_propagation_targets` option.
+static SPAN_URLS: LazyLock<Mutex<HashMap<sentry_core::protocol::SpanId, String>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// The OTEL span attributes containing the URL of an outgoing request, in order of precedence.
+const URL_ATTRIBUTES: [&str; 2] = ["url.full", "http.url"];
+
+/// Returns the URL of the request described by the OTEL span with the given ID, if it is known.
+pub(crate) fn span_url(span_id: &SpanId) -> Option<String> {
+    SPAN_URLS
+        .lock()
+        .unwrap()
+        .get(&convert_span_id(span_id))
+        .cloned()
+}
+
 /// An OpenTelemetry SpanProcessor that converts OTEL spans to Sentry spans/transactions and sends
 /// them to Sentry.
 #[derive(Debug, Clone)]
@@ -105,6 +123,7 @@ impl SpanProcessor for SentrySpanProcessor {
         let mut span_op = String::new();
         let mut span_start_timestamp = SystemTime::now();
         let mut parent_sentry_span = None;
+        let mut url = None;
         if let Some(data) = span.exported_data() {
             span_description = data.name.to_string();
             span_op = span_description.clone(); // TODO: infer this from OTEL span attributes
@@ -112,6 +131,12 @@ impl SpanProcessor for SentrySpanProcessor {
             if data.parent_span_id != SpanId::INVALID {
                 parent_sentry_span = span_map.get(&convert_span_id(&data.parent_span_id));
             };
+            url = URL_ATTRIBUTES.iter().find_map(|key| {
+                data.attributes
+                    .iter()
+                    .find(|attribute| attribute.key.as_str() == *key)
+                    .map(|attribute| attribute.value.as_str().into_owned())
+            });
         }
         let span_description = span_description.as_str();
         let span_op = span_op.as_str();
@@ -152,6 +177,12 @@ impl SpanProcessor for SentrySpanProcessor {
             }
         };
         span_map.insert(convert_span_id(&span_id), sentry_span);
+        if let Some(url) = url {
+            SPAN_URLS
+                .lock()
+                .unwrap()
+                .insert(convert_span_id(&span_id), url);
+        }
     }
 
     fn on_end(&self, data: SpanData) {
@@ -159,6 +190,7 @@ impl SpanProcessor for SentrySpanProcessor {
 
         let mut span_map = SPAN_MAP.lock().unwrap();
 
+        SPAN_URLS.lock().unwrap().remove(&convert_span_id(&span_id));
         let Some(sentry_span) = span_map.remove(&convert_span_id(&span_id)) else {
             return;
         };
@@ -7,6 +7,10 @@
 //! - injects Sentry tracing metadata in outgoing traces. This information can be used by
 //!   downstream Sentry SDKs to achieve distributed tracing.
 //!
+//! If the span being injected has a `url.full` or `http.url` attribute when it is started, the
+//! metadata is only injected if that URL matches the
+//! [`trace_propagation_targets`](sentry_core::ClientOptions::trace_propagation_targets) option.
+//!
 //! # Configuration
 //!
 //! This should be used together with [`crate::processor::SentrySpanProcessor`]. An example of
@@ -22,6 +26,7 @@ use opentelemetry::{
 use sentry_core::TracePropagationContext;
 
 use crate::converters::{convert_span_id, convert_trace_id};
+use crate::processor::span_url;
 
 const SENTRY_TRACE_KEY: &str = "sentry-trace";
 
@@ -59,7 +64,13 @@ impl TextMapPropagator for SentryPropagator {
         let trace_context =
             TracePropagationContext::new(convert_trace_id(&trace_id), convert_span_id(&span_id))
                 .with_sampled(sampled);
-        injector.set(SENTRY_TRACE_KEY, trace_context.sentry_trace_header());
+        let sentry_trace = match span_url(&span_id) {
+            Some(url) => trace_context.sentry_trace_header_for(&url),
+            None => Some(trace_context.sentry_trace_header()),
+        };
+        if let Some(sentry_trace) = sentry_trace {
+            injector.set(SENTRY_TRACE_KEY, sentry_trace);
+        }
     }
 
     fn extract_with_context(&self, ctx: &Context, extractor: &dyn Extractor) -> Context {
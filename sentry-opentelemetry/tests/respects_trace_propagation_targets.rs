@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use opentelemetry::{
+    propagation::TextMapPropagator,
+    trace::{TraceContextExt, Tracer, TracerProvider},
+    Context, KeyValue,
+};
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use sentry::{ClientOptions, Hub};
+use sentry_core::test::TestTransport;
+use sentry_opentelemetry::{SentryPropagator, SentrySpanProcessor};
+
+#[test]
+fn test_respects_trace_propagation_targets() {
+    let options = ClientOptions::new()
+        .dsn("https://test@sentry-opentelemetry.com/test")
+        .transport(TestTransport::new())
+        .traces_sample_rate(1.0)
+        .trace_propagation_targets(["^https://api\\.internal/"]);
+    Hub::current().bind_client(Some(Arc::new(options.into())));
+
+    let tracer_provider = SdkTracerProvider::builder()
+        .with_span_processor(SentrySpanProcessor::new())
+        .build();
+    let tracer = tracer_provider.tracer("test".to_string());
+    let propagator = SentryPropagator::new();
+
+    let inject = |url: Option<&str>| {
+        let mut builder = tracer.span_builder("outgoing request");
+        if let Some(url) = url {
+            builder = builder.with_attributes([KeyValue::new("url.full", url.to_owned())]);
+        }
+        let span = tracer.build(builder);
+        let ctx = Context::current_with_span(span);
+        let mut headers = HashMap::new();
+        propagator.inject_context(&ctx, &mut TestInjector(&mut headers));
+        headers.remove("sentry-trace")
+    };
+
+    assert!(inject(Some("https://api.internal/users")).is_some());
+    assert!(inject(Some("https://third-party.com/users")).is_none());
+    // The URL is unknown, so the headers are always injected.
+    assert!(inject(None).is_some());
+}
+
+struct TestInjector<'a>(&'a mut HashMap<String, String>);
+
+impl opentelemetry::propagation::Injector for TestInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        self.0.insert(key.to_string(), value);
+    }
+}
@@ -13,6 +13,13 @@
 //! ```
 //! let integration = sentry_panic::PanicIntegration::default().add_extractor(|info| None);
 //! ```
+//!
+//! On Linux, it can also attach the stacks of all other threads of the process
+//! to panic events, which helps with investigating deadlocks:
+//!
+//! ```
+//! let integration = sentry_panic::PanicIntegration::default().attach_all_threads(true);
+//! ```
 
 #![doc(html_favicon_url = "https://sentry-brand.storage.googleapis.com/favicon.ico")]
 #![doc(html_logo_url = "https://sentry-brand.storage.googleapis.com/sentry-glyph-black.png")]
@@ -22,7 +29,7 @@
 use std::panic::{self, PanicHookInfo};
 use std::sync::Once;
 
-use sentry_backtrace::current_stacktrace;
+use sentry_backtrace::{all_threads, current_stacktrace};
 use sentry_core::protocol::{Event, Exception, Level, Mechanism};
 use sentry_core::{ClientOptions, Integration};
 
@@ -46,12 +53,14 @@ type PanicExtractor = dyn Fn(&PanicHookInfo<'_>) -> Option<Event<'static>> + Sen
 #[derive(Default)]
 pub struct PanicIntegration {
     extractors: Vec<Box<PanicExtractor>>,
+    attach_all_threads: bool,
 }
 
 impl std::fmt::Debug for PanicIntegration {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("PanicIntegration")
             .field("extractors", &self.extractors.len())
+            .field("attach_all_threads", &self.attach_all_threads)
             .finish()
     }
 }
@@ -101,10 +110,42 @@ impl PanicIntegration {
         self
     }
 
+    /// Attaches the stacks of all threads of the process to panic events.
+    ///
+    /// The threads are added to the event's `threads`, with the panicking thread marked as
+    /// crashed, and the exceptions refer to it by its ID. The stacks of the other threads are
+    /// captured by interrupting them with a `SIGPROF` signal, see
+    /// [`sentry_backtrace::all_threads`] for details and caveats. This is only supported on
+    /// Linux, other platforms will only report the panicking thread.
+    ///
+    /// Capturing the stacks runs in the panic hook and delays the panic by up to half a second,
+    /// as threads which do not handle the signal in time are waited for, plus the time needed to
+    /// symbolicate their stacks. It installs a
+    /// process-wide `SIGPROF` handler, which does not work together with `SIGPROF` handlers
+    /// installed later on, and the stacks are only complete if the application is built with
+    /// frame pointers. This is therefore disabled by default.
+    ///
+    /// Defaults to `false`.
+    #[must_use]
+    pub fn attach_all_threads(self, attach_all_threads: bool) -> Self {
+        Self {
+            attach_all_threads,
+            ..self
+        }
+    }
+
     /// Creates an event from the given panic info.
     ///
     /// The stacktrace is calculated from the current frame.
     pub fn event_from_panic_info(&self, info: &PanicHookInfo<'_>) -> Event<'static> {
+        let mut event = self.create_event(info);
+        if self.attach_all_threads && event.threads.is_empty() {
+            attach_threads(&mut event);
+        }
+        event
+    }
+
+    fn create_event(&self, info: &PanicHookInfo<'_>) -> Event<'static> {
         for extractor in &self.extractors {
             if let Some(event) = extractor(info) {
                 return event;
@@ -134,3 +175,17 @@ impl PanicIntegration {
         }
     }
 }
+
+/// Adds all threads of the process to the event, marking the current one as crashed.
+fn attach_threads(event: &mut Event<'static>) {
+    let mut threads = all_threads(false);
+    for thread in &mut threads {
+        if thread.current {
+            thread.crashed = true;
+            for exception in event.exception.iter_mut() {
+                exception.thread_id = thread.id.clone();
+            }
+        }
+    }
+    event.threads = threads.into();
+}
@@ -0,0 +1,97 @@
+//! The Sentry reqwest integration.
+//!
+//! This integration instruments outgoing requests of [`reqwest`] clients, using the
+//! [`reqwest_middleware`] crate. For each request, the [`SentryMiddleware`]:
+//!
+//! - starts an `http.client` span as a child of the span active on the current scope, recording
+//!   the method, URL, status code and content lengths of the request,
+//! - adds an `http` breadcrumb once the response is received, and
+//! - attaches the `sentry-trace` header to requests whose URL matches the
+//!   [`trace_propagation_targets`](sentry_core::ClientOptions::trace_propagation_targets) client
+//!   option, so that the receiving service continues the trace.
+//!
+//! The W3C `traceparent` and `tracestate` headers are attached as well if the
+//! [`propagate_traceparent`](sentry_core::ClientOptions::propagate_traceparent) option is enabled.
+//! The `baggage` header with the dynamic sampling context is not attached yet.
+//!
+//! The URL is recorded without its query string and fragment, which are recorded separately in
+//! the `http.query` and `http.fragment` span data.
+//!
+//! # Example
+//!
+//! ```
+//! use reqwest_middleware::ClientBuilder;
+//! use sentry_reqwest::SentryMiddleware;
+//!
+//! let client = ClientBuilder::new(reqwest::Client::new())
+//!     .with(SentryMiddleware::new())
+//!     .build();
+//!
+//! # async fn run(client: reqwest_middleware::ClientWithMiddleware) {
+//! let response = client.get("https://example.com").send().await;
+//! # }
+//! ```
+
+#![doc(html_favicon_url = "https://sentry-brand.storage.googleapis.com/favicon.ico")]
+#![doc(html_logo_url = "https://sentry-brand.storage.googleapis.com/sentry-glyph-black.png")]
+#![warn(missing_docs)]
+
+use http::{Extensions, HeaderName, HeaderValue};
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next, Result};
+use sentry_core::utils::OutgoingHttpRequest;
+use sentry_core::Hub;
+
+/// Middleware that instruments outgoing requests of a [`reqwest`] client.
+///
+/// See the [crate level documentation](crate) for details.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SentryMiddleware;
+
+impl SentryMiddleware {
+    /// Creates a new middleware that instruments outgoing requests.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for SentryMiddleware {
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        let hub = Hub::current();
+        let content_length = req
+            .body()
+            .and_then(|body| body.as_bytes())
+            .and_then(|bytes| u64::try_from(bytes.len()).ok());
+        let request = OutgoingHttpRequest::start(
+            &hub,
+            req.method().as_str(),
+            req.url().as_str(),
+            content_length,
+        );
+        for (name, value) in request.trace_headers(&hub) {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(&value),
+            ) {
+                req.headers_mut().insert(name, value);
+            }
+        }
+
+        let res = next.run(req, extensions).await;
+        match &res {
+            Ok(response) => request.finish(
+                &hub,
+                Some(response.status().as_u16()),
+                response.content_length(),
+            ),
+            Err(_) => request.finish(&hub, None, None),
+        }
+        res
+    }
+}
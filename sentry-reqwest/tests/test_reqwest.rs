@@ -0,0 +1,86 @@
+use reqwest_middleware::ClientBuilder;
+use sentry_core::protocol::{EnvelopeItem, Level, SpanStatus};
+use sentry_core::{ClientOptions, Envelope, TransactionContext};
+use sentry_reqwest::SentryMiddleware;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Sends a request to a local server within a transaction, returning the captured envelopes and
+/// the request received by the server.
+fn send_request(path: &str, options: ClientOptions) -> (Vec<Envelope>, String) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let listener = runtime.block_on(TcpListener::bind("127.0.0.1:0")).unwrap();
+    let url = format!("http://{}{path}", listener.local_addr().unwrap());
+
+    let server = runtime.spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = vec![0; 4096];
+        let len = stream.read(&mut request).await.unwrap();
+        stream
+            .write_all(b"HTTP/1.1 503 Service Unavailable\r\ncontent-length: 5\r\n\r\nretry")
+            .await
+            .unwrap();
+        String::from_utf8_lossy(&request[..len]).into_owned()
+    });
+
+    let envelopes = sentry_core::test::with_captured_envelopes_options(
+        || {
+            let transaction =
+                sentry_core::start_transaction(TransactionContext::new("outgoing", "test"));
+            sentry_core::configure_scope(|scope| scope.set_span(Some(transaction.clone().into())));
+
+            let client = ClientBuilder::new(reqwest::Client::new())
+                .with(SentryMiddleware::new())
+                .build();
+            runtime.block_on(client.get(&url).send()).unwrap();
+
+            sentry_core::capture_message("after request", Level::Info);
+            transaction.finish();
+        },
+        options.traces_sample_rate(1.0),
+    );
+    let request = runtime.block_on(server).unwrap();
+    (envelopes, request)
+}
+
+#[test]
+fn test_records_span_and_breadcrumb() {
+    let (envelopes, request) = send_request("/users?id=1", ClientOptions::default());
+
+    let event = envelopes[0].event().unwrap();
+    let breadcrumb = &event.breadcrumbs[0];
+    assert_eq!(breadcrumb.ty, "http");
+    assert_eq!(breadcrumb.level, Level::Error);
+    assert_eq!(breadcrumb.data["method"], "GET");
+    assert_eq!(breadcrumb.data["status_code"], 503);
+
+    let Some(EnvelopeItem::Transaction(transaction)) = envelopes[1].items().next() else {
+        panic!("expected a transaction");
+    };
+    let span = &transaction.spans[0];
+    assert_eq!(span.op.as_deref(), Some("http.client"));
+    assert_eq!(span.status, Some(SpanStatus::Unavailable));
+    assert_eq!(span.data["http.request.method"], "GET");
+    assert!(span.data["url"].as_str().unwrap().ends_with("/users"));
+    assert_eq!(span.data["http.query"], "id=1");
+    assert_eq!(span.data["http.response.status_code"], 503);
+    assert_eq!(span.data["http.response_content_length"], 5);
+
+    let sentry_trace = format!("sentry-trace: {}-{}-1", span.trace_id, span.span_id);
+    assert!(
+        request.contains(&sentry_trace),
+        "request is missing {sentry_trace}: {request}"
+    );
+}
+
+#[test]
+fn test_respects_trace_propagation_targets() {
+    let (_, request) = send_request(
+        "/",
+        ClientOptions::new().trace_propagation_targets(["https://api.internal"]),
+    );
+    assert!(!request.contains("sentry-trace"), "{request}");
+}
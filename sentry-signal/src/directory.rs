@@ -0,0 +1,111 @@
+//! The directory crash reports are stored in.
+//!
+//! Crash reports are sent with the client's DSN, so the directory must not be writable by other
+//! users, who could otherwise plant reports or replace them with links to other files.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Returns the default directory for crash reports of the current executable.
+///
+/// This is a directory named after the executable in a `sentry-signal` directory in the user's
+/// cache directory, or in the system's temporary directory if there is none.
+pub(crate) fn default_directory() -> PathBuf {
+    let executable = env::current_exe()
+        .ok()
+        .and_then(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "unknown".to_owned());
+    match cache_directory() {
+        Some(cache) => cache.join("sentry-signal").join(executable),
+        None => env::temp_dir().join(format!("sentry-signal-{}-{executable}", user_id())),
+    }
+}
+
+/// Returns the user's cache directory, following the XDG base directory specification.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn cache_directory() -> Option<PathBuf> {
+    absolute_var("XDG_CACHE_HOME").or_else(|| Some(absolute_var("HOME")?.join(".cache")))
+}
+
+#[cfg(target_os = "macos")]
+fn cache_directory() -> Option<PathBuf> {
+    Some(absolute_var("HOME")?.join("Library").join("Caches"))
+}
+
+#[cfg(windows)]
+fn cache_directory() -> Option<PathBuf> {
+    absolute_var("LOCALAPPDATA")
+}
+
+#[cfg(not(any(unix, windows)))]
+fn cache_directory() -> Option<PathBuf> {
+    None
+}
+
+/// Returns the path in the environment variable `name`, if it is set to an absolute path.
+#[cfg_attr(not(any(unix, windows)), expect(dead_code))]
+fn absolute_var(name: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(env::var_os(name)?);
+    path.is_absolute().then_some(path)
+}
+
+#[cfg(unix)]
+fn user_id() -> libc::uid_t {
+    unsafe { libc::geteuid() }
+}
+
+#[cfg(not(unix))]
+fn user_id() -> &'static str {
+    "user"
+}
+
+/// Creates `directory` if needed, and checks that it can be used for crash reports.
+///
+/// On Unix, the directory is created with permissions for the current user only. An existing
+/// directory must be owned by the current user, and is restricted to it if it is accessible by
+/// other users.
+#[cfg(unix)]
+pub(crate) fn prepare(directory: &Path) -> io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+
+    fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(directory)?;
+    let metadata = fs::symlink_metadata(directory)?;
+    if !metadata.is_dir() {
+        return Err(io::Error::other("not a directory"));
+    }
+    if metadata.uid() != user_id() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "owned by another user",
+        ));
+    }
+    if metadata.mode() & 0o077 != 0 {
+        fs::set_permissions(directory, fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn prepare(directory: &Path) -> io::Result<()> {
+    fs::create_dir_all(directory)
+}
+
+/// Returns whether `metadata` describes a crash report written by the current user.
+///
+/// Links are never followed, and on Unix, reports must be owned by the current user and not be
+/// accessible by other users, as the signal handlers create them.
+pub(crate) fn is_own_file(metadata: &fs::Metadata) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        metadata.is_file() && metadata.uid() == user_id() && metadata.mode() & 0o077 == 0
+    }
+    #[cfg(not(unix))]
+    metadata.is_file()
+}
@@ -0,0 +1,395 @@
+//! The signal handlers writing crash reports.
+//!
+//! Everything the handlers need is prepared when the integration is set up, and published through
+//! a global pointer. The handlers then only use async-signal-safe functions, and keep the captured
+//! stack in a static buffer, so that they do not allocate and use little stack space.
+//!
+//! The stack is captured by walking the frame pointers of the crashed thread, starting from the
+//! registers saved in the signal context. Since the stack may be corrupted, frame records are only
+//! read where this cannot fault.
+
+use std::ffi::CString;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+use sentry_core::types::random_uuid;
+use sentry_core::{sentry_debug, ClientOptions};
+
+use crate::report::{self, EXTENSION, TEMP_EXTENSION};
+
+/// The handled signals, along with their names and descriptions.
+const SIGNALS: [(libc::c_int, &str, &str); 5] = [
+    (libc::SIGSEGV, "SIGSEGV", "Segmentation fault"),
+    (libc::SIGBUS, "SIGBUS", "Bus error"),
+    (libc::SIGILL, "SIGILL", "Illegal instruction"),
+    (libc::SIGFPE, "SIGFPE", "Floating point exception"),
+    (libc::SIGABRT, "SIGABRT", "Abort"),
+];
+
+/// The maximum number of frames captured.
+const MAX_FRAMES: usize = 256;
+
+/// A crash report prepared for the current process.
+struct Report {
+    temp_path: CString,
+    path: CString,
+    head: Vec<u8>,
+}
+
+/// The report written by the handlers, or null if none was prepared yet.
+///
+/// Reports are never freed once published, since a handler may be using them.
+static REPORT: AtomicPtr<Report> = AtomicPtr::new(ptr::null_mut());
+
+/// Whether a handler already started writing a report, so that only the first crash is reported.
+static CRASHED: AtomicBool = AtomicBool::new(false);
+
+/// The signal actions which were installed before ours, in the order of [`SIGNALS`].
+static PREVIOUS: OnceLock<[libc::sigaction; SIGNALS.len()]> = OnceLock::new();
+
+static FRAMES: [AtomicUsize; MAX_FRAMES] = [const { AtomicUsize::new(0) }; MAX_FRAMES];
+
+/// Prepares a crash report in `directory` and installs the signal handlers if needed.
+pub(crate) fn install(directory: &Path, options: &ClientOptions) -> io::Result<()> {
+    let event_id = random_uuid();
+    let path = |extension| {
+        let path = directory.join(format!("{event_id}.{extension}"));
+        CString::new(path.as_os_str().as_bytes()).map_err(io::Error::other)
+    };
+    let report = Report {
+        temp_path: path(TEMP_EXTENSION)?,
+        path: path(EXTENSION)?,
+        head: report::envelope_head(event_id, options),
+    };
+    REPORT.store(Box::into_raw(Box::new(report)), Ordering::Release);
+
+    PREVIOUS.get_or_init(|| {
+        let previous = SIGNALS.map(|(signal, _, _)| unsafe {
+            let mut previous: libc::sigaction = std::mem::zeroed();
+            libc::sigaction(signal, ptr::null(), &mut previous);
+            previous
+        });
+        for (signal, name, _) in SIGNALS {
+            if !install_handler(signal) {
+                sentry_debug!("Failed to install the {} handler", name);
+            }
+        }
+        previous
+    });
+    Ok(())
+}
+
+fn install_handler(signal: libc::c_int) -> bool {
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handle_signal as HandlerFn as usize;
+        // Use the alternate signal stack if there is one, to be able to report stack overflows.
+        action.sa_flags = libc::SA_SIGINFO | libc::SA_ONSTACK;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(signal, &action, ptr::null_mut()) == 0
+    }
+}
+
+type HandlerFn = extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void);
+
+extern "C" fn handle_signal(
+    signal: libc::c_int,
+    info: *mut libc::siginfo_t,
+    context: *mut libc::c_void,
+) {
+    let errno = unsafe { *errno_location() };
+    let Some(index) = SIGNALS.iter().position(|(number, _, _)| *number == signal) else {
+        return;
+    };
+
+    if !CRASHED.swap(true, Ordering::SeqCst) {
+        if let Some(report) = unsafe { REPORT.load(Ordering::Acquire).as_ref() } {
+            write_report(report, index, context);
+        }
+    }
+
+    // Hand the signal over to the previous action. Signals caused by a fault are raised again
+    // when the faulting instruction is executed again after returning, while signals sent by
+    // `kill` or `abort` are delivered to the previous handler directly, and then raised again
+    // with the default action in case that handler returns.
+    let Some(previous) = PREVIOUS.get().map(|previous| &previous[index]) else {
+        return;
+    };
+    unsafe {
+        libc::sigaction(signal, previous, ptr::null_mut());
+        let sent = info.as_ref().is_none_or(|info| info.si_code <= 0);
+        if sent {
+            let handler = previous.sa_sigaction;
+            if handler != libc::SIG_DFL && handler != libc::SIG_IGN {
+                if previous.sa_flags & libc::SA_SIGINFO != 0 {
+                    let handler: HandlerFn = std::mem::transmute(handler);
+                    handler(signal, info, context);
+                } else {
+                    let handler: extern "C" fn(libc::c_int) = std::mem::transmute(handler);
+                    handler(signal);
+                }
+            }
+            libc::signal(signal, libc::SIG_DFL);
+            libc::raise(signal);
+        }
+        *errno_location() = errno;
+    }
+}
+
+/// Writes the crash report for the signal at `index` of [`SIGNALS`], interrupting `context`.
+fn write_report(report: &Report, index: usize, context: *mut libc::c_void) {
+    let (number, name, description) = SIGNALS[index];
+    let frames = unsafe { capture_frames(context) };
+
+    let fd = unsafe {
+        libc::open(
+            report.temp_path.as_ptr(),
+            libc::O_WRONLY | libc::O_CREAT | libc::O_EXCL | libc::O_CLOEXEC,
+            0o600,
+        )
+    };
+    if fd < 0 {
+        return;
+    }
+    let mut out = Writer { fd, failed: false };
+
+    out.write(&report.head);
+    out.write(b",\"timestamp\":");
+    out.write_timestamp();
+    out.write(b",\"exception\":{\"values\":[{\"type\":\"");
+    out.write(name.as_bytes());
+    out.write(b"\",\"value\":\"");
+    out.write(description.as_bytes());
+    out.write(b"\",\"mechanism\":{\"type\":\"signalhandler\",\"handled\":false,");
+    out.write(b"\"synthetic\":true,\"meta\":{\"signal\":{\"number\":");
+    out.write_decimal(number.unsigned_abs().into());
+    out.write(b",\"name\":\"");
+    out.write(name.as_bytes());
+    out.write(b"\"}}},\"stacktrace\":{\"frames\":[");
+    // Sentry expects the outermost frame first.
+    for (i, frame) in frames.iter().rev().enumerate() {
+        if i > 0 {
+            out.write(b",");
+        }
+        out.write(b"{\"instruction_addr\":\"0x");
+        out.write_hex(frame.load(Ordering::Relaxed));
+        out.write(b"\"}");
+    }
+    out.write(b"]}}]}}\n");
+
+    let failed = out.failed;
+    unsafe {
+        libc::close(fd);
+        if failed {
+            libc::unlink(report.temp_path.as_ptr());
+        } else {
+            libc::rename(report.temp_path.as_ptr(), report.path.as_ptr());
+        }
+    }
+}
+
+/// Captures the instruction addresses of the thread interrupted at `context` into [`FRAMES`],
+/// innermost first.
+///
+/// The walk stops at the first frame record which is misaligned, unreadable or not further up the
+/// stack, so frames of code built without frame pointers may be missing.
+unsafe fn capture_frames(context: *mut libc::c_void) -> &'static [AtomicUsize] {
+    let Some(Registers { pc, fp, sp }) = registers(context) else {
+        return &[];
+    };
+    FRAMES[0].store(pc, Ordering::Relaxed);
+    let mut len = 1;
+    let mut fp = fp;
+    while len < MAX_FRAMES {
+        if fp < sp || !fp.is_multiple_of(std::mem::align_of::<usize>()) {
+            break;
+        }
+        // A frame record consists of the caller's frame pointer and the return address.
+        let Some([next_fp, return_address]) = read_frame_record(fp, sp) else {
+            break;
+        };
+        if return_address == 0 {
+            break;
+        }
+        FRAMES[len].store(return_address, Ordering::Relaxed);
+        len = len.saturating_add(1);
+        if next_fp <= fp {
+            break;
+        }
+        fp = next_fp;
+    }
+    &FRAMES[..len]
+}
+
+/// The registers of the crashed thread needed to walk its stack.
+struct Registers {
+    pc: usize,
+    fp: usize,
+    sp: usize,
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+unsafe fn registers(context: *mut libc::c_void) -> Option<Registers> {
+    let context = context.cast::<libc::ucontext_t>().as_ref()?;
+    let gregs = &context.uc_mcontext.gregs;
+    Some(Registers {
+        pc: gregs[libc::REG_RIP as usize] as usize,
+        fp: gregs[libc::REG_RBP as usize] as usize,
+        sp: gregs[libc::REG_RSP as usize] as usize,
+    })
+}
+
+#[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+unsafe fn registers(context: *mut libc::c_void) -> Option<Registers> {
+    let context = context.cast::<libc::ucontext_t>().as_ref()?;
+    let mcontext = &context.uc_mcontext;
+    Some(Registers {
+        pc: mcontext.pc as usize,
+        fp: mcontext.regs[29] as usize,
+        sp: mcontext.sp as usize,
+    })
+}
+
+#[cfg(all(target_os = "macos", target_arch = "x86_64"))]
+unsafe fn registers(context: *mut libc::c_void) -> Option<Registers> {
+    let context = context.cast::<libc::ucontext_t>().as_ref()?;
+    let state = &context.uc_mcontext.as_ref()?.__ss;
+    Some(Registers {
+        pc: state.__rip as usize,
+        fp: state.__rbp as usize,
+        sp: state.__rsp as usize,
+    })
+}
+
+#[cfg(all(target_os = "macos", target_arch = "aarch64"))]
+unsafe fn registers(context: *mut libc::c_void) -> Option<Registers> {
+    let context = context.cast::<libc::ucontext_t>().as_ref()?;
+    let state = &context.uc_mcontext.as_ref()?.__ss;
+    Some(Registers {
+        pc: state.__pc as usize,
+        fp: state.__fp as usize,
+        sp: state.__sp as usize,
+    })
+}
+
+#[cfg(not(all(
+    any(target_os = "linux", target_os = "macos"),
+    any(target_arch = "x86_64", target_arch = "aarch64")
+)))]
+unsafe fn registers(_context: *mut libc::c_void) -> Option<Registers> {
+    None
+}
+
+/// Reads the frame record at `fp` without faulting on invalid addresses.
+///
+/// On Linux, the memory is read with `process_vm_readv`, which fails instead of faulting.
+#[cfg(target_os = "linux")]
+unsafe fn read_frame_record(fp: usize, _sp: usize) -> Option<[usize; 2]> {
+    let mut record = [0usize; 2];
+    let size = std::mem::size_of_val(&record);
+    let local = libc::iovec {
+        iov_base: record.as_mut_ptr().cast(),
+        iov_len: size,
+    };
+    let remote = libc::iovec {
+        iov_base: fp as *mut libc::c_void,
+        iov_len: size,
+    };
+    let read = libc::process_vm_readv(libc::getpid(), &local, 1, &remote, 1, 0);
+    (read == size as isize).then_some(record)
+}
+
+/// Reads the frame record at `fp` without faulting on invalid addresses.
+///
+/// On macOS, the record is only read if it lies within the stack of the current thread.
+#[cfg(target_os = "macos")]
+unsafe fn read_frame_record(fp: usize, sp: usize) -> Option<[usize; 2]> {
+    let stack_top = libc::pthread_get_stackaddr_np(libc::pthread_self()) as usize;
+    let end = fp.checked_add(2 * std::mem::size_of::<usize>())?;
+    if fp < sp || end > stack_top {
+        return None;
+    }
+    Some(ptr::read_volatile(fp as *const [usize; 2]))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+unsafe fn read_frame_record(_fp: usize, _sp: usize) -> Option<[usize; 2]> {
+    None
+}
+
+/// Writes to a file descriptor without allocating.
+struct Writer {
+    fd: libc::c_int,
+    failed: bool,
+}
+
+impl Writer {
+    fn write(&mut self, mut bytes: &[u8]) {
+        while !self.failed && !bytes.is_empty() {
+            let written = unsafe { libc::write(self.fd, bytes.as_ptr().cast(), bytes.len()) };
+            match usize::try_from(written) {
+                Ok(written) => bytes = &bytes[written..],
+                Err(_) if unsafe { *errno_location() } == libc::EINTR => {}
+                Err(_) => self.failed = true,
+            }
+        }
+    }
+
+    fn write_decimal(&mut self, mut value: u64) {
+        let mut buf = [0u8; 20];
+        let mut start = buf.len();
+        loop {
+            start = start.saturating_sub(1);
+            buf[start] = b'0'.saturating_add((value % 10) as u8);
+            value /= 10;
+            if value == 0 {
+                break;
+            }
+        }
+        self.write(&buf[start..]);
+    }
+
+    fn write_hex(&mut self, mut value: usize) {
+        let mut buf = [0u8; 16];
+        let mut start = buf.len();
+        loop {
+            start = start.saturating_sub(1);
+            buf[start] = b"0123456789abcdef"[value & 0xf];
+            value >>= 4;
+            if value == 0 {
+                break;
+            }
+        }
+        self.write(&buf[start..]);
+    }
+
+    /// Writes the current time as fractional seconds since the epoch.
+    fn write_timestamp(&mut self) {
+        let mut now: libc::timespec = unsafe { std::mem::zeroed() };
+        unsafe { libc::clock_gettime(libc::CLOCK_REALTIME, &mut now) };
+        self.write_decimal(now.tv_sec.unsigned_abs());
+        self.write(b".");
+        // Pad the microseconds to six digits.
+        let micros = now.tv_nsec.unsigned_abs() / 1000;
+        let mut divisor = 100_000;
+        while divisor > 1 && micros < divisor {
+            self.write(b"0");
+            divisor /= 10;
+        }
+        self.write_decimal(micros);
+    }
+}
+
+#[cfg(target_os = "linux")]
+unsafe fn errno_location() -> *mut libc::c_int {
+    libc::__errno_location()
+}
+
+#[cfg(not(target_os = "linux"))]
+unsafe fn errno_location() -> *mut libc::c_int {
+    libc::__error()
+}
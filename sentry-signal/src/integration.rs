@@ -0,0 +1,82 @@
+use std::path::PathBuf;
+
+use sentry_core::{ClientOptions, Integration};
+
+use crate::{directory, report};
+
+/// The Sentry Signal handler Integration.
+///
+/// See the [crate level documentation](crate) for details.
+#[derive(Debug)]
+pub struct SignalIntegration {
+    directory: PathBuf,
+}
+
+impl SignalIntegration {
+    /// Creates a new Signal handler Integration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the directory crash reports are written to.
+    ///
+    /// The directory is created if it does not exist. All reports found in it are sent when the
+    /// integration is set up, so it should not be shared with other applications or with other
+    /// clients of the same application.
+    ///
+    /// On Unix, the directory must be owned by the current user, and is restricted to it. Crash
+    /// reports are neither written nor sent if it is owned by another user, and only reports owned
+    /// by the current user are sent.
+    ///
+    /// Defaults to a directory named after the executable in a `sentry-signal` directory in the
+    /// user's cache directory, such as `~/.cache/sentry-signal/<executable name>` on Linux.
+    #[must_use]
+    pub fn directory<P>(self, directory: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            directory: directory.into(),
+        }
+    }
+}
+
+impl Default for SignalIntegration {
+    fn default() -> Self {
+        Self {
+            directory: directory::default_directory(),
+        }
+    }
+}
+
+impl Integration for SignalIntegration {
+    fn name(&self) -> &'static str {
+        "signal"
+    }
+
+    fn setup(&self, options: &mut ClientOptions) {
+        if options.dsn.is_none() {
+            return;
+        }
+
+        if let Err(err) = directory::prepare(&self.directory) {
+            sentry_core::sentry_debug!(
+                "Not using crash report directory {}: {}",
+                self.directory.display(),
+                err
+            );
+            return;
+        }
+
+        report::send_pending(&self.directory, options);
+
+        #[cfg(unix)]
+        if let Err(err) = crate::handler::install(&self.directory, options) {
+            sentry_core::sentry_debug!(
+                "Failed to prepare crash reports in {}: {}",
+                self.directory.display(),
+                err
+            );
+        }
+    }
+}
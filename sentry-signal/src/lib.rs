@@ -0,0 +1,49 @@
+//! The Sentry Signal handler integration.
+//!
+//! The [`SignalIntegration`] reports native crashes, which terminate the process with a fatal
+//! signal instead of a Rust panic, for example segmentation faults in FFI code. It installs
+//! handlers for `SIGSEGV`, `SIGBUS`, `SIGILL`, `SIGFPE` and `SIGABRT`, which write a crash report
+//! to disk when the process crashes. The report is sent to Sentry on the next start of the
+//! application, once the integration is set up again.
+//!
+//! The signal handlers only use async-signal-safe functions and do not allocate. They capture the
+//! stack of the crashing thread by walking its frame pointers on Linux and macOS, so applications
+//! should be built with `-C force-frame-pointers=yes` for complete stacks. The event is
+//! therefore mostly serialized ahead of time, when the integration is set up, and only contains
+//! information known at that point: the release, environment and server name of the client
+//! options, and the [debug images](sentry_debug_images::debug_images) of the loaded libraries.
+//! The handlers add the time of the crash, an exception with a `signalhandler` mechanism
+//! describing the signal, and the unsymbolicated stack of the crashing thread. Scope data such
+//! as tags, breadcrumbs or the user are not included.
+//!
+//! After writing the report, the handlers restore the previously installed signal handlers, so
+//! that the signal is handled as it would have been without this integration, for example by
+//! printing Rust's stack overflow message or writing a core dump.
+//!
+//! Signal handlers are only installed on Unix platforms. On other platforms, the integration
+//! only sends reports left behind by previous runs.
+//!
+//! # Configuration
+//!
+//! Crash reports are written to a directory in the user's cache directory by default, named after
+//! the executable, such as `~/.cache/sentry-signal/<executable name>` on Linux. Since all reports
+//! in the directory are sent with the client's DSN, it should not be shared with other
+//! applications. On Unix, the directory is restricted to the current user, and crash reports are
+//! disabled if it belongs to another user.
+//!
+//! ```
+//! let integration =
+//!     sentry_signal::SignalIntegration::new().directory("/var/lib/my-app/crashes");
+//! ```
+
+#![doc(html_favicon_url = "https://sentry-brand.storage.googleapis.com/favicon.ico")]
+#![doc(html_logo_url = "https://sentry-brand.storage.googleapis.com/sentry-glyph-black.png")]
+#![warn(missing_docs)]
+
+mod directory;
+#[cfg(unix)]
+mod handler;
+mod integration;
+mod report;
+
+pub use integration::SignalIntegration;
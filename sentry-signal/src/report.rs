@@ -0,0 +1,184 @@
+//! Crash reports stored on disk.
+//!
+//! A report is an envelope with a single event item. Its event is serialized when the integration
+//! is set up, except for the timestamp and exception, which the signal handler appends. To make
+//! this possible, the item has no explicit length and is terminated by a newline instead.
+
+use std::borrow::Cow;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use sentry_core::protocol::{ClientSdkInfo, ClientSdkPackage, DebugMeta, Envelope, Event, Level};
+use sentry_core::types::Uuid;
+use sentry_core::{sentry_debug, ClientOptions, TransportOptions};
+
+use crate::directory;
+
+/// The file extension of crash reports.
+pub(crate) const EXTENSION: &str = "envelope";
+
+/// The file extension used while a crash report is being written.
+pub(crate) const TEMP_EXTENSION: &str = "envelope.tmp";
+
+/// How long to wait for the reports of previous runs to be sent before keeping them for later.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(30);
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Serializes the start of a crash report for an event with the given ID.
+///
+/// The returned bytes contain the envelope and item headers, followed by the event without its
+/// closing brace, so that further fields can be appended.
+pub(crate) fn envelope_head(event_id: Uuid, options: &ClientOptions) -> Vec<u8> {
+    let event = Event {
+        event_id,
+        level: Level::Fatal,
+        platform: "native".into(),
+        release: options.release.clone(),
+        environment: options.environment.clone(),
+        server_name: options.server_name.clone(),
+        debug_meta: Cow::Owned(DebugMeta {
+            images: sentry_debug_images::debug_images(),
+            ..Default::default()
+        }),
+        sdk: Some(Cow::Owned(ClientSdkInfo {
+            name: "sentry.rust".into(),
+            version: VERSION.into(),
+            integrations: vec!["signal".into()],
+            packages: vec![ClientSdkPackage {
+                name: "cargo:sentry".into(),
+                version: VERSION.into(),
+            }],
+        })),
+        ..Default::default()
+    };
+
+    let mut event = serde_json::to_value(event).unwrap_or_default();
+    if let Some(event) = event.as_object_mut() {
+        // The time of the crash is appended by the signal handler instead.
+        event.remove("timestamp");
+    }
+    let mut event = serde_json::to_vec(&event).unwrap_or_default();
+    event.pop();
+
+    let mut head =
+        format!("{{\"event_id\":\"{event_id}\"}}\n{{\"type\":\"event\"}}\n").into_bytes();
+    head.append(&mut event);
+    head
+}
+
+/// Sends the crash reports of previous runs found in `directory`.
+///
+/// The client's transport does not exist yet while the integration is set up, so the reports are
+/// sent with a separate transport created from the client options. Reports are removed once they
+/// were sent, which happens in the background. Reports which could not be sent in time are sent
+/// again on the next start, and deduplicated by Sentry based on their event ID.
+pub(crate) fn send_pending(directory: &Path, options: &ClientOptions) {
+    let reports = pending(directory);
+    if reports.is_empty() {
+        return;
+    }
+    let (Some(factory), Some(mut transport_options)) = (
+        options.transport.as_ref(),
+        TransportOptions::try_from_client_options(options),
+    ) else {
+        return;
+    };
+    // The offline cache belongs to the client's transport.
+    transport_options.offline_cache = None;
+
+    sentry_debug!("Sending {} crash reports of previous runs", reports.len());
+    let transport = factory.create_transport_with_options(transport_options);
+    for (_, envelope) in &reports {
+        transport.send_envelope(envelope.clone());
+    }
+    thread::spawn(move || {
+        if transport.flush(FLUSH_TIMEOUT) {
+            for (path, _) in reports {
+                remove_file(&path);
+            }
+        }
+    });
+}
+
+/// Reads the crash reports in `directory`, removing unreadable and partially written reports.
+///
+/// Only reports written by the signal handlers of the current user are read, which are named after
+/// the ID of their event. Other files are left untouched.
+fn pending(directory: &Path) -> Vec<(PathBuf, Envelope)> {
+    let Ok(read_dir) = fs::read_dir(directory) else {
+        return Vec::new();
+    };
+
+    let mut reports = Vec::new();
+    for entry in read_dir.flatten() {
+        let path = entry.path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if name.ends_with(&format!(".{TEMP_EXTENSION}")) {
+            // The process was terminated while writing the report.
+            remove_file(&path);
+            continue;
+        }
+        let Some(event_id) = name
+            .strip_suffix(&format!(".{EXTENSION}"))
+            .and_then(|event_id| event_id.parse::<Uuid>().ok())
+        else {
+            continue;
+        };
+        if !entry
+            .metadata()
+            .is_ok_and(|metadata| directory::is_own_file(&metadata))
+        {
+            sentry_debug!("Ignoring foreign crash report {}", path.display());
+            continue;
+        }
+        match Envelope::from_path(&path) {
+            Ok(envelope) if envelope.event().map(|event| event.event_id) == Some(event_id) => {
+                reports.push((path, envelope));
+            }
+            Ok(_) => sentry_debug!("Ignoring foreign crash report {}", path.display()),
+            Err(err) => {
+                sentry_debug!(
+                    "Removing unreadable crash report {}: {}",
+                    path.display(),
+                    err
+                );
+                remove_file(&path);
+            }
+        }
+    }
+    reports
+}
+
+fn remove_file(path: &Path) {
+    if let Err(err) = fs::remove_file(path) {
+        sentry_debug!("Failed to remove crash report {}: {}", path.display(), err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope_head() {
+        let event_id = sentry_core::types::random_uuid();
+        let options = ClientOptions::new().release("my-app@1.0.0");
+        let mut report = envelope_head(event_id, &options);
+        report.extend_from_slice(b",\"timestamp\":1700000000.5}\n");
+
+        let envelope = Envelope::from_slice(&report).unwrap();
+        let event = envelope.event().unwrap();
+        assert_eq!(event.event_id, event_id);
+        assert_eq!(event.level, Level::Fatal);
+        assert_eq!(event.release.as_deref(), Some("my-app@1.0.0"));
+        assert_eq!(
+            event.timestamp,
+            std::time::UNIX_EPOCH + Duration::from_secs_f64(1_700_000_000.5)
+        );
+    }
+}
@@ -0,0 +1,190 @@
+#![cfg(unix)]
+
+use std::env;
+use std::fs;
+use std::os::unix::process::ExitStatusExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant, SystemTime};
+
+use sentry_core::protocol::{DebugImage, Envelope, Event, Level};
+use sentry_core::ClientOptions;
+use sentry_signal::SignalIntegration;
+
+const SIGNAL_ENV: &str = "SENTRY_SIGNAL_TEST_SIGNAL";
+const DIRECTORY_ENV: &str = "SENTRY_SIGNAL_TEST_DIRECTORY";
+
+fn options(directory: &Path) -> ClientOptions {
+    ClientOptions::new().add_integration(SignalIntegration::new().directory(directory))
+}
+
+#[inline(never)]
+fn crash_with_segfault() {
+    unsafe { std::ptr::null_mut::<u8>().write_volatile(1) };
+}
+
+/// Crashes the process when spawned by [`crash`], and does nothing otherwise.
+#[test]
+fn crash_child() {
+    let (Ok(signal), Some(directory)) = (env::var(SIGNAL_ENV), env::var_os(DIRECTORY_ENV)) else {
+        return;
+    };
+    sentry_core::test::with_captured_envelopes_options(
+        || match signal.parse().unwrap() {
+            libc::SIGSEGV => crash_with_segfault(),
+            libc::SIGABRT => std::process::abort(),
+            signal => unsafe {
+                libc::raise(signal);
+            },
+        },
+        options(directory.as_ref()),
+    );
+    unreachable!("the process should have crashed");
+}
+
+/// Runs [`crash_child`] in a child process which crashes with `signal`, and returns the event it
+/// wrote to `directory`.
+fn crash(signal: libc::c_int, directory: &Path) -> Event<'static> {
+    let _ = fs::remove_dir_all(directory);
+    let status = Command::new(env::current_exe().unwrap())
+        .args(["crash_child", "--exact", "--nocapture", "--test-threads=1"])
+        .env(SIGNAL_ENV, signal.to_string())
+        .env(DIRECTORY_ENV, directory)
+        .stdout(Stdio::null())
+        .status()
+        .unwrap();
+    assert_eq!(status.signal(), Some(signal));
+
+    let reports = reports(directory);
+    assert_eq!(reports.len(), 1);
+    let envelope = Envelope::from_path(&reports[0]).unwrap();
+    envelope
+        .event()
+        .cloned()
+        .expect("the report should contain an event")
+}
+
+fn reports(directory: &Path) -> Vec<PathBuf> {
+    fs::read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "envelope")
+        })
+        .collect()
+}
+
+/// Resolves the name of the function at `ip` in the crashed process of `event`.
+fn symbol_name(event: &Event<'static>, ip: u64) -> Option<String> {
+    // Translate the address to this process, which loaded the executable at a different address.
+    let image_addr = |images: &[DebugImage]| {
+        images.iter().find_map(|image| match image {
+            DebugImage::Symbolic(image) if Path::new(&image.name) == env::current_exe().ok()? => {
+                Some(image.image_addr.0)
+            }
+            _ => None,
+        })
+    };
+    let crashed = image_addr(&event.debug_meta.images)?;
+    let current = image_addr(&sentry_debug_images::debug_images())?;
+    let ip = ip.checked_sub(crashed)?.checked_add(current)?;
+
+    let mut name = None;
+    backtrace::resolve(ip as *mut _, |symbol| {
+        name = symbol.name().map(|name| format!("{name:#}"));
+    });
+    name
+}
+
+fn test_directory(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("sentry-signal-test-{}-{name}", std::process::id()))
+}
+
+#[test]
+fn test_segfault_is_reported_on_next_start() {
+    let directory = test_directory("segfault");
+    let event = crash(libc::SIGSEGV, &directory);
+
+    assert_eq!(event.level, Level::Fatal);
+    let age = SystemTime::now().duration_since(event.timestamp).unwrap();
+    assert!(age < Duration::from_secs(60));
+    let exception = &event.exception[0];
+    assert_eq!(exception.ty, "SIGSEGV");
+    let mechanism = exception.mechanism.as_ref().unwrap();
+    assert_eq!(mechanism.ty, "signalhandler");
+    assert_eq!(mechanism.handled, Some(false));
+    let signal = mechanism.meta.signal.as_ref().unwrap();
+    assert_eq!(signal.number, libc::SIGSEGV);
+    assert_eq!(signal.name.as_deref(), Some("SIGSEGV"));
+    if cfg!(target_os = "linux") {
+        assert!(!event.debug_meta.images.is_empty());
+    }
+
+    // The innermost frame is the faulting function, not the signal handler.
+    let frames = &exception.stacktrace.as_ref().unwrap().frames;
+    let ip = frames.last().unwrap().instruction_addr.unwrap().0;
+    assert_eq!(
+        symbol_name(&event, ip).as_deref(),
+        Some("test_signal::crash_with_segfault")
+    );
+
+    // The report is sent and removed on the next start.
+    let envelopes = sentry_core::test::with_captured_envelopes_options(|| {}, options(&directory));
+    assert_eq!(envelopes.len(), 1);
+    assert_eq!(envelopes[0].event().unwrap().event_id, event.event_id);
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !reports(&directory).is_empty() {
+        assert!(Instant::now() < deadline, "the report should be removed");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_abort_is_reported() {
+    let directory = test_directory("abort");
+    let event = crash(libc::SIGABRT, &directory);
+    assert_eq!(event.exception[0].ty, "SIGABRT");
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_raised_bus_error_is_reported() {
+    let directory = test_directory("bus");
+    let event = crash(libc::SIGBUS, &directory);
+    assert_eq!(event.exception[0].ty, "SIGBUS");
+    assert!(!event.exception[0]
+        .stacktrace
+        .as_ref()
+        .unwrap()
+        .frames
+        .is_empty());
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_only_own_reports_are_sent() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let directory = test_directory("foreign");
+    let event = crash(libc::SIGSEGV, &directory);
+
+    // Copies of the report which are not named after its event.
+    let report = &reports(&directory)[0];
+    let renamed = directory.join(format!("{}.envelope", sentry_core::types::random_uuid()));
+    fs::copy(report, &renamed).unwrap();
+    fs::copy(report, directory.join("other.envelope")).unwrap();
+    fs::set_permissions(&directory, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let envelopes = sentry_core::test::with_captured_envelopes_options(|| {}, options(&directory));
+    assert_eq!(envelopes.len(), 1);
+    assert_eq!(envelopes[0].event().unwrap().event_id, event.event_id);
+    assert!(renamed.exists());
+
+    // The directory is restricted to the current user again.
+    let mode = fs::metadata(&directory).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o700);
+    fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use sentry_core::Integration;
+
+/// The default [`slow_poll_threshold`](TokioIntegration::slow_poll_threshold).
+const DEFAULT_SLOW_POLL_THRESHOLD: Duration = Duration::from_millis(100);
+
+/// How a slow poll of an instrumented future is reported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SlowPollAction {
+    /// Slow polls are not reported.
+    Ignore,
+    /// Slow polls are recorded as breadcrumbs.
+    #[default]
+    Breadcrumb,
+    /// Slow polls are captured as events.
+    Event,
+}
+
+/// The Sentry Tokio Integration.
+///
+/// Enables recording runtime metrics and optionally poll durations, and reporting slow polls of the futures
+/// instrumented by this crate. Futures running on hubs whose client does not have this integration
+/// are not reported.
+#[derive(Debug)]
+pub struct TokioIntegration {
+    pub(crate) poll_durations: bool,
+    pub(crate) slow_poll_threshold: Duration,
+    pub(crate) slow_poll_action: SlowPollAction,
+}
+
+impl Default for TokioIntegration {
+    fn default() -> Self {
+        Self {
+            poll_durations: false,
+            slow_poll_threshold: DEFAULT_SLOW_POLL_THRESHOLD,
+            slow_poll_action: SlowPollAction::default(),
+        }
+    }
+}
+
+impl TokioIntegration {
+    /// Creates a new Tokio Integration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether the duration of every poll is recorded as a distribution metric.
+    ///
+    /// This records a metric for every poll of every instrumented future, which quickly adds up
+    /// in busy applications, so it is meant for investigating individual services. Defaults to
+    /// `false`.
+    #[must_use]
+    pub fn poll_durations(mut self, poll_durations: bool) -> Self {
+        self.poll_durations = poll_durations;
+        self
+    }
+
+    /// Sets the duration after which a single poll is reported as slow.
+    ///
+    /// A future should not spend long between two `.await` points, because it blocks the runtime
+    /// thread polling it from running other tasks. Defaults to 100 ms.
+    #[must_use]
+    pub fn slow_poll_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_poll_threshold = threshold;
+        self
+    }
+
+    /// Sets how slow polls are reported.
+    ///
+    /// Defaults to [`SlowPollAction::Breadcrumb`].
+    #[must_use]
+    pub fn slow_poll_action(mut self, action: SlowPollAction) -> Self {
+        self.slow_poll_action = action;
+        self
+    }
+}
+
+impl Integration for TokioIntegration {
+    fn name(&self) -> &'static str {
+        "tokio"
+    }
+}
@@ -0,0 +1,52 @@
+//! The Sentry Tokio integration.
+//!
+//! This integration instruments futures running on a tokio runtime, to find tasks which block the
+//! runtime threads. Futures spawned with [`spawn`], or wrapped with [`instrument`], are timed
+//! every time they are polled:
+//!
+//! - If enabled with [`TokioIntegration::poll_durations`], the duration of each poll is recorded
+//!   as a `tokio.task.poll.duration` distribution metric, with the type name of the future in the
+//!   `tokio.task.type` attribute.
+//! - A poll which takes longer than the
+//!   [slow poll threshold](TokioIntegration::slow_poll_threshold) is reported as a breadcrumb, or
+//!   optionally as an event, naming the future. Such a future runs blocking code between two
+//!   `.await` points, which stops the runtime thread from polling other tasks.
+//!
+//! Additionally, [`report_runtime_metrics`] periodically records the number of worker threads,
+//! alive tasks, and the depth of the global task queue of the runtime as gauges.
+//!
+//! Metrics and slow polls are only recorded if the [`TokioIntegration`] is enabled on the client.
+//!
+//! # Example
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use sentry_tokio::{SlowPollAction, TokioIntegration};
+//!
+//! let integration = TokioIntegration::new()
+//!     .slow_poll_threshold(Duration::from_millis(50))
+//!     .slow_poll_action(SlowPollAction::Event);
+//! let options = sentry_core::ClientOptions::new().add_integration(integration);
+//!
+//! # async fn run() {
+//! sentry_tokio::report_runtime_metrics(Duration::from_secs(10));
+//!
+//! sentry_tokio::spawn(async {
+//!     // Blocks the runtime thread, and is reported as a slow poll.
+//!     std::thread::sleep(Duration::from_millis(100));
+//! });
+//! # }
+//! ```
+
+#![doc(html_favicon_url = "https://sentry-brand.storage.googleapis.com/favicon.ico")]
+#![doc(html_logo_url = "https://sentry-brand.storage.googleapis.com/sentry-glyph-black.png")]
+#![warn(missing_docs)]
+
+mod integration;
+mod runtime;
+mod task;
+
+pub use integration::{SlowPollAction, TokioIntegration};
+pub use runtime::report_runtime_metrics;
+pub use task::{instrument, spawn, Instrumented};
@@ -0,0 +1,56 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use sentry_core::metrics;
+use sentry_core::{Hub, SentryFutureExt};
+use tokio::runtime::{Handle, RuntimeMetrics};
+use tokio::task::JoinHandle;
+
+use crate::TokioIntegration;
+
+/// The shortest interval at which runtime metrics are reported.
+const MIN_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Spawns a task which reports the metrics of the current tokio runtime once per `interval`.
+///
+/// The following gauges are recorded on a new hub forked from the current one:
+///
+/// - `tokio.runtime.workers`: The number of worker threads of the runtime.
+/// - `tokio.runtime.alive_tasks`: The number of tasks which are currently alive.
+/// - `tokio.runtime.global_queue_depth`: The number of tasks in the global queue, which are
+///   scheduled but were not picked up by a worker thread yet.
+///
+/// Intervals below 10 ms are treated as 10 ms. The task runs until it is aborted, or the runtime
+/// shuts down.
+///
+/// # Panics
+///
+/// Panics if called outside of a tokio runtime, like [`tokio::spawn`].
+pub fn report_runtime_metrics(interval: Duration) -> JoinHandle<()> {
+    let runtime = Handle::current();
+    let hub = Arc::new(Hub::new_from_top(Hub::current()));
+    let task = async move {
+        let mut interval = tokio::time::interval(interval.max(MIN_INTERVAL));
+        loop {
+            interval.tick().await;
+            let hub = Hub::current();
+            hub.with_integration(|_: &TokioIntegration| record_metrics(&hub, &runtime.metrics()));
+        }
+    };
+    tokio::spawn(task.bind_hub(hub))
+}
+
+fn record_metrics(hub: &Hub, metrics: &RuntimeMetrics) {
+    hub.capture_metric(metrics::gauge(
+        "tokio.runtime.workers",
+        metrics.num_workers() as f64,
+    ));
+    hub.capture_metric(metrics::gauge(
+        "tokio.runtime.alive_tasks",
+        metrics.num_alive_tasks() as f64,
+    ));
+    hub.capture_metric(metrics::gauge(
+        "tokio.runtime.global_queue_depth",
+        metrics.global_queue_depth() as f64,
+    ));
+}
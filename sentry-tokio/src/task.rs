@@ -0,0 +1,125 @@
+use std::any::type_name;
+use std::borrow::Cow;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use sentry_core::metrics;
+use sentry_core::protocol::{Breadcrumb, Event, Level, Map, Unit};
+use sentry_core::{sentry_debug, Hub};
+use tokio::task::JoinHandle;
+
+use crate::{SlowPollAction, TokioIntegration};
+
+/// Spawns a future on the current tokio runtime, instrumenting its polls.
+///
+/// The future runs on a new hub forked from the current one like with [`sentry_core::spawn`], on
+/// which its polls are recorded. See [`instrument`] for what is recorded.
+///
+/// # Panics
+///
+/// Panics if called outside of a tokio runtime, like [`tokio::spawn`].
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    sentry_core::spawn(instrument(future))
+}
+
+/// Instruments the polls of a future.
+///
+/// The duration of every poll of the returned future is recorded as a `tokio.task.poll.duration`
+/// distribution, with the type name of the future in the `tokio.task.type` attribute. Polls taking
+/// longer than the [slow poll threshold](TokioIntegration::slow_poll_threshold) are reported as a
+/// breadcrumb or an event. Both are recorded on the hub which is current while the future is
+/// polled.
+pub fn instrument<F>(future: F) -> Instrumented<F>
+where
+    F: Future,
+{
+    Instrumented {
+        future,
+        name: type_name::<F>(),
+    }
+}
+
+/// A future whose polls are instrumented, see [`instrument`].
+#[derive(Debug)]
+pub struct Instrumented<F> {
+    future: F,
+    name: &'static str,
+}
+
+impl<F> Instrumented<F> {
+    /// Returns the type name of the instrumented future.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<F> Future for Instrumented<F>
+where
+    F: Future,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let name = self.name;
+        // https://doc.rust-lang.org/std/pin/index.html#pinning-is-structural-for-field
+        let future = unsafe { self.map_unchecked_mut(|s| &mut s.future) };
+        let start = Instant::now();
+        let poll = future.poll(cx);
+        let duration = start.elapsed();
+
+        Hub::with_active(|hub| {
+            hub.with_integration(|integration: &TokioIntegration| {
+                record_poll(hub, integration, name, duration);
+            })
+        });
+        poll
+    }
+}
+
+fn record_poll(hub: &Hub, integration: &TokioIntegration, name: &'static str, duration: Duration) {
+    if integration.poll_durations {
+        let metric =
+            metrics::distribution("tokio.task.poll.duration", duration.as_secs_f64() * 1e3)
+                .unit(Unit::Millisecond)
+                .attribute("tokio.task.type", name);
+        hub.capture_metric(metric);
+    }
+
+    if duration <= integration.slow_poll_threshold {
+        return;
+    }
+    let message = format!(
+        "Polling `{name}` blocked the runtime thread for {} ms",
+        duration.as_millis()
+    );
+    match integration.slow_poll_action {
+        SlowPollAction::Ignore => {}
+        SlowPollAction::Breadcrumb => {
+            let mut data = Map::new();
+            data.insert("tokio.task.type".into(), name.into());
+            hub.add_breadcrumb(Breadcrumb {
+                category: Some("tokio.slow_poll".into()),
+                level: Level::Warning,
+                message: Some(message),
+                data,
+                ..Default::default()
+            });
+        }
+        SlowPollAction::Event => {
+            sentry_debug!("Polling `{}` was slow, reporting it", name);
+            hub.capture_event(Event {
+                message: Some(message),
+                level: Level::Warning,
+                // The message contains the duration, which should not split the issue.
+                fingerprint: Cow::Owned(vec!["tokio-slow-poll".into(), name.into()]),
+                ..Default::default()
+            });
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use std::thread;
+use std::time::Duration;
+
+use sentry_core::protocol::{Envelope, EnvelopeItem, ItemContainer, Metric, MetricType, Unit};
+use sentry_core::ClientOptions;
+use sentry_tokio::{SlowPollAction, TokioIntegration};
+
+const THRESHOLD: Duration = Duration::from_millis(20);
+const BLOCKING: Duration = Duration::from_millis(60);
+
+fn options(integration: TokioIntegration) -> ClientOptions {
+    ClientOptions::new().add_integration(integration.slow_poll_threshold(THRESHOLD))
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+/// Blocks the runtime thread for longer than the slow poll threshold.
+async fn blocking() {
+    tokio::task::yield_now().await;
+    thread::sleep(BLOCKING);
+}
+
+fn metrics(envelopes: Vec<Envelope>) -> Vec<Metric> {
+    envelopes
+        .into_iter()
+        .flat_map(|envelope| envelope.into_items())
+        .filter_map(|item| match item {
+            EnvelopeItem::ItemContainer(ItemContainer::Metrics(metrics)) => Some(metrics),
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+#[test]
+fn test_slow_poll_breadcrumb() {
+    let events = sentry_core::test::with_captured_events_options(
+        || {
+            block_on(async {
+                sentry_tokio::spawn(async {
+                    blocking().await;
+                    // The slow poll is recorded after it returned.
+                    tokio::task::yield_now().await;
+                    sentry_core::capture_message("done", sentry_core::Level::Info);
+                })
+                .await
+                .unwrap();
+            });
+        },
+        options(TokioIntegration::new()),
+    );
+
+    assert_eq!(events.len(), 1);
+    let breadcrumbs = &events[0].breadcrumbs;
+    assert_eq!(breadcrumbs.len(), 1);
+    let breadcrumb = &breadcrumbs[0];
+    assert_eq!(breadcrumb.category.as_deref(), Some("tokio.slow_poll"));
+    let name = breadcrumb.data["tokio.task.type"].as_str().unwrap();
+    assert!(name.starts_with("test_tokio::"), "{name}");
+}
+
+#[test]
+fn test_slow_poll_event() {
+    let events = sentry_core::test::with_captured_events_options(
+        || {
+            block_on(async {
+                sentry_tokio::spawn(blocking()).await.unwrap();
+                sentry_tokio::spawn(tokio::task::yield_now()).await.unwrap();
+            });
+        },
+        options(TokioIntegration::new().slow_poll_action(SlowPollAction::Event)),
+    );
+
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert!(event
+        .message
+        .as_ref()
+        .unwrap()
+        .starts_with("Polling `test_tokio::blocking::{{closure}}` blocked the runtime thread"));
+    assert_eq!(event.fingerprint[1], "test_tokio::blocking::{{closure}}");
+}
+
+#[test]
+fn test_poll_durations() {
+    let envelopes = sentry_core::test::with_captured_envelopes_options(
+        || {
+            block_on(async {
+                sentry_tokio::spawn(blocking()).await.unwrap();
+            });
+        },
+        options(
+            TokioIntegration::new()
+                .poll_durations(true)
+                .slow_poll_action(SlowPollAction::Ignore),
+        ),
+    );
+
+    let metrics = metrics(envelopes);
+    // The future yields once, so it is polled twice.
+    assert_eq!(metrics.len(), 2);
+    for metric in &metrics {
+        assert_eq!(metric.r#type, MetricType::Distribution);
+        assert_eq!(metric.name, "tokio.task.poll.duration");
+        assert_eq!(metric.unit, Some(Unit::Millisecond));
+        assert_eq!(
+            metric.attributes["tokio.task.type"].0,
+            "test_tokio::blocking::{{closure}}"
+        );
+    }
+    assert!(metrics[1].value >= BLOCKING.as_secs_f64() * 1e3);
+}
+
+#[test]
+fn test_poll_durations_disabled_by_default() {
+    let envelopes = sentry_core::test::with_captured_envelopes_options(
+        || {
+            block_on(async {
+                sentry_tokio::spawn(blocking()).await.unwrap();
+            });
+        },
+        options(TokioIntegration::new().slow_poll_action(SlowPollAction::Ignore)),
+    );
+
+    assert!(metrics(envelopes).is_empty());
+}
+
+#[test]
+fn test_runtime_metrics_zero_interval() {
+    let envelopes = sentry_core::test::with_captured_envelopes_options(
+        || {
+            block_on(async {
+                let reporter = sentry_tokio::report_runtime_metrics(Duration::ZERO);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                reporter.abort();
+                assert!(reporter.await.unwrap_err().is_cancelled());
+            });
+        },
+        options(TokioIntegration::new()),
+    );
+
+    assert!(!metrics(envelopes).is_empty());
+}
+
+#[test]
+fn test_runtime_metrics() {
+    let envelopes = sentry_core::test::with_captured_envelopes_options(
+        || {
+            block_on(async {
+                let reporter = sentry_tokio::report_runtime_metrics(Duration::from_secs(60));
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                reporter.abort();
+            });
+        },
+        options(TokioIntegration::new()),
+    );
+
+    let metrics = metrics(envelopes);
+    let names: Vec<_> = metrics.iter().map(|metric| metric.name.as_ref()).collect();
+    assert_eq!(
+        names,
+        [
+            "tokio.runtime.workers",
+            "tokio.runtime.alive_tasks",
+            "tokio.runtime.global_queue_depth"
+        ]
+    );
+    assert!(metrics
+        .iter()
+        .all(|metric| metric.r#type == MetricType::Gauge));
+    assert_eq!(metrics[0].value, 1.0);
+}
+
+#[test]
+fn test_disabled() {
+    let envelopes = sentry_core::test::with_captured_envelopes(|| {
+        block_on(async {
+            sentry_tokio::spawn(blocking()).await.unwrap();
+        });
+    });
+    assert!(envelopes.is_empty());
+}
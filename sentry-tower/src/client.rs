@@ -0,0 +1,243 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use http::{Request, Response};
+use sentry_core::utils::OutgoingHttpRequest;
+use sentry_core::Hub;
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Tower Layer that instruments outgoing Http Requests.
+///
+/// The Service created by this Layer wraps an HTTP client. For each request, it starts an
+/// `http.client` span as a child of the span active on the current scope, adds an `http`
+/// breadcrumb once the response is received, and attaches the `sentry-trace` header to requests
+/// whose URL matches the
+/// [`trace_propagation_targets`](sentry_core::ClientOptions::trace_propagation_targets) client
+/// option.
+///
+/// # Example
+///
+/// ```rust
+/// # type Body = String;
+/// use tower::ServiceBuilder;
+///
+/// let client = ServiceBuilder::new()
+///     .layer(sentry_tower::SentryHttpClientLayer::new())
+///     .service(tower::service_fn(|_request: http::Request<Body>| async {
+///         Ok::<_, std::convert::Infallible>(http::Response::new(Body::new()))
+///     }));
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct SentryHttpClientLayer;
+
+impl SentryHttpClientLayer {
+    /// Creates a new Layer that instruments outgoing requests.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<S> Layer<S> for SentryHttpClientLayer {
+    type Service = SentryHttpClientService<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        SentryHttpClientService { service }
+    }
+}
+
+/// Tower Service that instruments outgoing Http Requests.
+///
+/// See [`SentryHttpClientLayer`] for details.
+#[derive(Clone)]
+pub struct SentryHttpClientService<S> {
+    service: S,
+}
+
+/// The Future returned from [`SentryHttpClientService`].
+///
+/// If the future is dropped before the response is received, the span of the request is finished
+/// with the `aborted` status.
+#[pin_project::pin_project]
+pub struct SentryHttpClientFuture<F> {
+    hub: Arc<Hub>,
+    request: Option<OutgoingHttpRequest>,
+    #[pin]
+    future: F,
+}
+
+impl<F, ResBody, Error> Future for SentryHttpClientFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, Error>>,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let slf = self.project();
+        let res = match slf.future.poll(cx) {
+            Poll::Ready(res) => res,
+            Poll::Pending => return Poll::Pending,
+        };
+        if let Some(request) = slf.request.take() {
+            match &res {
+                Ok(response) => request.finish(
+                    slf.hub,
+                    Some(response.status().as_u16()),
+                    content_length(response.headers()),
+                ),
+                Err(_) => request.finish(slf.hub, None, None),
+            }
+        }
+        Poll::Ready(res)
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for SentryHttpClientService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = SentryHttpClientFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<ReqBody>) -> Self::Future {
+        let hub = Hub::current();
+        let outgoing = OutgoingHttpRequest::start(
+            &hub,
+            request.method().as_str(),
+            &request.uri().to_string(),
+            content_length(request.headers()),
+        );
+        for (name, value) in outgoing.trace_headers(&hub) {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(&value),
+            ) {
+                request.headers_mut().insert(name, value);
+            }
+        }
+
+        SentryHttpClientFuture {
+            hub,
+            request: Some(outgoing),
+            future: self.service.call(request),
+        }
+    }
+}
+
+fn content_length(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get(header::CONTENT_LENGTH)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use http::StatusCode;
+    use sentry_core::protocol::{self, EnvelopeItem, Level};
+    use sentry_core::{ClientOptions, Envelope, TransactionContext};
+
+    use super::*;
+
+    /// Sends a request to `uri` within a transaction, returning the captured envelopes and the
+    /// `sentry-trace` header received by the inner service.
+    fn send_request(uri: &str, options: ClientOptions) -> (Vec<Envelope>, Option<String>) {
+        let received = Arc::new(Mutex::new(None));
+        let envelopes = sentry::test::with_captured_envelopes_options(
+            || {
+                let transaction =
+                    sentry_core::start_transaction(TransactionContext::new("outgoing", "test"));
+                sentry_core::configure_scope(|scope| {
+                    scope.set_span(Some(transaction.clone().into()))
+                });
+
+                let received = received.clone();
+                let mut service = SentryHttpClientLayer::new().layer(tower::service_fn(
+                    move |request: Request<()>| {
+                        *received.lock().unwrap() = request
+                            .headers()
+                            .get("sentry-trace")
+                            .map(|value| value.to_str().unwrap().to_owned());
+                        async {
+                            let response = Response::builder()
+                                .status(StatusCode::NOT_FOUND)
+                                .header(header::CONTENT_LENGTH, "42")
+                                .body(())
+                                .unwrap();
+                            Ok::<_, std::convert::Infallible>(response)
+                        }
+                    },
+                ));
+                let request = Request::builder().uri(uri).body(()).unwrap();
+                tokio::runtime::Runtime::new()
+                    .unwrap()
+                    .block_on(service.call(request))
+                    .unwrap();
+
+                sentry_core::capture_message("after request", Level::Info);
+                transaction.finish();
+            },
+            options.traces_sample_rate(1.0),
+        );
+        let received = received.lock().unwrap().take();
+        (envelopes, received)
+    }
+
+    #[test]
+    fn records_span_and_breadcrumb() {
+        let (envelopes, sentry_trace) =
+            send_request("https://example.com/users?id=1", ClientOptions::default());
+
+        let event = envelopes[0].event().unwrap();
+        let breadcrumb = &event.breadcrumbs[0];
+        assert_eq!(breadcrumb.ty, "http");
+        assert_eq!(breadcrumb.level, Level::Warning);
+        assert_eq!(breadcrumb.data["method"], "GET");
+        assert_eq!(breadcrumb.data["url"], "https://example.com/users");
+        assert_eq!(breadcrumb.data["status_code"], 404);
+
+        let Some(EnvelopeItem::Transaction(transaction)) = envelopes[1].items().next() else {
+            panic!("expected a transaction");
+        };
+        let span = &transaction.spans[0];
+        assert_eq!(span.op.as_deref(), Some("http.client"));
+        assert_eq!(
+            span.description.as_deref(),
+            Some("GET https://example.com/users")
+        );
+        assert_eq!(span.status, Some(protocol::SpanStatus::NotFound));
+        assert_eq!(span.data["http.query"], "id=1");
+        assert_eq!(span.data["http.response.status_code"], 404);
+        assert_eq!(span.data["http.response_content_length"], 42);
+
+        let sentry_trace = sentry_trace.expect("sentry-trace header was not attached");
+        assert_eq!(
+            sentry_trace,
+            format!("{}-{}-1", span.trace_id, span.span_id)
+        );
+    }
+
+    #[test]
+    fn respects_trace_propagation_targets() {
+        let options =
+            || ClientOptions::new().trace_propagation_targets(["^https://api\\.internal/"]);
+
+        let (_, sentry_trace) = send_request("https://api.internal/users", options());
+        assert!(sentry_trace.is_some());
+
+        let (_, sentry_trace) = send_request("https://third-party.com/users", options());
+        assert!(sentry_trace.is_none());
+    }
+}
@@ -3,9 +3,9 @@ use std::future::Future;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
-use http::{header, uri, Request, Response, StatusCode};
+use http::{header, uri, Request, Response};
 use pin_project::pinned_drop;
-use sentry_core::utils::{is_sensitive_header, scrub_pii_from_url};
+use sentry_core::utils::{is_sensitive_header, scrub_pii_from_url, span_status_from_http_status};
 use sentry_core::{protocol, Hub};
 use tower_layer::Layer;
 use tower_service::Service;
@@ -140,7 +140,7 @@ where
                 if let Some((transaction, parent_span)) = slf.transaction.take() {
                     if transaction.get_status().is_none() {
                         let status = match &res {
-                            Ok(res) => map_status(res.status()),
+                            Ok(res) => span_status_from_http_status(res.status().as_u16()),
                             Err(_) => protocol::SpanStatus::UnknownError,
                         };
                         transaction.set_status(status);
@@ -233,22 +233,6 @@ fn path_from_request<B>(request: &Request<B>) -> &str {
     request.uri().path()
 }
 
-fn map_status(status: StatusCode) -> protocol::SpanStatus {
-    match status {
-        StatusCode::UNAUTHORIZED => protocol::SpanStatus::Unauthenticated,
-        StatusCode::FORBIDDEN => protocol::SpanStatus::PermissionDenied,
-        StatusCode::NOT_FOUND => protocol::SpanStatus::NotFound,
-        StatusCode::TOO_MANY_REQUESTS => protocol::SpanStatus::ResourceExhausted,
-        status if status.is_client_error() => protocol::SpanStatus::InvalidArgument,
-        StatusCode::NOT_IMPLEMENTED => protocol::SpanStatus::Unimplemented,
-        StatusCode::SERVICE_UNAVAILABLE => protocol::SpanStatus::Unavailable,
-        status if status.is_server_error() => protocol::SpanStatus::InternalError,
-        StatusCode::CONFLICT => protocol::SpanStatus::AlreadyExists,
-        status if status.is_success() => protocol::SpanStatus::Ok,
-        _ => protocol::SpanStatus::UnknownError,
-    }
-}
-
 fn get_url_from_request<B>(request: &Request<B>) -> Option<url::Url> {
     let uri = request.uri().clone();
     let mut uri_parts = uri.into_parts();
@@ -341,4 +325,41 @@ mod tests {
         );
         assert_eq!(trace.parent_span_id, None);
     }
+
+    #[test]
+    fn transaction_continues_traceparent() {
+        let envelopes = sentry::test::with_captured_envelopes_options(
+            || {
+                tokio::runtime::Runtime::new().unwrap().block_on(async {
+                    let mut service =
+                        SentryHttpLayer::new()
+                            .enable_transaction()
+                            .layer(tower::service_fn(|_request| async {
+                                Ok::<_, std::convert::Infallible>(Response::new(()))
+                            }));
+                    let request = Request::builder()
+                        .uri("http://example.com/test")
+                        .header(
+                            "traceparent",
+                            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+                        )
+                        .body(())
+                        .unwrap();
+
+                    service.call(request).await.unwrap();
+                });
+            },
+            ClientOptions::new().traces_sample_rate(1.0),
+        );
+        let trace = trace_context_from_single_transaction(&envelopes);
+
+        assert_eq!(
+            trace.trace_id.to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+        assert_eq!(
+            trace.parent_span_id.map(|span_id| span_id.to_string()),
+            Some("00f067aa0ba902b7".to_owned())
+        );
+    }
 }
@@ -143,6 +143,25 @@
 //! This is because `axum` applies middleware in the opposite order as [`tower::ServiceBuilder`].
 //! Applying the layers in the wrong order can result in memory leaks.
 //!
+//! ## Instrumenting HTTP clients
+//!
+//! The `http` feature also offers the [`SentryHttpClientLayer`], which wraps a tower based HTTP
+//! client. Each outgoing request is recorded as an `http.client` span and an `http` breadcrumb,
+//! and the `sentry-trace` header is attached to requests whose URL matches the
+//! [`trace_propagation_targets`](sentry_core::ClientOptions::trace_propagation_targets) client
+//! option. The `baggage` header with the dynamic sampling context is not attached yet.
+//!
+//! ```rust
+//! # #[cfg(feature = "http")] {
+//! # type Body = String;
+//! let client = tower::ServiceBuilder::new()
+//!     .layer(sentry_tower::SentryHttpClientLayer::new())
+//!     .service(tower::service_fn(|_request: http::Request<Body>| async {
+//!         Ok::<_, std::convert::Infallible>(http::Response::new(Body::new()))
+//!     }));
+//! # }
+//! ```
+//!
 //! [`tower::ServiceBuilder`]: https://docs.rs/tower/latest/tower/struct.ServiceBuilder.html
 
 #![doc(html_favicon_url = "https://sentry-brand.storage.googleapis.com/favicon.ico")]
@@ -157,9 +176,13 @@ use sentry_core::{Hub, SentryFuture, SentryFutureExt};
 use tower_layer::Layer;
 use tower_service::Service;
 
+#[cfg(feature = "http")]
+mod client;
 #[cfg(feature = "http")]
 mod http;
 #[cfg(feature = "http")]
+pub use crate::client::*;
+#[cfg(feature = "http")]
 pub use crate::http::*;
 
 /// Provides a hub for each request
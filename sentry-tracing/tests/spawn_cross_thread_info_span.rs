@@ -0,0 +1,86 @@
+mod shared;
+
+use sentry::protocol::{Context, EnvelopeItem, Transaction};
+use sentry::{Envelope, Hub, HubSwitchGuard};
+
+/// Returns the only transaction in the envelopes.
+fn single_transaction(envelopes: Vec<Envelope>) -> Transaction<'static> {
+    let mut transactions: Vec<_> = envelopes
+        .into_iter()
+        .flat_map(|envelope| envelope.into_items())
+        .filter_map(|item| match item {
+            EnvelopeItem::Transaction(transaction) => Some(*transaction),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(transactions.len(), 1, "expected exactly one transaction");
+    transactions.pop().unwrap()
+}
+
+/// Asserts that the transaction has a child span with the given name, whose parent is the root
+/// span of the transaction.
+fn assert_child_span(transaction: &Transaction<'static>, name: &str) {
+    let Some(Context::Trace(trace)) = transaction.contexts.get("trace") else {
+        panic!("transaction should include trace context");
+    };
+    let child = transaction
+        .spans
+        .iter()
+        .find(|span| span.description.as_deref() == Some(name))
+        .unwrap_or_else(|| panic!("expected span {name:?} to be recorded in the transaction"));
+    assert_eq!(child.parent_span_id, Some(trace.span_id));
+}
+
+/// Tests that spans created on a thread spawned with `sentry::thread::spawn` are children of the
+/// span which was entered when spawning it, even though the `tracing` span stack is thread-local.
+#[test]
+fn spawn_thread_cross_thread_info_span() {
+    let _guard = HubSwitchGuard::new(Hub::new_from_top(Hub::current()).into());
+    let transport = shared::init_sentry(1.0);
+
+    {
+        let _parent = tracing::info_span!("spawn_thread_cross_thread_info_span").entered();
+        sentry::thread::spawn(|| {
+            let _child = tracing::info_span!("child").entered();
+        })
+        .join()
+        .expect("thread should complete successfully");
+    }
+
+    let transaction = single_transaction(transport.fetch_and_clear_envelopes());
+    assert_eq!(
+        transaction.name.as_deref(),
+        Some("spawn_thread_cross_thread_info_span")
+    );
+    assert_child_span(&transaction, "child");
+}
+
+/// Tests that spans created in a task spawned with `sentry::spawn` are children of the span which
+/// was entered when spawning it, even though the task runs on another thread.
+#[test]
+fn spawn_task_cross_thread_info_span() {
+    let _guard = HubSwitchGuard::new(Hub::new_from_top(Hub::current()).into());
+    let transport = shared::init_sentry(1.0);
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap();
+    {
+        let _parent = tracing::info_span!("spawn_task_cross_thread_info_span").entered();
+        runtime.block_on(async {
+            sentry::spawn(async {
+                let _child = tracing::info_span!("child").entered();
+            })
+            .await
+            .expect("task should complete successfully");
+        });
+    }
+
+    let transaction = single_transaction(transport.fetch_and_clear_envelopes());
+    assert_eq!(
+        transaction.name.as_deref(),
+        Some("spawn_task_cross_thread_info_span")
+    );
+    assert_child_span(&transaction, "child");
+}
@@ -3,8 +3,9 @@
 use std::mem;
 
 use crate::protocol::v7::{
-    Attachment, ClientReport, Envelope, EnvelopeItem, Event, ItemContainer, Log, Metric,
-    MonitorCheckIn, SessionAggregateItem, SessionAggregates, SessionUpdate, Span, Transaction,
+    Attachment, ClientReport, Envelope, EnvelopeItem, Event, Feedback, ItemContainer, Log, Metric,
+    MonitorCheckIn, SessionAggregateItem, SessionAggregates, SessionUpdate, Span, StreamedSpan,
+    Transaction,
 };
 
 use super::list::Iter as ClientReportItemIter;
@@ -152,6 +153,12 @@ impl LossSource for Metric {
     }
 }
 
+impl LossSource for StreamedSpan {
+    fn losses(&self) -> impl Iterator<Item = ItemLoss> + '_ {
+        ItemLossIter::new([ItemLoss::new(Category::Span, 1)])
+    }
+}
+
 impl LossSource for [ItemLoss] {
     fn losses(&self) -> impl Iterator<Item = ItemLoss> + '_ {
         self.iter().copied()
@@ -170,6 +177,10 @@ fn envelope_item_losses(envelope_item: &EnvelopeItem) -> ItemLossIter<'_> {
         EnvelopeItem::Attachment(attachment) => attachment_losses(attachment),
         EnvelopeItem::MonitorCheckIn(check_in) => monitor_check_in_losses(check_in),
         EnvelopeItem::ClientReport(client_report) => client_report_losses(client_report),
+        EnvelopeItem::Feedback(feedback) => feedback_losses(feedback),
+        EnvelopeItem::ProfileChunk(_) => {
+            ItemLossIter::new([ItemLoss::new(Category::ProfileChunk, 1)])
+        }
         EnvelopeItem::ItemContainer(item_container) => item_container_losses(item_container),
         EnvelopeItem::Raw => ItemLossIter::new([]),
     }
@@ -239,6 +250,11 @@ fn monitor_check_in_losses(_check_in: &MonitorCheckIn) -> ItemLossIter<'static>
     ItemLossIter::new([ItemLoss::new(Category::Monitor, 1)])
 }
 
+/// Returns feedback losses for a discarded user feedback item.
+fn feedback_losses(_feedback: &Feedback) -> ItemLossIter<'static> {
+    ItemLossIter::new([ItemLoss::new(Category::Feedback, 1)])
+}
+
 /// Returns the losses for a discarded client report.
 ///
 /// Client reports are never themselves recorded as losses; however, all the items recorded as
@@ -257,6 +273,10 @@ fn item_container_losses(item_container: &ItemContainer) -> ItemLossIter<'static
     match item_container {
         ItemContainer::Logs(logs) => log_losses(logs),
         ItemContainer::Metrics(metrics) => metric_losses(metrics),
+        ItemContainer::Spans(spans) => {
+            let quantity = spans.len().try_into().unwrap_or(u64::MAX);
+            ItemLossIter::new([ItemLoss::new(Category::Span, quantity)])
+        }
     }
 }
 
@@ -358,7 +378,7 @@ impl From<ClientReportItem> for ItemLoss {
 mod private {
     use super::{
         Attachment, ClientReport, Envelope, EnvelopeItem, Event, ItemContainer, ItemLoss, Log,
-        Metric, MonitorCheckIn, SessionAggregates, SessionUpdate, Span, Transaction,
+        Metric, MonitorCheckIn, SessionAggregates, SessionUpdate, Span, StreamedSpan, Transaction,
     };
 
     /// Prevents downstream implementations of [`LossSource`](super::LossSource).
@@ -377,5 +397,6 @@ mod private {
     impl Sealed for Span {}
     impl Sealed for Log {}
     impl Sealed for Metric {}
+    impl Sealed for StreamedSpan {}
     impl Sealed for [ItemLoss] {}
 }
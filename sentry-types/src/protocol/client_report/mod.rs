@@ -11,6 +11,7 @@ use crate::utils;
 
 pub use self::envelope_losses::{ItemLoss, LossSource};
 pub use self::list::Item;
+pub use self::relay_size::{log_byte_size, metric_byte_size, span_byte_size};
 
 mod envelope_losses;
 mod list;
@@ -55,6 +56,11 @@ indexed_enum! {
         BeforeSend,
         /// An event was dropped because of error event sampling.
         SampleRate,
+        /// An item was dropped by one of the filters configured in the client options, such as
+        /// `ignore_errors`.
+        Filtered,
+        /// An item was dropped from the offline cache because it exceeded its size or age budget.
+        CacheOverflow,
     }
 
     /// The category of data which was dropped.
@@ -115,6 +121,10 @@ indexed_enum! {
         ///
         /// [`TraceMetric`]: Category::TraceMetric
         TraceMetricByte,
+        /// A user feedback item.
+        Feedback,
+        /// A chunk of a continuous profile.
+        ProfileChunk,
     }
 }
 
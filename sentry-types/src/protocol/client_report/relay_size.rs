@@ -2,7 +2,7 @@
 
 use std::collections::BTreeMap;
 
-use crate::protocol::v7::{Log, LogAttribute, Metric, Value};
+use crate::protocol::v7::{Log, LogAttribute, Metric, StreamedSpan, Value};
 
 type AttributeMap<K> = BTreeMap<K, LogAttribute>;
 
@@ -12,7 +12,7 @@ type AttributeMap<K> = BTreeMap<K, LogAttribute>;
 /// attribute content bytes, clamped to at least `1`.
 ///
 /// [Relay's log size calculation]: https://github.com/getsentry/relay/blob/master/relay-ourlogs/src/size.rs
-pub(super) fn log_byte_size(log: &Log) -> u64 {
+pub fn log_byte_size(log: &Log) -> u64 {
     usize_to_u64(log.body.len())
         .saturating_add(log_attribute_map_byte_size(&log.attributes))
         .max(1)
@@ -24,13 +24,23 @@ pub(super) fn log_byte_size(log: &Log) -> u64 {
 /// plus numeric value bytes plus attribute content bytes, clamped to at least `1`.
 ///
 /// [Relay's trace metric size calculation]: https://github.com/getsentry/relay/blob/master/relay-server/src/processing/trace_metrics/utils.rs
-pub(super) fn metric_byte_size(metric: &Metric) -> u64 {
+pub fn metric_byte_size(metric: &Metric) -> u64 {
     usize_to_u64(metric.name.len())
         .saturating_add(8)
         .saturating_add(log_attribute_map_byte_size(&metric.attributes))
         .max(1)
 }
 
+/// Returns the estimated content size for a streamed span item.
+///
+/// The size is computed like the size of a log: span name bytes plus attribute content bytes,
+/// clamped to at least `1`.
+pub fn span_byte_size(span: &StreamedSpan) -> u64 {
+    usize_to_u64(span.name.len())
+        .saturating_add(log_attribute_map_byte_size(&span.attributes))
+        .max(1)
+}
+
 /// Converts `usize` byte counts to `u64`, saturating to `u64::MAX` on overflow.
 fn usize_to_u64(value: usize) -> u64 {
     u64::try_from(value).unwrap_or(u64::MAX)
@@ -11,8 +11,9 @@ use crate::{protocol::v7::ClientReport, utils::ts_rfc3339_opt};
 use super::v7 as protocol;
 
 use protocol::{
-    Attachment, AttachmentType, ClientSdkInfo, DynamicSamplingContext, Event, Log, Metric,
-    MonitorCheckIn, SessionAggregates, SessionUpdate, Transaction,
+    Attachment, AttachmentType, ClientSdkInfo, DynamicSamplingContext, Event, Feedback, Log,
+    Metric, MonitorCheckIn, ProfileChunk, SessionAggregates, SessionUpdate, StreamedSpan,
+    Transaction,
 };
 
 /// Raised if a envelope cannot be parsed from a given input.
@@ -133,9 +134,18 @@ enum EnvelopeItemType {
     /// Serialized to a `trace_metric` envelope item.
     #[serde(rename = "trace_metric")]
     MetricsContainer,
+    /// A container of Streamed Span items.
+    #[serde(rename = "span")]
+    SpansContainer,
     /// A client report.
     #[serde(rename = "client_report")]
     ClientReport,
+    /// A User Feedback Item type.
+    #[serde(rename = "feedback")]
+    Feedback,
+    /// A Profile Chunk Item type.
+    #[serde(rename = "profile_chunk")]
+    ProfileChunk,
 }
 
 /// An Envelope Item Header.
@@ -186,6 +196,16 @@ pub enum EnvelopeItem {
     MonitorCheckIn(MonitorCheckIn),
     /// An aggregated client report
     ClientReport(ClientReport),
+    /// A User Feedback Item.
+    ///
+    /// See the [User Feedback Item documentation](https://develop.sentry.dev/sdk/data-model/envelope-items/#user-feedback)
+    /// for more details.
+    Feedback(Box<Feedback>),
+    /// A Profile Chunk Item.
+    ///
+    /// See the [Profile Chunk documentation](https://develop.sentry.dev/sdk/telemetry/profiles/sample-format-v2/)
+    /// for more details.
+    ProfileChunk(Box<ProfileChunk>),
     /// A container for a list of multiple items.
     ItemContainer(ItemContainer),
     /// This is a sentinel item used to `filter` raw envelopes.
@@ -204,6 +224,8 @@ pub enum ItemContainer {
     Logs(Vec<Log>),
     /// A list of metrics.
     Metrics(Vec<Metric>),
+    /// A list of streamed spans.
+    Spans(Vec<StreamedSpan>),
 }
 
 impl ItemContainer {
@@ -212,6 +234,7 @@ impl ItemContainer {
         match self {
             Self::Logs(logs) => logs.len(),
             Self::Metrics(metrics) => metrics.len(),
+            Self::Spans(spans) => spans.len(),
         }
     }
 
@@ -220,6 +243,7 @@ impl ItemContainer {
         match self {
             Self::Logs(_) => "log",
             Self::Metrics(_) => "trace_metric",
+            Self::Spans(_) => "span",
         }
     }
 
@@ -228,6 +252,7 @@ impl ItemContainer {
         match self {
             Self::Logs(_) => "application/vnd.sentry.items.log+json",
             Self::Metrics(_) => "application/vnd.sentry.items.trace-metric+json",
+            Self::Spans(_) => "application/vnd.sentry.items.span.v2+json",
         }
     }
 
@@ -236,6 +261,7 @@ impl ItemContainer {
         match self {
             Self::Logs(logs) => logs.is_empty(),
             Self::Metrics(metrics) => metrics.is_empty(),
+            Self::Spans(spans) => spans.is_empty(),
         }
     }
 }
@@ -263,11 +289,18 @@ impl From<Vec<Metric>> for ItemContainer {
     }
 }
 
+impl From<Vec<StreamedSpan>> for ItemContainer {
+    fn from(spans: Vec<StreamedSpan>) -> Self {
+        Self::Spans(spans)
+    }
+}
+
 impl ItemContainer {
     fn item_type(&self) -> EnvelopeItemType {
         match self {
             Self::Logs(_) => EnvelopeItemType::LogsContainer,
             Self::Metrics(_) => EnvelopeItemType::MetricsContainer,
+            Self::Spans(_) => EnvelopeItemType::SpansContainer,
         }
     }
 }
@@ -282,6 +315,8 @@ impl EnvelopeItem {
             Self::Attachment(_) => Some(EnvelopeItemType::Attachment),
             Self::MonitorCheckIn(_) => Some(EnvelopeItemType::MonitorCheckIn),
             Self::ClientReport(_) => Some(EnvelopeItemType::ClientReport),
+            Self::Feedback(_) => Some(EnvelopeItemType::Feedback),
+            Self::ProfileChunk(_) => Some(EnvelopeItemType::ProfileChunk),
             Self::ItemContainer(container) => Some(container.item_type()),
             Self::Raw => None,
         }
@@ -342,12 +377,30 @@ impl From<Vec<Metric>> for EnvelopeItem {
     }
 }
 
+impl From<Vec<StreamedSpan>> for EnvelopeItem {
+    fn from(spans: Vec<StreamedSpan>) -> Self {
+        EnvelopeItem::ItemContainer(spans.into())
+    }
+}
+
 impl From<ClientReport> for EnvelopeItem {
     fn from(value: ClientReport) -> Self {
         EnvelopeItem::ClientReport(value)
     }
 }
 
+impl From<Feedback> for EnvelopeItem {
+    fn from(feedback: Feedback) -> Self {
+        EnvelopeItem::Feedback(feedback.into())
+    }
+}
+
+impl From<ProfileChunk> for EnvelopeItem {
+    fn from(profile_chunk: ProfileChunk) -> Self {
+        EnvelopeItem::ProfileChunk(profile_chunk.into())
+    }
+}
+
 /// An Iterator over the items of an Envelope.
 #[derive(Clone)]
 pub struct EnvelopeItemIter<'s> {
@@ -468,6 +521,8 @@ impl Envelope {
                 self.headers.event_id = Some(event.event_id);
             } else if let EnvelopeItem::Transaction(ref transaction) = item {
                 self.headers.event_id = Some(transaction.event_id);
+            } else if let EnvelopeItem::Feedback(ref feedback) = item {
+                self.headers.event_id = Some(feedback.event_id);
             }
         }
         items.push(item);
@@ -621,6 +676,10 @@ impl Envelope {
                 EnvelopeItem::ClientReport(client_report) => {
                     serde_json::to_writer(&mut item_buf, client_report)?
                 }
+                EnvelopeItem::Feedback(feedback) => serde_json::to_writer(&mut item_buf, feedback)?,
+                EnvelopeItem::ProfileChunk(profile_chunk) => {
+                    serde_json::to_writer(&mut item_buf, profile_chunk)?
+                }
                 EnvelopeItem::ItemContainer(container) => match container {
                     ItemContainer::Logs(logs) => {
                         let wrapper = ItemsSerdeWrapper { items: logs.into() };
@@ -632,6 +691,12 @@ impl Envelope {
                         };
                         serde_json::to_writer(&mut item_buf, &wrapper)?
                     }
+                    ItemContainer::Spans(spans) => {
+                        let wrapper = ItemsSerdeWrapper {
+                            items: spans.into(),
+                        };
+                        serde_json::to_writer(&mut item_buf, &wrapper)?
+                    }
                 },
                 EnvelopeItem::Raw => {
                     continue;
@@ -822,6 +887,12 @@ impl Envelope {
             EnvelopeItemType::ClientReport => {
                 serde_json::from_slice(payload).map(EnvelopeItem::ClientReport)
             }
+            EnvelopeItemType::Feedback => {
+                serde_json::from_slice(payload).map(EnvelopeItem::Feedback)
+            }
+            EnvelopeItemType::ProfileChunk => {
+                serde_json::from_slice(payload).map(EnvelopeItem::ProfileChunk)
+            }
             EnvelopeItemType::LogsContainer => {
                 serde_json::from_slice::<ItemsSerdeWrapper<_>>(payload)
                     .map(|x| EnvelopeItem::ItemContainer(ItemContainer::Logs(x.items.into())))
@@ -830,6 +901,10 @@ impl Envelope {
                 serde_json::from_slice::<ItemsSerdeWrapper<_>>(payload)
                     .map(|x| EnvelopeItem::ItemContainer(ItemContainer::Metrics(x.items.into())))
             }
+            EnvelopeItemType::SpansContainer => {
+                serde_json::from_slice::<ItemsSerdeWrapper<_>>(payload)
+                    .map(|x| EnvelopeItem::ItemContainer(ItemContainer::Spans(x.items.into())))
+            }
         }
         .map_err(EnvelopeError::InvalidItemPayload)?;
 
@@ -915,8 +990,10 @@ mod test {
     use crate::protocol::client_report::{Item, LossSource};
     use crate::protocol::v7::client_report::Category;
     use crate::protocol::v7::{
-        ClientReport, Level, LogLevel, MetricType, MonitorCheckInStatus, MonitorConfig,
-        MonitorSchedule, SampleRand, SessionAggregateItem, SessionAttributes, SessionStatus, Span,
+        ClientReport, Frame, Level, LogLevel, MetricType, MonitorCheckInContexts,
+        MonitorCheckInStatus, MonitorCheckInTrace, MonitorConfig, MonitorSchedule, ProfileSample,
+        SampleProfile, SampleRand, SessionAggregateItem, SessionAttributes, SessionStatus, Span,
+        ThreadMetadata,
     };
 
     fn to_str(envelope: Envelope) -> String {
@@ -1050,6 +1127,98 @@ mod test {
         )
     }
 
+    #[test]
+    fn test_feedback() {
+        let feedback = Feedback {
+            event_id: Uuid::parse_str("22d00b3f-d1b1-4b5d-8d20-49d138cd8a9c").unwrap(),
+            timestamp: timestamp("2020-07-20T14:51:14.296Z"),
+            message: "It crashed when I clicked save.".into(),
+            name: Some("Jane Doe".into()),
+            contact_email: Some("jane@example.com".into()),
+            associated_event_id: Some(
+                Uuid::parse_str("9ec79c33-ec99-42ab-8353-589fcb2e04dc").unwrap(),
+            ),
+            ..Default::default()
+        };
+        let envelope: Envelope = feedback.clone().into();
+        let serialized = to_str(envelope);
+        assert_eq!(
+            serialized,
+            r#"{"event_id":"22d00b3f-d1b1-4b5d-8d20-49d138cd8a9c"}
+{"type":"feedback","length":258}
+{"event_id":"22d00b3fd1b14b5d8d2049d138cd8a9c","timestamp":1595256674.296,"contexts":{"feedback":{"message":"It crashed when I clicked save.","contact_email":"jane@example.com","name":"Jane Doe","associated_event_id":"9ec79c33-ec99-42ab-8353-589fcb2e04dc"}}}
+"#
+        );
+
+        let deserialized = Envelope::from_slice(serialized.as_bytes()).unwrap();
+        let Some(EnvelopeItem::Feedback(deserialized_feedback)) = deserialized.items().next()
+        else {
+            panic!("expected a feedback item");
+        };
+        assert_eq!(deserialized_feedback.message, feedback.message);
+        assert_eq!(
+            deserialized_feedback.associated_event_id,
+            feedback.associated_event_id
+        );
+        assert_eq!(serialized, to_str(deserialized));
+    }
+
+    #[test]
+    fn test_profile_chunk() {
+        let mut thread_metadata = Map::new();
+        thread_metadata.insert(
+            "1".to_owned(),
+            ThreadMetadata {
+                name: Some("main".into()),
+            },
+        );
+        let profile_chunk = ProfileChunk {
+            chunk_id: Uuid::parse_str("22d00b3f-d1b1-4b5d-8d20-49d138cd8a9c").unwrap(),
+            profiler_id: Uuid::parse_str("9ec79c33-ec99-42ab-8353-589fcb2e04dc").unwrap(),
+            release: Some("app@1.0.0".into()),
+            profile: SampleProfile {
+                samples: vec![ProfileSample {
+                    timestamp: timestamp("2020-07-20T14:51:14.296Z"),
+                    stack_id: 0,
+                    thread_id: "1".into(),
+                }],
+                stacks: vec![vec![0, 1]],
+                frames: vec![
+                    Frame {
+                        function: Some("app::work".into()),
+                        ..Default::default()
+                    },
+                    Frame {
+                        function: Some("app::main".into()),
+                        ..Default::default()
+                    },
+                ],
+                thread_metadata,
+            },
+            ..Default::default()
+        };
+        let envelope: Envelope = profile_chunk.clone().into();
+        let serialized = to_str(envelope);
+        assert_eq!(
+            serialized,
+            r#"{}
+{"type":"profile_chunk","length":350}
+{"version":"2","chunk_id":"22d00b3fd1b14b5d8d2049d138cd8a9c","profiler_id":"9ec79c33ec9942ab8353589fcb2e04dc","platform":"rust","release":"app@1.0.0","profile":{"samples":[{"timestamp":1595256674.296,"stack_id":0,"thread_id":"1"}],"stacks":[[0,1]],"frames":[{"function":"app::work"},{"function":"app::main"}],"thread_metadata":{"1":{"name":"main"}}}}
+"#
+        );
+
+        let deserialized = Envelope::from_slice(serialized.as_bytes()).unwrap();
+        let Some(EnvelopeItem::ProfileChunk(deserialized_chunk)) = deserialized.items().next()
+        else {
+            panic!("expected a profile chunk item");
+        };
+        assert_eq!(
+            deserialized_chunk.profile.stacks,
+            profile_chunk.profile.stacks
+        );
+        assert_eq!(serialized, to_str(deserialized));
+    }
+
     #[test]
     fn test_monitor_checkin() {
         let check_in_id = Uuid::parse_str("22d00b3f-d1b1-4b5d-8d20-49d138cd8a9c").unwrap();
@@ -1070,6 +1239,7 @@ mod test {
                 failure_issue_threshold: None,
                 recovery_threshold: None,
             }),
+            contexts: None,
         };
         let envelope: Envelope = check_in.into();
         assert_eq!(
@@ -1101,6 +1271,7 @@ mod test {
                 failure_issue_threshold: Some(4),
                 recovery_threshold: Some(7),
             }),
+            contexts: None,
         };
         let envelope: Envelope = check_in.into();
         assert_eq!(
@@ -1112,6 +1283,39 @@ mod test {
         )
     }
 
+    #[test]
+    fn test_monitor_checkin_with_trace() {
+        let check_in_id = Uuid::parse_str("22d00b3f-d1b1-4b5d-8d20-49d138cd8a9c").unwrap();
+        let trace_id = "4c79f60c11214eb38604f4ae0781bfb2".parse().unwrap();
+
+        let check_in = MonitorCheckIn {
+            check_in_id,
+            monitor_slug: "my-monitor".into(),
+            status: MonitorCheckInStatus::InProgress,
+            duration: None,
+            environment: None,
+            monitor_config: None,
+            contexts: Some(MonitorCheckInContexts {
+                trace: Some(MonitorCheckInTrace { trace_id }),
+            }),
+        };
+        let envelope: Envelope = check_in.clone().into();
+        let serialized = to_str(envelope);
+        assert_eq!(
+            serialized,
+            r#"{}
+{"type":"check_in","length":170}
+{"check_in_id":"22d00b3fd1b14b5d8d2049d138cd8a9c","monitor_slug":"my-monitor","status":"in_progress","contexts":{"trace":{"trace_id":"4c79f60c11214eb38604f4ae0781bfb2"}}}
+"#
+        );
+
+        let deserialized = Envelope::from_slice(serialized.as_bytes()).unwrap();
+        let Some(EnvelopeItem::MonitorCheckIn(deserialized)) = deserialized.items().next() else {
+            panic!("expected check-in item");
+        };
+        assert_eq!(deserialized, &check_in);
+    }
+
     #[test]
     fn test_event_with_attachment() {
         let event_id = Uuid::parse_str("22d00b3f-d1b1-4b5d-8d20-49d138cd8a9c").unwrap();
@@ -1382,6 +1586,62 @@ some content
         assert!(actual.eq(expected.into_iter()));
     }
 
+    #[test]
+    fn test_span_container_header() {
+        let spans: EnvelopeItem = vec![StreamedSpan {
+            trace_id: "335e53d614474acc9f89e632b776cc28".parse().unwrap(),
+            span_id: "d42cee9fc3e74f5c".parse().unwrap(),
+            parent_span_id: None,
+            name: "GET /users".into(),
+            status: protocol::StreamedSpanStatus::Ok,
+            is_segment: true,
+            start_timestamp: timestamp("2026-03-02T13:36:02.000Z"),
+            end_timestamp: timestamp("2026-03-02T13:36:03.000Z"),
+            attributes: [("sentry.op".to_owned(), "http.server".into())].into(),
+        }]
+        .into();
+
+        let mut envelope = Envelope::new();
+        envelope.add_item(spans);
+
+        let expected = [
+            serde_json::json!({}),
+            serde_json::json!({
+                "type": "span",
+                "item_count": 1,
+                "content_type": "application/vnd.sentry.items.span.v2+json"
+            }),
+            serde_json::json!({
+                "items": [{
+                    "trace_id": "335e53d614474acc9f89e632b776cc28",
+                    "span_id": "d42cee9fc3e74f5c",
+                    "name": "GET /users",
+                    "status": "ok",
+                    "is_segment": true,
+                    "start_timestamp": 1772458562,
+                    "end_timestamp": 1772458563,
+                    "attributes": {
+                        "sentry.op": {"type": "string", "value": "http.server"}
+                    }
+                }]
+            }),
+        ];
+
+        let serialized = to_str(envelope);
+        let actual = serialized
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).expect("envelope has invalid JSON"));
+
+        assert!(actual.eq(expected.into_iter()));
+        assert_eq!(
+            Envelope::from_slice(serialized.as_bytes())
+                .unwrap()
+                .items()
+                .count(),
+            1
+        );
+    }
+
     // Test all possible item types in a single envelope
     #[test]
     fn test_deserialize_serialized() {
@@ -1479,6 +1739,12 @@ some content
         envelope.add_item(attachment);
         envelope.add_item(logs);
         envelope.add_item(metrics);
+        envelope.add_item(Feedback {
+            event_id: Uuid::parse_str("22d00b3f-d1b1-4b5d-8d20-49d138cd8a9e").unwrap(),
+            timestamp: timestamp("2020-07-20T14:51:14.296Z"),
+            message: "feedback".into(),
+            ..Default::default()
+        });
 
         let serialized = to_str(envelope);
         let deserialized = Envelope::from_slice(serialized.as_bytes()).unwrap();
@@ -1532,12 +1798,27 @@ some content
             environment: None,
             duration: None,
             monitor_config: None,
+            contexts: None,
         }
         .into();
 
         assert_eq!(collect_losses(&envelope), vec![(Category::Monitor, 1)]);
     }
 
+    #[test]
+    fn losses_on_drop_maps_feedback_to_feedback() {
+        let envelope: Envelope = Feedback::default().into();
+
+        assert_eq!(collect_losses(&envelope), vec![(Category::Feedback, 1)]);
+    }
+
+    #[test]
+    fn losses_on_drop_maps_profile_chunk_to_profile_chunk() {
+        let envelope: Envelope = ProfileChunk::default().into();
+
+        assert_eq!(collect_losses(&envelope), vec![(Category::ProfileChunk, 1)]);
+    }
+
     #[test]
     fn losses_on_drop_sums_session_aggregate_status_counts() {
         let envelope: Envelope = SessionAggregates {
@@ -1594,6 +1875,24 @@ some content
         );
     }
 
+    #[test]
+    fn losses_on_drop_counts_streamed_spans() {
+        let span = StreamedSpan {
+            trace_id: Default::default(),
+            span_id: Default::default(),
+            parent_span_id: None,
+            name: "span".into(),
+            status: Default::default(),
+            is_segment: false,
+            start_timestamp: SystemTime::UNIX_EPOCH,
+            end_timestamp: SystemTime::UNIX_EPOCH,
+            attributes: Map::new(),
+        };
+        let envelope: Envelope = vec![span.clone(), span].into();
+
+        assert_eq!(collect_losses(&envelope), vec![(Category::Span, 2)]);
+    }
+
     #[test]
     fn losses_on_drop_counts_minimal_log_bytes() {
         let envelope: Envelope = vec![Log {
@@ -0,0 +1,176 @@
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize, Serializer};
+use uuid::Uuid;
+
+use super::v7::{Map, User};
+use crate::utils::ts_seconds_float;
+
+/// User feedback, such as a user's description of what happened before a crash.
+///
+/// Feedback can be linked to a previously captured event by setting the
+/// [`associated_event_id`](Self::associated_event_id) to the ID returned when capturing the event.
+///
+/// Feedback is sent as a `feedback` envelope item, whose payload is an event carrying the feedback
+/// in its `feedback` context.
+///
+/// See the [User Feedback documentation](https://develop.sentry.dev/sdk/data-model/envelope-items/#user-feedback)
+/// for more details.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(into = "FeedbackEvent", from = "FeedbackEvent")]
+pub struct Feedback {
+    /// The ID of the feedback itself.
+    pub event_id: Uuid,
+    /// The time at which the feedback was given.
+    pub timestamp: SystemTime,
+    /// The feedback message (required).
+    pub message: String,
+    /// The name of the user giving feedback.
+    pub name: Option<String>,
+    /// The email address under which the user giving feedback may be contacted.
+    pub contact_email: Option<String>,
+    /// The ID of the event this feedback is about.
+    pub associated_event_id: Option<Uuid>,
+    /// The URL of the page or resource the feedback was given on.
+    pub url: Option<String>,
+    /// The release of the application the feedback was given in.
+    pub release: Option<String>,
+    /// The environment of the application the feedback was given in.
+    pub environment: Option<String>,
+    /// The user giving feedback.
+    pub user: Option<User>,
+    /// Tags for the feedback.
+    pub tags: Map<String, String>,
+}
+
+impl Default for Feedback {
+    fn default() -> Self {
+        Self {
+            event_id: crate::random_uuid(),
+            timestamp: SystemTime::now(),
+            message: Default::default(),
+            name: Default::default(),
+            contact_email: Default::default(),
+            associated_event_id: Default::default(),
+            url: Default::default(),
+            release: Default::default(),
+            environment: Default::default(),
+            user: Default::default(),
+            tags: Default::default(),
+        }
+    }
+}
+
+/// The serialized form of [`Feedback`]: an event with a `feedback` context.
+#[derive(Serialize, Deserialize)]
+struct FeedbackEvent {
+    #[serde(default = "crate::random_uuid", serialize_with = "serialize_id")]
+    event_id: Uuid,
+    #[serde(default = "SystemTime::now", with = "ts_seconds_float")]
+    timestamp: SystemTime,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    release: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    environment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    user: Option<User>,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    tags: Map<String, String>,
+    contexts: FeedbackContexts,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FeedbackContexts {
+    feedback: FeedbackContext,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FeedbackContext {
+    message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    contact_email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    associated_event_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+}
+
+fn serialize_id<S: Serializer>(uuid: &Uuid, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_some(&uuid.as_simple().to_string())
+}
+
+impl From<Feedback> for FeedbackEvent {
+    fn from(feedback: Feedback) -> Self {
+        let Feedback {
+            event_id,
+            timestamp,
+            message,
+            name,
+            contact_email,
+            associated_event_id,
+            url,
+            release,
+            environment,
+            user,
+            tags,
+        } = feedback;
+
+        Self {
+            event_id,
+            timestamp,
+            release,
+            environment,
+            user,
+            tags,
+            contexts: FeedbackContexts {
+                feedback: FeedbackContext {
+                    message,
+                    contact_email,
+                    name,
+                    associated_event_id,
+                    url,
+                },
+            },
+        }
+    }
+}
+
+impl From<FeedbackEvent> for Feedback {
+    fn from(event: FeedbackEvent) -> Self {
+        let FeedbackEvent {
+            event_id,
+            timestamp,
+            release,
+            environment,
+            user,
+            tags,
+            contexts:
+                FeedbackContexts {
+                    feedback:
+                        FeedbackContext {
+                            message,
+                            contact_email,
+                            name,
+                            associated_event_id,
+                            url,
+                        },
+                },
+        } = event;
+
+        Self {
+            event_id,
+            timestamp,
+            message,
+            name,
+            contact_email,
+            associated_event_id,
+            url,
+            release,
+            environment,
+            user,
+            tags,
+        }
+    }
+}
@@ -16,6 +16,8 @@ pub use v7 as latest;
 mod attachment;
 mod client_report;
 mod envelope;
+mod feedback;
 mod monitor;
+mod profile_chunk;
 mod session;
 mod unit;
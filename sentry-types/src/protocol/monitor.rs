@@ -3,6 +3,7 @@ use std::{error::Error, fmt::Display};
 use serde::{Deserialize, Serialize, Serializer};
 use uuid::Uuid;
 
+use super::v7::TraceId;
 use crate::crontab_validator;
 
 /// Error type for errors with parsing a crontab schedule
@@ -163,6 +164,46 @@ pub struct MonitorConfig {
     pub recovery_threshold: Option<u64>,
 }
 
+impl MonitorConfig {
+    /// Creates a monitor configuration with the given schedule, leaving all other settings to
+    /// their defaults on Sentry.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_types::protocol::v7::{MonitorConfig, MonitorSchedule};
+    ///
+    /// let config = MonitorConfig {
+    ///     timezone: Some("Europe/Vienna".into()),
+    ///     ..MonitorConfig::new(MonitorSchedule::from_crontab("0 3 * * *").unwrap())
+    /// };
+    /// ```
+    pub fn new(schedule: MonitorSchedule) -> Self {
+        Self {
+            schedule,
+            checkin_margin: None,
+            max_runtime: None,
+            timezone: None,
+            failure_issue_threshold: None,
+            recovery_threshold: None,
+        }
+    }
+}
+
+/// The contexts of a monitor check-in.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct MonitorCheckInContexts {
+    /// The trace the check-in is linked to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<MonitorCheckInTrace>,
+}
+
+/// Links a monitor check-in to a trace.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct MonitorCheckInTrace {
+    /// The ID of the trace the check-in is linked to.
+    pub trace_id: TraceId,
+}
+
 fn serialize_id<S: Serializer>(uuid: &Uuid, serializer: S) -> Result<S::Ok, S::Error> {
     serializer.serialize_some(&uuid.as_simple())
 }
@@ -195,4 +236,8 @@ pub struct MonitorCheckIn {
     /// this object.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub monitor_config: Option<MonitorConfig>,
+
+    /// Contexts of the check-in, such as the trace it is linked to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contexts: Option<MonitorCheckInContexts>,
 }
@@ -0,0 +1,109 @@
+use std::borrow::Cow;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize, Serializer};
+use uuid::Uuid;
+
+use super::v7::{ClientSdkInfo, Frame, Map};
+use crate::utils::ts_seconds_float;
+
+/// A chunk of a continuous profile.
+///
+/// A continuous profile is sent as a series of chunks, each containing the stack samples taken
+/// during a limited amount of time. All chunks of a profile share the same
+/// [`profiler_id`](Self::profiler_id), which also links the profile to the transactions recorded
+/// while it was running.
+///
+/// See the [Profile Chunk documentation](https://develop.sentry.dev/sdk/telemetry/profiles/sample-format-v2/)
+/// for more details.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProfileChunk {
+    /// The version of the profile format, which is always `"2"`.
+    #[serde(default = "default_version")]
+    pub version: Cow<'static, str>,
+    /// The ID of this chunk.
+    #[serde(serialize_with = "serialize_id")]
+    pub chunk_id: Uuid,
+    /// The ID of the profiler session this chunk belongs to.
+    #[serde(serialize_with = "serialize_id")]
+    pub profiler_id: Uuid,
+    /// The platform of the profiled application.
+    #[serde(default = "default_platform")]
+    pub platform: Cow<'static, str>,
+    /// The release of the profiled application.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release: Option<String>,
+    /// The environment of the profiled application.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+    /// Information about the SDK which recorded the profile.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_sdk: Option<ClientSdkInfo>,
+    /// The samples of the profile.
+    pub profile: SampleProfile,
+}
+
+impl Default for ProfileChunk {
+    fn default() -> Self {
+        Self {
+            version: default_version(),
+            chunk_id: crate::random_uuid(),
+            profiler_id: crate::random_uuid(),
+            platform: default_platform(),
+            release: Default::default(),
+            environment: Default::default(),
+            client_sdk: Default::default(),
+            profile: Default::default(),
+        }
+    }
+}
+
+/// The stack samples of a [`ProfileChunk`].
+///
+/// Frames and stacks are deduplicated: each [`ProfileSample`] refers to an entry in
+/// [`stacks`](Self::stacks), which is a list of indexes into [`frames`](Self::frames), ordered
+/// from the innermost to the outermost frame.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SampleProfile {
+    /// The stack samples, ordered by timestamp.
+    pub samples: Vec<ProfileSample>,
+    /// The distinct stacks referenced by the samples.
+    pub stacks: Vec<Vec<usize>>,
+    /// The distinct frames referenced by the stacks.
+    pub frames: Vec<Frame>,
+    /// Metadata of the sampled threads, keyed by thread ID.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub thread_metadata: Map<String, ThreadMetadata>,
+}
+
+/// A single stack sample of a thread.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProfileSample {
+    /// The time at which the sample was taken.
+    #[serde(with = "ts_seconds_float")]
+    pub timestamp: SystemTime,
+    /// The index of the sampled stack in [`SampleProfile::stacks`].
+    pub stack_id: usize,
+    /// The ID of the sampled thread.
+    pub thread_id: String,
+}
+
+/// Metadata of a sampled thread.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ThreadMetadata {
+    /// The name of the thread.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+fn default_version() -> Cow<'static, str> {
+    Cow::Borrowed("2")
+}
+
+fn default_platform() -> Cow<'static, str> {
+    Cow::Borrowed("rust")
+}
+
+fn serialize_id<S: Serializer>(uuid: &Uuid, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_some(&uuid.as_simple().to_string())
+}
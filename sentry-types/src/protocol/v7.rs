@@ -27,7 +27,9 @@ use crate::utils::{display_from_str_opt, ts_rfc3339_opt, ts_seconds_float};
 pub use self::client_report::Report as ClientReport;
 pub use super::attachment::*;
 pub use super::envelope::*;
+pub use super::feedback::Feedback;
 pub use super::monitor::*;
+pub use super::profile_chunk::{ProfileChunk, ProfileSample, SampleProfile, ThreadMetadata};
 pub use super::session::*;
 pub use super::unit::Unit;
 
@@ -35,7 +37,10 @@ pub use super::unit::Unit;
 ///
 /// [Client Reports]: https://develop.sentry.dev/sdk/telemetry/client-reports/
 pub mod client_report {
-    pub use super::super::client_report::{Category, Item, ItemLoss, LossSource, Reason, Report};
+    pub use super::super::client_report::{
+        log_byte_size, metric_byte_size, span_byte_size, Category, Item, ItemLoss, LossSource,
+        Reason, Report,
+    };
 }
 
 /// An arbitrary (JSON) value.
@@ -1111,6 +1116,8 @@ pub enum Context {
     Otel(Box<OtelContext>),
     /// HTTP response data.
     Response(Box<ResponseContext>),
+    /// Profiling data.
+    Profile(Box<ProfileContext>),
     /// Generic other context data.
     #[serde(rename = "unknown")]
     Other(Map<String, Value>),
@@ -1129,6 +1136,7 @@ impl Context {
             Context::Gpu(..) => "gpu",
             Context::Otel(..) => "otel",
             Context::Response(..) => "response",
+            Context::Profile(..) => "profile",
             Context::Other(..) => "unknown",
         }
     }
@@ -1202,6 +1210,15 @@ pub struct DeviceContext {
     /// The timezone of the device.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub timezone: Option<String>,
+    /// The number of logical processors of the device.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub processor_count: Option<u32>,
+    /// The description of the processor, usually its model name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_description: Option<String>,
+    /// The frequency of the processor in MHz.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub processor_frequency: Option<f64>,
     /// Additional arbitrary fields for forwards compatibility.
     #[serde(flatten)]
     pub other: Map<String, Value>,
@@ -1386,6 +1403,16 @@ pub struct ResponseContext {
     pub data: Option<Value>,
 }
 
+/// Profiling context, linking a transaction to a continuous profile.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProfileContext {
+    /// The ID of the profiler session which was running during the transaction.
+    ///
+    /// See [`ProfileChunk::profiler_id`].
+    #[serde(serialize_with = "event::serialize_id")]
+    pub profiler_id: Uuid,
+}
+
 /// Holds the identifier for a Span
 #[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Hash)]
 #[serde(try_from = "String", into = "String")]
@@ -1541,9 +1568,10 @@ into_context!(Trace, TraceContext);
 into_context!(Gpu, GpuContext);
 into_context!(Otel, OtelContext);
 into_context!(Response, ResponseContext);
+into_context!(Profile, ProfileContext);
 
 const INFERABLE_CONTEXTS: &[&str] = &[
-    "device", "os", "runtime", "app", "browser", "trace", "gpu", "otel", "response",
+    "device", "os", "runtime", "app", "browser", "trace", "gpu", "otel", "response", "profile",
 ];
 
 struct ContextsVisitor;
@@ -2024,6 +2052,16 @@ impl fmt::Display for SpanStatus {
     }
 }
 
+/// A named numeric measurement of a [`Transaction`], such as the number of processed rows.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Measurement {
+    /// The measured value.
+    pub value: f64,
+    /// The unit of the value.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<Unit>,
+}
+
 /// Represents a tracing transaction.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Transaction<'a> {
@@ -2086,6 +2124,9 @@ pub struct Transaction<'a> {
     /// Optionally the server (or device) name of this event.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub server_name: Option<Cow<'a, str>>,
+    /// Named numeric measurements of the transaction.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub measurements: Map<String, Measurement>,
 }
 
 impl Default for Transaction<'_> {
@@ -2106,6 +2147,7 @@ impl Default for Transaction<'_> {
             contexts: Default::default(),
             request: Default::default(),
             server_name: Default::default(),
+            measurements: Default::default(),
         }
     }
 }
@@ -2134,6 +2176,7 @@ impl<'a> Transaction<'a> {
             contexts: self.contexts,
             request: self.request,
             server_name: self.server_name.map(|x| Cow::Owned(x.into_owned())),
+            measurements: self.measurements,
         }
     }
 
@@ -2416,6 +2459,52 @@ pub struct Metric {
     pub attributes: Map<Cow<'static, str>, LogAttribute>,
 }
 
+/// The status of a [`StreamedSpan`].
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamedSpanStatus {
+    /// The operation completed successfully.
+    #[default]
+    Ok,
+    /// The operation failed.
+    Error,
+}
+
+/// A single span, sent on its own rather than as part of a transaction.
+///
+/// Streamed spans are sent in batches in a `span` item container. The root span of a local span
+/// tree is called a segment, and is marked with [`is_segment`](Self::is_segment).
+///
+/// See the [Span Protocol documentation](https://develop.sentry.dev/sdk/telemetry/spans/span-protocol/)
+/// for more details.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StreamedSpan {
+    /// The ID of the trace the span belongs to.
+    pub trace_id: TraceId,
+    /// The ID of the span.
+    pub span_id: SpanId,
+    /// The ID of the parent span, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_span_id: Option<SpanId>,
+    /// The name of the span.
+    pub name: String,
+    /// The status of the span.
+    #[serde(default)]
+    pub status: StreamedSpanStatus,
+    /// Whether the span is the root of a local span tree.
+    #[serde(default)]
+    pub is_segment: bool,
+    /// The time at which the span started.
+    #[serde(with = "ts_seconds_float")]
+    pub start_timestamp: SystemTime,
+    /// The time at which the span finished.
+    #[serde(with = "ts_seconds_float")]
+    pub end_timestamp: SystemTime,
+    /// Additional key-value attributes.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub attributes: Map<String, LogAttribute>,
+}
+
 /// An ID that identifies an organization in the Sentry backend.
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
 pub struct OrganizationId(u64);
@@ -107,6 +107,42 @@ mod test_event {
         );
     }
 
+    #[test]
+    fn test_transaction_measurements() {
+        let mut transaction = v7::Transaction {
+            event_id: event_id(),
+            ..Default::default()
+        };
+        transaction.measurements.insert(
+            "rows_processed".into(),
+            v7::Measurement {
+                value: 1234.0,
+                unit: None,
+            },
+        );
+        transaction.measurements.insert(
+            "cache_hit_ratio".into(),
+            v7::Measurement {
+                value: 0.75,
+                unit: Some(v7::Unit::Ratio),
+            },
+        );
+
+        let value = serde_json::to_value(&transaction).unwrap();
+        assert_eq!(
+            value["measurements"],
+            serde_json::json!({
+                "cache_hit_ratio": { "value": 0.75, "unit": "ratio" },
+                "rows_processed": { "value": 1234.0 },
+            })
+        );
+        let roundtripped: v7::Transaction<'_> = serde_json::from_value(value).unwrap();
+        assert_eq!(roundtripped.measurements, transaction.measurements);
+
+        let value = serde_json::to_value(v7::Transaction::default()).unwrap();
+        assert!(value.get("measurements").is_none());
+    }
+
     #[test]
     fn test_logger() {
         let event = v7::Event {
@@ -1160,6 +1196,9 @@ mod test_contexts {
                         external_free_storage: Some(2_097_152),
                         boot_time: Some(systime(2018, 2, 8, 12, 52, 12, 0)),
                         timezone: Some("Europe/Vienna".into()),
+                        processor_count: Some(6),
+                        cpu_description: Some("Apple A11 Bionic".into()),
+                        processor_frequency: Some(2390.0),
                         other: Default::default(),
                     }
                     .into(),
@@ -1179,7 +1218,8 @@ mod test_contexts {
              \"memory_size\":3137978368,\"free_memory\":322781184,\"usable_memory\":2843525120,\
              \"storage_size\":63989469184,\"free_storage\":31994734592,\"external_storage_size\":\
              2097152,\"external_free_storage\":2097152,\"boot_time\":\"2018-02-08T12:52:12Z\",\
-             \"timezone\":\"Europe/Vienna\"}}}"
+             \"timezone\":\"Europe/Vienna\",\"processor_count\":6,\"cpu_description\":\
+             \"Apple A11 Bionic\",\"processor_frequency\":2390.0}}}"
         );
     }
 
@@ -0,0 +1,198 @@
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use sentry_backtrace::ThreadHandle;
+use sentry_core::protocol::{Event, Exception, Level, Mechanism, Thread};
+use sentry_core::{sentry_debug, Hub};
+
+use crate::WatchdogIntegration;
+
+/// How often the watchdog checks whether heartbeats are overdue.
+const CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long to wait for a stalled thread to handle the signal capturing its stack.
+const STACK_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// The heartbeats watched by the watchdog thread.
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    entries: Vec::new(),
+    running: false,
+});
+
+struct Registry {
+    entries: Vec<Weak<Entry>>,
+    /// Whether the watchdog thread is running. It exits once all heartbeats were dropped.
+    running: bool,
+}
+
+#[derive(Debug)]
+pub(crate) struct Entry {
+    name: String,
+    deadline: Duration,
+    hub: Arc<Hub>,
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    last_beat: Instant,
+    /// The thread running the heartbeat's code, if known.
+    thread: Option<ThreadHandle>,
+    /// Whether the current stall was already reported.
+    reported: bool,
+}
+
+/// A heartbeat of a thread or task, which is watched by the watchdog.
+///
+/// The heartbeat must be sent with [`beat`](Heartbeat::beat) at least once per deadline, or the
+/// thread or task is reported as stalled. The heartbeat is no longer watched once it and all of its
+/// clones are dropped.
+#[derive(Clone, Debug)]
+pub struct Heartbeat {
+    entry: Arc<Entry>,
+}
+
+impl Heartbeat {
+    /// Registers a heartbeat for the current thread.
+    ///
+    /// The `name` identifies the thread in reported events. Stalls are reported on the current
+    /// hub, and include the stack of the current thread.
+    pub fn register<N>(name: N, deadline: Duration) -> Self
+    where
+        N: Into<String>,
+    {
+        Self::new(
+            name.into(),
+            deadline,
+            Hub::current(),
+            Some(ThreadHandle::current()),
+        )
+    }
+
+    pub(crate) fn new(
+        name: String,
+        deadline: Duration,
+        hub: Arc<Hub>,
+        thread: Option<ThreadHandle>,
+    ) -> Self {
+        let entry = Arc::new(Entry {
+            name,
+            deadline,
+            hub,
+            state: Mutex::new(State {
+                last_beat: Instant::now(),
+                thread,
+                reported: false,
+            }),
+        });
+
+        let mut registry = lock(&REGISTRY);
+        registry.entries.push(Arc::downgrade(&entry));
+        if !registry.running {
+            let spawned = thread::Builder::new()
+                .name("sentry-watchdog".into())
+                .spawn(watch);
+            match spawned {
+                Ok(_) => registry.running = true,
+                Err(err) => sentry_debug!("Failed to spawn the watchdog thread: {}", err),
+            }
+        }
+
+        Self { entry }
+    }
+
+    /// Sends the heartbeat, resetting its deadline.
+    pub fn beat(&self) {
+        let mut state = lock(&self.entry.state);
+        state.last_beat = Instant::now();
+        state.reported = false;
+    }
+
+    /// Returns the name of the heartbeat.
+    pub fn name(&self) -> &str {
+        &self.entry.name
+    }
+
+    /// Sets the thread running the heartbeat's code, whose stack is captured on stalls.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn set_thread(&self, thread: Option<ThreadHandle>) {
+        lock(&self.entry.state).thread = thread;
+    }
+}
+
+/// The loop of the watchdog thread.
+fn watch() {
+    loop {
+        thread::sleep(CHECK_INTERVAL);
+        let entries: Vec<_> = {
+            let mut registry = lock(&REGISTRY);
+            registry.entries.retain(|entry| entry.strong_count() > 0);
+            if registry.entries.is_empty() {
+                registry.running = false;
+                return;
+            }
+            registry.entries.iter().filter_map(Weak::upgrade).collect()
+        };
+
+        for entry in entries {
+            entry.check();
+        }
+    }
+}
+
+impl Entry {
+    /// Reports the heartbeat if it is overdue and was not reported yet.
+    fn check(&self) {
+        let (stalled_for, thread) = {
+            let mut state = lock(&self.state);
+            let stalled_for = state.last_beat.elapsed();
+            if state.reported || stalled_for <= self.deadline {
+                return;
+            }
+            state.reported = true;
+            (stalled_for, state.thread.clone())
+        };
+
+        self.hub
+            .with_integration(|integration: &WatchdogIntegration| {
+                sentry_debug!("Heartbeat `{}` is overdue, reporting it", self.name);
+                let thread = thread.filter(|_| integration.attach_stacks);
+                self.hub.capture_event(self.event(stalled_for, thread));
+            });
+    }
+
+    fn event(&self, stalled_for: Duration, thread: Option<ThreadHandle>) -> Event<'static> {
+        let mut thread = thread.map(|thread| thread.capture(STACK_TIMEOUT));
+        let stacktrace = thread.as_mut().and_then(|thread| thread.stacktrace.take());
+        let thread_id = thread.as_ref().and_then(|thread| thread.id.clone());
+
+        Event {
+            exception: vec![Exception {
+                ty: "WatchdogTimeout".into(),
+                value: Some(format!(
+                    "`{}` did not send a heartbeat for {} ms, exceeding its deadline of {} ms",
+                    self.name,
+                    stalled_for.as_millis(),
+                    self.deadline.as_millis()
+                )),
+                mechanism: Some(Mechanism {
+                    ty: "watchdog".into(),
+                    handled: Some(false),
+                    ..Default::default()
+                }),
+                stacktrace,
+                thread_id,
+                ..Default::default()
+            }]
+            .into(),
+            threads: thread.into_iter().collect::<Vec<Thread>>().into(),
+            level: Level::Error,
+            ..Default::default()
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
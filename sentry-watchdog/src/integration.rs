@@ -0,0 +1,42 @@
+use sentry_core::Integration;
+
+/// The Sentry Watchdog Integration.
+///
+/// Enables capturing events for stalled [`Heartbeat`](crate::Heartbeat)s. Heartbeats registered
+/// on hubs whose client does not have this integration are watched, but not reported.
+#[derive(Debug)]
+pub struct WatchdogIntegration {
+    pub(crate) attach_stacks: bool,
+}
+
+impl Default for WatchdogIntegration {
+    fn default() -> Self {
+        Self {
+            attach_stacks: true,
+        }
+    }
+}
+
+impl WatchdogIntegration {
+    /// Creates a new Watchdog Integration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether the stack of a stalled thread is attached to its event.
+    ///
+    /// The stack is captured on the watchdog thread by interrupting the stalled thread with a
+    /// `SIGPROF` signal, see [`sentry_backtrace::ThreadHandle`] for the caveats. The watchdog waits
+    /// at most 100 milliseconds for the stack, so that a thread which cannot handle the signal
+    /// does not delay the reports of other heartbeats. Defaults to `true`.
+    #[must_use]
+    pub fn attach_stacks(self, attach_stacks: bool) -> Self {
+        Self { attach_stacks }
+    }
+}
+
+impl Integration for WatchdogIntegration {
+    fn name(&self) -> &'static str {
+        "watchdog"
+    }
+}
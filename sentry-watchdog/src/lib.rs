@@ -0,0 +1,68 @@
+//! The Sentry Watchdog integration.
+//!
+//! The watchdog reports threads and async tasks which stall without panicking, for example
+//! because they are stuck in a deadlock or in an endless loop. Threads and tasks register a
+//! [`Heartbeat`] with a deadline, and then call [`Heartbeat::beat`] regularly, for example once
+//! per iteration of their main loop. When a heartbeat is not sent within its deadline, an event
+//! with a `watchdog` mechanism is captured.
+//!
+//! The event contains the stack of the stalled thread where supported, see
+//! [`sentry_backtrace::ThreadHandle`] for the caveats and
+//! [`WatchdogIntegration::attach_stacks`] to disable this. It is captured on the hub of the thread
+//! or task, so that it is linked to the span which is active on its scope. A stall is only
+//! reported once, until the next heartbeat is sent.
+//!
+//! Events are only captured if the [`WatchdogIntegration`] is enabled on the client.
+//!
+//! # Threads
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use sentry_watchdog::{Heartbeat, WatchdogIntegration};
+//!
+//! let options = sentry_core::ClientOptions::new().add_integration(WatchdogIntegration::new());
+//!
+//! std::thread::spawn(|| {
+//!     let heartbeat = Heartbeat::register("job-worker", Duration::from_secs(30));
+//!     # let jobs: Vec<fn()> = Vec::new();
+//!     for job in jobs {
+//!         heartbeat.beat();
+//!         job();
+//!     }
+//! });
+//! ```
+//!
+//! # Tokio
+//!
+//! With the `tokio` feature, [`tokio::spawn`](crate::tokio::spawn) spawns a task which is watched by a heartbeat. If
+//! the task stalls while it is being polled, the stack of the runtime thread polling it is
+//! captured.
+//!
+//! ```
+//! # #[cfg(feature = "tokio")]
+//! # async fn run() {
+//! use std::time::Duration;
+//!
+//! sentry_watchdog::tokio::spawn("event-loop", Duration::from_secs(30), |heartbeat| async move {
+//!     loop {
+//!         heartbeat.beat();
+//!         tokio::task::yield_now().await;
+//!     }
+//! });
+//! # }
+//! ```
+
+#![doc(html_favicon_url = "https://sentry-brand.storage.googleapis.com/favicon.ico")]
+#![doc(html_logo_url = "https://sentry-brand.storage.googleapis.com/sentry-glyph-black.png")]
+#![cfg_attr(doc_cfg, feature(doc_cfg))]
+#![warn(missing_docs)]
+
+mod heartbeat;
+mod integration;
+#[cfg(feature = "tokio")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "tokio")))]
+pub mod tokio;
+
+pub use heartbeat::Heartbeat;
+pub use integration::WatchdogIntegration;
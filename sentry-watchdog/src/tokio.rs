@@ -0,0 +1,65 @@
+//! Watching tokio tasks.
+//!
+//! **Feature:** `tokio` (*disabled by default*)
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use sentry_backtrace::ThreadHandle;
+use sentry_core::{Hub, SentryFutureExt};
+use tokio::task::JoinHandle;
+
+use crate::Heartbeat;
+
+/// Spawns a task on the current tokio runtime, which is watched by a heartbeat.
+///
+/// The task is created by calling `task` with the heartbeat, which it must send at least once per
+/// `deadline`. The task runs on a new hub forked from the current one, on which stalls are
+/// reported. If the task stalls while it is being polled, for example because it blocks the
+/// runtime thread, the stack of that thread is captured. Otherwise, the task is not running, and
+/// the event contains no stack.
+///
+/// # Panics
+///
+/// Panics if called outside of a tokio runtime, like [`tokio::spawn`].
+pub fn spawn<N, F, T>(name: N, deadline: Duration, task: F) -> JoinHandle<T::Output>
+where
+    N: Into<String>,
+    F: FnOnce(Heartbeat) -> T,
+    T: Future + Send + 'static,
+    T::Output: Send + 'static,
+{
+    let hub = Arc::new(Hub::new_from_top(Hub::current()));
+    let heartbeat = Heartbeat::new(name.into(), deadline, hub.clone(), None);
+    let future = Watched {
+        future: task(heartbeat.clone()),
+        heartbeat,
+    };
+    tokio::spawn(future.bind_hub(hub))
+}
+
+/// A future which records the thread polling it in its heartbeat.
+struct Watched<F> {
+    future: F,
+    heartbeat: Heartbeat,
+}
+
+impl<F> Future for Watched<F>
+where
+    F: Future,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let heartbeat = self.heartbeat.clone();
+        // https://doc.rust-lang.org/std/pin/index.html#pinning-is-structural-for-field
+        let future = unsafe { self.map_unchecked_mut(|s| &mut s.future) };
+        heartbeat.set_thread(Some(ThreadHandle::current()));
+        let poll = future.poll(cx);
+        heartbeat.set_thread(None);
+        poll
+    }
+}
@@ -0,0 +1,134 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use sentry_core::protocol::{Context, Event};
+use sentry_core::{ClientOptions, Hub, TransactionContext};
+use sentry_watchdog::{Heartbeat, WatchdogIntegration};
+
+const DEADLINE: Duration = Duration::from_millis(200);
+
+fn options() -> ClientOptions {
+    ClientOptions::new()
+        .traces_sample_rate(1.0)
+        .add_integration(WatchdogIntegration::new())
+}
+
+/// Blocks the current thread until the watchdog captured an event on the current hub.
+#[inline(never)]
+fn stall() {
+    let hub = Hub::current();
+    let start = Instant::now();
+    while hub.last_event_id().is_none() && start.elapsed() < Duration::from_secs(10) {
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+fn assert_stalled(event: &Event<'static>, name: &str) {
+    let exception = &event.exception[0];
+    assert_eq!(exception.ty, "WatchdogTimeout");
+    assert!(exception
+        .value
+        .as_ref()
+        .unwrap()
+        .starts_with(&format!("`{name}`")));
+    assert_eq!(exception.mechanism.as_ref().unwrap().ty, "watchdog");
+
+    if cfg!(any(target_os = "linux", target_os = "macos")) {
+        let frames = &exception.stacktrace.as_ref().unwrap().frames;
+        assert!(frames
+            .iter()
+            .any(|frame| frame.function.as_deref() == Some("test_watchdog::stall")));
+    }
+}
+
+#[test]
+fn test_stalled_thread() {
+    let events = sentry_core::test::with_captured_events_options(
+        || {
+            let transaction =
+                sentry_core::start_transaction(TransactionContext::new("worker", "task"));
+            sentry_core::configure_scope(|scope| scope.set_span(Some(transaction.clone().into())));
+
+            let heartbeat = Heartbeat::register("worker", DEADLINE);
+            heartbeat.beat();
+            stall();
+            transaction.finish();
+        },
+        options(),
+    );
+
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_stalled(event, "worker");
+
+    let thread = &event.threads[0];
+    assert!(!thread.current);
+    assert_eq!(event.exception[0].thread_id, thread.id);
+    if cfg!(target_os = "linux") {
+        assert!(thread.id.is_some());
+    }
+
+    let Some(Context::Trace(trace)) = event.contexts.get("trace") else {
+        panic!("the event should have a trace context");
+    };
+    assert_eq!(trace.op.as_deref(), Some("task"));
+}
+
+#[test]
+fn test_without_stacks() {
+    let events = sentry_core::test::with_captured_events_options(
+        || {
+            let heartbeat = Heartbeat::register("worker", DEADLINE);
+            heartbeat.beat();
+            stall();
+        },
+        ClientOptions::new().add_integration(WatchdogIntegration::new().attach_stacks(false)),
+    );
+
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event.exception[0].ty, "WatchdogTimeout");
+    assert!(event.exception[0].stacktrace.is_none());
+    assert!(event.threads.is_empty());
+}
+
+#[test]
+fn test_regular_heartbeats() {
+    let events = sentry_core::test::with_captured_events_options(
+        || {
+            let heartbeat = Heartbeat::register("busy", DEADLINE);
+            let start = Instant::now();
+            while start.elapsed() < Duration::from_millis(600) {
+                heartbeat.beat();
+                thread::sleep(Duration::from_millis(10));
+            }
+        },
+        options(),
+    );
+    assert!(events.is_empty());
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn test_tokio_task() {
+    let events = sentry_core::test::with_captured_events_options(
+        || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap();
+            runtime.block_on(async {
+                sentry_watchdog::tokio::spawn("event-loop", DEADLINE, |heartbeat| async move {
+                    heartbeat.beat();
+                    // Blocks the runtime thread, like a blocking call would.
+                    stall();
+                })
+                .await
+                .unwrap();
+            });
+        },
+        options(),
+    );
+
+    assert_eq!(events.len(), 1);
+    assert_stalled(&events[0], "event-loop");
+}
@@ -21,6 +21,11 @@ use crate::{ClientOptions, Integration};
 /// 3. [`ContextIntegration`] (`feature = "contexts"`)
 /// 4. [`PanicIntegration`] (`feature = "panic"`)
 /// 5. [`ProcessStacktraceIntegration`] (`feature = "backtrace"`)
+/// 6. [`DedupeIntegration`] (`feature = "dedupe"`)
+///
+/// The [`DedupeIntegration`] drops errors which are duplicates of the
+/// previously captured event within a short window. It is not added if a [`DedupeIntegration`]
+/// was already defined manually, so that it can be configured.
 ///
 /// Some integrations can be used multiple times, however, the
 /// [`PanicIntegration`] can not, and it will not pick up custom panic
@@ -44,6 +49,7 @@ use crate::{ClientOptions, Integration};
 /// [`ContextIntegration`]: integrations/contexts/struct.ContextIntegration.html
 /// [`PanicIntegration`]: integrations/panic/struct.PanicIntegration.html
 /// [`ProcessStacktraceIntegration`]: integrations/backtrace/struct.ProcessStacktraceIntegration.html
+/// [`DedupeIntegration`]: integrations/dedupe/struct.DedupeIntegration.html
 pub fn apply_defaults(mut opts: ClientOptions) -> ClientOptions {
     if opts.transport.is_none() {
         opts.transport = Some(Arc::new(DefaultTransportFactory));
@@ -74,6 +80,18 @@ pub fn apply_defaults(mut opts: ClientOptions) -> ClientOptions {
         {
             integrations.push(Arc::new(sentry_backtrace::ProcessStacktraceIntegration));
         }
+        #[cfg(feature = "dedupe")]
+        {
+            use sentry_dedupe::DedupeIntegration;
+
+            let is_configured = opts
+                .integrations
+                .iter()
+                .any(|integration| integration.as_any().is::<DedupeIntegration>());
+            if !is_configured {
+                integrations.push(Arc::new(DedupeIntegration::default()));
+            }
+        }
         integrations.extend(opts.integrations);
         opts.integrations = integrations;
     }
@@ -133,4 +151,25 @@ mod tests {
         let opts = apply_defaults(Default::default());
         assert_eq!(opts.environment.unwrap(), "env-from-env");
     }
+
+    #[cfg(feature = "dedupe")]
+    #[test]
+    fn test_configured_dedupe_integration_replaces_default() {
+        use sentry_dedupe::DedupeIntegration;
+
+        let count = |opts: &ClientOptions| {
+            opts.integrations
+                .iter()
+                .filter(|integration| integration.as_any().is::<DedupeIntegration>())
+                .count()
+        };
+
+        let opts = apply_defaults(Default::default());
+        assert_eq!(count(&opts), 1);
+
+        let opts =
+            ClientOptions::new().add_integration(DedupeIntegration::new().compare_messages(false));
+        let opts = apply_defaults(opts);
+        assert_eq!(count(&opts), 1);
+    }
 }
@@ -62,13 +62,22 @@
 //! | --------------    | ------- | -------------- | ---------- | ---------------------------------------------------------------------------------------- |
 //! | `backtrace`       | ✅      | 🔌             |            |                                                                                          |
 //! | `contexts`        | ✅      | 🔌             |            |                                                                                          |
+//! | `dedupe`          | ✅      | 🔌             |            |                                                                                          |
 //! | `panic`           | ✅      | 🔌             |            |                                                                                          |
 //! | `transport`       | ✅      |                |            |                                                                                          |
 //! | `anyhow`          |         | 🔌             |            |                                                                                          |
+//! | `data-scrubber`   |         | 🔌             |            | Requires extra setup; See [`sentry-data-scrubber`]'s documentation.                      |
+//! | `signal`          |         | 🔌             |            | Requires extra setup; See [`sentry-signal`]'s documentation.                             |
+//! | `watchdog`        |         | 🔌             |            | Requires extra setup; See [`sentry-watchdog`]'s documentation.                           |
+//! | `watchdog-tokio`  |         | 🔌             |            | Requires extra setup; See [`sentry-watchdog`]'s documentation.                           |
+//! | `tokio-runtime`   |         | 🔌             |            | Requires extra setup; See [`sentry-tokio`]'s documentation.                              |
+//! | `reqwest-middleware` |         | 🔌             |            | Requires extra setup; See [`sentry-reqwest`]'s documentation.                         |
 //! | `test`            |         |                |            |                                                                                          |
 //! | `logs`            | ✅      |                |            | Enables structured log capture APIs.                                                     |
 //! | `metrics`         | ✅      |                |            | Enables metrics capture APIs.                                                            |
 //! | `debug-images`    | ✅      | 🔌             |            |                                                                                          |
+//! | `profiling`       |         |                |            | Enables continuous CPU profiling; See [`profiling`](crate::profiling).                   |
+//! | `tokio`           |         |                |            | Enables [`spawn`] for tokio tasks; Enabled by `reqwest`.                                 |
 //! | `log`             |         | 🔌             |            | Requires extra setup; See [`sentry-log`]'s documentation.                                |
 //! | `slog`            |         | 🔌             |            | Requires extra setup; See [`sentry-slog`]'s documentation.                               |
 //! | `reqwest`         | ✅      |                |            |                                                                                          |
@@ -77,6 +86,8 @@
 //! | `ureq`            |         |                |            | `ureq` transport support using `rustls` by default                                       |
 //! | `ureq-native-tls` |         |                |            |                                                                                          |
 //! | `curl`            |         |                |            |                                                                                          |
+//! | `gzip`            |         |                |            | Enables gzip compression of envelopes; See [`CompressionOptions`].                       |
+//! | `zstd`            |         |                |            | Enables Zstandard compression of envelopes; See [`CompressionOptions`].                  |
 //! | `actix`           |         | 🔌             |            | Requires extra setup; See [`sentry-actix`]'s documentation.                              |
 //! | `tower`           |         | 🔌             |            | Requires extra setup; See [`sentry-tower`]'s documentation.                              |
 //! | `tracing`         |         | 🔌             |            | Requires extra setup; See [`sentry-tracing`]'s documentation.                            |
@@ -84,6 +95,11 @@
 //!
 //! [`sentry-log`]: https://crates.io/crates/sentry-log
 //! [`sentry-slog`]: https://crates.io/crates/sentry-slog
+//! [`sentry-data-scrubber`]: https://crates.io/crates/sentry-data-scrubber
+//! [`sentry-signal`]: https://crates.io/crates/sentry-signal
+//! [`sentry-watchdog`]: https://crates.io/crates/sentry-watchdog
+//! [`sentry-tokio`]: https://crates.io/crates/sentry-tokio
+//! [`sentry-reqwest`]: https://crates.io/crates/sentry-reqwest
 //! [`sentry-actix`]: https://crates.io/crates/sentry-actix
 //! [`sentry-tower`]: https://crates.io/crates/sentry-tower
 //! [`sentry-tracing`]: https://crates.io/crates/sentry-tracing
@@ -92,6 +108,7 @@
 //! ## Default features
 //! - `backtrace`: Enables backtrace support.
 //! - `contexts`: Enables capturing device, OS, and Rust contexts.
+//! - `dedupe`: Enables dropping consecutive duplicate events.
 //! - `panic`: Enables support for capturing panics.
 //! - `transport`: Enables the default transport, which is currently `reqwest` with `native-tls`.
 //! - `debug-images`: Enables capturing metadata about the loaded shared libraries.
@@ -100,12 +117,21 @@
 //!
 //! ## Debugging/Testing
 //! - `anyhow`: Enables support for the `anyhow` crate.
-//! - `test`: Enables testing support.
+//! - `test`: Enables testing support, including a [mock Relay server](crate::test::MockRelay)
+//!   for testing the transports.
 //!
 //! ## Metrics
 //! - `metrics`: Enables [`sentry::metrics`](crate::metrics) APIs for counters, gauges, and
 //!   distributions.
 //!
+//! ## Profiling
+//! - `profiling`: Enables [`sentry::profiling`](crate::profiling) APIs for continuous CPU profiling
+//!   of registered threads.
+//!
+//! ## Async
+//! - `tokio`: Enables [`sentry::spawn`](crate::spawn) and [`SentryRuntimeBuilderExt`], which bind
+//!   new hubs to tokio tasks and runtime threads. Enabled by the `reqwest` transport.
+//!
 //! ## Logging
 //! - `log`: Enables support for the `log` crate.
 //! - `slog`: Enables support for the `slog` crate.
@@ -122,6 +148,14 @@
 //!
 //! ## Integrations
 //! - `actix`: Enables support for the `actix-web` crate.
+//! - `data-scrubber`: Enables scrubbing secrets and personal data from events, transactions, logs
+//!   and metrics.
+//! - `signal`: Enables reporting native crashes caused by fatal signals, such as segmentation
+//!   faults.
+//! - `watchdog`: Enables reporting threads which stop sending heartbeats.
+//! - `watchdog-tokio`: Enables reporting `tokio` tasks which stop sending heartbeats.
+//! - `tokio-runtime`: Enables reporting slow polls of `tokio` tasks and runtime metrics.
+//! - `reqwest-middleware`: Enables instrumenting outgoing requests of `reqwest` clients.
 //! - `tower`: Enables support for the `tower` crate and those using it.
 //! - `tracing`: Enables support for the `tracing` crate and those using it.
 //! - `opentelemetry`: Enables support for the `opentelemetry` and `opentelemetry-sdk` crates.
@@ -136,6 +170,8 @@
 
 mod defaults;
 mod init;
+#[cfg(feature = "test")]
+pub mod test;
 pub mod transports;
 
 // re-export from core
@@ -214,10 +250,18 @@ pub mod integrations {
     #[cfg_attr(doc_cfg, doc(cfg(feature = "contexts")))]
     #[doc(inline)]
     pub use sentry_contexts as contexts;
+    #[cfg(feature = "data-scrubber")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "data-scrubber")))]
+    #[doc(inline)]
+    pub use sentry_data_scrubber as data_scrubber;
     #[cfg(feature = "debug-images")]
     #[cfg_attr(doc_cfg, doc(cfg(feature = "debug_images")))]
     #[doc(inline)]
     pub use sentry_debug_images as debug_images;
+    #[cfg(feature = "dedupe")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "dedupe")))]
+    #[doc(inline)]
+    pub use sentry_dedupe as dedupe;
     #[cfg(feature = "log")]
     #[cfg_attr(doc_cfg, doc(cfg(feature = "log")))]
     #[doc(inline)]
@@ -230,10 +274,22 @@ pub mod integrations {
     #[cfg_attr(doc_cfg, doc(cfg(feature = "panic")))]
     #[doc(inline)]
     pub use sentry_panic as panic;
+    #[cfg(feature = "reqwest-middleware")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "reqwest-middleware")))]
+    #[doc(inline)]
+    pub use sentry_reqwest as reqwest;
+    #[cfg(feature = "signal")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "signal")))]
+    #[doc(inline)]
+    pub use sentry_signal as signal;
     #[cfg(feature = "slog")]
     #[cfg_attr(doc_cfg, doc(cfg(feature = "slog")))]
     #[doc(inline)]
     pub use sentry_slog as slog;
+    #[cfg(feature = "tokio-runtime")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "tokio-runtime")))]
+    #[doc(inline)]
+    pub use sentry_tokio as tokio;
     #[cfg(feature = "tower")]
     #[cfg_attr(doc_cfg, doc(cfg(feature = "tower")))]
     #[doc(inline)]
@@ -242,7 +298,43 @@ pub mod integrations {
     #[cfg_attr(doc_cfg, doc(cfg(feature = "tracing")))]
     #[doc(inline)]
     pub use sentry_tracing as tracing;
+    #[cfg(feature = "watchdog")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "watchdog")))]
+    #[doc(inline)]
+    pub use sentry_watchdog as watchdog;
 }
 
 #[doc(inline)]
 pub use sentry_core::types::protocol::latest as protocol;
+
+/// Continuous CPU profiling.
+///
+/// **Feature:** `profiling` (*disabled by default*)
+///
+/// Threads opt into profiling by calling [`register_thread`]. While at least one thread is
+/// registered, a sampler thread captures the stacks of all registered threads every 10
+/// milliseconds, and sends them to Sentry as profile chunks once a minute and when the last thread
+/// is unregistered. Transactions started while the profiler is running are linked to its profile
+/// chunks.
+///
+/// Whether a profiler session is recorded is decided when it starts, according to
+/// [`ClientOptions::profile_session_sample_rate`]. Profiling is only supported on Linux and macOS,
+/// where stacks are captured by interrupting the registered threads with a `SIGPROF` signal and
+/// walking their frame pointers.
+///
+/// # Examples
+///
+/// ```
+/// let _sentry = sentry::init(sentry::ClientOptions::new().profile_session_sample_rate(1.0));
+///
+/// let _profiling = sentry::profiling::register_thread();
+/// // Work done on this thread is profiled until `_profiling` is dropped.
+/// ```
+///
+/// [`ClientOptions::profile_session_sample_rate`]: crate::ClientOptions::profile_session_sample_rate
+#[cfg(feature = "profiling")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "profiling")))]
+pub mod profiling {
+    pub use sentry_backtrace::profiling::*;
+    pub use sentry_core::profiling::*;
+}
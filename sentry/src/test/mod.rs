@@ -0,0 +1,28 @@
+//! This provides testing functionality for building tests.
+//!
+//! **Feature:** `test` (*disabled by default*)
+//!
+//! Besides the utilities of [`sentry_core::test`] for capturing events in-process, this module
+//! provides a [`MockRelay`], a local HTTP server standing in for Sentry, which can be used to test
+//! what the transports actually send: their headers, and how they handle rate limits, server
+//! errors and slow responses.
+//!
+//! # Example usage
+//!
+//! ```
+//! use sentry::test::with_captured_events;
+//! use sentry::{capture_message, Level};
+//!
+//! let events = with_captured_events(|| {
+//!     capture_message("Hello World!", Level::Warning);
+//! });
+//! assert_eq!(events.len(), 1);
+//! assert_eq!(events[0].message.as_ref().unwrap(), "Hello World!");
+//! ```
+
+#[doc(inline)]
+pub use sentry_core::test::*;
+
+mod relay;
+
+pub use relay::{MockRelay, MockResponse, RelayRequest};
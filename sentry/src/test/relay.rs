@@ -0,0 +1,393 @@
+//! A local mock of Relay, the service ingesting envelopes on Sentry's side.
+
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use sentry_core::protocol::Envelope;
+use sentry_core::types::Auth;
+
+/// The public key of the DSN returned by [`MockRelay::dsn`].
+const PUBLIC_KEY: &str = "public";
+/// The path envelopes are accepted on, for the project of [`MockRelay::dsn`].
+const ENVELOPE_PATH: &str = "/api/1/envelope/";
+/// How long connections wait for a client to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A local HTTP server which stands in for Sentry in tests of the transports.
+///
+/// The server accepts envelopes posted to the endpoint of [`MockRelay::dsn`], and records every
+/// request it receives. Requests are rejected like Relay would reject them:
+///
+/// - requests to other paths are answered with `404 Not Found`,
+/// - requests without a valid `X-Sentry-Auth` header for the DSN's public key are answered with
+///   `401 Unauthorized`,
+/// - bodies which cannot be decompressed or parsed as an envelope are answered with
+///   `400 Bad Request`.
+///
+/// All other requests are answered with the [`MockResponse`]s scripted with
+/// [`MockRelay::respond_with`], in order, and with `200 OK` once the script is exhausted.
+///
+/// The server shuts down when it is dropped.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use sentry::test::{MockRelay, MockResponse};
+///
+/// let relay = MockRelay::start();
+/// relay.respond_with(MockResponse::rate_limited("60:error:organization"));
+///
+/// let _guard = sentry::init(sentry::ClientOptions::new().dsn(&relay.dsn()));
+/// sentry::capture_message("Hello World!", sentry::Level::Info);
+///
+/// let requests = relay.wait_for_requests(1, Duration::from_secs(5));
+/// assert_eq!(requests[0].status(), 429);
+/// assert_eq!(requests[0].auth().unwrap().public_key(), "public");
+/// ```
+#[derive(Debug)]
+pub struct MockRelay {
+    addr: SocketAddr,
+    state: Arc<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    responses: Mutex<VecDeque<MockResponse>>,
+    requests: Mutex<Vec<RelayRequest>>,
+    received: Condvar,
+    shutdown: AtomicBool,
+}
+
+impl MockRelay {
+    /// Starts a server listening on a random local port.
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind mock relay");
+        let addr = listener
+            .local_addr()
+            .expect("mock relay should have an address");
+        let state = Arc::new(State::default());
+
+        let accepting = state.clone();
+        thread::Builder::new()
+            .name("sentry-mock-relay".into())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    if accepting.shutdown.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let Ok(stream) = stream else { continue };
+                    let state = accepting.clone();
+                    thread::spawn(move || {
+                        // Errors mean that the client went away, which the recorded requests show.
+                        let _ = handle_connection(stream, &state);
+                    });
+                }
+            })
+            .expect("failed to spawn mock relay thread");
+
+        Self { addr, state }
+    }
+
+    /// Returns a DSN pointing to this server.
+    pub fn dsn(&self) -> String {
+        format!("http://{PUBLIC_KEY}@{}/1", self.addr)
+    }
+
+    /// Appends a response to the script of responses for accepted requests.
+    pub fn respond_with(&self, response: MockResponse) {
+        lock(&self.state.responses).push_back(response);
+    }
+
+    /// Returns all requests received so far.
+    pub fn requests(&self) -> Vec<RelayRequest> {
+        lock(&self.state.requests).clone()
+    }
+
+    /// Returns the envelopes of all requests answered with a success status so far.
+    pub fn envelopes(&self) -> Vec<Envelope> {
+        lock(&self.state.requests)
+            .iter()
+            .filter(|request| (200..300).contains(&request.status))
+            .filter_map(|request| request.envelope.clone())
+            .collect()
+    }
+
+    /// Waits until at least `count` requests were received, and returns all requests.
+    ///
+    /// # Panics
+    ///
+    /// Panics if fewer than `count` requests were received within `timeout`.
+    pub fn wait_for_requests(&self, count: usize, timeout: Duration) -> Vec<RelayRequest> {
+        let deadline = Instant::now().checked_add(timeout);
+        let mut requests = lock(&self.state.requests);
+        while requests.len() < count {
+            let remaining = deadline
+                .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+                .unwrap_or(timeout);
+            if remaining.is_zero() {
+                panic!(
+                    "expected {count} requests to the mock relay, but received {}",
+                    requests.len()
+                );
+            }
+            requests = self
+                .state
+                .received
+                .wait_timeout(requests, remaining)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+        requests.clone()
+    }
+
+    /// Asserts that exactly `count` requests were received so far.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of received requests differs.
+    #[track_caller]
+    pub fn assert_request_count(&self, count: usize) {
+        let received = lock(&self.state.requests).len();
+        assert_eq!(
+            received, count,
+            "expected {count} requests to the mock relay, but received {received}"
+        );
+    }
+}
+
+impl Drop for MockRelay {
+    fn drop(&mut self) {
+        self.state.shutdown.store(true, Ordering::SeqCst);
+        // Wake up the accepting thread, so that it notices the shutdown.
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+/// A scripted response of a [`MockRelay`].
+#[derive(Clone, Debug)]
+pub struct MockResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    delay: Duration,
+}
+
+impl MockResponse {
+    /// Creates a response with the given HTTP status code.
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Creates a `200 OK` response.
+    pub fn ok() -> Self {
+        Self::status(200)
+    }
+
+    /// Creates a `429 Too Many Requests` response with the given `X-Sentry-Rate-Limits` header.
+    ///
+    /// The header consists of comma separated limits in the form
+    /// `<seconds>:<categories>:<scope>`, for example `60:error;transaction:organization`.
+    pub fn rate_limited(limits: &str) -> Self {
+        Self::status(429).header("X-Sentry-Rate-Limits", limits)
+    }
+
+    /// Adds a header to the response.
+    #[must_use]
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Delays the response by `delay`, to simulate a slow server.
+    #[must_use]
+    pub fn delay(self, delay: Duration) -> Self {
+        Self { delay, ..self }
+    }
+}
+
+/// A request received by a [`MockRelay`].
+#[derive(Clone, Debug)]
+pub struct RelayRequest {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    envelope: Option<Envelope>,
+    status: u16,
+}
+
+impl RelayRequest {
+    /// Returns the HTTP method of the request.
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// Returns the path of the request, including any query string.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the value of a header, ignoring the case of its name.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the body of the request as received, before any decompression.
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Returns the parsed `X-Sentry-Auth` header of the request, if it is valid.
+    pub fn auth(&self) -> Option<Auth> {
+        self.header("x-sentry-auth")?.parse().ok()
+    }
+
+    /// Returns the decompressed and parsed envelope of the request, if it is valid.
+    pub fn envelope(&self) -> Option<&Envelope> {
+        self.envelope.as_ref()
+    }
+
+    /// Returns the HTTP status code the request was answered with.
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn handle_connection(stream: TcpStream, state: &State) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_owned();
+    let path = parts.next().unwrap_or_default().to_owned();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            headers.push((key.trim().to_owned(), value.trim().to_owned()));
+        }
+    }
+
+    let mut request = RelayRequest {
+        method,
+        path,
+        headers,
+        body: Vec::new(),
+        envelope: None,
+        status: 200,
+    };
+    let content_length = request
+        .header("content-length")
+        .and_then(|length| length.parse().ok())
+        .unwrap_or(0);
+    request.body = vec![0; content_length];
+    reader.read_exact(&mut request.body)?;
+
+    request.envelope = decompress(request.header("content-encoding"), &request.body)
+        .and_then(|body| Envelope::from_slice(&body).ok());
+    let response = if request.method != "POST" || request.path != ENVELOPE_PATH {
+        MockResponse::status(404)
+    } else if request
+        .auth()
+        .is_none_or(|auth| auth.public_key() != PUBLIC_KEY)
+    {
+        MockResponse::status(401)
+    } else if request.envelope.is_none() {
+        MockResponse::status(400)
+    } else {
+        lock(&state.responses)
+            .pop_front()
+            .unwrap_or_else(MockResponse::ok)
+    };
+    request.status = response.status;
+
+    thread::sleep(response.delay);
+    let result = write_response(stream, &response);
+
+    lock(&state.requests).push(request);
+    state.received.notify_all();
+    result
+}
+
+fn write_response(mut stream: TcpStream, response: &MockResponse) -> io::Result<()> {
+    let body = if (200..300).contains(&response.status) {
+        "{}"
+    } else {
+        ""
+    };
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        reason(response.status),
+        body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(body.as_bytes())?;
+    stream.flush()
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        413 => "Payload Too Large",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "Unknown",
+    }
+}
+
+/// Decompresses a request body according to its `Content-Encoding`.
+///
+/// Returns `None` for encodings which are unknown or whose feature is not enabled.
+fn decompress(encoding: Option<&str>, body: &[u8]) -> Option<Vec<u8>> {
+    match encoding {
+        None | Some("identity") => Some(body.to_vec()),
+        #[cfg(feature = "gzip")]
+        Some("gzip") => {
+            let mut decompressed = Vec::new();
+            flate2::read::GzDecoder::new(body)
+                .read_to_end(&mut decompressed)
+                .ok()?;
+            Some(decompressed)
+        }
+        #[cfg(feature = "zstd")]
+        Some("zstd") => zstd::decode_all(body).ok(),
+        Some(_) => None,
+    }
+}
@@ -0,0 +1,103 @@
+//! Compression of serialized envelope bodies.
+
+use sentry_core::{CompressionAlgorithm, CompressionOptions};
+
+use crate::sentry_debug;
+
+/// Compresses a serialized envelope `body` according to the `options`.
+///
+/// Returns the body to send, along with the value of the `Content-Encoding` header if the body
+/// was compressed. Bodies below the configured threshold are returned unchanged, as are bodies
+/// which fail to compress or whose algorithm was not compiled in.
+pub(super) fn compress_body(
+    body: Vec<u8>,
+    options: Option<&CompressionOptions>,
+) -> (Vec<u8>, Option<&'static str>) {
+    let Some(options) = options else {
+        return (body, None);
+    };
+    if body.len() < options.threshold {
+        return (body, None);
+    }
+
+    match compress(&body, options.algorithm) {
+        Ok(compressed) => (compressed, Some(options.algorithm.content_encoding())),
+        Err(err) => {
+            sentry_debug!(
+                "Failed to compress envelope with {:?}, sending it uncompressed: {}",
+                options.algorithm,
+                err
+            );
+            (body, None)
+        }
+    }
+}
+
+fn compress(body: &[u8], algorithm: CompressionAlgorithm) -> std::io::Result<Vec<u8>> {
+    match algorithm {
+        #[cfg(feature = "gzip")]
+        CompressionAlgorithm::Gzip => {
+            use std::io::Write;
+
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), Default::default());
+            encoder.write_all(body)?;
+            encoder.finish()
+        }
+        #[cfg(feature = "zstd")]
+        CompressionAlgorithm::Zstd => zstd::encode_all(body, zstd::DEFAULT_COMPRESSION_LEVEL),
+        algorithm => {
+            let _ = body;
+            Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("the `sentry` crate was compiled without support for {algorithm:?}"),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_body_is_not_compressed() {
+        let options = CompressionOptions::new(CompressionAlgorithm::Gzip).threshold(100);
+        let (body, encoding) = compress_body(b"small".to_vec(), Some(&options));
+        assert_eq!(body, b"small");
+        assert_eq!(encoding, None);
+    }
+
+    #[test]
+    fn test_no_options_is_not_compressed() {
+        let (body, encoding) = compress_body(vec![b'a'; 4096], None);
+        assert_eq!(body, vec![b'a'; 4096]);
+        assert_eq!(encoding, None);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip() {
+        use std::io::Read;
+
+        let options = CompressionOptions::new(CompressionAlgorithm::Gzip).threshold(0);
+        let (body, encoding) = compress_body(vec![b'a'; 4096], Some(&options));
+        assert_eq!(encoding, Some("gzip"));
+        assert!(body.len() < 4096);
+
+        let mut decompressed = Vec::new();
+        flate2::read::GzDecoder::new(body.as_slice())
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, vec![b'a'; 4096]);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd() {
+        let options = CompressionOptions::new(CompressionAlgorithm::Zstd).threshold(0);
+        let (body, encoding) = compress_body(vec![b'a'; 4096], Some(&options));
+        assert_eq!(encoding, Some("zstd"));
+        assert!(body.len() < 4096);
+        assert_eq!(zstd::decode_all(body.as_slice()).unwrap(), vec![b'a'; 4096]);
+    }
+}
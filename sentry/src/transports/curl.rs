@@ -6,8 +6,9 @@ use sentry_core::client_report::Reason as LossReason;
 use sentry_core::TransportOptions;
 
 use super::{
+    compression::compress_body,
     thread::{TransportThread, TransportThreadOptions},
-    RateLimiter, HTTP_PAYLOAD_TOO_LARGE, HTTP_PAYLOAD_TOO_LARGE_MESSAGE,
+    RateLimiter, SendOutcome, HTTP_PAYLOAD_TOO_LARGE, HTTP_PAYLOAD_TOO_LARGE_MESSAGE,
 };
 
 use crate::{sentry_debug, types::Scheme, ClientOptions, Envelope, Transport};
@@ -82,6 +83,9 @@ impl CurlHttpTransport {
                     http_proxy,
                     https_proxy,
                     accept_invalid_certs,
+                    offline_cache,
+                    compression,
+                    retry,
                     client_report_recorder,
                     ..
                 },
@@ -129,12 +133,18 @@ impl CurlHttpTransport {
                         .record_lost_data(&envelope, LossReason::InternalError);
                 })
                 .expect("envelope should serialize successfully");
+            let (body, content_encoding) = compress_body(body, compression.as_ref());
             let mut body = Cursor::new(body);
 
             let mut retry_after = None;
             let mut sentry_header = None;
             let mut headers = curl::easy::List::new();
             headers.append(&format!("X-Sentry-Auth: {auth}")).unwrap();
+            if let Some(content_encoding) = content_encoding {
+                headers
+                    .append(&format!("Content-Encoding: {content_encoding}"))
+                    .unwrap();
+            }
             headers.append("Expect:").unwrap();
             handle.http_headers(headers).unwrap();
             handle.upload(true).unwrap();
@@ -191,37 +201,54 @@ impl CurlHttpTransport {
                         sentry_debug!("{HTTP_PAYLOAD_TOO_LARGE_MESSAGE}");
                     }
 
-                    if (400..=599).contains(&response_code)
+                    if (500..=599).contains(&response_code) {
+                        // The server returned an HTTP error response, so the envelope was rejected
+                        // at the HTTP layer even if curl also reported a transfer error. Server
+                        // errors are transient, so the envelope may be sent again later.
+                        SendOutcome::Failed {
+                            envelope,
+                            reason: LossReason::SendError,
+                        }
+                    } else if (400..=499).contains(&response_code)
                         && response_code != HTTP_RATE_LIMIT_STATUS
                     {
-                        // The server returned an HTTP error response, so the envelope was rejected
-                        // at the HTTP layer even if curl also reported a transfer error.
                         send_fn_client_report_recorder
                             .record_lost_data(&envelope, LossReason::SendError);
+                        SendOutcome::Done
                     } else if perform_failed && response_code == 0 {
                         // curl documents `CURLINFO_RESPONSE_CODE` as zero when no server response
                         // code has been received. If `perform` also failed, this means the send
                         // failed before an HTTP status was available, which is a network error.
-                        send_fn_client_report_recorder
-                            .record_lost_data(&envelope, LossReason::NetworkError);
+                        SendOutcome::Failed {
+                            envelope,
+                            reason: LossReason::NetworkError,
+                        }
+                    } else {
+                        SendOutcome::Done
                     }
                 }
                 Err(err) => {
                     sentry_debug!("Failed to send envelope: {}", err);
-                    let reason = if perform_failed {
+                    if perform_failed {
                         // `response_code` only errors when `CURLINFO_RESPONSE_CODE` is not
                         // supported. If `perform` failed too, treat the loss as the transfer error.
-                        LossReason::NetworkError
+                        SendOutcome::Failed {
+                            envelope,
+                            reason: LossReason::NetworkError,
+                        }
                     } else {
-                        LossReason::SendError
-                    };
-                    send_fn_client_report_recorder.record_lost_data(&envelope, reason);
+                        send_fn_client_report_recorder
+                            .record_lost_data(&envelope, LossReason::SendError);
+                        SendOutcome::Done
+                    }
                 }
             }
         };
 
         let thread = TransportThreadOptions::new(send_fn)
             .with_client_report_recorder(client_report_recorder)
+            .with_offline_cache(offline_cache)
+            .with_retry(retry)
             .spawn_thread();
         Self { thread }
     }
@@ -13,6 +13,17 @@ mod ratelimit;
 #[cfg(feature = "httpdate")]
 pub use self::ratelimit::{RateLimiter, RateLimitingCategory};
 
+#[cfg(any(feature = "curl", feature = "ureq", feature = "reqwest"))]
+mod compression;
+#[cfg(any(feature = "curl", feature = "ureq", feature = "reqwest"))]
+mod offline_cache;
+#[cfg(any(feature = "curl", feature = "ureq", feature = "reqwest"))]
+mod outcome;
+#[cfg(any(feature = "curl", feature = "ureq", feature = "reqwest"))]
+mod retry;
+#[cfg(any(feature = "curl", feature = "ureq", feature = "reqwest"))]
+pub use self::outcome::SendOutcome;
+
 #[cfg(any(feature = "curl", feature = "ureq"))]
 mod thread;
 #[cfg(any(feature = "curl", feature = "ureq"))]
@@ -0,0 +1,552 @@
+//! A disk-backed cache for envelopes which could not be sent.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use sentry_core::client_report::{Reason as LossReason, Recorder as ClientReportRecorder};
+use sentry_core::OfflineCacheOptions;
+
+use crate::protocol::client_report::{ItemLoss, LossSource};
+use crate::{sentry_debug, Envelope};
+
+/// The file extension of cached envelopes.
+const EXTENSION: &str = "envelope";
+
+/// The file extension used while an envelope is being written.
+const TEMP_EXTENSION: &str = "envelope.tmp";
+
+/// A directory of serialized envelopes, bounded by a total size and age budget.
+///
+/// Envelopes are stored one per file. File names start with the time at which the envelope was
+/// stored, followed by a sequence number, so that sorting the file names yields the envelopes in
+/// the order they were stored.
+///
+/// The directory is only scanned when the cache is opened. Afterwards, the cached envelopes are
+/// tracked in memory, so that neither storing nor replaying envelopes has to list the directory.
+/// The cache is owned by the transport thread, which is the only thread doing disk I/O for it.
+pub(super) struct OfflineCache {
+    options: OfflineCacheOptions,
+    client_report_recorder: ClientReportRecorder,
+    sequence: u64,
+    index: Index,
+}
+
+/// The cached envelopes, oldest first, and their total size.
+#[derive(Debug, Default)]
+struct Index {
+    entries: VecDeque<Entry>,
+    size: u64,
+}
+
+/// An envelope file in the cache.
+#[derive(Debug)]
+struct Entry {
+    path: PathBuf,
+    stored_at: SystemTime,
+    size: u64,
+    /// The losses to record if the envelope is evicted, computed when the envelope is stored so
+    /// that evicting it does not have to read it back.
+    losses: Vec<ItemLoss>,
+}
+
+impl Index {
+    fn push_back(&mut self, entry: Entry) {
+        self.size = self.size.saturating_add(entry.size);
+        self.entries.push_back(entry);
+    }
+
+    fn pop_front(&mut self) -> Option<Entry> {
+        let entry = self.entries.pop_front()?;
+        self.size = self.size.saturating_sub(entry.size);
+        Some(entry)
+    }
+}
+
+impl OfflineCache {
+    /// Opens the cache directory, creating it if needed.
+    ///
+    /// Temporary files and unreadable envelopes left behind by a previous run are removed, and
+    /// envelopes exceeding the budget are evicted. Returns [`None`] if the directory cannot be
+    /// created.
+    pub(super) fn open(
+        options: OfflineCacheOptions,
+        client_report_recorder: ClientReportRecorder,
+    ) -> Option<Self> {
+        if let Err(err) = fs::create_dir_all(&options.path) {
+            sentry_debug!(
+                "Failed to create offline cache directory {}: {}",
+                options.path.display(),
+                err
+            );
+            return None;
+        }
+
+        let mut index = Index::default();
+        for entry in scan(&options.path) {
+            index.push_back(entry);
+        }
+        let mut cache = Self {
+            options,
+            client_report_recorder,
+            sequence: 0,
+            index,
+        };
+        cache.enforce_budget();
+        Some(cache)
+    }
+
+    /// Writes the envelope to the cache, evicting the oldest envelopes if the size budget is
+    /// exceeded.
+    ///
+    /// If the envelope cannot be written, or is larger than the whole budget, it is recorded as
+    /// lost.
+    pub(super) fn store(&mut self, envelope: &Envelope) {
+        let Some(body) = self.serialize(envelope) else {
+            return;
+        };
+        match self.write_file(&body) {
+            Ok((path, stored_at)) => self.index.push_back(Entry {
+                path,
+                stored_at,
+                size: u64::try_from(body.len()).unwrap_or(u64::MAX),
+                losses: envelope.losses().collect(),
+            }),
+            Err(err) => {
+                sentry_debug!("Failed to write envelope to the offline cache: {}", err);
+                self.client_report_recorder
+                    .record_lost_data(envelope, LossReason::InternalError);
+            }
+        }
+        self.enforce_budget();
+    }
+
+    /// Returns the oldest cached envelope along with the path it is stored at.
+    ///
+    /// Expired and unreadable envelopes are removed before looking for the oldest one.
+    pub(super) fn peek(&mut self) -> Option<(PathBuf, Envelope)> {
+        self.remove_expired();
+        loop {
+            let entry = self.index.entries.front()?;
+            match Envelope::from_path(&entry.path) {
+                Ok(envelope) => return Some((entry.path.clone(), envelope)),
+                Err(err) => {
+                    sentry_debug!(
+                        "Removing unreadable envelope {} from the offline cache: {}",
+                        entry.path.display(),
+                        err
+                    );
+                    if let Some(entry) = self.index.pop_front() {
+                        remove_file(&entry.path);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Removes an envelope previously returned by [`Self::peek`], after it has been handled.
+    pub(super) fn remove(&mut self, path: &Path) {
+        let index = &mut self.index;
+        if let Some(position) = index.entries.iter().position(|entry| entry.path == path) {
+            if let Some(entry) = index.entries.remove(position) {
+                index.size = index.size.saturating_sub(entry.size);
+            }
+        }
+        remove_file(path);
+    }
+
+    /// Serializes the envelope, checking that it fits into the size budget at all.
+    fn serialize(&self, envelope: &Envelope) -> Option<Vec<u8>> {
+        let mut body = Vec::new();
+        if let Err(err) = envelope.to_writer(&mut body) {
+            sentry_debug!(
+                "Failed to serialize envelope for the offline cache: {}",
+                err
+            );
+            self.client_report_recorder
+                .record_lost_data(envelope, LossReason::InternalError);
+            return None;
+        }
+        if u64::try_from(body.len()).unwrap_or(u64::MAX) > self.options.max_size {
+            sentry_debug!("Envelope exceeds the offline cache size budget, dropping it");
+            self.client_report_recorder
+                .record_lost_data(envelope, LossReason::CacheOverflow);
+            return None;
+        }
+        Some(body)
+    }
+
+    /// Writes `body` to a new file, using a temporary file so that readers never observe a
+    /// partially written envelope.
+    ///
+    /// Returns the path of the file and the time the envelope was stored at.
+    fn write_file(&mut self, body: &[u8]) -> io::Result<(PathBuf, SystemTime)> {
+        let stored_at = SystemTime::now();
+        let micros = stored_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros();
+        let sequence = self.sequence;
+        self.sequence = self.sequence.wrapping_add(1);
+        let name = format!("{micros:020}-{sequence:010}");
+
+        let temp_path = self.options.path.join(format!("{name}.{TEMP_EXTENSION}"));
+        let path = self.options.path.join(format!("{name}.{EXTENSION}"));
+        fs::write(&temp_path, body)?;
+        fs::rename(&temp_path, &path).inspect_err(|_| remove_file(&temp_path))?;
+        Ok((path, stored_at))
+    }
+
+    /// Removes expired envelopes, then the oldest envelopes until the cache fits its size budget.
+    fn enforce_budget(&mut self) {
+        self.remove_expired();
+        while self.index.size > self.options.max_size {
+            let Some(entry) = self.index.pop_front() else {
+                break;
+            };
+            sentry_debug!("Offline cache is over its size budget, removing the oldest envelope");
+            self.evict(&entry);
+        }
+    }
+
+    /// Removes the envelopes older than the maximum age, which are the oldest ones in the index.
+    fn remove_expired(&mut self) {
+        let now = SystemTime::now();
+        while let Some(entry) = self.index.entries.front() {
+            let age = now.duration_since(entry.stored_at).unwrap_or_default();
+            if age <= self.options.max_age {
+                break;
+            }
+            if let Some(entry) = self.index.pop_front() {
+                sentry_debug!("Removing expired envelope from the offline cache");
+                self.evict(&entry);
+            }
+        }
+    }
+
+    /// Removes a cached envelope, recording its contents as lost.
+    fn evict(&self, entry: &Entry) {
+        self.client_report_recorder
+            .record_lost_data(entry.losses.as_slice(), LossReason::CacheOverflow);
+        remove_file(&entry.path);
+    }
+}
+
+/// Lists the cached envelopes in `dir`, oldest first, removing leftover temporary files and
+/// unreadable envelopes.
+///
+/// Each envelope is read once, to compute the losses to record if it is evicted.
+fn scan(dir: &Path) -> Vec<Entry> {
+    let read_dir = match fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        Err(err) => {
+            sentry_debug!("Failed to read the offline cache directory: {}", err);
+            return Vec::new();
+        }
+    };
+
+    let mut entries: Vec<_> = read_dir
+        .filter_map(Result::ok)
+        .filter_map(|dir_entry| {
+            let path = dir_entry.path();
+            if is_temp_file(&path) {
+                // A write that was interrupted, for example by a crash.
+                remove_file(&path);
+                return None;
+            }
+            let stored_at = parse_stored_at(&path)?;
+            let size = dir_entry.metadata().ok()?.len();
+            let envelope = match Envelope::from_path(&path) {
+                Ok(envelope) => envelope,
+                Err(err) => {
+                    sentry_debug!(
+                        "Removing unreadable envelope {} from the offline cache: {}",
+                        path.display(),
+                        err
+                    );
+                    remove_file(&path);
+                    return None;
+                }
+            };
+            Some(Entry {
+                path,
+                stored_at,
+                size,
+                losses: envelope.losses().collect(),
+            })
+        })
+        .collect();
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    entries
+}
+
+fn is_temp_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.ends_with(&format!(".{TEMP_EXTENSION}")))
+}
+
+/// Parses the time an envelope was stored at from its file name.
+///
+/// Returns [`None`] for files which are not cached envelopes.
+fn parse_stored_at(path: &Path) -> Option<SystemTime> {
+    if path.extension()? != EXTENSION {
+        return None;
+    }
+    let (micros, _sequence) = path.file_stem()?.to_str()?.split_once('-')?;
+    UNIX_EPOCH.checked_add(Duration::from_micros(micros.parse().ok()?))
+}
+
+fn remove_file(path: &Path) {
+    if let Err(err) = fs::remove_file(path) {
+        sentry_debug!(
+            "Failed to remove {} from the offline cache: {}",
+            path.display(),
+            err
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::protocol::{Event, Level};
+
+    /// Creates a fresh cache directory below the system temp directory.
+    fn cache_dir() -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "sentry-offline-cache-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn event_envelope(message: &str) -> Envelope {
+        Envelope::from(Event {
+            message: Some(message.into()),
+            level: Level::Error,
+            // A fixed timestamp keeps the serialized size deterministic.
+            timestamp: UNIX_EPOCH,
+            ..Default::default()
+        })
+    }
+
+    fn message(envelope: &Envelope) -> &str {
+        envelope.event().unwrap().message.as_deref().unwrap()
+    }
+
+    fn envelope_size(envelope: &Envelope) -> u64 {
+        let mut body = Vec::new();
+        envelope.to_writer(&mut body).unwrap();
+        body.len() as u64
+    }
+
+    #[test]
+    fn test_replays_in_order() {
+        let dir = cache_dir();
+        let mut cache =
+            OfflineCache::open(OfflineCacheOptions::new(&dir), Default::default()).unwrap();
+
+        cache.store(&event_envelope("first"));
+        cache.store(&event_envelope("second"));
+
+        let (path, envelope) = cache.peek().unwrap();
+        assert_eq!(message(&envelope), "first");
+        cache.remove(&path);
+
+        // A new cache in the same directory, as after a restart, sees the remaining envelope.
+        drop(cache);
+        let mut cache =
+            OfflineCache::open(OfflineCacheOptions::new(&dir), Default::default()).unwrap();
+        let (path, envelope) = cache.peek().unwrap();
+        assert_eq!(message(&envelope), "second");
+        cache.remove(&path);
+
+        assert!(cache.peek().is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_size_budget_evicts_oldest() {
+        let dir = cache_dir();
+        // Enough room for the two most recent envelopes only.
+        let max_size =
+            envelope_size(&event_envelope("second")) + envelope_size(&event_envelope("third"));
+        let mut cache = OfflineCache::open(
+            OfflineCacheOptions::new(&dir).max_size(max_size),
+            Default::default(),
+        )
+        .unwrap();
+
+        cache.store(&event_envelope("first"));
+        cache.store(&event_envelope("second"));
+        cache.store(&event_envelope("third"));
+
+        let (path, envelope) = cache.peek().unwrap();
+        assert_eq!(message(&envelope), "second");
+        cache.remove(&path);
+        let (path, envelope) = cache.peek().unwrap();
+        assert_eq!(message(&envelope), "third");
+        cache.remove(&path);
+
+        assert!(cache.peek().is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_oversized_envelope_is_not_stored() {
+        let dir = cache_dir();
+        let mut cache = OfflineCache::open(
+            OfflineCacheOptions::new(&dir).max_size(10),
+            Default::default(),
+        )
+        .unwrap();
+
+        cache.store(&event_envelope("too large"));
+
+        assert!(cache.peek().is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_expired_envelopes_are_removed() {
+        let dir = cache_dir();
+        let mut cache = OfflineCache::open(
+            OfflineCacheOptions::new(&dir).max_age(Duration::ZERO),
+            Default::default(),
+        )
+        .unwrap();
+
+        cache.store(&event_envelope("expired"));
+        std::thread::sleep(Duration::from_millis(2));
+
+        assert!(cache.peek().is_none());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_ignores_foreign_and_unreadable_files() {
+        let dir = cache_dir();
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("README.txt"), "not an envelope").unwrap();
+        // A recent file, so that it is not removed as expired.
+        let stored_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .saturating_sub(Duration::from_secs(1))
+            .as_micros();
+        let corrupt = dir.join(format!("{stored_at:020}-{:010}.{EXTENSION}", 0));
+        fs::write(&corrupt, "{not json").unwrap();
+
+        let mut cache =
+            OfflineCache::open(OfflineCacheOptions::new(&dir), Default::default()).unwrap();
+        cache.store(&event_envelope("valid"));
+
+        // The corrupt file sorts first but cannot be read and is removed when opening the cache.
+        let (path, envelope) = cache.peek().unwrap();
+        assert_eq!(message(&envelope), "valid");
+        cache.remove(&path);
+        assert!(!corrupt.exists());
+        assert!(dir.join("README.txt").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_removes_leftover_temp_files() {
+        let dir = cache_dir();
+        fs::create_dir_all(&dir).unwrap();
+        let temp = dir.join(format!("{:020}-{:010}.{TEMP_EXTENSION}", 0, 0));
+        fs::write(&temp, "partial").unwrap();
+
+        let mut cache =
+            OfflineCache::open(OfflineCacheOptions::new(&dir), Default::default()).unwrap();
+
+        assert!(cache.peek().is_none());
+        assert!(!temp.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_evicts_without_reading_envelopes() {
+        use crate::protocol::client_report::Category;
+
+        let dir = cache_dir();
+        let max_size = envelope_size(&event_envelope("first"));
+        let mut cache = OfflineCache::open(
+            OfflineCacheOptions::new(&dir).max_size(max_size),
+            Default::default(),
+        )
+        .unwrap();
+
+        cache.store(&event_envelope("first"));
+        let entry = cache.index.entries.front().unwrap();
+        assert!(matches!(
+            entry.losses.as_slice(),
+            [ItemLoss {
+                category: Category::Error,
+                quantity: 1,
+                ..
+            }]
+        ));
+
+        // The oldest envelope is evicted even though it cannot be read anymore.
+        fs::write(&entry.path, "{not json").unwrap();
+        cache.store(&event_envelope("other"));
+
+        let (path, envelope) = cache.peek().unwrap();
+        assert_eq!(message(&envelope), "other");
+        cache.remove(&path);
+
+        assert!(cache.peek().is_none());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    fn test_transport_thread_replays_failed_envelopes() {
+        use std::sync::{Arc, Mutex};
+
+        use crate::transports::{RateLimiter, SendOutcome, TokioTransportThreadOptions};
+
+        let dir = cache_dir();
+
+        // The first thread fails to send, so the envelope ends up in the cache.
+        let thread = TokioTransportThreadOptions::new(|envelope, rl: RateLimiter| async move {
+            let reason = sentry_core::client_report::Reason::NetworkError;
+            (rl, SendOutcome::Failed { envelope, reason })
+        })
+        .with_offline_cache(Some(OfflineCacheOptions::new(&dir)))
+        .spawn_thread();
+        thread.send(event_envelope("cached"));
+        assert!(thread.flush(Duration::from_secs(5)));
+        drop(thread);
+
+        // The next thread, as after a restart, sends the cached envelope on start.
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let thread_sent = sent.clone();
+        let thread = TokioTransportThreadOptions::new(move |envelope, rl: RateLimiter| {
+            thread_sent.lock().unwrap().push(envelope);
+            async move { rl }
+        })
+        .with_offline_cache(Some(OfflineCacheOptions::new(&dir)))
+        .spawn_thread();
+        assert!(thread.flush(Duration::from_secs(5)));
+        drop(thread);
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(message(&sent[0]), "cached");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,49 @@
+use sentry_core::client_report::Reason as LossReason;
+
+#[cfg(feature = "reqwest")]
+use super::RateLimiter;
+use crate::Envelope;
+
+/// The outcome of an attempt to send an [`Envelope`].
+///
+/// Send functions passed to the transport threads may return this to tell the thread whether the
+/// envelope needs further handling. Send functions returning `()` (or, for the tokio transport
+/// thread, only the [`RateLimiter`]) are treated as always returning [`SendOutcome::Done`].
+#[derive(Debug)]
+#[non_exhaustive]
+#[expect(
+    clippy::large_enum_variant,
+    reason = "The envelope is moved back to the transport thread instead of being cloned, and \
+    the outcome is consumed right after sending."
+)]
+pub enum SendOutcome {
+    /// The envelope was handled, either because it was sent successfully, or because it was
+    /// rejected for a reason which sending it again would not fix.
+    ///
+    /// The send function is responsible for recording any losses in the latter case.
+    Done,
+    /// The envelope could not be sent because of a transient failure, such as a network error or
+    /// a server error.
+    ///
+    /// The transport thread decides what happens to the envelope, for example storing it in the
+    /// offline cache. If the envelope is dropped, it is recorded as lost with the given `reason`.
+    Failed {
+        /// The envelope which could not be sent.
+        envelope: Envelope,
+        /// The reason to record if the envelope is ultimately dropped.
+        reason: LossReason,
+    },
+}
+
+impl From<()> for SendOutcome {
+    fn from(_: ()) -> Self {
+        Self::Done
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl From<RateLimiter> for (RateLimiter, SendOutcome) {
+    fn from(rl: RateLimiter) -> Self {
+        (rl, SendOutcome::Done)
+    }
+}
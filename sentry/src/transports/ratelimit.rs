@@ -17,6 +17,9 @@ pub struct RateLimiter {
     attachment: Option<SystemTime>,
     log_item: Option<SystemTime>,
     trace_metric: Option<SystemTime>,
+    feedback: Option<SystemTime>,
+    profile_chunk: Option<SystemTime>,
+    span: Option<SystemTime>,
 }
 
 impl RateLimiter {
@@ -63,6 +66,9 @@ impl RateLimiter {
                     "attachment" => self.attachment = new_time,
                     "log_item" => self.log_item = new_time,
                     "trace_metric" => self.trace_metric = new_time,
+                    "feedback" => self.feedback = new_time,
+                    "profile_chunk" => self.profile_chunk = new_time,
+                    "span" => self.span = new_time,
                     _ => {}
                 }
             }
@@ -98,6 +104,9 @@ impl RateLimiter {
             RateLimitingCategory::Attachment => self.attachment,
             RateLimitingCategory::LogItem => self.log_item,
             RateLimitingCategory::TraceMetric => self.trace_metric,
+            RateLimitingCategory::Feedback => self.feedback,
+            RateLimitingCategory::ProfileChunk => self.profile_chunk,
+            RateLimitingCategory::Span => self.span,
         }?;
         time_left.duration_since(SystemTime::now()).ok()
     }
@@ -141,6 +150,11 @@ impl RateLimiter {
                     EnvelopeItem::ItemContainer(ItemContainer::Metrics(_)) => {
                         RateLimitingCategory::TraceMetric
                     }
+                    EnvelopeItem::ItemContainer(ItemContainer::Spans(_)) => {
+                        RateLimitingCategory::Span
+                    }
+                    EnvelopeItem::Feedback(_) => RateLimitingCategory::Feedback,
+                    EnvelopeItem::ProfileChunk(_) => RateLimitingCategory::ProfileChunk,
                     _ => RateLimitingCategory::Any,
                 })
             },
@@ -169,6 +183,12 @@ pub enum RateLimitingCategory {
     LogItem,
     /// Rate Limit pertaining to Trace Metrics.
     TraceMetric,
+    /// Rate Limit pertaining to User Feedback.
+    Feedback,
+    /// Rate Limit pertaining to Profile Chunks.
+    ProfileChunk,
+    /// Rate Limit pertaining to streamed Spans.
+    Span,
 }
 
 /// Returns the [`SystemTime`] after the given duration has passed.
@@ -236,9 +256,84 @@ mod tests {
         assert!(
             rl.is_disabled(RateLimitingCategory::TraceMetric).unwrap() <= Duration::from_secs(120)
         );
+        assert!(
+            rl.is_disabled(RateLimitingCategory::Feedback).unwrap() <= Duration::from_secs(120)
+        );
         assert!(rl.is_disabled(RateLimitingCategory::Any).unwrap() <= Duration::from_secs(120));
     }
 
+    #[test]
+    fn test_feedback_rate_limit() {
+        use crate::protocol::{Event, Feedback};
+
+        let mut rl = RateLimiter::new();
+        rl.update_from_sentry_header("60:feedback:organization");
+
+        assert!(rl.is_disabled(RateLimitingCategory::Feedback).unwrap() <= Duration::from_secs(60));
+        assert!(rl.is_disabled(RateLimitingCategory::Error).is_none());
+
+        let mut envelope = Envelope::new();
+        envelope.add_item(Event::default());
+        envelope.add_item(Feedback::default());
+
+        let envelope = rl.filter(envelope, &Default::default()).unwrap();
+        let mut items = envelope.items();
+        assert!(matches!(items.next(), Some(EnvelopeItem::Event(_))));
+        assert!(items.next().is_none());
+    }
+
+    #[test]
+    fn test_profile_chunk_rate_limit() {
+        use crate::protocol::{Event, ProfileChunk};
+
+        let mut rl = RateLimiter::new();
+        rl.update_from_sentry_header("60:profile_chunk:organization");
+
+        assert!(
+            rl.is_disabled(RateLimitingCategory::ProfileChunk).unwrap() <= Duration::from_secs(60)
+        );
+        assert!(rl.is_disabled(RateLimitingCategory::Error).is_none());
+
+        let mut envelope = Envelope::new();
+        envelope.add_item(Event::default());
+        envelope.add_item(ProfileChunk::default());
+
+        let envelope = rl.filter(envelope, &Default::default()).unwrap();
+        let mut items = envelope.items();
+        assert!(matches!(items.next(), Some(EnvelopeItem::Event(_))));
+        assert!(items.next().is_none());
+    }
+
+    #[test]
+    fn test_span_rate_limit() {
+        use crate::protocol::{Event, StreamedSpan, StreamedSpanStatus};
+
+        let mut rl = RateLimiter::new();
+        rl.update_from_sentry_header("60:span:organization");
+
+        assert!(rl.is_disabled(RateLimitingCategory::Span).unwrap() <= Duration::from_secs(60));
+        assert!(rl.is_disabled(RateLimitingCategory::Transaction).is_none());
+
+        let mut envelope = Envelope::new();
+        envelope.add_item(Event::default());
+        envelope.add_item(vec![StreamedSpan {
+            trace_id: Default::default(),
+            span_id: Default::default(),
+            parent_span_id: None,
+            name: "span".into(),
+            status: StreamedSpanStatus::Ok,
+            is_segment: true,
+            start_timestamp: SystemTime::now(),
+            end_timestamp: SystemTime::now(),
+            attributes: Default::default(),
+        }]);
+
+        let envelope = rl.filter(envelope, &Default::default()).unwrap();
+        let mut items = envelope.items();
+        assert!(matches!(items.next(), Some(EnvelopeItem::Event(_))));
+        assert!(items.next().is_none());
+    }
+
     #[test]
     fn test_retry_after() {
         let mut rl = RateLimiter::new();
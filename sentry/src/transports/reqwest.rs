@@ -5,8 +5,9 @@ use sentry_core::client_report::Reason as LossReason;
 use sentry_core::TransportOptions;
 
 use super::{
+    compression::compress_body,
     tokio_thread::{TransportThread, TransportThreadOptions},
-    RateLimiter, HTTP_PAYLOAD_TOO_LARGE, HTTP_PAYLOAD_TOO_LARGE_MESSAGE,
+    RateLimiter, SendOutcome, HTTP_PAYLOAD_TOO_LARGE, HTTP_PAYLOAD_TOO_LARGE_MESSAGE,
 };
 
 use crate::{sentry_debug, ClientOptions, Envelope, Transport};
@@ -83,6 +84,9 @@ impl ReqwestHttpTransport {
                     http_proxy,
                     https_proxy,
                     accept_invalid_certs,
+                    offline_cache,
+                    compression,
+                    retry,
                     client_report_recorder,
                     ..
                 },
@@ -133,14 +137,19 @@ impl ReqwestHttpTransport {
                         .record_lost_data(&envelope, LossReason::InternalError);
                 })
                 .expect("envelope should serialize successfully");
-            let request = client.post(&url).header("X-Sentry-Auth", &auth).body(body);
+            let (body, content_encoding) = compress_body(body, compression.as_ref());
+            let mut request = client.post(&url).header("X-Sentry-Auth", &auth);
+            if let Some(content_encoding) = content_encoding {
+                request = request.header(ReqwestHeaders::CONTENT_ENCODING, content_encoding);
+            }
+            let request = request.body(body);
 
             let client_report_recorder = send_fn_client_report_recorder.clone();
 
             // NOTE: because of lifetime issues, building the request using the
             // `client` has to happen outside of this async block.
             async move {
-                match request.send().await {
+                let outcome = match request.send().await {
                     Ok(response) => {
                         let headers = response.headers();
 
@@ -173,25 +182,37 @@ impl ReqwestHttpTransport {
                             sentry_debug!("{HTTP_PAYLOAD_TOO_LARGE_MESSAGE}");
                         }
 
-                        if (400..=599).contains(&response_status)
-                            && response_status != HTTP_RATE_LIMIT_STATUS
-                        {
-                            client_report_recorder
-                                .record_lost_data(&envelope, LossReason::SendError);
+                        if (500..=599).contains(&response_status) {
+                            SendOutcome::Failed {
+                                envelope,
+                                reason: LossReason::SendError,
+                            }
+                        } else {
+                            if (400..=499).contains(&response_status)
+                                && response_status != HTTP_RATE_LIMIT_STATUS
+                            {
+                                client_report_recorder
+                                    .record_lost_data(&envelope, LossReason::SendError);
+                            }
+                            SendOutcome::Done
                         }
                     }
                     Err(err) => {
                         sentry_debug!("Failed to send envelope: {}", err);
-                        client_report_recorder
-                            .record_lost_data(&envelope, LossReason::NetworkError);
+                        SendOutcome::Failed {
+                            envelope,
+                            reason: LossReason::NetworkError,
+                        }
                     }
-                }
-                rl
+                };
+                (rl, outcome)
             }
         };
 
         let thread = TransportThreadOptions::new(send_fn)
             .with_client_report_recorder(client_report_recorder)
+            .with_offline_cache(offline_cache)
+            .with_retry(retry)
             .spawn_thread();
         Self { thread }
     }
@@ -0,0 +1,381 @@
+//! Shared retry support for the transport threads.
+
+use std::time::{Duration, Instant};
+
+use sentry_core::client_report::Reason as LossReason;
+use sentry_core::RetryPolicy;
+
+use super::ratelimit::{RateLimiter, RateLimitingCategory};
+use crate::{sentry_debug, Envelope};
+
+/// Tracks the attempts to send a single envelope under a [`RetryPolicy`].
+#[derive(Debug)]
+pub(super) struct Backoff {
+    policy: RetryPolicy,
+    /// The number of attempts made so far.
+    attempts: u32,
+    /// The instant after which no further attempt is started, if representable.
+    deadline: Option<Instant>,
+}
+
+impl Backoff {
+    /// Starts tracking an envelope whose first attempt has just been made.
+    pub(super) fn new(policy: RetryPolicy) -> Self {
+        Self {
+            policy,
+            attempts: 1,
+            deadline: Instant::now().checked_add(policy.deadline),
+        }
+    }
+
+    /// Returns how long to wait before the next attempt, or `None` if the envelope should not be
+    /// retried anymore.
+    ///
+    /// The delay grows exponentially with jitter, and is extended to the end of any rate limit
+    /// which applies to all categories.
+    pub(super) fn next_delay(&mut self, rl: &RateLimiter) -> Option<Duration> {
+        if self.attempts >= self.policy.max_attempts {
+            sentry_debug!("Giving up on envelope after {} attempts", self.attempts);
+            return None;
+        }
+
+        let mut delay = jitter(self.backoff());
+        if let Some(rate_limited) = rl.is_disabled(RateLimitingCategory::Any) {
+            delay = delay.max(rate_limited);
+        }
+        if let Some(deadline) = self.deadline {
+            if Instant::now()
+                .checked_add(delay)
+                .is_none_or(|at| at > deadline)
+            {
+                sentry_debug!("Giving up on envelope, retrying would exceed the deadline");
+                return None;
+            }
+        }
+
+        self.attempts = self.attempts.saturating_add(1);
+        Some(delay)
+    }
+
+    /// The delay before the next attempt, before jitter is applied.
+    fn backoff(&self) -> Duration {
+        let retries = self.attempts.saturating_sub(1);
+        let factor = 1u32.checked_shl(retries).unwrap_or(u32::MAX);
+        self.policy
+            .initial_backoff
+            .saturating_mul(factor)
+            .min(self.policy.max_backoff)
+    }
+}
+
+/// The envelopes waiting for their next attempt.
+///
+/// The transport thread keeps handling new envelopes and flushes while envelopes wait here, and
+/// only wakes up to retry once the earliest of them is due.
+#[derive(Debug, Default)]
+pub(super) struct RetryQueue {
+    entries: Vec<PendingRetry>,
+}
+
+/// An envelope waiting for its next attempt.
+#[derive(Debug)]
+struct PendingRetry {
+    envelope: Envelope,
+    /// The reason the envelope is recorded as lost with, if it is not retried anymore.
+    reason: LossReason,
+    backoff: Backoff,
+    /// The instant before which the envelope is not retried.
+    not_before: Instant,
+}
+
+impl RetryQueue {
+    /// Schedules the next attempt of an envelope whose last attempt failed for `reason`.
+    ///
+    /// Returns the envelope back if it should not be retried anymore.
+    pub(super) fn schedule(
+        &mut self,
+        envelope: Envelope,
+        reason: LossReason,
+        mut backoff: Backoff,
+        rl: &RateLimiter,
+    ) -> Option<Envelope> {
+        let Some(not_before) = backoff
+            .next_delay(rl)
+            .and_then(|delay| Instant::now().checked_add(delay))
+        else {
+            return Some(envelope);
+        };
+        sentry_debug!(
+            "Retrying envelope in {}ms",
+            not_before
+                .saturating_duration_since(Instant::now())
+                .as_millis()
+        );
+        self.entries.push(PendingRetry {
+            envelope,
+            reason,
+            backoff,
+            not_before,
+        });
+        None
+    }
+
+    /// Returns the instant at which the earliest retry is due.
+    pub(super) fn next_due(&self) -> Option<Instant> {
+        self.entries.iter().map(|entry| entry.not_before).min()
+    }
+
+    /// Removes an envelope whose retry is due, along with its backoff.
+    pub(super) fn pop_due(&mut self) -> Option<(Envelope, Backoff)> {
+        let now = Instant::now();
+        let position = self
+            .entries
+            .iter()
+            .position(|entry| entry.not_before <= now)?;
+        let entry = self.entries.swap_remove(position);
+        Some((entry.envelope, entry.backoff))
+    }
+
+    /// Returns whether no envelope is waiting for a retry.
+    pub(super) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes all waiting envelopes, along with the reason to record them as lost with.
+    pub(super) fn drain(&mut self) -> impl Iterator<Item = (Envelope, LossReason)> + '_ {
+        self.entries
+            .drain(..)
+            .map(|entry| (entry.envelope, entry.reason))
+    }
+}
+
+/// Randomly shortens `delay` by up to half, so that clients do not retry in lockstep.
+fn jitter(delay: Duration) -> Duration {
+    delay.mul_f64(rand::random_range(0.5..=1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::new()
+            .max_attempts(10)
+            .initial_backoff(Duration::from_secs(1))
+            .max_backoff(Duration::from_secs(8))
+            .deadline(Duration::from_secs(3600))
+    }
+
+    #[test]
+    fn test_exponential_backoff_with_jitter() {
+        let rl = RateLimiter::new();
+        let mut backoff = Backoff::new(policy());
+
+        for expected in [1, 2, 4, 8, 8] {
+            let expected = Duration::from_secs(expected);
+            let delay = backoff.next_delay(&rl).unwrap();
+            assert!(delay <= expected, "{delay:?} > {expected:?}");
+            assert!(delay >= expected / 2, "{delay:?} < {expected:?} / 2");
+        }
+    }
+
+    #[test]
+    fn test_max_attempts() {
+        let rl = RateLimiter::new();
+        let mut backoff = Backoff::new(policy().max_attempts(3));
+
+        assert!(backoff.next_delay(&rl).is_some());
+        assert!(backoff.next_delay(&rl).is_some());
+        assert!(backoff.next_delay(&rl).is_none());
+
+        let mut backoff = Backoff::new(policy().max_attempts(1));
+        assert!(backoff.next_delay(&rl).is_none());
+    }
+
+    #[test]
+    fn test_deadline() {
+        let rl = RateLimiter::new();
+        let mut backoff = Backoff::new(policy().deadline(Duration::from_millis(1500)));
+
+        // The first retry waits at most one second, the second one at least one more.
+        assert!(backoff.next_delay(&rl).is_some());
+        thread::sleep(Duration::from_millis(600));
+        assert!(backoff.next_delay(&rl).is_none());
+    }
+
+    #[test]
+    fn test_respects_rate_limits() {
+        let mut rl = RateLimiter::new();
+        rl.update_from_retry_after("60");
+
+        let mut backoff = Backoff::new(policy());
+        let delay = backoff.next_delay(&rl).unwrap();
+        assert!(delay > Duration::from_secs(50), "{delay:?}");
+
+        // A rate limit beyond the deadline ends the retries.
+        let mut backoff = Backoff::new(policy().deadline(Duration::from_secs(30)));
+        assert!(backoff.next_delay(&rl).is_none());
+    }
+
+    #[test]
+    fn test_retry_queue_yields_due_envelopes() {
+        let rl = RateLimiter::new();
+        let mut queue = RetryQueue::default();
+        let reason = LossReason::NetworkError;
+
+        let soon = Backoff::new(policy().initial_backoff(Duration::from_millis(10)));
+        let later = Backoff::new(policy().initial_backoff(Duration::from_secs(60)));
+        assert!(queue
+            .schedule(Envelope::new(), reason, later, &rl)
+            .is_none());
+        assert!(queue.schedule(Envelope::new(), reason, soon, &rl).is_none());
+
+        let next_due = queue.next_due().unwrap();
+        assert!(next_due <= Instant::now() + Duration::from_millis(10));
+        assert!(queue.pop_due().is_none());
+
+        thread::sleep(next_due.saturating_duration_since(Instant::now()));
+        assert!(queue.pop_due().is_some());
+        assert!(queue.pop_due().is_none());
+        assert!(!queue.is_empty());
+
+        assert_eq!(queue.drain().count(), 1);
+        assert!(queue.is_empty());
+        assert!(queue.next_due().is_none());
+    }
+
+    #[test]
+    fn test_retry_queue_returns_exhausted_envelopes() {
+        let rl = RateLimiter::new();
+        let mut queue = RetryQueue::default();
+
+        let backoff = Backoff::new(policy().max_attempts(1));
+        let reason = LossReason::NetworkError;
+        assert!(queue
+            .schedule(Envelope::new(), reason, backoff, &rl)
+            .is_some());
+        assert!(queue.is_empty());
+    }
+
+    fn message(envelope: &Envelope) -> String {
+        envelope.event().unwrap().message.clone().unwrap()
+    }
+
+    fn message_envelope(message: &str) -> Envelope {
+        Envelope::from(crate::protocol::Event {
+            message: Some(message.into()),
+            ..Default::default()
+        })
+    }
+
+    /// A policy whose retries do not happen during the test.
+    fn slow_policy() -> RetryPolicy {
+        policy()
+            .initial_backoff(Duration::from_secs(60))
+            .max_backoff(Duration::from_secs(60))
+    }
+
+    #[cfg(any(feature = "curl", feature = "ureq"))]
+    #[test]
+    fn test_std_transport_thread_sends_during_backoff() {
+        use std::sync::Mutex;
+
+        use crate::transports::{SendOutcome, StdTransportThreadOptions};
+
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let thread_sent = sent.clone();
+        let thread = StdTransportThreadOptions::new(move |envelope, _rl: &mut RateLimiter| {
+            if message(&envelope) == "failing" {
+                let reason = LossReason::NetworkError;
+                return SendOutcome::Failed { envelope, reason };
+            }
+            thread_sent.lock().unwrap().push(message(&envelope));
+            SendOutcome::Done
+        })
+        .with_retry(Some(slow_policy()))
+        .spawn_thread();
+
+        thread.send(message_envelope("failing"));
+        thread.send(message_envelope("other"));
+        // The flush times out, as the failing envelope is waiting for its retry, but the other
+        // envelope is sent in the meantime.
+        assert!(!thread.flush(Duration::from_millis(100)));
+        assert_eq!(*sent.lock().unwrap(), ["other"]);
+
+        let start = Instant::now();
+        drop(thread);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    fn test_transport_thread_retries_failed_envelopes() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        use crate::protocol::Event;
+        use crate::transports::{SendOutcome, TokioTransportThreadOptions};
+
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let thread_attempts = attempts.clone();
+        let thread = TokioTransportThreadOptions::new(move |envelope, rl: RateLimiter| {
+            let attempt = thread_attempts.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if attempt < 2 {
+                    let reason = sentry_core::client_report::Reason::SendError;
+                    (rl, SendOutcome::Failed { envelope, reason })
+                } else {
+                    (rl, SendOutcome::Done)
+                }
+            }
+        })
+        .with_retry(Some(
+            RetryPolicy::new().initial_backoff(Duration::from_millis(10)),
+        ))
+        .spawn_thread();
+
+        thread.send(Envelope::from(Event::default()));
+        assert!(thread.flush(Duration::from_secs(5)));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    fn test_transport_thread_sends_during_backoff() {
+        use std::sync::Mutex;
+
+        use crate::transports::{SendOutcome, TokioTransportThreadOptions};
+
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let thread_sent = sent.clone();
+        let thread = TokioTransportThreadOptions::new(move |envelope, rl: RateLimiter| {
+            let failed = message(&envelope) == "failing";
+            if !failed {
+                thread_sent.lock().unwrap().push(message(&envelope));
+            }
+            async move {
+                if failed {
+                    let reason = LossReason::NetworkError;
+                    (rl, SendOutcome::Failed { envelope, reason })
+                } else {
+                    (rl, SendOutcome::Done)
+                }
+            }
+        })
+        .with_retry(Some(slow_policy()))
+        .spawn_thread();
+
+        thread.send(message_envelope("failing"));
+        thread.send(message_envelope("other"));
+        // The flush times out, as the failing envelope is waiting for its retry, but the other
+        // envelope is sent in the meantime.
+        assert!(!thread.flush(Duration::from_millis(100)));
+        assert_eq!(*sent.lock().unwrap(), ["other"]);
+
+        let start = Instant::now();
+        drop(thread);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -1,12 +1,16 @@
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::sync::mpsc::{sync_channel, RecvTimeoutError, SyncSender, TrySendError};
 use std::sync::Arc;
 use std::thread::{self, JoinHandle};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use sentry_core::client_report::{Reason as ClientReportReason, Recorder as ClientReportRecorder};
+use sentry_core::{OfflineCacheOptions, RetryPolicy};
 
+use super::offline_cache::OfflineCache;
 use super::ratelimit::{RateLimiter, RateLimitingCategory};
+use super::retry::{Backoff, RetryQueue};
+use super::SendOutcome;
 #[cfg(doc)]
 use super::{StdTransportThread, StdTransportThreadOptions}; // so we can use pub re-exports in docs
 use crate::{sentry_debug, Envelope};
@@ -35,6 +39,8 @@ pub struct TransportThread {
 pub struct TransportThreadOptions<F> {
     send_fn: F,
     client_report_recorder: ClientReportRecorder,
+    offline_cache: Option<OfflineCacheOptions>,
+    retry: Option<RetryPolicy>,
 }
 
 impl<F> TransportThreadOptions<F> {
@@ -43,6 +49,8 @@ impl<F> TransportThreadOptions<F> {
         Self {
             send_fn,
             client_report_recorder: Default::default(),
+            offline_cache: None,
+            retry: None,
         }
     }
 
@@ -53,11 +61,33 @@ impl<F> TransportThreadOptions<F> {
             ..self
         }
     }
+
+    /// Enable the offline cache, configured by the given [`OfflineCacheOptions`].
+    ///
+    /// Envelopes whose send function returns [`SendOutcome::Failed`] are written to the cache by
+    /// the transport thread and sent later.
+    pub fn with_offline_cache(self, offline_cache: Option<OfflineCacheOptions>) -> Self {
+        Self {
+            offline_cache,
+            ..self
+        }
+    }
+
+    /// Enable retries of envelopes whose send function returns [`SendOutcome::Failed`],
+    /// configured by the given [`RetryPolicy`].
+    ///
+    /// Envelopes are retried on the transport thread, after the backoff configured by the policy.
+    /// The thread keeps sending other envelopes while an envelope waits for its retry. An envelope which is still failing once the policy is exhausted, or when the thread shuts
+    /// down, is stored in the offline cache if one is configured, and recorded as lost otherwise.
+    pub fn with_retry(self, retry: Option<RetryPolicy>) -> Self {
+        Self { retry, ..self }
+    }
 }
 
-impl<F> TransportThreadOptions<F>
+impl<F, R> TransportThreadOptions<F>
 where
-    F: FnMut(Envelope, &mut RateLimiter) + Send + 'static,
+    F: FnMut(Envelope, &mut RateLimiter) -> R + Send + 'static,
+    R: Into<SendOutcome>,
 {
     /// Spawn a [`StdTransportThread`], configured per these options.
     pub fn spawn_thread(self) -> TransportThread {
@@ -78,56 +108,79 @@ impl TransportThread {
     }
 
     /// Spawn a new background thread with options.
-    fn with_options<SendFn>(options: TransportThreadOptions<SendFn>) -> Self
+    fn with_options<SendFn, R>(options: TransportThreadOptions<SendFn>) -> Self
     where
-        SendFn: FnMut(Envelope, &mut RateLimiter) + Send + 'static,
+        SendFn: FnMut(Envelope, &mut RateLimiter) -> R + Send + 'static,
+        R: Into<SendOutcome>,
     {
         let TransportThreadOptions {
-            send_fn: mut send,
+            send_fn: send,
             client_report_recorder,
+            offline_cache,
+            retry,
         } = options;
         let (sender, receiver) = sync_channel(30);
         let shutdown = Arc::new(AtomicBool::new(false));
         let shutdown_worker = shutdown.clone();
-        let handle_client_report_recorder = client_report_recorder.clone();
+        let worker_client_report_recorder = client_report_recorder.clone();
         let handle = thread::Builder::new()
             .name("sentry-transport".into())
             .spawn(move || {
-                let mut rl = RateLimiter::new();
+                // The cache is only ever accessed from this thread, so that no disk I/O happens
+                // on the threads sending envelopes.
+                let offline_cache = offline_cache.and_then(|options| {
+                    OfflineCache::open(options, worker_client_report_recorder.clone())
+                });
+                let mut worker = Worker {
+                    send,
+                    rl: RateLimiter::new(),
+                    shutdown: shutdown_worker,
+                    retry,
+                    retries: RetryQueue::default(),
+                    offline_cache,
+                    client_report_recorder: worker_client_report_recorder,
+                };
 
-                for task in receiver.into_iter() {
-                    if shutdown_worker.load(Ordering::SeqCst) {
-                        return;
-                    }
-                    let envelope = match task {
-                        Task::SendEnvelope(envelope) => envelope,
-                        Task::Flush(sender) => {
-                            sender.send(()).ok();
-                            continue;
-                        }
-                        Task::Shutdown => {
-                            return;
+                // Deliver anything left over from a previous run first.
+                worker.send_cached();
+
+                // Flushes are answered once no envelope is waiting for a retry anymore.
+                let mut flushes = Vec::new();
+                loop {
+                    // Wait for the next task, but no longer than until the next retry is due.
+                    let task = match worker.retries.next_due() {
+                        Some(due) => {
+                            match receiver
+                                .recv_timeout(due.saturating_duration_since(Instant::now()))
+                            {
+                                Ok(task) => Some(task),
+                                Err(RecvTimeoutError::Timeout) => None,
+                                Err(RecvTimeoutError::Disconnected) => break,
+                            }
                         }
+                        None => match receiver.recv() {
+                            Ok(task) => Some(task),
+                            Err(_) => break,
+                        },
                     };
-
-                    if let Some(time_left) = rl.is_disabled(RateLimitingCategory::Any) {
-                        sentry_debug!(
-                            "Skipping event send because we're disabled due to rate limits for {}s",
-                            time_left.as_secs()
-                        );
-                        handle_client_report_recorder
-                            .record_lost_data(&envelope, ClientReportReason::RatelimitBackoff);
-                        continue;
+                    if worker.is_shutting_down() {
+                        break;
                     }
-                    match rl.filter(envelope, &handle_client_report_recorder) {
-                        Some(envelope) => {
-                            send(envelope, &mut rl);
-                        }
-                        None => {
-                            sentry_debug!("Envelope was discarded due to per-item rate limits");
+                    match task {
+                        Some(Task::SendEnvelope(envelope)) => worker.send_queued(envelope),
+                        Some(Task::Flush(sender)) => flushes.push(sender),
+                        Some(Task::Shutdown) => break,
+                        None => {}
+                    }
+
+                    worker.send_due_retries();
+                    if worker.retries.is_empty() {
+                        for sender in flushes.drain(..) {
+                            sender.send(()).ok();
                         }
-                    };
+                    }
                 }
+                worker.give_up_retries();
             })
             .ok();
 
@@ -157,7 +210,6 @@ impl TransportThread {
             let Task::SendEnvelope(envelope) = task else {
                 unreachable!("we sent a `SendEnvelope` task");
             };
-
             self.client_report_recorder
                 .record_lost_data(&envelope, reason);
         }
@@ -165,6 +217,8 @@ impl TransportThread {
 
     /// Flush all pending [`Envelope`]s.
     ///
+    /// [`Envelope`]s waiting for a retry are pending until they are sent or given up on.
+    ///
     /// Returns true if successful within given timeout.
     pub fn flush(&self, timeout: Duration) -> bool {
         let (sender, receiver) = sync_channel(1);
@@ -173,6 +227,115 @@ impl TransportThread {
     }
 }
 
+/// The state of the transport thread.
+struct Worker<SendFn> {
+    send: SendFn,
+    rl: RateLimiter,
+    shutdown: Arc<AtomicBool>,
+    retry: Option<RetryPolicy>,
+    retries: RetryQueue,
+    offline_cache: Option<OfflineCache>,
+    client_report_recorder: ClientReportRecorder,
+}
+
+impl<SendFn, R> Worker<SendFn>
+where
+    SendFn: FnMut(Envelope, &mut RateLimiter) -> R,
+    R: Into<SendOutcome>,
+{
+    fn is_shutting_down(&self) -> bool {
+        self.shutdown.load(Ordering::SeqCst)
+    }
+
+    /// Sends an envelope taken from the queue, unless it is rate limited.
+    fn send_queued(&mut self, envelope: Envelope) {
+        if let Some(time_left) = self.rl.is_disabled(RateLimitingCategory::Any) {
+            sentry_debug!(
+                "Skipping event send because we're disabled due to rate limits for {}s",
+                time_left.as_secs()
+            );
+            self.client_report_recorder
+                .record_lost_data(&envelope, ClientReportReason::RatelimitBackoff);
+            return;
+        }
+        match self.rl.filter(envelope, &self.client_report_recorder) {
+            Some(envelope) => self.attempt(envelope, None),
+            None => {
+                sentry_debug!("Envelope was discarded due to per-item rate limits");
+            }
+        }
+    }
+
+    /// Sends the envelopes whose retry is due.
+    fn send_due_retries(&mut self) {
+        while let Some((envelope, backoff)) = self.retries.pop_due() {
+            // Rate limits for some of the items may have started in the meantime.
+            if let Some(envelope) = self.rl.filter(envelope, &self.client_report_recorder) {
+                self.attempt(envelope, Some(backoff));
+            }
+        }
+    }
+
+    /// Makes an attempt to send the envelope, scheduling a retry if it fails.
+    ///
+    /// `backoff` tracks the previous attempts, and is [`None`] for the first one.
+    fn attempt(&mut self, envelope: Envelope, backoff: Option<Backoff>) {
+        match (self.send)(envelope, &mut self.rl).into() {
+            SendOutcome::Done => self.send_cached(),
+            SendOutcome::Failed { envelope, reason } => {
+                let envelope = match self.retry {
+                    Some(retry) => {
+                        let backoff = backoff.unwrap_or_else(|| Backoff::new(retry));
+                        self.retries.schedule(envelope, reason, backoff, &self.rl)
+                    }
+                    None => Some(envelope),
+                };
+                if let Some(envelope) = envelope {
+                    self.give_up(&envelope, reason);
+                }
+            }
+        }
+    }
+
+    /// Stores the envelopes still waiting for a retry in the offline cache, or records them as
+    /// lost.
+    fn give_up_retries(&mut self) {
+        let retries: Vec<_> = self.retries.drain().collect();
+        for (envelope, reason) in retries {
+            self.give_up(&envelope, reason);
+        }
+    }
+
+    /// Stores an envelope which could not be sent in the offline cache, or records it as lost.
+    fn give_up(&mut self, envelope: &Envelope, reason: ClientReportReason) {
+        match &mut self.offline_cache {
+            Some(cache) => cache.store(envelope),
+            None => self
+                .client_report_recorder
+                .record_lost_data(envelope, reason),
+        }
+    }
+
+    /// Sends cached envelopes, oldest first, until the cache is empty or sending fails.
+    fn send_cached(&mut self) {
+        while let Some((path, envelope)) = self.offline_cache.as_mut().and_then(OfflineCache::peek)
+        {
+            if self.is_shutting_down() || self.rl.is_disabled(RateLimitingCategory::Any).is_some() {
+                return;
+            }
+            if let Some(envelope) = self.rl.filter(envelope, &self.client_report_recorder) {
+                if let SendOutcome::Failed { .. } = (self.send)(envelope, &mut self.rl).into() {
+                    // Keep the envelope cached and try again once sending succeeds.
+                    return;
+                }
+            }
+            if let Some(cache) = &mut self.offline_cache {
+                cache.remove(&path);
+            }
+        }
+    }
+}
+
 impl Drop for TransportThread {
     fn drop(&mut self) {
         self.shutdown.store(true, Ordering::SeqCst);
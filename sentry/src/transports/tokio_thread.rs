@@ -1,12 +1,19 @@
+use std::mem;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TryRecvError, TrySendError};
 use std::sync::Arc;
 use std::thread::{self, JoinHandle};
 use std::time::Duration;
 
+use tokio::sync::Notify;
+
 use sentry_core::client_report::{Reason as ClientReportReason, Recorder as ClientReportRecorder};
+use sentry_core::{OfflineCacheOptions, RetryPolicy};
 
+use super::offline_cache::OfflineCache;
 use super::ratelimit::{RateLimiter, RateLimitingCategory};
+use super::retry::{Backoff, RetryQueue};
+use super::SendOutcome;
 #[cfg(doc)]
 use super::{TokioTransportThread, TokioTransportThreadOptions}; // so we can use pub re-exports in docs
 use crate::{sentry_debug, Envelope};
@@ -25,6 +32,8 @@ enum Task {
 /// A background-thread powered by [`tokio`] dedicated to sending [`Envelope`]s while respecting the rate limits imposed in the responses.
 pub struct TransportThread {
     sender: SyncSender<Task>,
+    /// Wakes up the transport thread after a task was queued.
+    notify: Arc<Notify>,
     shutdown: Arc<AtomicBool>,
     handle: Option<JoinHandle<()>>,
     client_report_recorder: ClientReportRecorder,
@@ -35,6 +44,8 @@ pub struct TransportThread {
 pub struct TransportThreadOptions<F> {
     send_fn: F,
     client_report_recorder: ClientReportRecorder,
+    offline_cache: Option<OfflineCacheOptions>,
+    retry: Option<RetryPolicy>,
 }
 
 impl<F> TransportThreadOptions<F> {
@@ -43,6 +54,8 @@ impl<F> TransportThreadOptions<F> {
         Self {
             send_fn,
             client_report_recorder: Default::default(),
+            offline_cache: None,
+            retry: None,
         }
     }
 
@@ -53,13 +66,35 @@ impl<F> TransportThreadOptions<F> {
             ..self
         }
     }
+
+    /// Enable the offline cache, configured by the given [`OfflineCacheOptions`].
+    ///
+    /// Envelopes whose send future resolves to a [`SendOutcome::Failed`] are written to the cache
+    /// by the transport thread and sent later.
+    pub fn with_offline_cache(self, offline_cache: Option<OfflineCacheOptions>) -> Self {
+        Self {
+            offline_cache,
+            ..self
+        }
+    }
+
+    /// Enable retries of envelopes whose send future resolves to a [`SendOutcome::Failed`],
+    /// configured by the given [`RetryPolicy`].
+    ///
+    /// Envelopes are retried on the transport thread, after the backoff configured by the policy.
+    /// The thread keeps sending other envelopes while an envelope waits for its retry. An envelope which is still failing once the policy is exhausted, or when the thread shuts
+    /// down, is stored in the offline cache if one is configured, and recorded as lost otherwise.
+    pub fn with_retry(self, retry: Option<RetryPolicy>) -> Self {
+        Self { retry, ..self }
+    }
 }
 
 impl<F, SendFuture> TransportThreadOptions<F>
 where
     F: FnMut(Envelope, RateLimiter) -> SendFuture + Send + 'static,
     // NOTE: return RateLimiter to avoid lifetime issues with mutable borrowing across await.
-    SendFuture: std::future::Future<Output = RateLimiter>,
+    SendFuture: std::future::Future,
+    SendFuture::Output: Into<(RateLimiter, SendOutcome)>,
 {
     /// Spawn a [`TokioTransportThread`], configured per these options.
     pub fn spawn_thread(self) -> TransportThread {
@@ -86,68 +121,53 @@ impl TransportThread {
     where
         SendFn: FnMut(Envelope, RateLimiter) -> SendFuture + Send + 'static,
         // NOTE: return RateLimiter to avoid lifetime issues with mutable borrowing across await.
-        SendFuture: std::future::Future<Output = RateLimiter>,
+        SendFuture: std::future::Future,
+        SendFuture::Output: Into<(RateLimiter, SendOutcome)>,
     {
         let TransportThreadOptions {
-            send_fn: mut send,
+            send_fn: send,
             client_report_recorder,
+            offline_cache,
+            retry,
         } = options;
         let (sender, receiver) = sync_channel(30);
+        let notify = Arc::new(Notify::new());
         let shutdown = Arc::new(AtomicBool::new(false));
+        let worker_notify = notify.clone();
         let shutdown_worker = shutdown.clone();
-        let handle_client_report_recorder = client_report_recorder.clone();
+        let worker_client_report_recorder = client_report_recorder.clone();
         let handle = thread::Builder::new()
             .name("sentry-transport".into())
             .spawn(move || {
+                // The cache is only ever accessed from this thread, so that no disk I/O happens
+                // on the threads sending envelopes.
+                let offline_cache = offline_cache.and_then(|options| {
+                    OfflineCache::open(options, worker_client_report_recorder.clone())
+                });
+                let worker = Worker {
+                    send,
+                    rl: RateLimiter::new(),
+                    shutdown: shutdown_worker,
+                    retry,
+                    retries: RetryQueue::default(),
+                    offline_cache,
+                    client_report_recorder: worker_client_report_recorder,
+                };
+
                 // create a runtime on the transport thread
                 let rt = tokio::runtime::Builder::new_current_thread()
                     .enable_all()
                     .build()
                     .unwrap();
 
-                let mut rl = RateLimiter::new();
-
                 // and block on an async fn in this runtime/thread
-                rt.block_on(async move {
-                    for task in receiver.into_iter() {
-                        if shutdown_worker.load(Ordering::SeqCst) {
-                            return;
-                        }
-                        let envelope = match task {
-                            Task::SendEnvelope(envelope) => envelope,
-                            Task::Flush(sender) => {
-                                sender.send(()).ok();
-                                continue;
-                            }
-                            Task::Shutdown => {
-                                return;
-                            }
-                        };
-
-                        if let Some(time_left) = rl.is_disabled(RateLimitingCategory::Any) {
-                            sentry_debug!(
-                                "Skipping event send because we're disabled due to rate limits for {}s",
-                                time_left.as_secs()
-                            );
-                            handle_client_report_recorder
-                                .record_lost_data(&envelope, ClientReportReason::RatelimitBackoff);
-                            continue;
-                        }
-                        match rl.filter(envelope, &handle_client_report_recorder) {
-                            Some(envelope) => {
-                                rl = send(envelope, rl).await;
-                            }
-                            None => {
-                                sentry_debug!("Envelope was discarded due to per-item rate limits");
-                            }
-                        };
-                    }
-                })
+                rt.block_on(worker.run(receiver, &worker_notify));
             })
             .ok();
 
         Self {
             sender,
+            notify,
             shutdown,
             handle,
             client_report_recorder,
@@ -161,7 +181,9 @@ impl TransportThread {
         // Using send here would mean that when the channel fills up for whatever
         // reason, trying to send an envelope would block everything. We'd rather
         // drop the envelope in that case.
-        if let Err(e) = self.sender.try_send(Task::SendEnvelope(envelope)) {
+        let result = self.sender.try_send(Task::SendEnvelope(envelope));
+        self.notify.notify_one();
+        if let Err(e) = result {
             sentry_debug!("envelope dropped: {e}");
 
             // Get back the envelope from the TrySendError so we can record it as lost.
@@ -172,7 +194,6 @@ impl TransportThread {
             let Task::SendEnvelope(envelope) = task else {
                 unreachable!("we sent a `SendEnvelope` task");
             };
-
             self.client_report_recorder
                 .record_lost_data(&envelope, reason);
         }
@@ -180,18 +201,187 @@ impl TransportThread {
 
     /// Flush all pending [`Envelope`]s.
     ///
+    /// [`Envelope`]s waiting for a retry are pending until they are sent or given up on.
+    ///
     /// Returns true if successful within given timeout.
     pub fn flush(&self, timeout: Duration) -> bool {
         let (sender, receiver) = sync_channel(1);
         let _ = self.sender.send(Task::Flush(sender));
+        self.notify.notify_one();
         receiver.recv_timeout(timeout).is_ok()
     }
 }
 
+/// The state of the transport thread.
+struct Worker<SendFn> {
+    send: SendFn,
+    rl: RateLimiter,
+    shutdown: Arc<AtomicBool>,
+    retry: Option<RetryPolicy>,
+    retries: RetryQueue,
+    offline_cache: Option<OfflineCache>,
+    client_report_recorder: ClientReportRecorder,
+}
+
+impl<SendFn, SendFuture> Worker<SendFn>
+where
+    SendFn: FnMut(Envelope, RateLimiter) -> SendFuture,
+    SendFuture: std::future::Future,
+    SendFuture::Output: Into<(RateLimiter, SendOutcome)>,
+{
+    /// Handles the queued tasks until the thread shuts down.
+    ///
+    /// The queue is drained whenever `notify` is notified, and envelopes waiting for a retry are
+    /// sent once they are due in the meantime.
+    async fn run(mut self, receiver: Receiver<Task>, notify: &Notify) {
+        // Deliver anything left over from a previous run first.
+        self.send_cached().await;
+
+        // Flushes are answered once no envelope is waiting for a retry anymore.
+        let mut flushes = Vec::new();
+        loop {
+            let task = match receiver.try_recv() {
+                Ok(task) => Some(task),
+                Err(TryRecvError::Empty) => {
+                    // Wait for the next task, but no longer than until the next retry is due.
+                    match self.retries.next_due() {
+                        Some(due) => {
+                            tokio::select! {
+                                _ = notify.notified() => {}
+                                _ = tokio::time::sleep_until(due.into()) => {}
+                            }
+                        }
+                        None => notify.notified().await,
+                    }
+                    None
+                }
+                Err(TryRecvError::Disconnected) => break,
+            };
+            if self.is_shutting_down() {
+                break;
+            }
+            match task {
+                Some(Task::SendEnvelope(envelope)) => self.send_queued(envelope).await,
+                Some(Task::Flush(sender)) => flushes.push(sender),
+                Some(Task::Shutdown) => break,
+                None => {}
+            }
+
+            self.send_due_retries().await;
+            if self.retries.is_empty() {
+                for sender in flushes.drain(..) {
+                    sender.send(()).ok();
+                }
+            }
+        }
+        self.give_up_retries();
+    }
+
+    fn is_shutting_down(&self) -> bool {
+        self.shutdown.load(Ordering::SeqCst)
+    }
+
+    /// Sends the rate limiter along with an envelope, taking it back once the send completes.
+    async fn send_envelope(&mut self, envelope: Envelope) -> SendOutcome {
+        let outcome;
+        (self.rl, outcome) = (self.send)(envelope, mem::take(&mut self.rl)).await.into();
+        outcome
+    }
+
+    /// Sends an envelope taken from the queue, unless it is rate limited.
+    async fn send_queued(&mut self, envelope: Envelope) {
+        if let Some(time_left) = self.rl.is_disabled(RateLimitingCategory::Any) {
+            sentry_debug!(
+                "Skipping event send because we're disabled due to rate limits for {}s",
+                time_left.as_secs()
+            );
+            self.client_report_recorder
+                .record_lost_data(&envelope, ClientReportReason::RatelimitBackoff);
+            return;
+        }
+        match self.rl.filter(envelope, &self.client_report_recorder) {
+            Some(envelope) => self.attempt(envelope, None).await,
+            None => {
+                sentry_debug!("Envelope was discarded due to per-item rate limits");
+            }
+        }
+    }
+
+    /// Sends the envelopes whose retry is due.
+    async fn send_due_retries(&mut self) {
+        while let Some((envelope, backoff)) = self.retries.pop_due() {
+            // Rate limits for some of the items may have started in the meantime.
+            if let Some(envelope) = self.rl.filter(envelope, &self.client_report_recorder) {
+                self.attempt(envelope, Some(backoff)).await;
+            }
+        }
+    }
+
+    /// Makes an attempt to send the envelope, scheduling a retry if it fails.
+    ///
+    /// `backoff` tracks the previous attempts, and is [`None`] for the first one.
+    async fn attempt(&mut self, envelope: Envelope, backoff: Option<Backoff>) {
+        match self.send_envelope(envelope).await {
+            SendOutcome::Done => self.send_cached().await,
+            SendOutcome::Failed { envelope, reason } => {
+                let envelope = match self.retry {
+                    Some(retry) => {
+                        let backoff = backoff.unwrap_or_else(|| Backoff::new(retry));
+                        self.retries.schedule(envelope, reason, backoff, &self.rl)
+                    }
+                    None => Some(envelope),
+                };
+                if let Some(envelope) = envelope {
+                    self.give_up(&envelope, reason);
+                }
+            }
+        }
+    }
+
+    /// Stores the envelopes still waiting for a retry in the offline cache, or records them as
+    /// lost.
+    fn give_up_retries(&mut self) {
+        let retries: Vec<_> = self.retries.drain().collect();
+        for (envelope, reason) in retries {
+            self.give_up(&envelope, reason);
+        }
+    }
+
+    /// Stores an envelope which could not be sent in the offline cache, or records it as lost.
+    fn give_up(&mut self, envelope: &Envelope, reason: ClientReportReason) {
+        match &mut self.offline_cache {
+            Some(cache) => cache.store(envelope),
+            None => self
+                .client_report_recorder
+                .record_lost_data(envelope, reason),
+        }
+    }
+
+    /// Sends cached envelopes, oldest first, until the cache is empty or sending fails.
+    async fn send_cached(&mut self) {
+        while let Some((path, envelope)) = self.offline_cache.as_mut().and_then(OfflineCache::peek)
+        {
+            if self.is_shutting_down() || self.rl.is_disabled(RateLimitingCategory::Any).is_some() {
+                return;
+            }
+            if let Some(envelope) = self.rl.filter(envelope, &self.client_report_recorder) {
+                if let SendOutcome::Failed { .. } = self.send_envelope(envelope).await {
+                    // Keep the envelope cached and try again once sending succeeds.
+                    return;
+                }
+            }
+            if let Some(cache) = &mut self.offline_cache {
+                cache.remove(&path);
+            }
+        }
+    }
+}
+
 impl Drop for TransportThread {
     fn drop(&mut self) {
         self.shutdown.store(true, Ordering::SeqCst);
         let _ = self.sender.send(Task::Shutdown);
+        self.notify.notify_one();
         if let Some(handle) = self.handle.take() {
             handle.join().unwrap();
         }
@@ -12,8 +12,9 @@ use ureq::tls::{TlsConfig, TlsProvider};
 use ureq::{Agent, Proxy};
 
 use super::{
+    compression::compress_body,
     thread::{TransportThread, TransportThreadOptions},
-    RateLimiter, HTTP_PAYLOAD_TOO_LARGE, HTTP_PAYLOAD_TOO_LARGE_MESSAGE,
+    RateLimiter, SendOutcome, HTTP_PAYLOAD_TOO_LARGE, HTTP_PAYLOAD_TOO_LARGE_MESSAGE,
 };
 
 use crate::{sentry_debug, types::Scheme, ClientOptions, Envelope, Transport};
@@ -93,6 +94,9 @@ impl UreqHttpTransport {
                         feature = "rustls-no-provider"
                     ))]
                     accept_invalid_certs,
+                    offline_cache,
+                    compression,
+                    retry,
                     client_report_recorder,
                     ..
                 },
@@ -161,9 +165,12 @@ impl UreqHttpTransport {
                         .record_lost_data(&envelope, LossReason::InternalError);
                 })
                 .expect("envelope should serialize successfully");
-            let request = agent
-                .post(&url)
-                .header("X-Sentry-Auth", &auth)
+            let (body, content_encoding) = compress_body(body, compression.as_ref());
+            let mut request = agent.post(&url).header("X-Sentry-Auth", &auth);
+            if let Some(content_encoding) = content_encoding {
+                request = request.header("Content-Encoding", content_encoding);
+            }
+            let request = request
                 .config()
                 .http_status_as_error(false)
                 .build()
@@ -197,23 +204,35 @@ impl UreqHttpTransport {
                         sentry_debug!("{HTTP_PAYLOAD_TOO_LARGE_MESSAGE}");
                     }
 
-                    if (400..=599).contains(&response_status)
-                        && response_status != HTTP_RATE_LIMIT_STATUS
-                    {
-                        send_fn_client_report_recorder
-                            .record_lost_data(&envelope, LossReason::SendError);
+                    if (500..=599).contains(&response_status) {
+                        SendOutcome::Failed {
+                            envelope,
+                            reason: LossReason::SendError,
+                        }
+                    } else {
+                        if (400..=499).contains(&response_status)
+                            && response_status != HTTP_RATE_LIMIT_STATUS
+                        {
+                            send_fn_client_report_recorder
+                                .record_lost_data(&envelope, LossReason::SendError);
+                        }
+                        SendOutcome::Done
                     }
                 }
                 Err(err) => {
                     sentry_debug!("Failed to send envelope: {}", err);
-                    send_fn_client_report_recorder
-                        .record_lost_data(&envelope, LossReason::NetworkError);
+                    SendOutcome::Failed {
+                        envelope,
+                        reason: LossReason::NetworkError,
+                    }
                 }
             }
         };
 
         let thread = TransportThreadOptions::new(send_fn)
             .with_client_report_recorder(client_report_recorder)
+            .with_offline_cache(offline_cache)
+            .with_retry(retry)
             .spawn_thread();
         Self { thread }
     }
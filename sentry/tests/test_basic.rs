@@ -4,7 +4,7 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use sentry::protocol::{
-    Attachment, Context, DynamicSamplingContext, EnvelopeHeaders, EnvelopeItem,
+    Attachment, Context, DynamicSamplingContext, EnvelopeHeaders, EnvelopeItem, Feedback, User,
 };
 use sentry::types::{Dsn, Uuid};
 
@@ -216,6 +216,77 @@ fn test_attachment_sent_from_scope() {
     ));
 }
 
+#[test]
+fn test_capture_feedback() {
+    let mut event_id = Uuid::nil();
+    let mut feedback_id = Uuid::nil();
+    let envelopes = sentry::test::with_captured_envelopes_options(
+        || {
+            sentry::configure_scope(|scope| {
+                scope.set_user(Some(User {
+                    id: Some("42".into()),
+                    ..Default::default()
+                }));
+                scope.set_tag("component", "checkout");
+            });
+            event_id = sentry::capture_message("test", sentry::Level::Error);
+            feedback_id = sentry::capture_feedback(Feedback {
+                message: "It broke".into(),
+                associated_event_id: Some(event_id),
+                ..Default::default()
+            });
+        },
+        sentry::ClientOptions::new().release("app@1.0.0"),
+    );
+
+    assert_eq!(envelopes.len(), 2);
+    assert!(!feedback_id.is_nil());
+    let envelope = &envelopes[1];
+    assert_eq!(envelope.uuid(), Some(&feedback_id));
+
+    let mut items = envelope.items();
+    let Some(EnvelopeItem::Feedback(feedback)) = items.next() else {
+        panic!("expected feedback item");
+    };
+    assert!(items.next().is_none());
+    assert_eq!(feedback.message, "It broke");
+    assert_eq!(feedback.associated_event_id, Some(event_id));
+    assert_eq!(feedback.release.as_deref(), Some("app@1.0.0"));
+    assert_eq!(feedback.user.as_ref().unwrap().id.as_deref(), Some("42"));
+    assert_eq!(feedback.tags["component"], "checkout");
+}
+
+#[test]
+fn test_before_send_feedback() {
+    let mut feedback_id = sentry::types::random_uuid();
+    let envelopes = sentry::test::with_captured_envelopes_options(
+        || {
+            sentry::capture_feedback(Feedback {
+                message: "My email is jane@example.com".into(),
+                ..Default::default()
+            });
+            feedback_id = sentry::capture_feedback(Feedback {
+                message: "drop me".into(),
+                ..Default::default()
+            });
+        },
+        sentry::ClientOptions::new().before_send_feedback(|mut feedback| {
+            if feedback.message == "drop me" {
+                return None;
+            }
+            feedback.message = feedback.message.replace("jane@example.com", "[email]");
+            Some(feedback)
+        }),
+    );
+
+    assert!(feedback_id.is_nil());
+    assert_eq!(envelopes.len(), 1);
+    let Some(EnvelopeItem::Feedback(feedback)) = envelopes[0].items().next() else {
+        panic!("expected feedback item");
+    };
+    assert_eq!(feedback.message, "My email is [email]");
+}
+
 #[cfg(feature = "panic")]
 #[test]
 fn test_panic_scope_pop() {
@@ -262,6 +333,47 @@ fn test_panic_scope_pop() {
     );
 }
 
+#[cfg(feature = "panic")]
+#[test]
+fn test_panic_attach_all_threads() {
+    let options = sentry::ClientOptions::new().add_integration(
+        sentry::integrations::panic::PanicIntegration::new().attach_all_threads(true),
+    );
+
+    let (sender, receiver) = std::sync::mpsc::channel::<()>();
+    let worker = std::thread::Builder::new()
+        .name("idle-worker".into())
+        .spawn(move || receiver.recv())
+        .unwrap();
+
+    let events = sentry::test::with_captured_events_options(
+        || {
+            let panic = std::panic::catch_unwind(|| panic!("deadlocked"));
+            assert!(panic.is_err());
+        },
+        options,
+    );
+    drop(sender);
+    worker.join().unwrap().unwrap_err();
+
+    assert_eq!(events.len(), 1);
+    let threads = &events[0].threads.values;
+    let crashed: Vec<_> = threads.iter().filter(|thread| thread.crashed).collect();
+    assert_eq!(crashed.len(), 1);
+    assert!(crashed[0].current);
+    assert!(crashed[0].id.is_some());
+    assert_eq!(events[0].exception[0].thread_id, crashed[0].id);
+
+    if cfg!(target_os = "linux") {
+        let worker = threads
+            .iter()
+            .find(|thread| thread.name.as_deref() == Some("idle-worker"))
+            .expect("the worker thread should be captured");
+        assert!(!worker.crashed);
+        assert!(worker.stacktrace.is_some());
+    }
+}
+
 #[cfg(feature = "logs")]
 #[test]
 fn test_basic_capture_log() {
@@ -0,0 +1,197 @@
+#![cfg(all(
+    any(feature = "curl", feature = "ureq", feature = "reqwest"),
+    any(feature = "gzip", feature = "zstd")
+))]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use sentry::protocol::{Envelope, Event};
+use sentry::{
+    ClientOptions, CompressionAlgorithm, CompressionOptions, Transport, TransportOptions,
+};
+
+/// A request received by the [`MockServer`].
+struct Request {
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A minimal HTTP server which answers every request with `200 OK`.
+struct MockServer {
+    port: u16,
+    requests: mpsc::Receiver<Request>,
+}
+
+impl MockServer {
+    fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (sender, requests) = mpsc::channel();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+
+                let mut headers = Vec::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some((key, value)) = line.split_once(':') {
+                        headers.push((key.trim().to_owned(), value.trim().to_owned()));
+                    }
+                }
+
+                let content_length = headers
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case("content-length"))
+                    .map(|(_, value)| value.parse().unwrap())
+                    .unwrap_or(0);
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                    .unwrap();
+                if sender.send(Request { headers, body }).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self { port, requests }
+    }
+
+    fn transport_options(&self, compression: CompressionOptions) -> TransportOptions {
+        let options = ClientOptions::new()
+            .dsn(&format!("http://public@127.0.0.1:{}/1", self.port))
+            .compression(compression);
+        TransportOptions::try_from_client_options(&options).unwrap()
+    }
+
+    fn next_request(&self) -> Request {
+        self.requests
+            .recv_timeout(Duration::from_secs(10))
+            .expect("mock server should receive a request")
+    }
+}
+
+fn large_envelope() -> Envelope {
+    Envelope::from(Event {
+        message: Some("x".repeat(4096)),
+        ..Default::default()
+    })
+}
+
+fn serialized(envelope: &Envelope) -> Vec<u8> {
+    let mut body = Vec::new();
+    envelope.to_writer(&mut body).unwrap();
+    body
+}
+
+fn decompress(encoding: &str, body: &[u8]) -> Vec<u8> {
+    let mut decompressed = Vec::new();
+    match encoding {
+        #[cfg(feature = "gzip")]
+        "gzip" => {
+            flate2::read::GzDecoder::new(body)
+                .read_to_end(&mut decompressed)
+                .unwrap();
+        }
+        #[cfg(feature = "zstd")]
+        "zstd" => {
+            zstd::stream::read::Decoder::new(body)
+                .unwrap()
+                .read_to_end(&mut decompressed)
+                .unwrap();
+        }
+        encoding => panic!("unexpected content encoding {encoding:?}"),
+    }
+    decompressed
+}
+
+/// The algorithms compiled into the `sentry` crate.
+const ALGORITHMS: &[CompressionAlgorithm] = &[
+    #[cfg(feature = "gzip")]
+    CompressionAlgorithm::Gzip,
+    #[cfg(feature = "zstd")]
+    CompressionAlgorithm::Zstd,
+];
+
+/// Sends a large and a small envelope through the transport created by `build`, and checks that
+/// only the large one is compressed.
+fn assert_compresses<F>(build: F)
+where
+    F: Fn(TransportOptions) -> Arc<dyn Transport>,
+{
+    for &algorithm in ALGORITHMS {
+        let server = MockServer::start();
+        let transport = build(server.transport_options(CompressionOptions::new(algorithm)));
+
+        let envelope = large_envelope();
+        let expected = serialized(&envelope);
+        transport.send_envelope(envelope);
+        let request = server.next_request();
+        let encoding = request
+            .header("content-encoding")
+            .expect("large envelopes should be compressed");
+        assert_eq!(encoding, algorithm.content_encoding());
+        assert!(request.header("x-sentry-auth").is_some());
+        assert!(request.body.len() < expected.len());
+        assert_eq!(decompress(encoding, &request.body), expected);
+
+        let envelope = Envelope::from(Event::default());
+        let expected = serialized(&envelope);
+        transport.send_envelope(envelope);
+        let request = server.next_request();
+        assert_eq!(request.header("content-encoding"), None);
+        assert_eq!(request.body, expected);
+
+        assert!(transport.shutdown(Duration::from_secs(5)));
+    }
+}
+
+#[cfg(feature = "reqwest")]
+#[test]
+fn test_reqwest_compression() {
+    assert_compresses(|options| {
+        Arc::new(sentry::transports::ReqwestHttpTransportOptions::from(options).build())
+    });
+}
+
+#[cfg(feature = "curl")]
+#[test]
+fn test_curl_compression() {
+    assert_compresses(|options| {
+        Arc::new(sentry::transports::CurlHttpTransportOptions::from(options).build())
+    });
+}
+
+#[cfg(feature = "ureq")]
+#[test]
+fn test_ureq_compression() {
+    assert_compresses(|options| {
+        Arc::new(sentry::transports::UreqHttpTransportOptions::from(options).build())
+    });
+}
@@ -0,0 +1,129 @@
+#![cfg(all(feature = "test", feature = "data-scrubber"))]
+
+use sentry::integrations::data_scrubber::DataScrubberIntegration;
+use sentry::protocol::{EnvelopeItem, Event, ItemContainer, Request};
+
+#[test]
+fn test_scrubs_events_before_send() {
+    let options = sentry::ClientOptions::new()
+        .add_integration(DataScrubberIntegration::new())
+        .before_send(|event| {
+            assert_eq!(event.extra["password"], "[Filtered]");
+            Some(event)
+        });
+
+    let events = sentry::test::with_captured_events_options(
+        || {
+            sentry::capture_event(Event {
+                message: Some("paid with 4111 1111 1111 1111".into()),
+                extra: [("password".to_owned(), "hunter2".into())]
+                    .into_iter()
+                    .collect(),
+                request: Some(Request {
+                    headers: [("Authorization".to_owned(), "Bearer abc".to_owned())]
+                        .into_iter()
+                        .collect(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            });
+        },
+        options,
+    );
+
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event.message.as_deref(), Some("paid with [Filtered]"));
+    assert_eq!(event.extra["password"], "[Filtered]");
+    assert_eq!(
+        event.request.as_ref().unwrap().headers["Authorization"],
+        "[Filtered]"
+    );
+}
+
+#[test]
+fn test_scrubs_transactions() {
+    let options = sentry::ClientOptions::new()
+        .traces_sample_rate(1.0)
+        .add_integration(DataScrubberIntegration::new());
+
+    let envelopes = sentry::test::with_captured_envelopes_options(
+        || {
+            let transaction =
+                sentry::start_transaction(sentry::TransactionContext::new("login", "http.server"));
+            let span = transaction.start_child("db.query", "SELECT * FROM users");
+            span.set_data("auth_token", "abc".into());
+            span.set_data("rows", 1.into());
+            span.finish();
+            transaction.finish();
+        },
+        options,
+    );
+
+    let transaction = envelopes
+        .iter()
+        .flat_map(|envelope| envelope.items())
+        .find_map(|item| match item {
+            EnvelopeItem::Transaction(transaction) => Some(transaction),
+            _ => None,
+        })
+        .expect("expected a transaction");
+    assert_eq!(transaction.spans[0].data["auth_token"], "[Filtered]");
+    assert_eq!(transaction.spans[0].data["rows"], 1);
+}
+
+#[test]
+fn test_scrubs_streamed_spans() {
+    let options = sentry::ClientOptions::new()
+        .traces_sample_rate(1.0)
+        .trace_lifecycle(sentry::TraceLifecycle::Stream)
+        .add_integration(DataScrubberIntegration::new());
+
+    let envelopes = sentry::test::with_captured_envelopes_options(
+        || {
+            let transaction =
+                sentry::start_transaction(sentry::TransactionContext::new("login", "http.server"));
+            let span = transaction.start_child("db.query", "SELECT * FROM users");
+            span.set_data("auth_token", "abc".into());
+            span.finish();
+            transaction.finish();
+        },
+        options,
+    );
+
+    let span = envelopes
+        .iter()
+        .flat_map(|envelope| envelope.items())
+        .find_map(|item| match item {
+            EnvelopeItem::ItemContainer(ItemContainer::Spans(spans)) => spans.first(),
+            _ => None,
+        })
+        .expect("expected a streamed span");
+    assert_eq!(span.attributes["auth_token"].0, "[Filtered]");
+}
+
+#[cfg(feature = "logs")]
+#[test]
+fn test_scrubs_logs() {
+    let options = sentry::ClientOptions::new()
+        .enable_logs(true)
+        .add_integration(DataScrubberIntegration::empty().deny_key("api_key"));
+
+    let envelopes = sentry::test::with_captured_envelopes_options(
+        || {
+            sentry::logger_info!(api_key = "abc", user.id = "42", "Request failed");
+        },
+        options,
+    );
+
+    let log = envelopes
+        .iter()
+        .flat_map(|envelope| envelope.items())
+        .find_map(|item| match item {
+            EnvelopeItem::ItemContainer(ItemContainer::Logs(logs)) => logs.first(),
+            _ => None,
+        })
+        .expect("expected a log");
+    assert_eq!(log.attributes["api_key"], "[Filtered]".into());
+    assert_eq!(log.attributes["user.id"], "42".into());
+}
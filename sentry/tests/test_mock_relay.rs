@@ -0,0 +1,134 @@
+#![cfg(all(
+    feature = "test",
+    any(feature = "curl", feature = "ureq", feature = "reqwest")
+))]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use sentry::protocol::{Envelope, Event, Transaction};
+use sentry::test::{MockRelay, MockResponse};
+use sentry::{ClientOptions, RetryPolicy, Transport, TransportFactory, TransportOptions};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn transport_options(dsn: &str) -> TransportOptions {
+    let options = ClientOptions::new().dsn(dsn).retry(
+        RetryPolicy::new()
+            .max_attempts(2)
+            .initial_backoff(Duration::from_millis(10)),
+    );
+    TransportOptions::try_from_client_options(&options).unwrap()
+}
+
+fn event_envelope(message: &str) -> Envelope {
+    Envelope::from(Event {
+        message: Some(message.into()),
+        ..Default::default()
+    })
+}
+
+fn transaction_envelope() -> Envelope {
+    let mut envelope = Envelope::new();
+    envelope.add_item(Transaction::default());
+    envelope
+}
+
+/// Checks the requests sent by the transport created by `build` against a [`MockRelay`].
+fn assert_transport<F>(build: F)
+where
+    F: Fn(TransportOptions) -> Arc<dyn Transport>,
+{
+    // Envelopes are authenticated and sent to the project's envelope endpoint.
+    let relay = MockRelay::start();
+    let transport = build(transport_options(&relay.dsn()));
+    transport.send_envelope(event_envelope("first"));
+    let requests = relay.wait_for_requests(1, TIMEOUT);
+    assert_eq!(requests[0].method(), "POST");
+    assert_eq!(requests[0].path(), "/api/1/envelope/");
+    assert_eq!(requests[0].status(), 200);
+    let auth = requests[0].auth().expect("request should be authenticated");
+    assert_eq!(auth.public_key(), "public");
+    assert!(auth.client_agent().unwrap().starts_with("sentry.rust/"));
+    let events: Vec<_> = relay
+        .envelopes()
+        .iter()
+        .filter_map(|envelope| envelope.event().cloned())
+        .collect();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].message.as_deref(), Some("first"));
+
+    // Rate limited categories are not sent anymore.
+    relay.respond_with(MockResponse::rate_limited("60:error:organization"));
+    transport.send_envelope(event_envelope("rate limited"));
+    transport.send_envelope(event_envelope("dropped"));
+    transport.send_envelope(transaction_envelope());
+    assert!(transport.flush(TIMEOUT));
+    let requests = relay.wait_for_requests(3, TIMEOUT);
+    assert_eq!(requests[1].status(), 429);
+    assert!(requests[2].envelope().unwrap().event().is_none());
+    relay.assert_request_count(3);
+    assert!(transport.shutdown(TIMEOUT));
+
+    // Server errors are retried.
+    let relay = MockRelay::start();
+    relay.respond_with(MockResponse::status(503));
+    let transport = build(transport_options(&relay.dsn()));
+    transport.send_envelope(event_envelope("retried"));
+    let requests = relay.wait_for_requests(2, TIMEOUT);
+    assert_eq!(requests[0].status(), 503);
+    assert_eq!(requests[1].status(), 200);
+    assert_eq!(requests[0].envelope(), requests[1].envelope());
+    assert!(transport.shutdown(TIMEOUT));
+
+    // Flushing waits for slow responses.
+    let relay = MockRelay::start();
+    relay.respond_with(MockResponse::ok().delay(Duration::from_millis(500)));
+    let transport = build(transport_options(&relay.dsn()));
+    transport.send_envelope(event_envelope("slow"));
+    assert!(!transport.flush(Duration::from_millis(50)));
+    assert!(transport.flush(TIMEOUT));
+    relay.assert_request_count(1);
+    assert!(transport.shutdown(TIMEOUT));
+}
+
+#[test]
+fn test_mock_relay_rejects_invalid_requests() {
+    let relay = MockRelay::start();
+    let dsn = relay.dsn().replace("public@", "other@");
+
+    // Scripted responses only apply to accepted requests.
+    relay.respond_with(MockResponse::status(500));
+    let transport = sentry::transports::DefaultTransportFactory
+        .create_transport_with_options(transport_options(&dsn));
+    transport.send_envelope(event_envelope("unauthorized"));
+    let requests = relay.wait_for_requests(1, TIMEOUT);
+    assert_eq!(requests[0].status(), 401);
+    assert_eq!(requests[0].auth().unwrap().public_key(), "other");
+    assert!(relay.envelopes().is_empty());
+    assert!(transport.shutdown(TIMEOUT));
+}
+
+#[cfg(feature = "reqwest")]
+#[test]
+fn test_reqwest_mock_relay() {
+    assert_transport(|options| {
+        Arc::new(sentry::transports::ReqwestHttpTransportOptions::from(options).build())
+    });
+}
+
+#[cfg(feature = "curl")]
+#[test]
+fn test_curl_mock_relay() {
+    assert_transport(|options| {
+        Arc::new(sentry::transports::CurlHttpTransportOptions::from(options).build())
+    });
+}
+
+#[cfg(feature = "ureq")]
+#[test]
+fn test_ureq_mock_relay() {
+    assert_transport(|options| {
+        Arc::new(sentry::transports::UreqHttpTransportOptions::from(options).build())
+    });
+}
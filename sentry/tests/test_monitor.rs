@@ -0,0 +1,154 @@
+#![cfg(feature = "test")]
+
+use std::panic::{self, AssertUnwindSafe};
+
+use sentry::monitor;
+use sentry::protocol::{
+    EnvelopeItem, MonitorCheckIn, MonitorCheckInStatus, MonitorConfig, MonitorSchedule,
+};
+use sentry::Envelope;
+
+fn check_ins(envelopes: &[Envelope]) -> Vec<MonitorCheckIn> {
+    envelopes
+        .iter()
+        .flat_map(|envelope| envelope.items())
+        .filter_map(|item| match item {
+            EnvelopeItem::MonitorCheckIn(check_in) => Some(check_in.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_with_check_in() {
+    let config = MonitorConfig::new(MonitorSchedule::from_crontab("0 3 * * *").unwrap());
+    let mut trace_id = None;
+    let envelopes = sentry::test::with_captured_envelopes_options(
+        || {
+            trace_id = Some(sentry::configure_scope(|scope| {
+                scope.iter_trace_propagation_headers().next().unwrap().1
+            }));
+            let result =
+                monitor::with_check_in("nightly", Some(config.clone()), || Ok::<_, ()>(42));
+            assert_eq!(result, Ok(42));
+        },
+        sentry::ClientOptions::new().environment("production"),
+    );
+
+    let check_ins = check_ins(&envelopes);
+    assert_eq!(check_ins.len(), 2);
+    let (started, finished) = (&check_ins[0], &check_ins[1]);
+
+    assert_eq!(started.monitor_slug, "nightly");
+    assert_eq!(started.status, MonitorCheckInStatus::InProgress);
+    assert_eq!(started.duration, None);
+    assert_eq!(started.environment.as_deref(), Some("production"));
+    assert_eq!(started.monitor_config.as_ref(), Some(&config));
+
+    assert_eq!(finished.check_in_id, started.check_in_id);
+    assert_eq!(finished.status, MonitorCheckInStatus::Ok);
+    assert!(finished.duration.is_some());
+    assert_eq!(finished.monitor_config.as_ref(), Some(&config));
+
+    let trace_id = trace_id.unwrap();
+    for check_in in &check_ins {
+        let trace = check_in.contexts.as_ref().unwrap().trace.as_ref().unwrap();
+        assert!(trace_id.starts_with(&trace.trace_id.to_string()));
+    }
+}
+
+#[test]
+fn test_with_check_in_error() {
+    let envelopes = sentry::test::with_captured_envelopes(|| {
+        let result: Result<(), &str> = monitor::with_check_in("nightly", None, || Err("failed"));
+        assert!(result.is_err());
+    });
+
+    let check_ins = check_ins(&envelopes);
+    assert_eq!(check_ins.len(), 2);
+    assert_eq!(check_ins[1].status, MonitorCheckInStatus::Error);
+    assert_eq!(check_ins[1].monitor_config, None);
+}
+
+#[test]
+fn test_with_check_in_panic() {
+    let envelopes = sentry::test::with_captured_envelopes(|| {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            monitor::with_check_in("nightly", None, || -> Result<(), ()> {
+                panic!("job failed")
+            })
+        }));
+        assert!(result.is_err());
+    });
+
+    let check_ins = check_ins(&envelopes);
+    assert_eq!(check_ins.len(), 2);
+    assert_eq!(check_ins[1].status, MonitorCheckInStatus::Error);
+}
+
+#[test]
+fn test_check_in_guard() {
+    let envelopes = sentry::test::with_captured_envelopes(|| {
+        let guard = monitor::start_check_in("first", None);
+        guard.finish(MonitorCheckInStatus::Error);
+
+        let _guard = monitor::start_check_in("second", None);
+    });
+
+    let check_ins = check_ins(&envelopes);
+    let statuses: Vec<_> = check_ins
+        .iter()
+        .map(|check_in| (check_in.monitor_slug.as_str(), check_in.status))
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            ("first", MonitorCheckInStatus::InProgress),
+            ("first", MonitorCheckInStatus::Error),
+            ("second", MonitorCheckInStatus::InProgress),
+            ("second", MonitorCheckInStatus::Ok),
+        ]
+    );
+}
+
+#[test]
+fn test_with_check_in_async() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let envelopes = sentry::test::with_captured_envelopes(|| {
+        let job = monitor::with_check_in_async("nightly", None, async { Ok::<_, ()>(()) });
+        assert!(runtime.block_on(job).is_ok());
+    });
+
+    let check_ins = check_ins(&envelopes);
+    assert_eq!(check_ins.len(), 2);
+    assert_eq!(check_ins[0].status, MonitorCheckInStatus::InProgress);
+    assert_eq!(check_ins[1].status, MonitorCheckInStatus::Ok);
+}
+
+#[test]
+fn test_with_check_in_async_cancelled() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let envelopes = sentry::test::with_captured_envelopes(|| {
+        let job = monitor::with_check_in_async("nightly", None, async {
+            std::future::pending::<()>().await;
+            Ok::<_, ()>(())
+        });
+        // The job is polled once and then dropped, as the other branch is ready.
+        runtime.block_on(async {
+            tokio::select! {
+                biased;
+                _ = job => panic!("the job never completes"),
+                _ = std::future::ready(()) => {}
+            }
+        });
+    });
+
+    let check_ins = check_ins(&envelopes);
+    assert_eq!(check_ins.len(), 2);
+    assert_eq!(check_ins[0].status, MonitorCheckInStatus::InProgress);
+    assert_eq!(check_ins[1].status, MonitorCheckInStatus::Error);
+}
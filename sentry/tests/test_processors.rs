@@ -109,3 +109,73 @@ fn test_before_breadcrumb_callback_drop() {
     assert_eq!(event.message.as_ref().unwrap(), "Hello World!");
     assert_eq!(event.breadcrumbs.len(), 0);
 }
+
+#[test]
+fn test_transaction_processors_and_before_send_transaction() {
+    let envelopes = sentry::test::with_captured_envelopes_options(
+        || {
+            sentry::configure_scope(|scope| {
+                scope.add_transaction_processor(|mut transaction| {
+                    transaction.tags.insert("processed".into(), "yes".into());
+                    Some(transaction)
+                });
+            });
+            for name in ["GET /health", "GET /users"] {
+                let transaction =
+                    sentry::start_transaction(sentry::TransactionContext::new(name, "http.server"));
+                let span = transaction.start_child("db", "SELECT * FROM users");
+                span.set_data("db.password", "hunter2".into());
+                span.finish();
+                transaction.finish();
+            }
+        },
+        sentry::ClientOptions::new()
+            .traces_sample_rate(1.0)
+            .before_send_transaction(|mut transaction| {
+                if transaction.name.as_deref() == Some("GET /health") {
+                    return None;
+                }
+                assert_eq!(transaction.tags["processed"], "yes");
+                transaction.name = Some("GET /users/{id}".into());
+                for span in &mut transaction.spans {
+                    span.data.remove("db.password");
+                }
+                Some(transaction)
+            }),
+    );
+
+    assert_eq!(envelopes.len(), 1);
+    let Some(sentry::protocol::EnvelopeItem::Transaction(transaction)) =
+        envelopes[0].items().next()
+    else {
+        panic!("expected a transaction");
+    };
+    assert_eq!(transaction.name.as_deref(), Some("GET /users/{id}"));
+    assert!(!transaction.spans[0].data.contains_key("db.password"));
+}
+
+#[test]
+fn test_before_send_transaction_can_use_the_hub() {
+    let events = sentry::test::with_captured_events_options(
+        || {
+            sentry::start_transaction(sentry::TransactionContext::new("job", "queue.task"))
+                .finish();
+            sentry::capture_message("after the transaction", sentry::Level::Info);
+        },
+        sentry::ClientOptions::new()
+            .traces_sample_rate(1.0)
+            .before_send_transaction(|transaction| {
+                sentry::add_breadcrumb(sentry::Breadcrumb {
+                    message: transaction.name.clone(),
+                    ..Default::default()
+                });
+                sentry::configure_scope(|scope| scope.set_tag("sent", "job"));
+                Some(transaction)
+            }),
+    );
+
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event.breadcrumbs[0].message.as_deref(), Some("job"));
+    assert_eq!(event.tags["sent"], "job");
+}
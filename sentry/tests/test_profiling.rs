@@ -0,0 +1,101 @@
+#![cfg(all(
+    feature = "test",
+    feature = "profiling",
+    any(target_os = "linux", target_os = "macos")
+))]
+
+use std::hint::black_box;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use sentry::protocol::{Context, EnvelopeItem, ProfileChunk};
+use sentry::Envelope;
+
+/// The profiler is global, so tests registering threads must not run concurrently.
+static PROFILER_LOCK: Mutex<()> = Mutex::new(());
+
+fn profile_chunks(envelopes: &[Envelope]) -> Vec<ProfileChunk> {
+    envelopes
+        .iter()
+        .flat_map(|envelope| envelope.items())
+        .filter_map(|item| match item {
+            EnvelopeItem::ProfileChunk(chunk) => Some((**chunk).clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn busy_loop(duration: Duration) {
+    let start = Instant::now();
+    let mut value = 0u64;
+    while start.elapsed() < duration {
+        value = black_box(value.wrapping_add(1));
+    }
+}
+
+#[test]
+fn test_profiling() {
+    let _lock = PROFILER_LOCK.lock().unwrap();
+    let envelopes = sentry::test::with_captured_envelopes_options(
+        || {
+            let registration = sentry::profiling::register_thread();
+            assert!(sentry::profiling::profiler_id().is_some());
+
+            let transaction =
+                sentry::start_transaction(sentry::TransactionContext::new("profiled", "test"));
+            busy_loop(Duration::from_millis(300));
+            transaction.finish();
+
+            drop(registration);
+            assert_eq!(sentry::profiling::profiler_id(), None);
+        },
+        sentry::ClientOptions::new()
+            .release("app@1.0.0")
+            .traces_sample_rate(1.0)
+            .profile_session_sample_rate(1.0),
+    );
+
+    let chunks = profile_chunks(&envelopes);
+    assert_eq!(chunks.len(), 1);
+    let chunk = &chunks[0];
+    assert_eq!(chunk.release.as_deref(), Some("app@1.0.0"));
+    assert_eq!(chunk.client_sdk.as_ref().unwrap().name, "sentry.rust");
+
+    let profile = &chunk.profile;
+    assert!(!profile.samples.is_empty());
+    for sample in &profile.samples {
+        assert!(profile.stacks[sample.stack_id]
+            .iter()
+            .all(|&frame| frame < profile.frames.len()));
+    }
+    assert!(profile.frames.iter().any(|frame| frame
+        .function
+        .as_deref()
+        .is_some_and(|function| function.contains("busy_loop"))));
+
+    let transaction = envelopes
+        .iter()
+        .find_map(|envelope| {
+            envelope.items().find_map(|item| match item {
+                EnvelopeItem::Transaction(transaction) => Some(transaction),
+                _ => None,
+            })
+        })
+        .unwrap();
+    match transaction.contexts.get("profile") {
+        Some(Context::Profile(profile)) => assert_eq!(profile.profiler_id, chunk.profiler_id),
+        context => panic!("unexpected profile context: {context:?}"),
+    }
+}
+
+#[test]
+fn test_profiling_not_sampled() {
+    let _lock = PROFILER_LOCK.lock().unwrap();
+    let envelopes = sentry::test::with_captured_envelopes(|| {
+        let _registration = sentry::profiling::register_thread();
+        assert_eq!(sentry::profiling::profiler_id(), None);
+        busy_loop(Duration::from_millis(50));
+    });
+
+    assert!(profile_chunks(&envelopes).is_empty());
+}